@@ -1,9 +1,9 @@
 use criterion::{black_box, Criterion};
-use log::error;
+use log::{error, info};
 use osmgraphing::{
     configs, helpers,
     io::network::graph::Parser,
-    network::{Graph, NodeIdx},
+    network::{CustomizableCH, Graph, NodeIdx, NodeOrdering},
     routing::dijkstra::{self, Dijkstra},
 };
 use std::time::Duration;
@@ -35,58 +35,88 @@ fn do_benchmark(criterion: &mut Criterion) {
     ];
 
     // create graph
-    let graph = match Parser::parse_and_finalize(parsing_cfg) {
+    let graph = match Parser::parse_and_finalize(parsing_cfg.clone()) {
         Ok(graph) => graph,
         Err(msg) => {
             error!("{}", msg);
             return;
         }
     };
-    let nodes = graph.nodes();
+
+    // contract graph for the ch-dijkstra, considering all metrics of all routing-strs
+    let ch_graph = {
+        let (_dim, routing_str) = routing_strs[routing_strs.len() - 1];
+        let routing_cfg = configs::routing::Config::from_str(routing_str, graph.cfg());
+        match CustomizableCH::new(&graph).customize(&graph, &routing_cfg) {
+            Ok(ch_graph) => ch_graph,
+            Err(msg) => {
+                error!("{}", msg);
+                return;
+            }
+        }
+    };
 
     // routing
+    // Routes are given by node-ids, since node-indices depend on the graph's node-ordering.
     let labelled_routes = vec![
         // short route (~3 km)
         (
             "",
             " with short routes (~3 km)",
-            vec![(
-                nodes.idx_from(283_500_532).expect("A"),
-                nodes.idx_from(283_501_263).expect("B"),
-            )],
+            vec![(283_500_532, 283_501_263)],
         ),
         // medium route (~30 km)
         (
             "",
             " with medium routes (~30 km)",
-            vec![(
-                nodes.idx_from(283_483_998).expect("C"),
-                nodes.idx_from(1_746_745_421).expect("D"),
-            )],
+            vec![(283_483_998, 1_746_745_421)],
         ),
         // long route (~56 km)
         (
             "",
             " with long routes (~56 km)",
-            vec![(
-                nodes.idx_from(1_700_989_735).expect("E"),
-                nodes.idx_from(456_478_793).expect("F"),
-            )],
+            vec![(1_700_989_735, 456_478_793)],
         ),
     ];
+    let graph_routes: Vec<_> = labelled_routes
+        .iter()
+        .map(|(_prefix, _suffix, routes)| idx_routes_of(&graph, routes))
+        .collect();
+    let ch_graph_routes: Vec<_> = labelled_routes
+        .iter()
+        .map(|(_prefix, _suffix, routes)| idx_routes_of(&ch_graph, routes))
+        .collect();
 
     // benchmarking shortest routing
-    for (dim, routing_str) in routing_strs {
+    for &(dim, routing_str) in routing_strs.iter() {
         let routing_cfg = configs::routing::Config::from_str(routing_str, graph.cfg());
+        let mut ch_routing_cfg = routing_cfg.clone();
+        ch_routing_cfg.routing_algo = configs::routing::RoutingAlgo::CHDijkstra;
 
-        for (prefix, suffix, routes) in labelled_routes.iter() {
+        // report search-spaces
+        for (i, (_prefix, suffix, _routes)) in labelled_routes.iter().enumerate() {
+            report_settled_nodes(
+                &format!("Dijkstra (bidir, {}){}", dim, suffix),
+                &graph,
+                &graph_routes[i],
+                &routing_cfg,
+            );
+            report_settled_nodes(
+                &format!("CH-Dijkstra (bidir, {}){}", dim, suffix),
+                &ch_graph,
+                &ch_graph_routes[i],
+                &ch_routing_cfg,
+            );
+        }
+
+        for (i, (prefix, suffix, _routes)) in labelled_routes.iter().enumerate() {
             criterion.bench_function(
                 &format!("{}Shortest Dijkstra (bidir, {}){}", prefix, dim, suffix),
                 |b| {
                     b.iter(|| {
                         bidir_shortest_dijkstra(
                             black_box(&graph),
-                            black_box(&routes),
+                            black_box(&graph_routes[i]),
                             black_box(&routing_cfg),
                         )
                     })
@@ -94,13 +124,83 @@ fn do_benchmark(criterion: &mut Criterion) {
             );
         }
 
+        // benchmarking shortest routing on contracted graph
+        for (i, (prefix, suffix, _routes)) in labelled_routes.iter().enumerate() {
+            criterion.bench_function(
+                &format!("{}Shortest CH-Dijkstra (bidir, {}){}", prefix, dim, suffix),
+                |b| {
+                    b.iter(|| {
+                        bidir_shortest_dijkstra(
+                            black_box(&ch_graph),
+                            black_box(&ch_graph_routes[i]),
+                            black_box(&ch_routing_cfg),
+                        )
+                    })
+                },
+            );
+        }
+
         // benchmarking fastest routing
-        for (prefix, suffix, routes) in labelled_routes.iter() {
+        for (i, (prefix, suffix, _routes)) in labelled_routes.iter().enumerate() {
             criterion.bench_function(
                 &format!("{}Fastest Dijkstra (bidir, {}){}", prefix, dim, suffix),
                 |b| {
                     b.iter(|| {
                         bidir_fastest_dijkstra(
+                            black_box(&graph),
+                            black_box(&graph_routes[i]),
+                            black_box(&routing_cfg),
+                        )
+                    })
+                },
+            );
+        }
+    }
+
+    // benchmarking node-orderings, which change the memory-layout, but not the search-space
+    let (_dim, routing_str) = routing_strs[0];
+    for &node_ordering in &[
+        NodeOrdering::Id,
+        NodeOrdering::Hilbert,
+        NodeOrdering::Dfs,
+        NodeOrdering::Bfs,
+        NodeOrdering::ChLevel,
+    ] {
+        let mut parsing_cfg = parsing_cfg.clone();
+        parsing_cfg.node_ordering = node_ordering;
+        let graph = match Parser::parse_and_finalize(parsing_cfg) {
+            Ok(graph) => graph,
+            Err(msg) => {
+                error!("{}", msg);
+                return;
+            }
+        };
+        let mut routing_cfg = configs::routing::Config::from_str(routing_str, graph.cfg());
+
+        // ch-levels are only known for contracted graphs, which are built with the same node-ordering
+        let (graph, algo_label) = if node_ordering == NodeOrdering::ChLevel {
+            routing_cfg.routing_algo = configs::routing::RoutingAlgo::CHDijkstra;
+            match CustomizableCH::new(&graph).customize(&graph, &routing_cfg) {
+                Ok(ch_graph) => (ch_graph, "CH-Dijkstra"),
+                Err(msg) => {
+                    error!("{}", msg);
+                    return;
+                }
+            }
+        } else {
+            (graph, "Dijkstra")
+        };
+
+        for (prefix, suffix, routes) in labelled_routes.iter() {
+            let routes = idx_routes_of(&graph, routes);
+            criterion.bench_function(
+                &format!(
+                    "{}Shortest {} (bidir, 1D, {:?}-ordering){}",
+                    prefix, algo_label, node_ordering, suffix
+                ),
+                |b| {
+                    b.iter(|| {
+                        bidir_shortest_dijkstra(
                             black_box(&graph),
                             black_box(&routes),
                             black_box(&routing_cfg),
@@ -112,6 +212,19 @@ fn do_benchmark(criterion: &mut Criterion) {
     }
 }
 
+fn idx_routes_of(graph: &Graph, routes: &[(i64, i64)]) -> Vec<(NodeIdx, NodeIdx)> {
+    let nodes = graph.nodes();
+    routes
+        .iter()
+        .map(|&(src_id, dst_id)| {
+            (
+                nodes.idx_from(src_id).expect("Src-id should be in graph."),
+                nodes.idx_from(dst_id).expect("Dst-id should be in graph."),
+            )
+        })
+        .collect()
+}
+
 fn bidir_shortest_dijkstra(
     graph: &Graph,
     routes: &Vec<(NodeIdx, NodeIdx)>,
@@ -145,3 +258,31 @@ fn bidir_fastest_dijkstra(
         });
     }
 }
+
+/// Logs the number of settled nodes per query, since the runtime alone doesn't show the search-space.
+/// The logs are shown with `RUST_LOG=routing=info`, since the benchmark logs warnings only by default.
+fn report_settled_nodes(
+    label: &str,
+    graph: &Graph,
+    routes: &Vec<(NodeIdx, NodeIdx)>,
+    cfg: &configs::routing::Config,
+) {
+    let mut dijkstra = Dijkstra::new();
+
+    for &(src_idx, dst_idx) in routes.iter() {
+        let _option_path = dijkstra.compute_best_path(dijkstra::Query {
+            src_idx,
+            dst_idx,
+            graph,
+            routing_cfg: cfg,
+        });
+        info!(
+            "{}: settled {} nodes ({} stalled) from {} to {}",
+            label,
+            dijkstra.settled_node_count(),
+            dijkstra.stalled_node_count(),
+            graph.nodes().id(src_idx),
+            graph.nodes().id(dst_idx),
+        );
+    }
+}
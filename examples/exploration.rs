@@ -70,6 +70,10 @@ fn run() -> err::Feedback {
         "Ran Exploration-query in {} ms",
         now.elapsed().as_micros() as f64 / 1_000.0,
     );
+    info!(
+        "Pruned {} cell(s) due to tolerances.",
+        explorator.pruned_cell_count()
+    );
     if !found_paths.is_empty() {
         info!("Found {} path(s):", found_paths.len());
         found_paths.iter().for_each(|path| info!("  {}", path))
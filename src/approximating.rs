@@ -169,6 +169,17 @@ impl Approx<f64> {
     pub fn approx(&self) -> f64 {
         (self.0 / accuracy::F64_ABS).round() * accuracy::F64_ABS
     }
+
+    /// Compares with the given absolute tolerance (at least `accuracy::F64_ABS`),
+    /// e.g. for metrics stored with less precision (see `Precision::tolerance(...)`).
+    pub fn eq_within(&self, other: &Approx<f64>, tolerance: f64) -> bool {
+        (self.0 - other.0).abs() <= tolerance.max(accuracy::F64_ABS)
+    }
+
+    /// Like `eq_within(...)`, but checks if `self` is less than or equal to `other`.
+    pub fn le_within(&self, other: &Approx<f64>, tolerance: f64) -> bool {
+        self.0 <= other.0 + tolerance.max(accuracy::F64_ABS)
+    }
 }
 
 impl PartialOrd for Approx<f64> {
@@ -0,0 +1,139 @@
+use log::{error, info};
+use osmgraphing::{
+    configs,
+    helpers::{err, init_logging},
+    io,
+    routing::Engine,
+    server::Server,
+};
+use std::{sync::Arc, time::Instant};
+
+fn main() {
+    let args = parse_cmdline();
+    let result = init_logging(&args.max_log_level, &[]);
+    if let Err(msg) = result {
+        error!("{}{}", msg, "\n");
+        panic!("{}", msg);
+    }
+    let result = run(args);
+    if let Err(msg) = result {
+        error!("{}{}", msg, "\n");
+        panic!("{}", msg);
+    }
+}
+
+fn run(args: CmdlineArgs) -> err::Feedback {
+    info!("EXECUTE osmgraphing-server");
+
+    // check configs before parsing the graph
+
+    let mut server_cfg = configs::server::Config::try_from_yaml(&args.cfg)?;
+    if let Some(address) = args.address {
+        server_cfg.address = address;
+    }
+    let parsing_cfg = configs::parsing::Config::try_from_yaml(&args.cfg)?;
+
+    // parse graph
+
+    let now = Instant::now();
+    let graph = io::network::graph::Parser::parse_and_finalize(parsing_cfg)?;
+    info!(
+        "FINISHED Parsed graph in {} seconds ({} µs).",
+        now.elapsed().as_secs(),
+        now.elapsed().as_micros(),
+    );
+    info!("");
+    info!("{}", graph);
+    info!("");
+
+    let routing_cfg = configs::routing::Config::try_from_yaml(&args.cfg, graph.cfg())?;
+
+    // serve
+
+    let engine = Engine::with_num_threads(Arc::new(graph), server_cfg.num_threads);
+    Server::bind(&server_cfg, engine, routing_cfg)?.run()
+}
+
+fn parse_cmdline<'a>() -> CmdlineArgs {
+    let arg_log_level = clap::Arg::with_name(constants::ids::MAX_LOG_LEVEL)
+        .long("log")
+        .short("l")
+        .value_name("FILTER-LEVEL")
+        .help(
+            "Sets the logging-level according to the env-variable 'RUST_LOG'. The env-variable \
+            'RUST_LOG' has precedence. It takes values of modules, e.g. export RUST_LOG='warn,\
+            osmgraphing=info' for getting warn's by default, but 'info' about the others",
+        )
+        .takes_value(true)
+        .required(false)
+        .case_insensitive(true)
+        .default_value("INFO")
+        .possible_values(&vec!["TRACE", "DEBUG", "INFO", "WARN", "ERROR"]);
+
+    let arg_cfg = clap::Arg::with_name(constants::ids::CFG)
+        .long("config")
+        .short("c")
+        .value_name("PATH")
+        .help(
+            "Sets the parser, the routing and the server according to this config. \
+            The routing-section defines the default-alphas of requests.",
+        )
+        .takes_value(true)
+        .required(true);
+
+    let arg_address = clap::Arg::with_name(constants::ids::ADDRESS)
+        .long("address")
+        .short("a")
+        .value_name("HOST:PORT")
+        .help("Overwrites the address of the config's server-section.")
+        .takes_value(true)
+        .required(false);
+
+    clap::App::new("osmgraphing-server")
+        .version(env!("CARGO_PKG_VERSION"))
+        .author(env!("CARGO_PKG_AUTHORS"))
+        .about(env!("CARGO_PKG_DESCRIPTION"))
+        .long_about(
+            "\n\
+            This tool takes a config-file, parses the chosen graph with specified settings, and \
+            serves routing-requests (route, matrix, explorator, isochrones, nearest-node) as \
+            JSON via HTTP.",
+        )
+        .arg(arg_log_level)
+        .arg(arg_cfg)
+        .arg(arg_address)
+        .get_matches()
+        .into()
+}
+
+mod constants {
+    pub mod ids {
+        pub const MAX_LOG_LEVEL: &str = "max-log-level";
+        pub const CFG: &str = "cfg";
+        pub const ADDRESS: &str = "address";
+    }
+}
+
+struct CmdlineArgs {
+    max_log_level: String,
+    cfg: String,
+    address: Option<String>,
+}
+
+impl<'a> From<clap::ArgMatches<'a>> for CmdlineArgs {
+    fn from(matches: clap::ArgMatches<'a>) -> CmdlineArgs {
+        let max_log_level = matches
+            .value_of(constants::ids::MAX_LOG_LEVEL)
+            .expect(&format!("cmdline-arg: {}", constants::ids::MAX_LOG_LEVEL));
+        let cfg = matches
+            .value_of(constants::ids::CFG)
+            .expect(&format!("cmdline-arg: {}", constants::ids::CFG));
+        let address = matches.value_of(constants::ids::ADDRESS);
+
+        CmdlineArgs {
+            max_log_level: String::from(max_log_level),
+            cfg: String::from(cfg),
+            address: address.map(String::from),
+        }
+    }
+}
@@ -1,14 +1,14 @@
 use log::{debug, info};
 use osmgraphing::{
-    configs::{self, routing::RoutingAlgo},
+    configs::{self, balancing::ContractionMethod},
     helpers::err,
     io,
-    network::Graph,
+    network::{CustomizableCH, Graph},
 };
 use rand::SeedableRng;
 use std::{path::Path, sync::Arc, time::Instant};
 
-pub mod multithreading;
+pub mod workloads;
 
 pub fn run(args: CmdlineArgs) -> err::Feedback {
     // check writing-cfg
@@ -27,6 +27,13 @@ pub fn run(args: CmdlineArgs) -> err::Feedback {
     // check routing-cfg
     let _ = configs::routing::Config::try_from_yaml(&args.cfg, custom_graph.cfg())?;
 
+    // The node-ordering of the CCH depends only on the graph's structure,
+    // which doesn't change while balancing.
+    let cch = match balancing_cfg.contraction_method {
+        ContractionMethod::MultiChConstructor => None,
+        ContractionMethod::CustomizableCH => Some(CustomizableCH::new(&custom_graph)),
+    };
+
     // start balancing
 
     simulation_pipeline::prepare_results(&args.cfg, &mut balancing_cfg)?;
@@ -53,11 +60,24 @@ pub fn run(args: CmdlineArgs) -> err::Feedback {
         // simulate and create new balanced graph
 
         simulation_pipeline::prepare_iteration(iter, &balancing_cfg)?;
-        simulation_pipeline::write_multi_ch_graph(&balancing_cfg, graph, iter)?;
-        simulation_pipeline::construct_ch_graph(&balancing_cfg, iter)?;
-        let ch_graph = simulation_pipeline::read_in_ch_graph(&balancing_cfg, iter)?;
-        let routing_cfg =
-            simulation_pipeline::read_in_routing_cfg(&balancing_cfg, iter, &args.cfg, &ch_graph)?;
+        let (ch_graph, routing_cfg) = if let Some(cch) = &cch {
+            let routing_cfg =
+                simulation_pipeline::read_in_routing_cfg(&balancing_cfg, iter, &args.cfg, &graph)?;
+            let ch_graph = simulation_pipeline::customize_ch_graph(cch, &graph, &routing_cfg)?;
+            (ch_graph, routing_cfg)
+        } else {
+            let cost_accuracy = simulation_pipeline::cost_accuracy(&graph);
+            simulation_pipeline::write_multi_ch_graph(&balancing_cfg, graph, iter)?;
+            simulation_pipeline::construct_ch_graph(&balancing_cfg, iter, cost_accuracy)?;
+            let ch_graph = simulation_pipeline::read_in_ch_graph(&balancing_cfg, iter)?;
+            let routing_cfg = simulation_pipeline::read_in_routing_cfg(
+                &balancing_cfg,
+                iter,
+                &args.cfg,
+                &ch_graph,
+            )?;
+            (ch_graph, routing_cfg)
+        };
 
         let mut arc_ch_graph = Arc::new(ch_graph);
         simulation_pipeline::balance(
@@ -80,10 +100,16 @@ pub fn run(args: CmdlineArgs) -> err::Feedback {
 }
 
 mod simulation_pipeline {
-    use super::multithreading;
+    use super::workloads;
     use chrono;
     use log::info;
-    use osmgraphing::{configs, defaults, helpers::err, io, multi_ch_constructor, network::Graph};
+    use osmgraphing::{
+        configs, defaults,
+        helpers::err,
+        io, multi_ch_constructor,
+        network::{CustomizableCH, Graph},
+        routing::Engine,
+    };
     use std::{
         fs,
         path::{Path, PathBuf},
@@ -177,9 +203,25 @@ mod simulation_pipeline {
         super::write_edges(&graph, &writing_cfg)
     }
 
+    /// The multi-ch-constructor compares costs with this accuracy, which has to respect
+    /// the storage-precisions of the graph's metrics (e.g. fixed-point metrics).
+    pub fn cost_accuracy(graph: &Graph) -> f64 {
+        graph
+            .metrics()
+            .iter()
+            .map(|values| {
+                let max_value = values
+                    .iter()
+                    .fold(0.0, |max: f64, value| max.max(value.abs()));
+                values.precision().tolerance(max_value)
+            })
+            .fold(defaults::accuracy::F64_ABS, f64::max)
+    }
+
     pub fn construct_ch_graph(
         balancing_cfg: &configs::balancing::Config,
         iter: usize,
+        cost_accuracy: f64,
     ) -> err::Feedback {
         let mut mchc_cfg = balancing_cfg.multi_ch_constructor.clone();
 
@@ -192,12 +234,29 @@ mod simulation_pipeline {
         mchc_cfg.fmi_graph = iter_dir.join(mchc_cfg.fmi_graph);
         mchc_cfg.ch_fmi_graph = iter_dir.join(mchc_cfg.ch_fmi_graph);
 
-        mchc_cfg.cost_accuracy = defaults::accuracy::F64_ABS;
+        mchc_cfg.cost_accuracy = cost_accuracy;
 
         // multi_ch_constructor::build(&mchc_cfg)?;
         multi_ch_constructor::construct_ch_graph(&mchc_cfg)
     }
 
+    /// Replaces writing the graph, running the multi-ch-constructor and reading in its result.
+    /// The routing-cfg is needed, because only its considered metrics are customized.
+    pub fn customize_ch_graph(
+        cch: &CustomizableCH,
+        graph: &Graph,
+        routing_cfg: &configs::routing::Config,
+    ) -> err::Result<Graph> {
+        let now = Instant::now();
+        let ch_graph = cch.customize(graph, routing_cfg)?;
+        info!(
+            "Customized ch-graph in {} seconds ({} µs).",
+            now.elapsed().as_secs(),
+            now.elapsed().as_micros(),
+        );
+        Ok(ch_graph)
+    }
+
     pub fn read_in_ch_graph(
         balancing_cfg: &configs::balancing::Config,
         iter: usize,
@@ -314,14 +373,11 @@ mod simulation_pipeline {
         // reverse this vector to make splice efficient
         let route_pairs = io::routing::Parser::parse(&arc_routing_cfg)?;
 
-        let mut master = multithreading::Master::spawn_some(
-            balancing_cfg.num_threads,
-            &arc_ch_graph,
-            &arc_routing_cfg,
-        )?;
-        let (abs_workloads, chosen_paths) = master.work_off(
+        // The engine has to be dropped before updating the graph.
+        let (abs_workloads, chosen_paths) = workloads::work_off(
+            &Engine::with_num_threads(Arc::clone(arc_ch_graph), balancing_cfg.num_threads),
             route_pairs,
-            &arc_ch_graph,
+            &arc_routing_cfg,
             rng,
             balancing_cfg.monitoring.is_writing_for_smarts,
         )?;
@@ -0,0 +1,158 @@
+use log::{debug, info, trace, warn};
+use osmgraphing::{
+    configs::{self, routing::RoutingAlgo},
+    defaults,
+    helpers::err,
+    network::RoutePair,
+    routing::{paths::Path, Engine},
+};
+use progressing::{mapping::Bar as MappingBar, Baring};
+use rand::distributions::{Distribution, Uniform};
+
+/// Routes all route-pairs (as often as their route-count) and counts the (flattened) paths' edges.
+///
+/// Chosen paths are returned if requested.
+/// They are not necessarily the same as found paths (e.g. when using explorator).
+pub fn work_off(
+    engine: &Engine,
+    route_pairs: Vec<(RoutePair<i64>, usize)>,
+    routing_cfg: &configs::routing::Config,
+    rng: &mut rand_pcg::Lcg64Xsh32,
+    is_collecting_paths: bool,
+) -> err::Result<(Vec<usize>, Option<Vec<Path>>)> {
+    info!("Using routing-algo: {:?}", routing_cfg.routing_algo);
+    info!("Using {} threads working off", engine.num_threads());
+
+    let graph = engine.graph();
+    let num_of_route_pairs = route_pairs.len();
+
+    let mut abs_workloads: Vec<usize> = vec![0; graph.fwd_edges().count()];
+    let mut chosen_paths = if is_collecting_paths {
+        // num_of_route_pairs is not accurate, but lower bound
+        Some(Vec::with_capacity(num_of_route_pairs))
+    } else {
+        None
+    };
+    // for mean and variance
+    let mut num_of_explorations = 0;
+    let mut sum_of_found_paths = 0.0;
+    let mut sum_of_squared_found_paths = 0.0;
+
+    let mut progress_bar = MappingBar::with_range(0, num_of_route_pairs).timed();
+
+    info!("START Executing routes and analyzing workload",);
+    for batch in route_pairs.chunks(defaults::balancing::BATCH_SIZE) {
+        let idx_pairs = batch
+            .iter()
+            .map(|(route_pair, _)| route_pair.into_idx(graph))
+            .collect();
+
+        // find paths and choose the used ones
+
+        let mut batch_paths = Vec::new();
+        match routing_cfg.routing_algo {
+            RoutingAlgo::Dijkstra | RoutingAlgo::CHDijkstra => {
+                for (best_path, &(_, route_count)) in engine
+                    .route_batch(idx_pairs, routing_cfg)?
+                    .into_iter()
+                    .zip(batch)
+                {
+                    if let Some(best_path) = best_path {
+                        batch_paths.push((best_path.flatten(graph), route_count));
+                    } else {
+                        warn!("Didn't find any path when executing Dijkstra.")
+                    }
+                }
+            }
+            RoutingAlgo::Explorator { algo: _ } => {
+                for ((found_paths, pruned_cell_count), &(route_pair, route_count)) in engine
+                    .explore_batch_counting_pruned(idx_pairs, routing_cfg)?
+                    .into_iter()
+                    .zip(batch)
+                {
+                    debug!(
+                        "Pruned {} cell(s) exploring from ({}) to ({}).",
+                        pruned_cell_count, route_pair.src, route_pair.dst
+                    );
+                    let num_of_found_paths = found_paths.len() as f64;
+                    num_of_explorations += 1;
+                    sum_of_found_paths += num_of_found_paths;
+                    sum_of_squared_found_paths += num_of_found_paths * num_of_found_paths;
+
+                    if found_paths.len() > 0 {
+                        let die = Uniform::from(0..found_paths.len());
+                        for _ in 0..route_count {
+                            let chosen_path = found_paths[die.sample(rng)].clone();
+                            trace!("    {}", chosen_path);
+                            batch_paths.push((chosen_path.flatten(graph), 1));
+                        }
+                    } else {
+                        warn!("Didn't find any path when explorating.")
+                    }
+                }
+            }
+        }
+
+        // Update workload by looping over all chosen paths
+        // -> Paths have to be flattened,
+        // -> or future shortcuts using the resulting workload
+        //    will lead to wrong best-paths, because counts won't be cumulated.
+
+        for (path, route_count) in batch_paths {
+            for &edge_idx in &path {
+                abs_workloads[*edge_idx] += route_count;
+            }
+
+            if let Some(chosen_paths) = chosen_paths.as_mut() {
+                for _ in 0..route_count {
+                    chosen_paths.push(path.clone());
+                }
+            }
+        }
+
+        // print and update progress
+
+        // num_of_routes is ignored here
+        progress_bar.add(batch.len());
+        if progress_bar.has_progressed_significantly() {
+            progress_bar.remember_significant_progress();
+            info!("{}", progress_bar);
+            if num_of_explorations > 0 {
+                let (mean, std_dev) = mean_and_std_dev(
+                    num_of_explorations,
+                    sum_of_found_paths,
+                    sum_of_squared_found_paths,
+                );
+                debug!(
+                    "{}{:.1}{}{:.1}{}",
+                    "On average over all route-pairs so far, ",
+                    mean,
+                    "+-",
+                    std_dev,
+                    " path(s) per routing-query were found.",
+                );
+            }
+        }
+    }
+
+    if num_of_explorations > 0 {
+        let (mean, std_dev) = mean_and_std_dev(
+            num_of_explorations,
+            sum_of_found_paths,
+            sum_of_squared_found_paths,
+        );
+        info!(
+            "{}{:.1}{}{:.1}{}",
+            "On average, ", mean, "+-", std_dev, " path(s) per exploration were found.",
+        );
+    }
+
+    Ok((abs_workloads, chosen_paths))
+}
+
+fn mean_and_std_dev(n: usize, sum: f64, sum_of_squares: f64) -> (f64, f64) {
+    let n = n as f64;
+    let mean = sum / n;
+    let var = (sum_of_squares / n - mean * mean).max(0.0);
+    (mean, var.sqrt())
+}
@@ -1,20 +1,15 @@
 use log::{debug, error, info, warn};
-#[cfg(feature = "gpl")]
 mod balancing;
 use osmgraphing::{
     configs::{self, routing::RoutingAlgo},
+    defaults,
     helpers::{err, init_logging},
     io,
     network::{Graph, RoutePair},
-    routing::dijkstra::{self, Dijkstra},
+    routing::{dijkstra, Engine, Workspace},
 };
-#[cfg(feature = "gpl")]
-use osmgraphing::{defaults, routing::explorating::ConvexHullExplorator};
-#[cfg(feature = "gpl")]
 use rand::SeedableRng;
-use std::{convert::TryFrom, path::PathBuf, time::Instant};
-#[cfg(feature = "gpl")]
-use std::{fs, sync::Arc};
+use std::{convert::TryFrom, fs, path::PathBuf, sync::Arc, time::Instant};
 
 //------------------------------------------------------------------------------------------------//
 // points in Germany
@@ -87,6 +82,48 @@ fn run(args: CmdlineArgs) -> err::Feedback {
         graph
     };
 
+    // statistics and validation of built graph
+
+    if args.is_printing_stats {
+        let stats = graph.analyze();
+        info!("{}", stats);
+        for issue in stats.issues() {
+            warn!("{}", issue);
+        }
+        // json is printed to stdout, while logs are printed to stderr
+        let json = serde_json::to_string_pretty(&stats.to_json()).map_err(|e| {
+            err::Msg::from(format!(
+                "Couldn't serialize graph-stats due to error: {}",
+                e
+            ))
+        })?;
+        println!("{}", json);
+    }
+
+    // differences to another graph
+
+    if let Some(new_cfg) = &args.diff_cfg {
+        // get config by provided user-input
+
+        let writing_cfg = configs::writing::diff::Config::try_from_yaml(&args.cfg)?;
+
+        // check if new file does already exist
+
+        if writing_cfg.file.exists() && !args.overwrite {
+            return Err(err::Msg::from(format!(
+                "New diff-file {} does already exist. Please remove it.",
+                writing_cfg.file.display()
+            )));
+        }
+
+        // the provided graph is the old one
+
+        let new_parsing_cfg = configs::parsing::Config::try_from_yaml(new_cfg)?;
+        let new_graph = io::network::graph::Parser::parse_and_finalize(new_parsing_cfg)?;
+        let diff = graph.diff(&new_graph, writing_cfg.edge_matching)?;
+        io::diff::Writer::write(&diff, &writing_cfg)?;
+    }
+
     // writing built graph
 
     if args.is_writing_graph {
@@ -136,6 +173,35 @@ fn run(args: CmdlineArgs) -> err::Feedback {
         debug!("");
     }
 
+    // writing edges as geojson
+
+    if args.is_writing_geojson {
+        // get config by provided user-input
+
+        let writing_cfg = configs::writing::geojson::Config::try_from_yaml(&args.cfg)?;
+
+        // check if new file does already exist
+
+        if writing_cfg.file.exists() && !args.overwrite {
+            return Err(err::Msg::from(format!(
+                "New file {} does already exist. Please remove it.",
+                writing_cfg.file.display()
+            )));
+        }
+
+        // writing to file
+
+        // measure writing-time
+        let now = Instant::now();
+        io::geojson::Writer::write_graph(&graph, &writing_cfg)?;
+        debug!(
+            "Finished writing in {} seconds ({} µs).",
+            now.elapsed().as_secs(),
+            now.elapsed().as_micros(),
+        );
+        debug!("");
+    }
+
     // writing routes to fileargs.overwrite
 
     if args.is_writing_route_pairs {
@@ -169,15 +235,14 @@ fn run(args: CmdlineArgs) -> err::Feedback {
     // routing-example
 
     if args.is_routing || args.is_evaluating_balance {
+        let arc_graph = Arc::new(graph);
         if !args.is_evaluating_balance {
-            do_simply_routing(&args, &graph)?;
+            do_simply_routing(&args, &Engine::new(arc_graph))?;
         } else {
-            #[cfg(feature = "gpl")]
-            do_evaluating_routing(&args, &Arc::new(graph))?;
+            do_evaluating_routing(&args, &arc_graph)?;
         }
     }
 
-    #[cfg(feature = "gpl")]
     if args.is_balancing {
         balancing::run(balancing::CmdlineArgs {
             max_log_level: args.max_log_level.clone(),
@@ -188,11 +253,31 @@ fn run(args: CmdlineArgs) -> err::Feedback {
     Ok(())
 }
 
-fn do_simply_routing(args: &CmdlineArgs, graph: &Graph) -> err::Feedback {
+fn do_simply_routing(args: &CmdlineArgs, engine: &Engine) -> err::Feedback {
+    let graph = engine.graph();
+    // queries are logged one by one, hence one workspace is enough
+    let mut workspace = engine.workspace();
+    let Workspace {
+        dijkstra,
+        explorator,
+    } = &mut *workspace;
+
     // get config by provided user-input
     let routing_cfg = configs::routing::Config::try_from_yaml(&args.cfg, graph.cfg())?;
     info!("EXECUTE Do routing with alphas: {:?}", routing_cfg.alphas);
 
+    // computed paths are exported, if the config has a section `writing.paths`
+    let writing_cfg = configs::writing::paths::Config::try_from_yaml(&args.cfg)?;
+    if let Some(writing_cfg) = &writing_cfg {
+        if writing_cfg.file.exists() && !args.overwrite {
+            return Err(err::Msg::from(format!(
+                "New paths-file {} does already exist. Please remove it.",
+                writing_cfg.file.display()
+            )));
+        }
+    }
+    let mut found_paths_of_all_queries = Vec::new();
+
     // get routing-pairs
     let routing_pairs = io::routing::Parser::parse(&routing_cfg)?;
     let iter_route_pairs = routing_pairs
@@ -201,10 +286,9 @@ fn do_simply_routing(args: &CmdlineArgs, graph: &Graph) -> err::Feedback {
 
     match routing_cfg.routing_algo {
         RoutingAlgo::Dijkstra | RoutingAlgo::CHDijkstra => {
-            let mut dijkstra = Dijkstra::new();
+            dijkstra.collect_stats(true);
 
             for (RoutePair { src, dst }, _route_count) in iter_route_pairs {
-                let now = Instant::now();
                 let best_path = dijkstra.compute_best_path(dijkstra::Query {
                     src_idx: src.idx(),
                     dst_idx: dst.idx(),
@@ -212,10 +296,9 @@ fn do_simply_routing(args: &CmdlineArgs, graph: &Graph) -> err::Feedback {
                     routing_cfg: &routing_cfg,
                 });
                 info!("");
-                info!(
-                    "Ran Dijkstra-query in {} ms",
-                    now.elapsed().as_micros() as f64 / 1_000.0,
-                );
+                if let Some(stats) = dijkstra.last_stats() {
+                    info!("Ran Dijkstra-query in {}", stats);
+                }
 
                 if let Some(best_path) = best_path {
                     let best_path = best_path.flatten(&graph);
@@ -226,15 +309,13 @@ fn do_simply_routing(args: &CmdlineArgs, graph: &Graph) -> err::Feedback {
                         src,
                         dst
                     );
+                    found_paths_of_all_queries.push(best_path);
                 } else {
                     warn!("No path from ({}) to ({}).", src, dst);
                 }
             }
         }
-        #[cfg(feature = "gpl")]
         RoutingAlgo::Explorator { algo } => {
-            let mut dijkstra = Dijkstra::new();
-            let mut explorator = ConvexHullExplorator::new();
             let mut routing_cfg = routing_cfg.clone();
             routing_cfg.routing_algo = RoutingAlgo::from(algo);
 
@@ -247,7 +328,7 @@ fn do_simply_routing(args: &CmdlineArgs, graph: &Graph) -> err::Feedback {
                         graph: &graph,
                         routing_cfg: &routing_cfg,
                     },
-                    &mut dijkstra,
+                    dijkstra,
                 );
 
                 info!("");
@@ -255,11 +336,16 @@ fn do_simply_routing(args: &CmdlineArgs, graph: &Graph) -> err::Feedback {
                     "Ran Exploration-query in {} ms",
                     now.elapsed().as_micros() as f64 / 1_000.0,
                 );
+                info!(
+                    "Pruned {} cell(s) due to tolerances.",
+                    explorator.pruned_cell_count()
+                );
 
                 if !found_paths.is_empty() {
                     if !found_paths.is_empty() {
                         info!("Found {} path(s):", found_paths.len());
-                        found_paths.iter().for_each(|path| info!("  {}", path))
+                        found_paths.iter().for_each(|path| info!("  {}", path));
+                        found_paths_of_all_queries.extend(found_paths);
                     } else {
                         info!("No path found from ({}) to ({}).", src, dst);
                     }
@@ -270,10 +356,13 @@ fn do_simply_routing(args: &CmdlineArgs, graph: &Graph) -> err::Feedback {
         }
     }
 
+    if let Some(writing_cfg) = &writing_cfg {
+        io::routing::PathsWriter::write(&found_paths_of_all_queries, &graph, writing_cfg)?;
+    }
+
     Ok(())
 }
 
-#[cfg(feature = "gpl")]
 fn do_evaluating_routing(args: &CmdlineArgs, arc_graph: &Arc<Graph>) -> err::Feedback {
     // get config by provided user-input
     let routing_cfg = configs::routing::Config::try_from_yaml(&args.cfg, arc_graph.cfg())?;
@@ -291,15 +380,10 @@ fn do_evaluating_routing(args: &CmdlineArgs, arc_graph: &Arc<Graph>) -> err::Fee
 
     // work-off multithreaded
 
-    let arc_routing_cfg = Arc::new(routing_cfg);
-    let mut master = balancing::multithreading::Master::spawn_some(
-        evaluating_balance_cfg.num_threads,
-        &arc_graph,
-        &arc_routing_cfg,
-    )?;
-    let (abs_workloads, chosen_paths) = master.work_off(
+    let (abs_workloads, chosen_paths) = balancing::workloads::work_off(
+        &Engine::with_num_threads(Arc::clone(arc_graph), evaluating_balance_cfg.num_threads),
         route_pairs,
-        &arc_graph,
+        &routing_cfg,
         &mut rng,
         evaluating_balance_cfg.monitoring.is_writing_for_smarts,
     )?;
@@ -330,11 +414,7 @@ fn parse_cmdline<'a>() -> err::Result<CmdlineArgs> {
             "\n\
             This tool takes a config-file, parses the chosen graph with specified settings, and \
             can execute specified tasks. Such tasks may be exporting the graph as fmi-map-file or \
-            doing some routing-queries (if provided in config-file).\n\
-            \n\
-            NOTE\n\
-            Some cmdline-arguments can only be used with the cargo-feature 'gpl' and hence are \
-            hidden without it.",
+            doing some routing-queries (if provided in config-file).",
         );
 
     let args = {
@@ -367,6 +447,36 @@ fn parse_cmdline<'a>() -> err::Result<CmdlineArgs> {
         args.arg(arg_parser_cfg)
     };
 
+    let args = {
+        let arg_is_printing_stats = clap::Arg::with_name(constants::ids::IS_PRINTING_STATS)
+            .long("stats")
+            .help(
+                "Prints statistics of the generated graph as json to stdout, e.g. degrees, \
+                components and metrics. Suspicious data, like self-loops or negative metrics, is \
+                listed as issues and logged as warning.",
+            )
+            .takes_value(false)
+            .requires(constants::ids::CFG);
+        args.arg(arg_is_printing_stats)
+    };
+
+    let args = {
+        let arg_diff_cfg = clap::Arg::with_name(constants::ids::DIFF_CFG)
+            .long("diff")
+            .value_name("PATH")
+            .help(
+                "Parses a new graph with the parsing-config of this file and compares it with \
+                the graph of the provided config, e.g. of another osm-extract or \
+                balancer-iteration. Nodes are matched by their ids, edges by their src- and \
+                dst-ids or by their edge-ids. Added, removed and changed nodes and edges (with \
+                their metrics' deltas) are written as csv or GeoJSON as described in the \
+                provided config (writing.diff).",
+            )
+            .takes_value(true)
+            .requires(constants::ids::CFG);
+        args.arg(arg_diff_cfg)
+    };
+
     let args = {
         let arg_is_writing_graph = clap::Arg::with_name(constants::ids::IS_WRITING_GRAPH)
             .long("writing_graph")
@@ -391,6 +501,18 @@ fn parse_cmdline<'a>() -> err::Result<CmdlineArgs> {
         args.arg(arg_is_writing_edges)
     };
 
+    let args = {
+        let arg_is_writing_geojson = clap::Arg::with_name(constants::ids::IS_WRITING_GEOJSON)
+            .long("writing_geojson")
+            .help(
+                "The generated graph's edges will be exported as GeoJSON \
+               as described in the provided config.",
+            )
+            .takes_value(false)
+            .requires(constants::ids::CFG);
+        args.arg(arg_is_writing_geojson)
+    };
+
     let args = {
         let arg_is_writing_route_pairs =
             clap::Arg::with_name(constants::ids::IS_WRITING_ROUTE_PAIRS)
@@ -434,7 +556,6 @@ fn parse_cmdline<'a>() -> err::Result<CmdlineArgs> {
                 py ./scripts/balancing/visualizer --results-dir <RESULTS_DIR/DATE>\n",
             )
             .takes_value(false)
-            .hidden(!cfg!(feature = "gpl"))
             .requires(constants::ids::CFG);
         args.arg(arg_is_balancing)
     };
@@ -451,7 +572,6 @@ fn parse_cmdline<'a>() -> err::Result<CmdlineArgs> {
                 py ./scripts/balancing/visualizer --results-dir <RESULTS_DIR/DATE>\n",
             )
             .takes_value(false)
-            .hidden(!cfg!(feature = "gpl"))
             .requires(constants::ids::CFG);
         args.arg(arg_is_evaluating_balance)
     };
@@ -473,11 +593,13 @@ mod constants {
     pub mod ids {
         pub const MAX_LOG_LEVEL: &str = "max-log-level";
         pub const CFG: &str = "cfg";
+        pub const IS_PRINTING_STATS: &str = "is_printing_stats";
+        pub const DIFF_CFG: &str = "diff_cfg";
         pub const IS_WRITING_GRAPH: &str = "is_writing_graph";
         pub const IS_WRITING_EDGES: &str = "is_writing_edges";
+        pub const IS_WRITING_GEOJSON: &str = "is_writing_geojson";
         pub const IS_WRITING_ROUTE_PAIRS: &str = "is_writing_route_pairs";
         pub const IS_ROUTING: &str = "is_routing";
-        pub const IS_EXPLORATING: &str = "is_explorating";
         pub const IS_BALANCING: &str = "is_balancing";
         pub const IS_EVALUATING_BALANCE: &str = "is_evaluating_balance";
         pub const OVERWRITE: &str = "overwrite";
@@ -487,11 +609,13 @@ mod constants {
 struct CmdlineArgs {
     max_log_level: String,
     cfg: String,
+    is_printing_stats: bool,
+    diff_cfg: Option<String>,
     is_writing_graph: bool,
     is_writing_edges: bool,
+    is_writing_geojson: bool,
     is_writing_route_pairs: bool,
     is_routing: bool,
-    #[cfg(feature = "gpl")]
     is_balancing: bool,
     is_evaluating_balance: bool,
     overwrite: bool,
@@ -507,38 +631,30 @@ impl<'a> TryFrom<clap::ArgMatches<'a>> for CmdlineArgs {
         let cfg = matches
             .value_of(constants::ids::CFG)
             .expect(&format!("cmdline-arg: {}", constants::ids::CFG));
+        let is_printing_stats = matches.is_present(constants::ids::IS_PRINTING_STATS);
+        let diff_cfg = matches.value_of(constants::ids::DIFF_CFG).map(String::from);
         let is_writing_graph = matches.is_present(constants::ids::IS_WRITING_GRAPH);
         let is_writing_edges = matches.is_present(constants::ids::IS_WRITING_EDGES);
+        let is_writing_geojson = matches.is_present(constants::ids::IS_WRITING_GEOJSON);
         let is_writing_route_pairs = matches.is_present(constants::ids::IS_WRITING_ROUTE_PAIRS);
         let is_routing = matches.is_present(constants::ids::IS_ROUTING);
-        let is_explorating = matches.is_present(constants::ids::IS_EXPLORATING);
         let is_balancing = matches.is_present(constants::ids::IS_BALANCING);
         let is_evaluating_balance = matches.is_present(constants::ids::IS_EVALUATING_BALANCE);
         let overwrite = matches.is_present(constants::ids::OVERWRITE);
 
-        if is_explorating || is_balancing || is_evaluating_balance {
-            check_for_activated_feature()?;
-        }
-
         Ok(CmdlineArgs {
             max_log_level: String::from(max_log_level),
             cfg: String::from(cfg),
+            is_printing_stats,
+            diff_cfg,
             is_writing_graph,
             is_writing_edges,
+            is_writing_geojson,
             is_writing_route_pairs,
             is_routing,
-            #[cfg(feature = "gpl")]
             is_balancing,
             is_evaluating_balance,
             overwrite,
         })
     }
 }
-
-fn check_for_activated_feature() -> err::Feedback {
-    if !cfg!(feature = "gpl") {
-        return Err(err::Msg::from("Please activate cargo-feature gpl."));
-    }
-
-    Ok(())
-}
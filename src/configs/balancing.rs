@@ -15,6 +15,7 @@ use std::{
 pub struct Config {
     pub results_dir: PathBuf,
     pub multi_ch_constructor: multi_ch_constructor::Config,
+    pub contraction_method: ContractionMethod,
     pub iter_0_cfg: PathBuf,
     pub iter_i_cfg: PathBuf,
     pub optimization: Optimization,
@@ -100,6 +101,10 @@ impl From<ProtoConfig> for Config {
             iter_0_cfg: proto_cfg.iter_0_cfg,
             iter_i_cfg: proto_cfg.iter_i_cfg,
             multi_ch_constructor: proto_cfg.multi_ch_constructor,
+            contraction_method: proto_cfg
+                .contraction_method
+                .map(ContractionMethod::from)
+                .unwrap_or(defaults::balancing::CONTRACTION_METHOD),
             // +1 because analysing last graph needs one iteration as well
             num_iter: proto_cfg.num_metric_updates + 1,
             monitoring: MonitoringConfig::from(proto_cfg.monitoring),
@@ -147,6 +152,24 @@ impl From<ProtoOptimizationMethod> for OptimizationMethod {
     }
 }
 
+/// How the ch-graph is created in every iteration.
+#[derive(Copy, Clone, Debug)]
+pub enum ContractionMethod {
+    /// Runs the multi-ch-constructor on the whole graph.
+    MultiChConstructor,
+    /// Computes the node-ordering once and customizes the shortcuts in every iteration.
+    CustomizableCH,
+}
+
+impl From<ProtoContractionMethod> for ContractionMethod {
+    fn from(proto_method: ProtoContractionMethod) -> ContractionMethod {
+        match proto_method {
+            ProtoContractionMethod::MultiChConstructor => ContractionMethod::MultiChConstructor,
+            ProtoContractionMethod::CustomizableCH => ContractionMethod::CustomizableCH,
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct MonitoringConfig {
     pub edges_info: configs::writing::network::edges::Config,
@@ -173,6 +196,7 @@ pub struct ProtoConfig {
     pub iter_0_cfg: PathBuf,
     pub iter_i_cfg: PathBuf,
     pub multi_ch_constructor: multi_ch_constructor::Config,
+    pub contraction_method: Option<ProtoContractionMethod>,
     pub num_metric_updates: usize,
     pub monitoring: ProtoMonitoringConfig,
     pub optimization: ProtoOptimization,
@@ -189,6 +213,7 @@ impl From<RawConfig> for ProtoConfig {
             seed: raw_cfg.seed,
             results_dir: raw_cfg.results_dir,
             multi_ch_constructor: raw_cfg.multi_ch_constructor,
+            contraction_method: raw_cfg.contraction_method.map(ProtoContractionMethod::from),
             num_metric_updates: raw_cfg.number_of_metric_updates,
             iter_0_cfg: raw_cfg.iter_0_cfg,
             iter_i_cfg: raw_cfg.iter_i_cfg,
@@ -201,6 +226,21 @@ impl From<RawConfig> for ProtoConfig {
     }
 }
 
+#[derive(Clone, Debug)]
+pub enum ProtoContractionMethod {
+    MultiChConstructor,
+    CustomizableCH,
+}
+
+impl From<RawContractionMethod> for ProtoContractionMethod {
+    fn from(raw_method: RawContractionMethod) -> ProtoContractionMethod {
+        match raw_method {
+            RawContractionMethod::MultiChConstructor => ProtoContractionMethod::MultiChConstructor,
+            RawContractionMethod::CustomizableCH => ProtoContractionMethod::CustomizableCH,
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct ProtoMonitoringConfig {
     pub edges_info: configs::writing::network::edges::Config,
@@ -266,6 +306,8 @@ pub struct RawContent {
     pub iter_i_cfg: PathBuf,
     #[serde(flatten)]
     pub multi_ch_constructor: multi_ch_constructor::Config,
+    #[serde(rename = "contraction")]
+    pub contraction_method: Option<RawContractionMethod>,
     #[serde(rename = "number_of_metric-updates")]
     pub number_of_metric_updates: usize,
     pub monitoring: RawMonitoringConfig,
@@ -278,6 +320,15 @@ pub struct RawContent {
     pub is_err_when_metric_is_zero: Option<bool>,
 }
 
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub enum RawContractionMethod {
+    #[serde(rename = "multi-ch-constructor")]
+    MultiChConstructor,
+    #[serde(rename = "cch")]
+    CustomizableCH,
+}
+
 #[derive(Clone, Debug, Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct RawMonitoringConfig {
@@ -0,0 +1,227 @@
+use crate::{
+    configs::{self, parsing::edges::metrics::UnitInfo, SimpleId},
+    defaults,
+    helpers::err,
+    io::SupportingFileExts,
+    network::{MetricIdx, SnappingFilter},
+};
+use kissunits::{distance::Kilometers, speed::KilometersPerHour};
+use serde::Deserialize;
+use std::{
+    fs::OpenOptions,
+    path::{Path, PathBuf},
+};
+
+/// # Specifying map-matching
+///
+/// Gps-traces are matched onto the graph's edges with a hidden Markov model (see `routing::matching::MapMatcher`).
+/// The metric `distance` has to be given in kilometers (and must not be normalized), because it is compared to great-circle-distances.
+/// All other parameters are optional and given in kilometers (or kilometers per hour).
+#[derive(Clone, Debug)]
+pub struct Config {
+    pub trace_file: Option<PathBuf>,
+    pub distance_idx: MetricIdx,
+    pub search_radius: Kilometers,
+    pub max_candidates: usize,
+    pub gps_sigma: Kilometers,
+    pub transition_beta: Kilometers,
+    pub max_detour: Kilometers,
+    pub max_speed: Option<KilometersPerHour>,
+    pub snapping_filter: SnappingFilter,
+}
+
+impl SupportingFileExts for Config {
+    fn supported_exts<'a>() -> &'a [&'a str] {
+        &["yaml"]
+    }
+}
+
+impl Config {
+    pub fn try_from_str(
+        yaml_str: &str,
+        parsing_cfg: &configs::parsing::Config,
+    ) -> err::Result<Config> {
+        let proto_cfg = {
+            match serde_yaml::from_str(yaml_str) {
+                Ok(proto_cfg) => proto_cfg,
+                Err(e) => {
+                    return Err(err::Msg::from(format!(
+                        "Serde couldn't parse yaml-str due to error: {}",
+                        e
+                    )))
+                }
+            }
+        };
+        Config::try_from_proto(proto_cfg, parsing_cfg)
+    }
+
+    pub fn from_str(yaml_str: &str, parsing_cfg: &configs::parsing::Config) -> Config {
+        match Config::try_from_str(yaml_str, parsing_cfg) {
+            Ok(cfg) => cfg,
+            Err(msg) => panic!("{}", msg),
+        }
+    }
+
+    fn try_from_proto(
+        proto_cfg: ProtoConfig,
+        parsing_cfg: &configs::parsing::Config,
+    ) -> err::Result<Config> {
+        let metrics = &parsing_cfg.edges.metrics;
+        let distance_idx = metrics.try_idx_of(&proto_cfg.distance_id)?;
+        if metrics.units[*distance_idx] != UnitInfo::Kilometers || metrics.are_normalized {
+            return Err(err::Msg::from(format!(
+                "The matching's distance {} should be a metric in (non-normalized) kilometers.",
+                proto_cfg.distance_id
+            )));
+        }
+
+        for (key, value) in &[
+            ("search-radius", proto_cfg.search_radius.0),
+            ("gps-sigma", proto_cfg.gps_sigma.0),
+            ("transition-beta", proto_cfg.transition_beta.0),
+        ] {
+            if value.is_nan() || *value <= 0.0 {
+                return Err(err::Msg::from(format!(
+                    "The matching's {} should be positive, but is {}.",
+                    key, value
+                )));
+            }
+        }
+
+        Ok(Config {
+            trace_file: proto_cfg.trace_file,
+            distance_idx,
+            search_radius: proto_cfg.search_radius,
+            max_candidates: proto_cfg.max_candidates,
+            gps_sigma: proto_cfg.gps_sigma,
+            transition_beta: proto_cfg.transition_beta,
+            max_detour: proto_cfg.max_detour,
+            max_speed: proto_cfg.max_speed,
+            snapping_filter: proto_cfg.snapping_filter,
+        })
+    }
+
+    pub fn try_from_yaml<P: AsRef<Path> + ?Sized>(
+        path: &P,
+        parsing_cfg: &configs::parsing::Config,
+    ) -> err::Result<Config> {
+        let path = path.as_ref();
+        let file = {
+            Config::find_supported_ext(path)?;
+            match OpenOptions::new().read(true).open(path) {
+                Ok(file) => file,
+                Err(e) => {
+                    return Err(err::Msg::from(format!(
+                        "Couldn't open {} due to error: {}",
+                        path.display(),
+                        e
+                    )))
+                }
+            }
+        };
+
+        let proto_cfg = match serde_yaml::from_reader(file) {
+            Ok(proto_cfg) => proto_cfg,
+            Err(e) => {
+                return Err(err::Msg::from(format!(
+                    "Serde couldn't read {} due to error: {}",
+                    path.display(),
+                    e
+                )))
+            }
+        };
+        Config::try_from_proto(proto_cfg, parsing_cfg)
+    }
+
+    pub fn from_yaml<P: AsRef<Path> + ?Sized>(
+        path: &P,
+        parsing_cfg: &configs::parsing::Config,
+    ) -> Config {
+        match Config::try_from_yaml(path, parsing_cfg) {
+            Ok(cfg) => cfg,
+            Err(msg) => panic!("{}", msg),
+        }
+    }
+}
+
+/// Don't deny unknown fields to allow multiple configs in one yaml-file.
+#[derive(Debug, Deserialize)]
+#[serde(from = "RawConfig")]
+pub struct ProtoConfig {
+    pub trace_file: Option<PathBuf>,
+    pub distance_id: SimpleId,
+    pub search_radius: Kilometers,
+    pub max_candidates: usize,
+    pub gps_sigma: Kilometers,
+    pub transition_beta: Kilometers,
+    pub max_detour: Kilometers,
+    pub max_speed: Option<KilometersPerHour>,
+    pub snapping_filter: SnappingFilter,
+}
+
+impl From<RawConfig> for ProtoConfig {
+    fn from(raw_cfg: RawConfig) -> ProtoConfig {
+        let raw_cfg = raw_cfg.matching;
+
+        ProtoConfig {
+            trace_file: raw_cfg.trace_file,
+            distance_id: raw_cfg.distance_id,
+            search_radius: Kilometers(
+                raw_cfg
+                    .search_radius
+                    .unwrap_or(defaults::matching::SEARCH_RADIUS_KM),
+            ),
+            max_candidates: raw_cfg
+                .max_candidates
+                .unwrap_or(defaults::matching::MAX_CANDIDATES),
+            gps_sigma: Kilometers(
+                raw_cfg
+                    .gps_sigma
+                    .unwrap_or(defaults::matching::GPS_SIGMA_KM),
+            ),
+            transition_beta: Kilometers(
+                raw_cfg
+                    .transition_beta
+                    .unwrap_or(defaults::matching::TRANSITION_BETA_KM),
+            ),
+            max_detour: Kilometers(
+                raw_cfg
+                    .max_detour
+                    .unwrap_or(defaults::matching::MAX_DETOUR_KM),
+            ),
+            max_speed: raw_cfg.max_speed.map(KilometersPerHour),
+            snapping_filter: raw_cfg
+                .snapping_filter
+                .unwrap_or(defaults::matching::SNAPPING_FILTER),
+        }
+    }
+}
+
+/// Don't deny unknown fields to allow multiple configs in one yaml-file.
+#[derive(Debug, Deserialize)]
+pub struct RawConfig {
+    pub matching: RawContent,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RawContent {
+    #[serde(rename = "trace-file")]
+    pub trace_file: Option<PathBuf>,
+    #[serde(rename = "distance")]
+    pub distance_id: SimpleId,
+    #[serde(rename = "search-radius")]
+    pub search_radius: Option<f64>,
+    #[serde(rename = "max-candidates")]
+    pub max_candidates: Option<usize>,
+    #[serde(rename = "gps-sigma")]
+    pub gps_sigma: Option<f64>,
+    #[serde(rename = "transition-beta")]
+    pub transition_beta: Option<f64>,
+    #[serde(rename = "max-detour")]
+    pub max_detour: Option<f64>,
+    #[serde(rename = "max-speed")]
+    pub max_speed: Option<f64>,
+    #[serde(rename = "snapping")]
+    pub snapping_filter: Option<SnappingFilter>,
+}
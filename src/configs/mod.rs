@@ -1,12 +1,12 @@
 use serde::Deserialize;
 use std::{fmt, fmt::Display};
 
-#[cfg(feature = "gpl")]
 pub mod balancing;
-#[cfg(feature = "gpl")]
 pub mod evaluating_balance;
+pub mod matching;
 pub mod parsing;
 pub mod routing;
+pub mod server;
 pub mod writing;
 
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Ord, PartialOrd)]
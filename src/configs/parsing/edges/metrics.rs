@@ -1,6 +1,6 @@
 use crate::{
     configs::{parsing::generating::edges::metrics as gen, SimpleId},
-    defaults::capacity::DimVec,
+    defaults::{self, accuracy, capacity::DimVec},
     helpers::err,
     network::MetricIdx,
 };
@@ -9,15 +9,26 @@ use kissunits::{
     time::{Hours, Minutes, Seconds},
 };
 use serde::Deserialize;
+use std::{collections::BTreeMap, convert::TryFrom};
 
 #[derive(Clone, Debug)]
 pub struct Config {
     pub are_normalized: bool,
     pub units: DimVec<UnitInfo>,
     pub ids: DimVec<SimpleId>,
+    /// Storage-precisions of metrics by their (final) ids, other metrics are stored with the default precision.
+    pub precisions: BTreeMap<SimpleId, Precision>,
 }
 
 impl Config {
+    /// The precision, which the metric is stored with in the graph.
+    pub fn precision_of(&self, idx: MetricIdx) -> Precision {
+        match self.precisions.get(&self.ids[*idx]) {
+            Some(precision) => *precision,
+            None => defaults::parsing::METRIC_PRECISION,
+        }
+    }
+
     pub fn try_idx_of<S>(&self, id: S) -> err::Result<MetricIdx>
     where
         S: AsRef<str>,
@@ -57,6 +68,11 @@ pub enum UnitInfo {
     Hours,
     KilometersPerHour,
     LaneCount,
+    /// Factor of at least `1.0` (most comfortable), parsed from a way's tags `surface`,
+    /// `smoothness` and `tracktype` (see `StreetCategory::parse_discomfort`)
+    Discomfort,
+    /// Kilometers weighted by `Discomfort`, e.g. for preferring paved roads
+    DiscomfortKilometers,
     F64,
 }
 
@@ -70,6 +86,8 @@ impl From<ProtoUnitInfo> for UnitInfo {
             ProtoUnitInfo::Hours => UnitInfo::Hours,
             ProtoUnitInfo::KilometersPerHour => UnitInfo::KilometersPerHour,
             ProtoUnitInfo::LaneCount => UnitInfo::LaneCount,
+            ProtoUnitInfo::Discomfort => UnitInfo::Discomfort,
+            ProtoUnitInfo::DiscomfortKilometers => UnitInfo::DiscomfortKilometers,
             ProtoUnitInfo::F64 => UnitInfo::F64,
         }
     }
@@ -85,6 +103,8 @@ impl From<gen::UnitInfo> for UnitInfo {
             gen::UnitInfo::Hours => UnitInfo::Hours,
             gen::UnitInfo::KilometersPerHour => UnitInfo::KilometersPerHour,
             gen::UnitInfo::LaneCount => UnitInfo::LaneCount,
+            gen::UnitInfo::Discomfort => UnitInfo::Discomfort,
+            gen::UnitInfo::DiscomfortKilometers => UnitInfo::DiscomfortKilometers,
             gen::UnitInfo::F64 => UnitInfo::F64,
         }
     }
@@ -100,7 +120,9 @@ impl UnitInfo {
                 | UnitInfo::Minutes
                 | UnitInfo::Hours
                 | UnitInfo::KilometersPerHour
-                | UnitInfo::LaneCount => None,
+                | UnitInfo::LaneCount
+                | UnitInfo::Discomfort
+                | UnitInfo::DiscomfortKilometers => None,
             },
             UnitInfo::Kilometers => match to {
                 UnitInfo::Meters => Some(*Meters::from(Kilometers(raw_value))),
@@ -109,7 +131,9 @@ impl UnitInfo {
                 | UnitInfo::Minutes
                 | UnitInfo::Hours
                 | UnitInfo::KilometersPerHour
-                | UnitInfo::LaneCount => None,
+                | UnitInfo::LaneCount
+                | UnitInfo::Discomfort
+                | UnitInfo::DiscomfortKilometers => None,
             },
             UnitInfo::Seconds => match to {
                 UnitInfo::Seconds | UnitInfo::F64 => Some(raw_value),
@@ -118,7 +142,9 @@ impl UnitInfo {
                 UnitInfo::Meters
                 | UnitInfo::Kilometers
                 | UnitInfo::KilometersPerHour
-                | UnitInfo::LaneCount => None,
+                | UnitInfo::LaneCount
+                | UnitInfo::Discomfort
+                | UnitInfo::DiscomfortKilometers => None,
             },
             UnitInfo::Minutes => match to {
                 UnitInfo::Minutes | UnitInfo::F64 => Some(raw_value),
@@ -127,7 +153,9 @@ impl UnitInfo {
                 UnitInfo::Meters
                 | UnitInfo::Kilometers
                 | UnitInfo::KilometersPerHour
-                | UnitInfo::LaneCount => None,
+                | UnitInfo::LaneCount
+                | UnitInfo::Discomfort
+                | UnitInfo::DiscomfortKilometers => None,
             },
             UnitInfo::Hours => match to {
                 UnitInfo::Hours | UnitInfo::F64 => Some(raw_value),
@@ -136,7 +164,9 @@ impl UnitInfo {
                 UnitInfo::Meters
                 | UnitInfo::Kilometers
                 | UnitInfo::KilometersPerHour
-                | UnitInfo::LaneCount => None,
+                | UnitInfo::LaneCount
+                | UnitInfo::Discomfort
+                | UnitInfo::DiscomfortKilometers => None,
             },
             UnitInfo::KilometersPerHour => match to {
                 UnitInfo::KilometersPerHour | UnitInfo::F64 => Some(raw_value),
@@ -145,7 +175,9 @@ impl UnitInfo {
                 | UnitInfo::Seconds
                 | UnitInfo::Minutes
                 | UnitInfo::Hours
-                | UnitInfo::LaneCount => None,
+                | UnitInfo::LaneCount
+                | UnitInfo::Discomfort
+                | UnitInfo::DiscomfortKilometers => None,
             },
             UnitInfo::LaneCount => match to {
                 UnitInfo::LaneCount | UnitInfo::F64 => Some(raw_value),
@@ -154,7 +186,31 @@ impl UnitInfo {
                 | UnitInfo::Seconds
                 | UnitInfo::Minutes
                 | UnitInfo::Hours
-                | UnitInfo::KilometersPerHour => None,
+                | UnitInfo::KilometersPerHour
+                | UnitInfo::Discomfort
+                | UnitInfo::DiscomfortKilometers => None,
+            },
+            UnitInfo::Discomfort => match to {
+                UnitInfo::Discomfort | UnitInfo::F64 => Some(raw_value),
+                UnitInfo::Meters
+                | UnitInfo::Kilometers
+                | UnitInfo::Seconds
+                | UnitInfo::Minutes
+                | UnitInfo::Hours
+                | UnitInfo::KilometersPerHour
+                | UnitInfo::LaneCount
+                | UnitInfo::DiscomfortKilometers => None,
+            },
+            UnitInfo::DiscomfortKilometers => match to {
+                UnitInfo::DiscomfortKilometers | UnitInfo::F64 => Some(raw_value),
+                UnitInfo::Meters
+                | UnitInfo::Kilometers
+                | UnitInfo::Seconds
+                | UnitInfo::Minutes
+                | UnitInfo::Hours
+                | UnitInfo::KilometersPerHour
+                | UnitInfo::LaneCount
+                | UnitInfo::Discomfort => None,
             },
             UnitInfo::F64 => Some(raw_value),
         };
@@ -174,6 +230,98 @@ impl UnitInfo {
     }
 }
 
+/// How a metric's values are stored in the graph.
+/// Values are always accessed as `f64`, but less precision saves memory.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Precision {
+    F64,
+    F32,
+    /// Fixed-point values, storing `value / scale` rounded as `u32`.
+    /// Normalized metrics are normalized before being stored.
+    U32 {
+        scale: f64,
+    },
+}
+
+impl Precision {
+    /// The absolute tolerance, which values of this precision should be compared with (see `Approx::eq_within(...)`).
+    pub fn tolerance(&self, value: f64) -> f64 {
+        match self {
+            Precision::F64 => accuracy::F64_ABS,
+            Precision::F32 => (accuracy::F32_REL * value.abs()).max(accuracy::F64_ABS),
+            Precision::U32 { scale } => (0.5 * scale).max(accuracy::F64_ABS),
+        }
+    }
+}
+
+impl TryFrom<ProtoPrecision> for Precision {
+    type Error = err::Msg;
+
+    fn try_from(proto_precision: ProtoPrecision) -> err::Result<Precision> {
+        match (proto_precision.precision, proto_precision.scale) {
+            (ProtoPrecisionInfo::F64, None) => Ok(Precision::F64),
+            (ProtoPrecisionInfo::F32, None) => Ok(Precision::F32),
+            (ProtoPrecisionInfo::U32, Some(scale)) => {
+                if scale.is_finite() && scale > 0.0 {
+                    Ok(Precision::U32 { scale })
+                } else {
+                    Err(format!(
+                        "The scale {} of metric {} has to be positive.",
+                        scale, proto_precision.id
+                    )
+                    .into())
+                }
+            }
+            (ProtoPrecisionInfo::U32, None) => Err(format!(
+                "The precision U32 of metric {} needs a scale.",
+                proto_precision.id
+            )
+            .into()),
+            (ProtoPrecisionInfo::F64, Some(_)) | (ProtoPrecisionInfo::F32, Some(_)) => {
+                Err(format!(
+                    "Only the precision U32 has a scale, but metric {} has a scale.",
+                    proto_precision.id
+                )
+                .into())
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ProtoPrecision {
+    pub id: SimpleId,
+    pub precision: ProtoPrecisionInfo,
+    pub scale: Option<f64>,
+}
+
+impl From<RawPrecision> for ProtoPrecision {
+    fn from(raw_precision: RawPrecision) -> ProtoPrecision {
+        ProtoPrecision {
+            id: raw_precision.id,
+            precision: ProtoPrecisionInfo::from(raw_precision.precision),
+            scale: raw_precision.scale,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum ProtoPrecisionInfo {
+    F64,
+    F32,
+    U32,
+}
+
+impl From<RawPrecisionInfo> for ProtoPrecisionInfo {
+    fn from(raw_info: RawPrecisionInfo) -> ProtoPrecisionInfo {
+        match raw_info {
+            RawPrecisionInfo::F64 => ProtoPrecisionInfo::F64,
+            RawPrecisionInfo::F32 => ProtoPrecisionInfo::F32,
+            RawPrecisionInfo::U32 => ProtoPrecisionInfo::U32,
+        }
+    }
+}
+
 #[derive(Copy, Clone, Debug, Deserialize, Eq, PartialEq)]
 pub enum ProtoUnitInfo {
     Meters,
@@ -183,6 +331,8 @@ pub enum ProtoUnitInfo {
     Hours,
     KilometersPerHour,
     LaneCount,
+    Discomfort,
+    DiscomfortKilometers,
     F64,
 }
 
@@ -196,6 +346,8 @@ impl From<RawUnitInfo> for ProtoUnitInfo {
             RawUnitInfo::Hours => ProtoUnitInfo::Hours,
             RawUnitInfo::KilometersPerHour => ProtoUnitInfo::KilometersPerHour,
             RawUnitInfo::LaneCount => ProtoUnitInfo::LaneCount,
+            RawUnitInfo::Discomfort => ProtoUnitInfo::Discomfort,
+            RawUnitInfo::DiscomfortKilometers => ProtoUnitInfo::DiscomfortKilometers,
             RawUnitInfo::F64 => ProtoUnitInfo::F64,
         }
     }
@@ -210,5 +362,22 @@ pub enum RawUnitInfo {
     Hours,
     KilometersPerHour,
     LaneCount,
+    Discomfort,
+    DiscomfortKilometers,
+    F64,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RawPrecision {
+    pub id: SimpleId,
+    pub precision: RawPrecisionInfo,
+    pub scale: Option<f64>,
+}
+
+#[derive(Copy, Clone, Debug, Deserialize, Eq, PartialEq)]
+pub enum RawPrecisionInfo {
     F64,
+    F32,
+    U32,
 }
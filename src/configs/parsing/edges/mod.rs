@@ -5,7 +5,8 @@ use crate::{
 };
 use serde::Deserialize;
 pub mod metrics;
-use std::convert::TryFrom;
+pub mod ways;
+use std::{collections::BTreeMap, convert::TryFrom};
 
 #[derive(Clone, Debug)]
 pub struct Config {
@@ -14,6 +15,12 @@ pub struct Config {
 
     // store only metrics for quick access
     pub metrics: metrics::Config,
+
+    // street-names and -refs of pbf-ways, e.g. for guidance
+    pub is_keeping_streets: bool,
+
+    // ids and tags of pbf-ways, e.g. for finding edges in osm
+    pub ways: Option<ways::Config>,
 }
 
 impl TryFrom<ProtoConfig> for Config {
@@ -58,6 +65,39 @@ impl TryFrom<ProtoConfig> for Config {
             }
         }
 
+        // way-columns are written like categories, hence their ids have to be unique as well
+
+        if let Some(proto_ways) = &proto_cfg.ways {
+            let way_ids: Vec<&SimpleId> = Some(&proto_ways.id)
+                .into_iter()
+                .chain(proto_ways.tags.iter())
+                .collect();
+            for (i, way_id) in way_ids.iter().enumerate() {
+                let is_duplicate = way_ids[(i + 1)..].contains(way_id)
+                    || proto_cfg.categories.iter().any(|category| match category {
+                        ProtoCategory::Ignored => false,
+                        ProtoCategory::Meta { info: _, id }
+                        | ProtoCategory::Metric { unit: _, id } => id == *way_id,
+                    });
+                if is_duplicate {
+                    return Err(format!("Config has duplicate id: {}", way_id).into());
+                }
+            }
+        }
+
+        // precisions are checked against the final metric-ids when finalizing the graph
+
+        let mut precisions = BTreeMap::new();
+        for proto_precision in proto_cfg.precisions.into_iter() {
+            let id = proto_precision.id.clone();
+            if precisions
+                .insert(id.clone(), metrics::Precision::try_from(proto_precision)?)
+                .is_some()
+            {
+                return Err(format!("Config has duplicate precision of metric {}", id).into());
+            }
+        }
+
         // Fill categories, ids and create mapping: id -> idx
 
         for category in proto_cfg.categories.into_iter() {
@@ -85,7 +125,12 @@ impl TryFrom<ProtoConfig> for Config {
                     .unwrap_or(defaults::parsing::WILL_NORMALIZE_METRICS_BY_MEAN),
                 units: metric_units,
                 ids: metric_ids,
+                precisions,
             },
+            is_keeping_streets: proto_cfg
+                .is_keeping_streets
+                .unwrap_or(defaults::parsing::WILL_KEEP_STREETS),
+            ways: proto_cfg.ways.map(ways::Config::from),
         })
     }
 }
@@ -182,6 +227,9 @@ impl From<generating::edges::MetaInfo> for MetaInfo {
 #[serde(from = "RawConfig", deny_unknown_fields)]
 pub struct ProtoConfig {
     pub are_metrics_normalized: Option<bool>,
+    pub is_keeping_streets: Option<bool>,
+    pub ways: Option<ways::ProtoConfig>,
+    pub precisions: Vec<metrics::ProtoPrecision>,
     pub categories: Vec<ProtoCategory>,
 }
 
@@ -189,6 +237,14 @@ impl From<RawConfig> for ProtoConfig {
     fn from(raw_cfg: RawConfig) -> ProtoConfig {
         ProtoConfig {
             are_metrics_normalized: raw_cfg.are_metrics_normalized,
+            is_keeping_streets: raw_cfg.is_keeping_streets,
+            ways: raw_cfg.ways.map(ways::ProtoConfig::from),
+            precisions: raw_cfg
+                .precisions
+                .unwrap_or_default()
+                .into_iter()
+                .map(metrics::ProtoPrecision::from)
+                .collect(),
             categories: raw_cfg.data.into_iter().map(ProtoCategory::from).collect(),
         }
     }
@@ -250,6 +306,10 @@ impl From<RawMetaInfo> for ProtoMetaInfo {
 pub struct RawConfig {
     #[serde(rename = "will_normalize_metrics_by_mean")]
     are_metrics_normalized: Option<bool>,
+    #[serde(rename = "will_keep_streets")]
+    is_keeping_streets: Option<bool>,
+    ways: Option<ways::RawConfig>,
+    precisions: Option<Vec<metrics::RawPrecision>>,
     data: Vec<RawCategory>,
 }
 
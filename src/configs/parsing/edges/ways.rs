@@ -0,0 +1,53 @@
+use crate::configs::SimpleId;
+use serde::Deserialize;
+
+/// Osm-ways, whose ids and tags are kept for their edges as part of their streets (see `network::Street`).
+///
+/// The way-id can be written as edge-column (e.g. via `io::network::edges::Writer`) using the `id`
+/// as column-id, while tags are free text and hence only accessible via `network::StreetAccessor`.
+#[derive(Clone, Debug)]
+pub struct Config {
+    pub id: SimpleId,
+    pub tags: Vec<SimpleId>,
+}
+
+impl Config {
+    /// Returns the position of the tag in every `network::Street`'s tags.
+    pub fn tag_idx_of<S>(&self, key: S) -> Option<usize>
+    where
+        S: AsRef<str>,
+    {
+        self.tags.iter().position(|tag| tag.0 == key.as_ref())
+    }
+}
+
+impl From<ProtoConfig> for Config {
+    fn from(proto_cfg: ProtoConfig) -> Config {
+        Config {
+            id: proto_cfg.id,
+            tags: proto_cfg.tags,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ProtoConfig {
+    pub id: SimpleId,
+    pub tags: Vec<SimpleId>,
+}
+
+impl From<RawConfig> for ProtoConfig {
+    fn from(raw_cfg: RawConfig) -> ProtoConfig {
+        ProtoConfig {
+            id: raw_cfg.id,
+            tags: raw_cfg.tags.unwrap_or_default(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RawConfig {
+    pub id: SimpleId,
+    pub tags: Option<Vec<SimpleId>>,
+}
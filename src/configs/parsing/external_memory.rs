@@ -0,0 +1,72 @@
+use crate::{defaults, helpers::err};
+use serde::Deserialize;
+use std::{convert::TryFrom, path::PathBuf};
+
+/// Builds the graph with bounded memory (see `network::GraphBuilder`), e.g. for continent-scale pbf-files.
+///
+/// Proto-edges are spilled as sorted runs into a temporary directory and merged by their src-id when finalizing.
+/// For pbf-files, nodes' coordinates are spilled as well, so the map-file is read only once.
+/// The final graph itself has to fit into memory.
+#[derive(Clone, Debug)]
+pub struct Config {
+    /// Temporary files are created in a new subdirectory, which is removed afterwards.
+    pub dir: PathBuf,
+    /// Maximum number of bytes of buffered proto-edges before they are spilled.
+    pub memory_budget_b: usize,
+    /// Maximum number of runs, which are merged at once.
+    pub max_merged_runs: usize,
+}
+
+impl TryFrom<ProtoConfig> for Config {
+    type Error = err::Msg;
+
+    fn try_from(proto_cfg: ProtoConfig) -> err::Result<Config> {
+        let memory_budget_mb = proto_cfg
+            .memory_budget_mb
+            .unwrap_or(defaults::parsing::external_memory::MEMORY_BUDGET_MB);
+        if memory_budget_mb == 0 {
+            return Err("The memory-budget of external-memory has to be positive.".into());
+        }
+        let max_merged_runs = proto_cfg
+            .max_merged_runs
+            .unwrap_or(defaults::parsing::external_memory::MAX_MERGED_RUNS);
+        if max_merged_runs < 2 {
+            return Err("The max-merged-runs of external-memory has to be at least 2.".into());
+        }
+
+        Ok(Config {
+            dir: proto_cfg
+                .dir
+                .unwrap_or_else(defaults::parsing::external_memory::dir),
+            memory_budget_b: memory_budget_mb * 1_000_000,
+            max_merged_runs,
+        })
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ProtoConfig {
+    pub dir: Option<PathBuf>,
+    pub memory_budget_mb: Option<usize>,
+    pub max_merged_runs: Option<usize>,
+}
+
+impl From<RawConfig> for ProtoConfig {
+    fn from(raw_cfg: RawConfig) -> ProtoConfig {
+        ProtoConfig {
+            dir: raw_cfg.dir,
+            memory_budget_mb: raw_cfg.memory_budget_mb,
+            max_merged_runs: raw_cfg.max_merged_runs,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RawConfig {
+    pub dir: Option<PathBuf>,
+    #[serde(rename = "memory-budget-mb")]
+    pub memory_budget_mb: Option<usize>,
+    #[serde(rename = "max-merged-runs")]
+    pub max_merged_runs: Option<usize>,
+}
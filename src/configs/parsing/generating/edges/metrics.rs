@@ -36,6 +36,10 @@ pub enum UnitInfo {
     Hours,
     KilometersPerHour,
     LaneCount,
+    /// Factor of at least `1.0` (most comfortable), parsed from a way's surface-tags
+    Discomfort,
+    /// Kilometers weighted by `Discomfort`, calculated from both
+    DiscomfortKilometers,
     F64,
 }
 
@@ -49,6 +53,8 @@ impl From<ProtoUnitInfo> for UnitInfo {
             ProtoUnitInfo::Hours => UnitInfo::Hours,
             ProtoUnitInfo::KilometersPerHour => UnitInfo::KilometersPerHour,
             ProtoUnitInfo::LaneCount => UnitInfo::LaneCount,
+            ProtoUnitInfo::Discomfort => UnitInfo::Discomfort,
+            ProtoUnitInfo::DiscomfortKilometers => UnitInfo::DiscomfortKilometers,
             ProtoUnitInfo::F64 => UnitInfo::F64,
         }
     }
@@ -65,6 +71,8 @@ impl UnitInfo {
                 UnitInfo::Hours => None,
                 UnitInfo::KilometersPerHour => None,
                 UnitInfo::LaneCount => None,
+                UnitInfo::Discomfort => None,
+                UnitInfo::DiscomfortKilometers => None,
                 UnitInfo::F64 => Some(raw_value),
             },
             UnitInfo::Kilometers => match to {
@@ -75,6 +83,8 @@ impl UnitInfo {
                 UnitInfo::Hours => None,
                 UnitInfo::KilometersPerHour => None,
                 UnitInfo::LaneCount => None,
+                UnitInfo::Discomfort => None,
+                UnitInfo::DiscomfortKilometers => None,
                 UnitInfo::F64 => Some(raw_value),
             },
             UnitInfo::Seconds => match to {
@@ -85,6 +95,8 @@ impl UnitInfo {
                 UnitInfo::Hours => Some(*Hours::from(Seconds(raw_value))),
                 UnitInfo::KilometersPerHour => None,
                 UnitInfo::LaneCount => None,
+                UnitInfo::Discomfort => None,
+                UnitInfo::DiscomfortKilometers => None,
                 UnitInfo::F64 => Some(raw_value),
             },
             UnitInfo::Minutes => match to {
@@ -95,6 +107,8 @@ impl UnitInfo {
                 UnitInfo::Hours => Some(*Hours::from(Minutes(raw_value))),
                 UnitInfo::KilometersPerHour => None,
                 UnitInfo::LaneCount => None,
+                UnitInfo::Discomfort => None,
+                UnitInfo::DiscomfortKilometers => None,
                 UnitInfo::F64 => Some(raw_value),
             },
             UnitInfo::Hours => match to {
@@ -105,6 +119,8 @@ impl UnitInfo {
                 UnitInfo::Hours => Some(raw_value),
                 UnitInfo::KilometersPerHour => None,
                 UnitInfo::LaneCount => None,
+                UnitInfo::Discomfort => None,
+                UnitInfo::DiscomfortKilometers => None,
                 UnitInfo::F64 => Some(raw_value),
             },
             UnitInfo::KilometersPerHour => match to {
@@ -115,6 +131,8 @@ impl UnitInfo {
                 UnitInfo::Hours => None,
                 UnitInfo::KilometersPerHour => Some(raw_value),
                 UnitInfo::LaneCount => None,
+                UnitInfo::Discomfort => None,
+                UnitInfo::DiscomfortKilometers => None,
                 UnitInfo::F64 => Some(raw_value),
             },
             UnitInfo::LaneCount => match to {
@@ -125,6 +143,32 @@ impl UnitInfo {
                 UnitInfo::Hours => None,
                 UnitInfo::KilometersPerHour => None,
                 UnitInfo::LaneCount => Some(raw_value),
+                UnitInfo::Discomfort => None,
+                UnitInfo::DiscomfortKilometers => None,
+                UnitInfo::F64 => Some(raw_value),
+            },
+            UnitInfo::Discomfort => match to {
+                UnitInfo::Meters => None,
+                UnitInfo::Kilometers => None,
+                UnitInfo::Seconds => None,
+                UnitInfo::Minutes => None,
+                UnitInfo::Hours => None,
+                UnitInfo::KilometersPerHour => None,
+                UnitInfo::LaneCount => None,
+                UnitInfo::Discomfort => Some(raw_value),
+                UnitInfo::DiscomfortKilometers => None,
+                UnitInfo::F64 => Some(raw_value),
+            },
+            UnitInfo::DiscomfortKilometers => match to {
+                UnitInfo::Meters => None,
+                UnitInfo::Kilometers => None,
+                UnitInfo::Seconds => None,
+                UnitInfo::Minutes => None,
+                UnitInfo::Hours => None,
+                UnitInfo::KilometersPerHour => None,
+                UnitInfo::LaneCount => None,
+                UnitInfo::Discomfort => None,
+                UnitInfo::DiscomfortKilometers => Some(raw_value),
                 UnitInfo::F64 => Some(raw_value),
             },
             UnitInfo::F64 => Some(raw_value),
@@ -156,6 +200,8 @@ impl UnitInfo {
                     UnitInfo::Hours => false,
                     UnitInfo::KilometersPerHour => false,
                     UnitInfo::LaneCount => false,
+                    UnitInfo::Discomfort => false,
+                    UnitInfo::DiscomfortKilometers => false,
                     UnitInfo::F64 => false,
                 } && match unit_b {
                     UnitInfo::Meters => false,
@@ -165,6 +211,8 @@ impl UnitInfo {
                     UnitInfo::Hours => false,
                     UnitInfo::KilometersPerHour => true,
                     UnitInfo::LaneCount => false,
+                    UnitInfo::Discomfort => false,
+                    UnitInfo::DiscomfortKilometers => false,
                     UnitInfo::F64 => false,
                 } {
                     Some(*Seconds::from(Kilometers(raw_a) / KilometersPerHour(raw_b)))
@@ -181,6 +229,8 @@ impl UnitInfo {
                     UnitInfo::Hours => false,
                     UnitInfo::KilometersPerHour => false,
                     UnitInfo::LaneCount => false,
+                    UnitInfo::Discomfort => false,
+                    UnitInfo::DiscomfortKilometers => false,
                     UnitInfo::F64 => false,
                 } && match unit_b {
                     UnitInfo::Meters => false,
@@ -190,6 +240,8 @@ impl UnitInfo {
                     UnitInfo::Hours => false,
                     UnitInfo::KilometersPerHour => true,
                     UnitInfo::LaneCount => false,
+                    UnitInfo::Discomfort => false,
+                    UnitInfo::DiscomfortKilometers => false,
                     UnitInfo::F64 => false,
                 } {
                     Some(*Minutes::from(Kilometers(raw_a) / KilometersPerHour(raw_b)))
@@ -206,6 +258,8 @@ impl UnitInfo {
                     UnitInfo::Hours => false,
                     UnitInfo::KilometersPerHour => false,
                     UnitInfo::LaneCount => false,
+                    UnitInfo::Discomfort => false,
+                    UnitInfo::DiscomfortKilometers => false,
                     UnitInfo::F64 => false,
                 } && match unit_b {
                     UnitInfo::Meters => false,
@@ -215,6 +269,8 @@ impl UnitInfo {
                     UnitInfo::Hours => false,
                     UnitInfo::KilometersPerHour => true,
                     UnitInfo::LaneCount => false,
+                    UnitInfo::Discomfort => false,
+                    UnitInfo::DiscomfortKilometers => false,
                     UnitInfo::F64 => false,
                 } {
                     Some(*Hours::from(Kilometers(raw_a) / KilometersPerHour(raw_b)))
@@ -231,6 +287,8 @@ impl UnitInfo {
                     UnitInfo::Hours => false,
                     UnitInfo::KilometersPerHour => false,
                     UnitInfo::LaneCount => false,
+                    UnitInfo::Discomfort => false,
+                    UnitInfo::DiscomfortKilometers => false,
                     UnitInfo::F64 => false,
                 } && match unit_b {
                     UnitInfo::Meters => false,
@@ -240,6 +298,8 @@ impl UnitInfo {
                     UnitInfo::Hours => true,
                     UnitInfo::KilometersPerHour => false,
                     UnitInfo::LaneCount => false,
+                    UnitInfo::Discomfort => false,
+                    UnitInfo::DiscomfortKilometers => false,
                     UnitInfo::F64 => false,
                 } {
                     Some(*KilometersPerHour::from(Kilometers(raw_a) / Hours(raw_b)))
@@ -248,6 +308,37 @@ impl UnitInfo {
                 }
             }
             UnitInfo::LaneCount => None,
+            UnitInfo::Discomfort => None,
+            UnitInfo::DiscomfortKilometers => {
+                if match unit_a {
+                    UnitInfo::Meters => false,
+                    UnitInfo::Kilometers => true,
+                    UnitInfo::Seconds => false,
+                    UnitInfo::Minutes => false,
+                    UnitInfo::Hours => false,
+                    UnitInfo::KilometersPerHour => false,
+                    UnitInfo::LaneCount => false,
+                    UnitInfo::Discomfort => false,
+                    UnitInfo::DiscomfortKilometers => false,
+                    UnitInfo::F64 => false,
+                } && match unit_b {
+                    UnitInfo::Meters => false,
+                    UnitInfo::Kilometers => false,
+                    UnitInfo::Seconds => false,
+                    UnitInfo::Minutes => false,
+                    UnitInfo::Hours => false,
+                    UnitInfo::KilometersPerHour => false,
+                    UnitInfo::LaneCount => false,
+                    UnitInfo::Discomfort => true,
+                    UnitInfo::DiscomfortKilometers => false,
+                    UnitInfo::F64 => false,
+                } {
+                    // the distance is weighted by the edge's discomfort (see `UnitInfo::Discomfort`)
+                    Some(raw_a * raw_b)
+                } else {
+                    None
+                }
+            }
             UnitInfo::F64 => None,
         };
 
@@ -287,6 +378,8 @@ pub enum ProtoUnitInfo {
     Hours,
     KilometersPerHour,
     LaneCount,
+    Discomfort,
+    DiscomfortKilometers,
     F64,
 }
 
@@ -300,6 +393,8 @@ impl From<RawUnitInfo> for ProtoUnitInfo {
             RawUnitInfo::Hours => ProtoUnitInfo::Hours,
             RawUnitInfo::KilometersPerHour => ProtoUnitInfo::KilometersPerHour,
             RawUnitInfo::LaneCount => ProtoUnitInfo::LaneCount,
+            RawUnitInfo::Discomfort => ProtoUnitInfo::Discomfort,
+            RawUnitInfo::DiscomfortKilometers => ProtoUnitInfo::DiscomfortKilometers,
             RawUnitInfo::F64 => ProtoUnitInfo::F64,
         }
     }
@@ -320,5 +415,7 @@ pub enum RawUnitInfo {
     Hours,
     KilometersPerHour,
     LaneCount,
+    Discomfort,
+    DiscomfortKilometers,
     F64,
 }
@@ -1,6 +1,8 @@
 use crate::{
+    defaults,
     helpers::err,
     io::{network::graph::Parser, SupportingFileExts},
+    network::NodeOrdering,
 };
 use serde::Deserialize;
 use std::{
@@ -10,6 +12,7 @@ use std::{
 };
 
 pub mod edges;
+pub mod external_memory;
 pub mod generating;
 pub mod nodes;
 pub mod vehicles;
@@ -33,6 +36,12 @@ pub struct Config {
     pub nodes: nodes::Config,
     pub edges: edges::Config,
     pub generating: Option<generating::Config>,
+    /// If provided, the graph is built with bounded memory.
+    pub external_memory: Option<external_memory::Config>,
+    /// Number of threads processing the map-file's blocks and finalizing the graph.
+    pub num_threads: usize,
+    /// Order of the graph's nodes in memory, which is applied when finalizing the graph.
+    pub node_ordering: NodeOrdering,
 }
 
 impl SupportingFileExts for Config {
@@ -96,6 +105,19 @@ impl TryFrom<ProtoConfig> for Config {
             nodes: nodes::Config::from(proto_cfg.nodes),
             edges: edges::Config::try_from(proto_cfg.edges)?,
             generating: proto_cfg.generating.map(generating::Config::from),
+            external_memory: match proto_cfg.external_memory {
+                Some(proto_external_memory) => {
+                    Some(external_memory::Config::try_from(proto_external_memory)?)
+                }
+                None => None,
+            },
+            num_threads: proto_cfg
+                .num_threads
+                .unwrap_or_else(defaults::parsing::num_threads)
+                .max(1),
+            node_ordering: proto_cfg
+                .node_ordering
+                .unwrap_or(defaults::parsing::NODE_ORDERING),
         })
     }
 }
@@ -108,6 +130,9 @@ pub struct ProtoConfig {
     pub nodes: nodes::ProtoConfig,
     pub edges: edges::ProtoConfig,
     pub generating: Option<generating::ProtoConfig>,
+    pub external_memory: Option<external_memory::ProtoConfig>,
+    pub num_threads: Option<usize>,
+    pub node_ordering: Option<NodeOrdering>,
 }
 
 impl From<RawConfig> for ProtoConfig {
@@ -120,6 +145,11 @@ impl From<RawConfig> for ProtoConfig {
             nodes: nodes::ProtoConfig::from(raw_cfg.nodes),
             edges: edges::ProtoConfig::from(raw_cfg.edges),
             generating: raw_cfg.generating.map(generating::ProtoConfig::from),
+            external_memory: raw_cfg
+                .external_memory
+                .map(external_memory::ProtoConfig::from),
+            num_threads: raw_cfg.num_threads,
+            node_ordering: raw_cfg.node_ordering,
         }
     }
 }
@@ -139,4 +169,10 @@ pub struct RawContent {
     pub nodes: nodes::RawConfig,
     pub edges: edges::RawConfig,
     pub generating: Option<generating::RawConfig>,
+    #[serde(rename = "external-memory")]
+    pub external_memory: Option<external_memory::RawConfig>,
+    #[serde(rename = "number_of_threads")]
+    pub num_threads: Option<usize>,
+    #[serde(rename = "node-ordering")]
+    pub node_ordering: Option<NodeOrdering>,
 }
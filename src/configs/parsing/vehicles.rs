@@ -1,10 +1,26 @@
 use crate::{defaults, network::vehicles::Category as VehicleCategory};
 use serde::Deserialize;
+use std::collections::BTreeMap;
 
 #[derive(Clone, Debug)]
 pub struct Config {
     pub category: VehicleCategory,
     pub are_drivers_picky: bool,
+    /// Factors of the parsed speed (`KilometersPerHour`) per value of the osm-tag `surface`
+    pub surface_speed_factors: BTreeMap<String, f64>,
+}
+
+impl Config {
+    /// Returns the factor of the speed on the given surface (osm-tag `surface`).
+    pub fn surface_speed_factor_of(&self, surface: Option<&str>) -> f64 {
+        match surface.and_then(|surface| {
+            self.surface_speed_factors
+                .get(&surface.trim().to_ascii_lowercase())
+        }) {
+            Some(factor) => *factor,
+            None => defaults::parsing::vehicles::SURFACE_SPEED_FACTOR,
+        }
+    }
 }
 
 impl Default for Config {
@@ -12,6 +28,7 @@ impl Default for Config {
         Config {
             category: defaults::parsing::vehicles::CATEGORY,
             are_drivers_picky: defaults::parsing::vehicles::ARE_DRIVERS_PICKY,
+            surface_speed_factors: BTreeMap::new(),
         }
     }
 }
@@ -21,6 +38,7 @@ impl From<ProtoConfig> for Config {
         Config {
             category: proto_cfg.category,
             are_drivers_picky: proto_cfg.are_drivers_picky,
+            surface_speed_factors: proto_cfg.surface_speed_factors,
         }
     }
 }
@@ -29,6 +47,7 @@ impl From<ProtoConfig> for Config {
 pub struct ProtoConfig {
     pub category: VehicleCategory,
     pub are_drivers_picky: bool,
+    pub surface_speed_factors: BTreeMap<String, f64>,
 }
 
 impl From<RawConfig> for ProtoConfig {
@@ -36,6 +55,12 @@ impl From<RawConfig> for ProtoConfig {
         ProtoConfig {
             category: raw_cfg.category,
             are_drivers_picky: raw_cfg.are_drivers_picky,
+            surface_speed_factors: raw_cfg
+                .surface_speed_factors
+                .unwrap_or_default()
+                .into_iter()
+                .map(|(surface, factor)| (surface.trim().to_ascii_lowercase(), factor))
+                .collect(),
         }
     }
 }
@@ -45,4 +70,5 @@ impl From<RawConfig> for ProtoConfig {
 pub struct RawConfig {
     pub category: VehicleCategory,
     pub are_drivers_picky: bool,
+    pub surface_speed_factors: Option<BTreeMap<String, f64>>,
 }
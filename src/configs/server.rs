@@ -0,0 +1,130 @@
+use crate::{defaults, helpers::err, io::SupportingFileExts, network::SnappingFilter};
+use serde::Deserialize;
+use std::{fs::OpenOptions, path::Path};
+
+#[derive(Clone, Debug)]
+pub struct Config {
+    pub address: String,
+    pub num_threads: usize,
+    pub snapping_filter: SnappingFilter,
+    /// Matrix-requests with more route-pairs (srcs times dsts) are rejected.
+    pub max_matrix_size: usize,
+}
+
+impl SupportingFileExts for Config {
+    fn supported_exts<'a>() -> &'a [&'a str] {
+        &["yaml"]
+    }
+}
+
+impl Config {
+    pub fn try_from_str(yaml_str: &str) -> err::Result<Config> {
+        let proto_cfg: ProtoConfig = {
+            match serde_yaml::from_str(yaml_str) {
+                Ok(proto_cfg) => proto_cfg,
+                Err(e) => {
+                    return Err(err::Msg::from(format!(
+                        "Serde couldn't parse yaml-str due to error: {}",
+                        e
+                    )))
+                }
+            }
+        };
+        Ok(Config::from(proto_cfg))
+    }
+
+    pub fn try_from_yaml<P: AsRef<Path> + ?Sized>(path: &P) -> err::Result<Config> {
+        let path = path.as_ref();
+        let file = {
+            Config::find_supported_ext(path)?;
+            match OpenOptions::new().read(true).open(path) {
+                Ok(file) => file,
+                Err(e) => {
+                    return Err(err::Msg::from(format!(
+                        "Couldn't open {} due to error: {}",
+                        path.display(),
+                        e
+                    )))
+                }
+            }
+        };
+
+        let proto_cfg: ProtoConfig = match serde_yaml::from_reader(file) {
+            Ok(proto_cfg) => proto_cfg,
+            Err(e) => {
+                return Err(err::Msg::from(format!(
+                    "Serde couldn't read {} due to error: {}",
+                    path.display(),
+                    e
+                )))
+            }
+        };
+        Ok(Config::from(proto_cfg))
+    }
+
+    pub fn from_yaml<P: AsRef<Path> + ?Sized>(path: &P) -> Config {
+        match Config::try_from_yaml(path) {
+            Ok(cfg) => cfg,
+            Err(msg) => panic!("{}", msg),
+        }
+    }
+}
+
+impl From<ProtoConfig> for Config {
+    fn from(proto_cfg: ProtoConfig) -> Config {
+        Config {
+            address: proto_cfg
+                .address
+                .unwrap_or_else(|| String::from(defaults::server::ADDRESS)),
+            num_threads: proto_cfg
+                .num_threads
+                .unwrap_or(defaults::server::NUM_THREADS),
+            snapping_filter: proto_cfg
+                .snapping_filter
+                .unwrap_or(defaults::server::SNAPPING_FILTER),
+            max_matrix_size: proto_cfg
+                .max_matrix_size
+                .unwrap_or(defaults::server::MAX_MATRIX_SIZE),
+        }
+    }
+}
+
+/// Don't deny unknown fields to allow multiple configs in one yaml-file.
+#[derive(Debug, Deserialize)]
+#[serde(from = "RawConfig")]
+pub struct ProtoConfig {
+    pub address: Option<String>,
+    pub num_threads: Option<usize>,
+    pub snapping_filter: Option<SnappingFilter>,
+    pub max_matrix_size: Option<usize>,
+}
+
+impl From<RawConfig> for ProtoConfig {
+    fn from(raw_cfg: RawConfig) -> ProtoConfig {
+        let raw_cfg = raw_cfg.server;
+
+        ProtoConfig {
+            address: raw_cfg.address,
+            num_threads: raw_cfg.num_threads,
+            snapping_filter: raw_cfg.snapping_filter,
+            max_matrix_size: raw_cfg.max_matrix_size,
+        }
+    }
+}
+
+/// Don't deny unknown fields to allow multiple configs in one yaml-file.
+#[derive(Debug, Deserialize)]
+pub struct RawConfig {
+    pub server: RawContent,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RawContent {
+    pub address: Option<String>,
+    #[serde(rename = "number_of_threads")]
+    pub num_threads: Option<usize>,
+    #[serde(rename = "snapping")]
+    pub snapping_filter: Option<SnappingFilter>,
+    pub max_matrix_size: Option<usize>,
+}
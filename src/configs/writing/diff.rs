@@ -0,0 +1,138 @@
+use crate::{
+    defaults,
+    helpers::err,
+    io::{diff::Writer, SupportingFileExts},
+    network::EdgeMatching,
+};
+use serde::Deserialize;
+use std::{
+    fs::OpenOptions,
+    path::{Path, PathBuf},
+};
+
+/// Describes the export of the differences between two graphs (see `Graph::diff(...)`).
+///
+/// The file's extension decides the format, which is either `csv` or GeoJSON (`geojson`, `json`).
+#[derive(Clone, Debug, Deserialize)]
+#[serde(from = "WrappedProtoConfig")]
+pub struct Config {
+    pub file: PathBuf,
+    pub edge_matching: EdgeMatching,
+}
+
+impl SupportingFileExts for Config {
+    fn supported_exts<'a>() -> &'a [&'a str] {
+        &["yaml"]
+    }
+}
+
+impl From<WrappedProtoConfig> for Config {
+    fn from(proto_cfg: WrappedProtoConfig) -> Config {
+        Config::from(proto_cfg.writing)
+    }
+}
+
+impl From<ProtoConfig> for Config {
+    fn from(proto_cfg: ProtoConfig) -> Config {
+        Config {
+            file: proto_cfg.file,
+            edge_matching: proto_cfg
+                .edge_matching
+                .unwrap_or(defaults::diffing::EDGE_MATCHING),
+        }
+    }
+}
+
+impl Config {
+    pub fn try_from_yaml<P: AsRef<Path> + ?Sized>(path: &P) -> err::Result<Config> {
+        let path = path.as_ref();
+        let file = {
+            Config::find_supported_ext(path)?;
+            match OpenOptions::new().read(true).open(path) {
+                Ok(file) => file,
+                Err(e) => {
+                    return Err(err::Msg::from(format!(
+                        "Couldn't open {} due to error: {}",
+                        path.display(),
+                        e
+                    )))
+                }
+            }
+        };
+
+        let cfg: Config = match serde_yaml::from_reader(file) {
+            Ok(cfg) => cfg,
+            Err(e) => {
+                return Err(err::Msg::from(format!(
+                    "Serde couldn't read {} due to error: {}",
+                    path.display(),
+                    e
+                )))
+            }
+        };
+
+        match Writer::find_supported_ext(&cfg.file) {
+            Ok(_) => Ok(cfg),
+            Err(msg) => Err(err::Msg::from(format!("Wrong writer-diff-file: {}", msg))),
+        }
+    }
+
+    pub fn from_yaml<P: AsRef<Path> + ?Sized>(path: &P) -> Config {
+        match Config::try_from_yaml(path) {
+            Ok(cfg) => cfg,
+            Err(msg) => panic!("{}", msg),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(from = "WrappedRawConfig")]
+pub struct WrappedProtoConfig {
+    pub writing: ProtoConfig,
+}
+
+impl From<WrappedRawConfig> for WrappedProtoConfig {
+    fn from(raw_cfg: WrappedRawConfig) -> WrappedProtoConfig {
+        WrappedProtoConfig {
+            writing: ProtoConfig::from(raw_cfg.writing),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(from = "RawConfig")]
+pub struct ProtoConfig {
+    pub file: PathBuf,
+    pub edge_matching: Option<EdgeMatching>,
+}
+
+impl From<RawConfig> for ProtoConfig {
+    fn from(raw_cfg: RawConfig) -> ProtoConfig {
+        let raw_cfg = raw_cfg.diff;
+
+        ProtoConfig {
+            file: raw_cfg.file,
+            edge_matching: raw_cfg.edge_matching,
+        }
+    }
+}
+
+/// Don't deny unknown fields to allow multiple configs in one yaml-file.
+#[derive(Debug, Deserialize)]
+pub struct WrappedRawConfig {
+    pub writing: RawConfig,
+}
+
+/// Don't deny unknown fields to allow multiple configs in one yaml-file.
+#[derive(Debug, Deserialize)]
+pub struct RawConfig {
+    pub diff: RawContent,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RawContent {
+    pub file: PathBuf,
+    #[serde(rename = "edge-matching")]
+    pub edge_matching: Option<EdgeMatching>,
+}
@@ -0,0 +1,181 @@
+use crate::{
+    configs::SimpleId,
+    defaults,
+    helpers::err,
+    io::{geojson::Writer, SupportingFileExts},
+    network::BoundingBox,
+};
+use kissunits::geo::Coordinate;
+use serde::Deserialize;
+use std::{
+    fs::OpenOptions,
+    path::{Path, PathBuf},
+};
+
+/// Describes the export of the graph's edges as GeoJSON.
+///
+/// Every edge is a LineString with its metrics as properties.
+/// The edges can be restricted to a bounding-box and coloured by a metric (property `stroke`).
+#[derive(Clone, Debug, Deserialize)]
+#[serde(from = "WrappedProtoConfig")]
+pub struct Config {
+    pub file: PathBuf,
+    pub is_writing_shortcuts: bool,
+    pub is_denormalizing: bool,
+    pub color_id: Option<SimpleId>,
+    pub bbox: Option<BoundingBox>,
+}
+
+impl SupportingFileExts for Config {
+    fn supported_exts<'a>() -> &'a [&'a str] {
+        &["yaml"]
+    }
+}
+
+impl From<WrappedProtoConfig> for Config {
+    fn from(proto_cfg: WrappedProtoConfig) -> Config {
+        Config::from(proto_cfg.writing)
+    }
+}
+
+impl From<ProtoConfig> for Config {
+    fn from(proto_cfg: ProtoConfig) -> Config {
+        Config {
+            file: proto_cfg.file,
+            is_writing_shortcuts: proto_cfg
+                .is_writing_shortcuts
+                .unwrap_or(defaults::parsing::IS_USING_SHORTCUTS),
+            is_denormalizing: proto_cfg
+                .is_denormalizing
+                .unwrap_or(defaults::writing::WILL_DENORMALIZE_METRICS_BY_MEAN),
+            color_id: proto_cfg.color_id,
+            bbox: proto_cfg.bbox,
+        }
+    }
+}
+
+impl Config {
+    pub fn try_from_yaml<P: AsRef<Path> + ?Sized>(path: &P) -> err::Result<Config> {
+        let path = path.as_ref();
+        let file = {
+            Config::find_supported_ext(path)?;
+            match OpenOptions::new().read(true).open(path) {
+                Ok(file) => file,
+                Err(e) => {
+                    return Err(err::Msg::from(format!(
+                        "Couldn't open {} due to error: {}",
+                        path.display(),
+                        e
+                    )))
+                }
+            }
+        };
+
+        let cfg: Config = match serde_yaml::from_reader(file) {
+            Ok(cfg) => cfg,
+            Err(e) => {
+                return Err(err::Msg::from(format!(
+                    "Serde couldn't read {} due to error: {}",
+                    path.display(),
+                    e
+                )))
+            }
+        };
+
+        match Writer::find_supported_ext(&cfg.file) {
+            Ok(_) => Ok(cfg),
+            Err(msg) => Err(err::Msg::from(format!(
+                "Wrong writer-geojson-file: {}",
+                msg
+            ))),
+        }
+    }
+
+    pub fn from_yaml<P: AsRef<Path> + ?Sized>(path: &P) -> Config {
+        match Config::try_from_yaml(path) {
+            Ok(cfg) => cfg,
+            Err(msg) => panic!("{}", msg),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(from = "WrappedRawConfig")]
+pub struct WrappedProtoConfig {
+    pub writing: ProtoConfig,
+}
+
+impl From<WrappedRawConfig> for WrappedProtoConfig {
+    fn from(raw_cfg: WrappedRawConfig) -> WrappedProtoConfig {
+        WrappedProtoConfig {
+            writing: ProtoConfig::from(raw_cfg.writing),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(from = "RawConfig")]
+pub struct ProtoConfig {
+    pub file: PathBuf,
+    pub is_writing_shortcuts: Option<bool>,
+    pub is_denormalizing: Option<bool>,
+    pub color_id: Option<SimpleId>,
+    pub bbox: Option<BoundingBox>,
+}
+
+impl From<RawConfig> for ProtoConfig {
+    fn from(raw_cfg: RawConfig) -> ProtoConfig {
+        let raw_cfg = raw_cfg.geojson;
+
+        ProtoConfig {
+            file: raw_cfg.file,
+            is_writing_shortcuts: raw_cfg.is_writing_shortcuts,
+            is_denormalizing: raw_cfg.is_denormalizing,
+            color_id: raw_cfg.color_id,
+            bbox: raw_cfg.bbox.map(|raw_bbox| BoundingBox {
+                min: Coordinate {
+                    lat: raw_bbox.min_lat,
+                    lon: raw_bbox.min_lon,
+                },
+                max: Coordinate {
+                    lat: raw_bbox.max_lat,
+                    lon: raw_bbox.max_lon,
+                },
+            }),
+        }
+    }
+}
+
+/// Don't deny unknown fields to allow multiple configs in one yaml-file.
+#[derive(Debug, Deserialize)]
+pub struct WrappedRawConfig {
+    pub writing: RawConfig,
+}
+
+/// Don't deny unknown fields to allow multiple configs in one yaml-file.
+#[derive(Debug, Deserialize)]
+pub struct RawConfig {
+    pub geojson: RawContent,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RawContent {
+    pub file: PathBuf,
+    #[serde(rename = "with_shortcuts")]
+    pub is_writing_shortcuts: Option<bool>,
+    #[serde(rename = "will_denormalize_metrics_by_mean")]
+    pub is_denormalizing: Option<bool>,
+    #[serde(rename = "color-by")]
+    pub color_id: Option<SimpleId>,
+    pub bbox: Option<RawBoundingBox>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct RawBoundingBox {
+    pub min_lat: f64,
+    pub min_lon: f64,
+    pub max_lat: f64,
+    pub max_lon: f64,
+}
@@ -1,3 +1,6 @@
+pub mod diff;
+pub mod geojson;
 pub mod network;
+pub mod paths;
 pub mod routing;
 pub mod smarts;
@@ -0,0 +1,132 @@
+use crate::{
+    defaults,
+    helpers::err,
+    io::{routing::PathsWriter, SupportingFileExts},
+};
+use serde::Deserialize;
+use std::{
+    fs::OpenOptions,
+    path::{Path, PathBuf},
+};
+
+/// Describes the export of computed paths as gpx or kml (chosen by the file's extension), e.g. for navigation-devices.
+#[derive(Clone, Debug)]
+pub struct Config {
+    pub file: PathBuf,
+    pub gpx_kind: GpxKind,
+    pub is_denormalizing: bool,
+}
+
+/// Paths can be written as gpx-tracks (`trk`) or gpx-routes (`rte`).
+#[derive(Copy, Clone, Debug, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum GpxKind {
+    Track,
+    Route,
+}
+
+impl SupportingFileExts for Config {
+    fn supported_exts<'a>() -> &'a [&'a str] {
+        &["yaml"]
+    }
+}
+
+impl From<ProtoConfig> for Config {
+    fn from(proto_cfg: ProtoConfig) -> Config {
+        Config {
+            file: proto_cfg.file,
+            gpx_kind: proto_cfg
+                .gpx_kind
+                .unwrap_or(defaults::writing::paths::GPX_KIND),
+            is_denormalizing: proto_cfg
+                .is_denormalizing
+                .unwrap_or(defaults::writing::WILL_DENORMALIZE_METRICS_BY_MEAN),
+        }
+    }
+}
+
+impl Config {
+    /// Returns `None`, if the yaml-file has no section `writing.paths`, since exporting paths is optional.
+    pub fn try_from_yaml<P: AsRef<Path> + ?Sized>(path: &P) -> err::Result<Option<Config>> {
+        let path = path.as_ref();
+        let file = {
+            Config::find_supported_ext(path)?;
+            match OpenOptions::new().read(true).open(path) {
+                Ok(file) => file,
+                Err(e) => {
+                    return Err(err::Msg::from(format!(
+                        "Couldn't open {} due to error: {}",
+                        path.display(),
+                        e
+                    )))
+                }
+            }
+        };
+
+        let raw_cfg: WrappedRawConfig = match serde_yaml::from_reader(file) {
+            Ok(raw_cfg) => raw_cfg,
+            Err(e) => {
+                return Err(err::Msg::from(format!(
+                    "Serde couldn't read {} due to error: {}",
+                    path.display(),
+                    e
+                )))
+            }
+        };
+        let cfg = match raw_cfg.writing.and_then(|raw_cfg| raw_cfg.paths) {
+            Some(raw_content) => Config::from(ProtoConfig::from(raw_content)),
+            None => return Ok(None),
+        };
+
+        match PathsWriter::find_supported_ext(&cfg.file) {
+            Ok(_) => Ok(Some(cfg)),
+            Err(msg) => Err(err::Msg::from(format!("Wrong writer-paths-file: {}", msg))),
+        }
+    }
+
+    pub fn from_yaml<P: AsRef<Path> + ?Sized>(path: &P) -> Option<Config> {
+        match Config::try_from_yaml(path) {
+            Ok(cfg) => cfg,
+            Err(msg) => panic!("{}", msg),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ProtoConfig {
+    pub file: PathBuf,
+    pub gpx_kind: Option<GpxKind>,
+    pub is_denormalizing: Option<bool>,
+}
+
+impl From<RawContent> for ProtoConfig {
+    fn from(raw_content: RawContent) -> ProtoConfig {
+        ProtoConfig {
+            file: raw_content.file,
+            gpx_kind: raw_content.gpx_kind,
+            is_denormalizing: raw_content.is_denormalizing,
+        }
+    }
+}
+
+/// Don't deny unknown fields to allow multiple configs in one yaml-file.
+#[derive(Debug, Deserialize)]
+pub struct WrappedRawConfig {
+    pub writing: Option<RawConfig>,
+}
+
+/// Don't deny unknown fields to allow multiple configs in one yaml-file.
+#[derive(Debug, Deserialize)]
+pub struct RawConfig {
+    pub paths: Option<RawContent>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RawContent {
+    pub file: PathBuf,
+    #[serde(rename = "gpx")]
+    pub gpx_kind: Option<GpxKind>,
+    #[serde(rename = "will_denormalize_metrics_by_mean")]
+    pub is_denormalizing: Option<bool>,
+}
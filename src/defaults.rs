@@ -13,6 +13,8 @@ pub mod accuracy {
     ///     -> 1e-6 degrees equals <= 0.11 m (equator)
     ///   -> 1e-5 degrees points to a person in a room, see https://xkcd.com/2170/
     pub const F64_ABS: f64 = 0.000_001;
+    /// relative precision of metrics stored as f32
+    pub const F32_REL: f64 = f32::EPSILON as f64;
     // F64_ABS = 10^(-F64__FMT_DIGITS)
     // but +1 because of float-representation
     // e.g. 0.000_001 is 0.000_000_9.....
@@ -26,21 +28,51 @@ pub mod speed {
 }
 
 pub mod capacity {
-    use crate::compiler;
-
-    // For optimal performance and memory-usage:
-    // Change this value before compiling, dependent of your number of stored metrics in the graph.
-    pub const SMALL_VEC_INLINE_SIZE: usize = compiler::GRAPH_DIM;
+    // The graph stores its metrics per metric with runtime-dimension.
+    // Only temporary metric-vectors (e.g. costs or alphas) are inlined up to this size,
+    // bigger dimensions are stored on the heap.
+    pub const SMALL_VEC_INLINE_SIZE: usize = 8;
     pub type DimVec<T> = smallvec::SmallVec<[T; SMALL_VEC_INLINE_SIZE]>;
     pub const MAX_BYTE_PER_CHUNK: usize = 200 * 1_000_000;
 }
 
 pub mod parsing {
+    use crate::{configs::parsing::edges::metrics::Precision, network::NodeOrdering};
+
     // provided by multi-ch-constructor
     pub const NO_SHORTCUT_IDX: &str = "-1";
     pub const IS_USING_SHORTCUTS: bool = false;
 
     pub const WILL_NORMALIZE_METRICS_BY_MEAN: bool = false;
+    /// Street-names are only needed for guidance, but cost memory.
+    pub const WILL_KEEP_STREETS: bool = false;
+    /// Storage-precision of metrics, which are not configured in `edges.precisions`
+    pub const METRIC_PRECISION: Precision = Precision::F64;
+
+    /// Nodes are kept sorted by id, so node-indices are stable across orderings.
+    pub const NODE_ORDERING: NodeOrdering = NodeOrdering::Id;
+
+    /// Parsing and finalizing use all cores.
+    pub fn num_threads() -> usize {
+        std::thread::available_parallelism()
+            .map(usize::from)
+            .unwrap_or(1)
+    }
+
+    pub mod external_memory {
+        use std::path::PathBuf;
+
+        /// Buffered proto-edges are spilled beyond this budget.
+        pub const MEMORY_BUDGET_MB: usize = 1_000;
+        /// Runs are merged in several passes of at most this many runs, bounding open files and their buffers.
+        pub const MAX_MERGED_RUNS: usize = 64;
+        /// Buffered node-ids are deduplicated, when their number has doubled, but not below this number.
+        pub const MIN_DEDUP_NODE_COUNT: usize = 1_000_000;
+
+        pub fn dir() -> PathBuf {
+            std::env::temp_dir()
+        }
+    }
 
     // vehicles
 
@@ -49,6 +81,8 @@ pub mod parsing {
 
         pub const CATEGORY: VehicleCategory = VehicleCategory::Car;
         pub const ARE_DRIVERS_PICKY: bool = true;
+        /// Speed of ways with surfaces, which are not configured in `surface_speed_factors`
+        pub const SURFACE_SPEED_FACTOR: f64 = 1.0;
     }
 }
 
@@ -58,6 +92,17 @@ pub mod writing {
 
     pub const IS_WRITING_WITH_HEADER: bool = true;
     pub const WILL_DENORMALIZE_METRICS_BY_MEAN: bool = false;
+
+    pub mod paths {
+        use crate::configs::writing::paths::GpxKind;
+
+        /// Tracks are supported by more devices than routes.
+        pub const GPX_KIND: GpxKind = GpxKind::Track;
+        pub const CREATOR: &str = "osmgraphing";
+        /// Namespace (and its prefix) of the gpx-extensions, since gpx allows only elements of other namespaces there.
+        pub const GPX_NAMESPACE: &str = "https://github.com/dominicparga/osmgraphing";
+        pub const GPX_NAMESPACE_PREFIX: &str = "osmgraphing";
+    }
 }
 
 pub mod smarts {
@@ -81,9 +126,74 @@ pub mod routing {
     /// This sort isn't stable in combination with a ch-construction and varying metrics, because a ch-constructor sets the ch-levels dependent on the metrics.
     /// In result, edges can't be identified in balancer.
     pub const IS_USING_CH_LEVEL_SPEEDUP: bool = true;
+    /// If true, the ch-dijkstra doesn't relax edges of settled nodes, which can be reached cheaper via a higher node (stall-on-demand).
+    /// This reduces the search-space, but costs an additional scan of the incoming edges per settled node,
+    /// which may not pay off for graphs with many shortcuts per node (e.g. customized via CCH).
+    pub const IS_USING_STALL_ON_DEMAND: bool = true;
+    /// Number of threads, which are used by the `routing::Engine` for working off batches.
+    pub const NUM_THREADS: usize = 4;
+    /// Every arc of a customized CCH keeps at most this many pareto-optimal shortcuts,
+    /// because the pareto-sets can grow combinatorially with the number of metrics.
+    pub const CCH_MAX_PARETO_SET_SIZE: usize = 32;
+}
+
+pub mod guidance {
+    // turn-angles in degrees, below which a turn is classified as the previous kind
+    pub const MIN_SLIGHT_TURN_DEG: f64 = 20.0;
+    pub const MIN_TURN_DEG: f64 = 60.0;
+    pub const MIN_SHARP_TURN_DEG: f64 = 120.0;
+    pub const MIN_U_TURN_DEG: f64 = 170.0;
+}
+
+pub mod analyzing {
+    /// Percentiles of every metric, which are reported by `Graph::analyze()`
+    pub const PERCENTILES: [usize; 7] = [1, 5, 25, 50, 75, 95, 99];
+    /// Only the sizes of the largest components are reported, since small graphs may have many tiny components.
+    pub const MAX_LISTED_COMPONENTS: usize = 10;
+}
+
+pub mod diffing {
+    use crate::network::EdgeMatching;
+
+    /// Edge-ids are not given by every map-file, but src- and dst-ids are.
+    pub const EDGE_MATCHING: EdgeMatching = EdgeMatching::NodeIds;
+}
+
+pub mod server {
+    use crate::network::SnappingFilter;
+
+    pub const ADDRESS: &str = "127.0.0.1:8080";
+    /// Number of threads handling requests
+    pub const NUM_THREADS: usize = 4;
+    /// Requests with larger bodies are rejected.
+    pub const MAX_BODY_SIZE: usize = 16 * 1024 * 1024;
+    /// Requests with a larger request-line plus headers are rejected.
+    pub const MAX_HEADER_SIZE: usize = 8 * 1024;
+    /// Matrix-requests with more route-pairs (srcs times dsts) are rejected.
+    pub const MAX_MATRIX_SIZE: usize = 10_000;
+    /// Connections are closed if a request can't be read in this time.
+    pub const READ_TIMEOUT_SECS: u64 = 30;
+    /// Coordinates in requests are snapped to nodes of the largest component, so routes exist.
+    pub const SNAPPING_FILTER: SnappingFilter = SnappingFilter::LargestComponent;
+}
+
+pub mod matching {
+    use crate::network::SnappingFilter;
+
+    /// Edges farther away from a gps-point aren't considered as candidates.
+    pub const SEARCH_RADIUS_KM: f64 = 0.05;
+    /// Number of the closest edges per gps-point, which are considered as candidates
+    pub const MAX_CANDIDATES: usize = 8;
+    /// Standard-deviation of gps-measurements
+    pub const GPS_SIGMA_KM: f64 = 0.01;
+    /// Expected difference between route-distance and great-circle-distance of consecutive gps-points
+    pub const TRANSITION_BETA_KM: f64 = 0.05;
+    /// Routes between consecutive candidates are not searched beyond their great-circle-distance plus this detour.
+    pub const MAX_DETOUR_KM: f64 = 2.0;
+    /// Gps-points are matched onto edges of the largest component, so routes between them exist.
+    pub const SNAPPING_FILTER: SnappingFilter = SnappingFilter::LargestComponent;
 }
 
-#[cfg(feature = "gpl")]
 pub mod balancing {
     use crate::{
         approximating::Approx,
@@ -95,15 +205,14 @@ pub mod balancing {
     use log::{info, warn};
     use std::cmp::max;
 
-    // A high work-size could be less productive since less dynamic:
-    // Work-size is adjusted for thread of index 0, implying that a high work-size leads to less parallelization in the end.
-    // With k threads, k times work-size will be worked off before an adjustment follows.
-    // If k times work-size is a huge part of the total work, the remaining work is done with less threads.
-    pub const INIT_WORK_SIZE: usize = 50;
-    pub const WORK_SIZE_PLUS: usize = 30;
-    pub const WORK_SIZE_MINUS: usize = 10;
+    /// Route-pairs are worked off (in parallel) in batches of this size.
+    /// After every batch, the progress is logged and the found paths are counted into the workloads,
+    /// so a high batch-size costs memory, while a low batch-size lets threads idle at the end of every batch.
+    pub const BATCH_SIZE: usize = 1_000;
     pub const NUM_THREADS: usize = 4;
     pub const IS_ERR_WHEN_METRIC_IS_ZERO: bool = true;
+    pub const CONTRACTION_METHOD: configs::balancing::ContractionMethod =
+        configs::balancing::ContractionMethod::MultiChConstructor;
 
     pub mod stats {
         pub const DIR: &str = "stats";
@@ -173,7 +282,7 @@ pub mod balancing {
 
         for (edge_idx, new_metric) in new_metrics.iter_mut().enumerate() {
             *new_metric = {
-                let old_metric = metrics[EdgeIdx(edge_idx)][*old_metric_idx];
+                let old_metric = metrics[old_metric_idx].get(EdgeIdx(edge_idx));
 
                 match balancing_cfg.optimization.method {
                     configs::balancing::OptimizationMethod::ExplicitEuler { correction } => {
@@ -252,14 +361,13 @@ pub mod balancing {
         // update graph's metric
 
         for (edge_idx, new_metric) in new_metrics.into_iter().enumerate() {
-            metrics[EdgeIdx(edge_idx)][*old_metric_idx] = new_metric;
+            metrics[old_metric_idx].set(EdgeIdx(edge_idx), new_metric);
         }
 
         Ok(())
     }
 }
 
-#[cfg(feature = "gpl")]
 pub mod explorating {
     pub mod files {
 
@@ -275,8 +383,9 @@ pub mod network {
     }
 
     use crate::{
+        configs::parsing::edges::ways,
         defaults,
-        network::{vehicles::Category as VehicleCategory, StreetCategory},
+        network::{vehicles::Category as VehicleCategory, Street, StreetCategory},
     };
     use kissunits::speed::KilometersPerHour;
     use log::warn;
@@ -332,6 +441,31 @@ pub mod network {
             } as f64)
         }
 
+        /// Discomfort of ways without any surface-tags
+        fn discomfort(&self) -> f64 {
+            match self {
+                StreetCategory::Motorway
+                | StreetCategory::MotorwayLink
+                | StreetCategory::Trunk
+                | StreetCategory::TrunkLink
+                | StreetCategory::Primary
+                | StreetCategory::PrimaryLink
+                | StreetCategory::Secondary
+                | StreetCategory::SecondaryLink
+                | StreetCategory::Tertiary
+                | StreetCategory::TertiaryLink
+                | StreetCategory::Unclassified
+                | StreetCategory::Residential
+                | StreetCategory::LivingStreet
+                | StreetCategory::Service
+                | StreetCategory::Road
+                | StreetCategory::Cycleway
+                | StreetCategory::Pedestrian => 1.0,
+                StreetCategory::Track => 2.0,
+                StreetCategory::Path => 1.8,
+            }
+        }
+
         pub fn is_for(&self, vehicle_category: &VehicleCategory, is_driver_picky: bool) -> bool {
             match vehicle_category {
                 VehicleCategory::Car => self.is_for_vehicles(is_driver_picky),
@@ -560,6 +694,121 @@ pub mod network {
             }
         }
 
+        /// Parses the tags `surface`, `smoothness` and `tracktype` of the way into a factor of at least
+        /// `1.0`, where `1.0` is the most comfortable (e.g. smooth asphalt).
+        /// The worst of all given tags is taken.
+        /// Ways without any of these tags get a default-value depending on their street-category.
+        pub fn parse_discomfort(&self, way: &Way) -> f64 {
+            let surface = way.tags.get("surface").and_then(|value| {
+                match value.trim().to_ascii_lowercase().as_ref() {
+                    "asphalt" | "concrete" | "concrete:lanes" | "paved" => Some(1.0),
+                    "concrete:plates" | "metal" | "paving_stones" | "wood" => Some(1.2),
+                    "compacted" | "fine_gravel" => Some(1.3),
+                    "sett" => Some(1.5),
+                    "gravel" | "pebblestone" => Some(1.8),
+                    "cobblestone" | "dirt" | "earth" | "ground" | "unhewn_cobblestone"
+                    | "unpaved" => Some(2.0),
+                    "grass" | "grass_paver" | "mud" | "sand" => Some(3.0),
+                    _ => {
+                        warn!(
+                            "Unknown surface `{}` of way-id `{}` -> ignored",
+                            value, way.id.0
+                        );
+                        None
+                    }
+                }
+            });
+            let smoothness = way.tags.get("smoothness").and_then(|value| {
+                match value.trim().to_ascii_lowercase().as_ref() {
+                    "excellent" | "good" => Some(1.0),
+                    "intermediate" => Some(1.2),
+                    "bad" => Some(1.6),
+                    "very_bad" => Some(2.2),
+                    "horrible" => Some(3.0),
+                    "very_horrible" => Some(4.0),
+                    "impassable" => Some(5.0),
+                    _ => {
+                        warn!(
+                            "Unknown smoothness `{}` of way-id `{}` -> ignored",
+                            value, way.id.0
+                        );
+                        None
+                    }
+                }
+            });
+            let tracktype = way.tags.get("tracktype").and_then(|value| {
+                match value.trim().to_ascii_lowercase().as_ref() {
+                    "grade1" => Some(1.0),
+                    "grade2" => Some(1.4),
+                    "grade3" => Some(1.8),
+                    "grade4" => Some(2.4),
+                    "grade5" => Some(3.0),
+                    _ => {
+                        warn!(
+                            "Unknown tracktype `{}` of way-id `{}` -> ignored",
+                            value, way.id.0
+                        );
+                        None
+                    }
+                }
+            });
+
+            match vec![surface, smoothness, tracktype]
+                .into_iter()
+                .flatten()
+                .fold(None, |worst: Option<f64>, discomfort| match worst {
+                    Some(worst) => Some(worst.max(discomfort)),
+                    None => Some(discomfort),
+                }) {
+                Some(discomfort) => discomfort,
+                None => self.discomfort(),
+            }
+        }
+
+        /// Parses the tags `name`, `ref` and `junction` of the way.
+        /// If osm-ways are configured, the way's id and configured tags are parsed as well.
+        pub fn parse_street(&self, way: &Way, ways_cfg: Option<&ways::Config>) -> Street {
+            let parse_tag = |key: &str| {
+                way.tags
+                    .get(key)
+                    .map(|value| value.trim())
+                    .filter(|value| !value.is_empty())
+                    .map(String::from)
+            };
+
+            Street {
+                name: parse_tag("name"),
+                reference: parse_tag("ref"),
+                is_motorway: matches!(
+                    self,
+                    StreetCategory::Motorway
+                        | StreetCategory::MotorwayLink
+                        | StreetCategory::Trunk
+                        | StreetCategory::TrunkLink
+                ),
+                is_link: matches!(
+                    self,
+                    StreetCategory::MotorwayLink
+                        | StreetCategory::TrunkLink
+                        | StreetCategory::PrimaryLink
+                        | StreetCategory::SecondaryLink
+                        | StreetCategory::TertiaryLink
+                ),
+                is_roundabout: match way.tags.get("junction") {
+                    Some(junction) => matches!(
+                        junction.trim().to_ascii_lowercase().as_ref(),
+                        "roundabout" | "circular"
+                    ),
+                    None => false,
+                },
+                way_id: ways_cfg.map(|_| way.id.0),
+                tags: match ways_cfg {
+                    Some(ways_cfg) => ways_cfg.tags.iter().map(|key| parse_tag(&key.0)).collect(),
+                    None => Vec::new(),
+                },
+            }
+        }
+
         /// return (is_oneway, is_reverse)
         pub fn parse_oneway(&self, way: &Way) -> (bool, bool) {
             let is_oneway = true;
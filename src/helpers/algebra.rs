@@ -33,6 +33,10 @@ impl Matrix {
         }
     }
 
+    pub fn determinant(&self) -> f64 {
+        self.data.determinant()
+    }
+
     pub fn lu(self) -> LU {
         LU {
             data: self.data.lu(),
@@ -0,0 +1,365 @@
+use crate::{
+    defaults::{accuracy, capacity::DimVec},
+    helpers::{self, algebra, err},
+};
+use smallvec::smallvec;
+use std::collections::HashMap;
+
+/// Ghost-points are placed this far (relative to the points' spread) from the points.
+const GHOST_SCALE: f64 = 1_000.0;
+/// A facet is degenerate, if its volume relative to the product of its edges' lengths is below this,
+/// which doesn't depend on the points' scale.
+const DEGENERACY_TOLERANCE: f64 = 1e-12;
+
+/// Incremental convex hull (beneath-beyond) for the small dimensions used in exploration.
+///
+/// The exploration is only interested in the lower hull of all points,
+/// i.e. the hull of all points, where every point is extended by the positive orthant.
+/// The orthant's rays are approximated by ghost-points (one per dimension),
+/// which are placed far away from all points.
+/// A facet can contain ghost-points, which is represented by rays in the facet's dimension.
+///
+/// Every added point gets a vertex-id, which is just its index of insertion.
+/// Facets are simplices of `dim` vertices (points and rays).
+/// Their ids are stable, meaning that facets of the same vertices and rays have the same id,
+/// even if the hull has been rebuilt.
+///
+/// Points, which are (nearly) on the hull, are not inserted, hence every ridge keeps exactly two facets.
+/// Whether a point is on the hull depends on a tolerance relative to the points' spread.
+pub struct ConvexHull {
+    dim: usize,
+    points: Vec<DimVec<f64>>,
+    ghosts: Vec<DimVec<f64>>,
+    // bounding-box of the points, which have been used for placing the ghost-points
+    bbox_min: DimVec<f64>,
+    bbox_max: DimVec<f64>,
+    // points closer to a facet are on the facet
+    tolerance: f64,
+    facets: Vec<Facet>,
+    facet_ids: HashMap<Vec<usize>, usize>,
+    // Every facet's normal is oriented away from this point.
+    interior: DimVec<f64>,
+}
+
+pub struct Facet {
+    id: usize,
+    // internal vertices (including ghosts) for building the hull
+    raw_vertices: Vec<usize>,
+    vertices: Vec<usize>,
+    rays: Vec<usize>,
+    normal: DimVec<f64>,
+    offset: f64,
+}
+
+impl Facet {
+    pub fn id(&self) -> usize {
+        self.id
+    }
+
+    /// Vertex-ids of the facet's points
+    pub fn vertices(&self) -> &Vec<usize> {
+        &self.vertices
+    }
+
+    /// Dimensions, whose rays are part of the facet.
+    /// A facet's normal is orthogonal to its rays.
+    pub fn rays(&self) -> &Vec<usize> {
+        &self.rays
+    }
+
+    /// Outward normal of unit-length
+    pub fn normal(&self) -> &DimVec<f64> {
+        &self.normal
+    }
+
+    /// Signed distance of the point to the facet's hyperplane.
+    /// A positive distance means that the point is outside.
+    fn distance(&self, point: &[f64]) -> f64 {
+        helpers::dot_product(&self.normal, point) - self.offset
+    }
+}
+
+impl ConvexHull {
+    pub fn new(dim: usize) -> ConvexHull {
+        ConvexHull {
+            dim,
+            points: Vec::new(),
+            ghosts: Vec::new(),
+            bbox_min: smallvec![0.0; dim],
+            bbox_max: smallvec![0.0; dim],
+            tolerance: 0.0,
+            facets: Vec::new(),
+            facet_ids: HashMap::new(),
+            interior: smallvec![0.0; dim],
+        }
+    }
+
+    pub fn dim(&self) -> usize {
+        self.dim
+    }
+
+    /// Returns the vertex-id of the added point.
+    pub fn add_vertex(&mut self, point: &[f64]) -> err::Result<usize> {
+        if point.len() != self.dim {
+            return Err(format!(
+                "The point {:?} should have dimension {}, but has dimension {}.",
+                point,
+                self.dim,
+                point.len()
+            )
+            .into());
+        }
+
+        let id = self.points.len();
+        self.points.push(DimVec::from_slice(point));
+
+        if self.dim == 0 {
+            return Ok(id);
+        }
+
+        // Ghost-points are only valid for points in the bounding-box.
+        let is_in_bbox = self.points.len() > 1
+            && (0..self.dim).all(|i| self.bbox_min[i] <= point[i] && point[i] <= self.bbox_max[i]);
+        if is_in_bbox {
+            self.insert(id);
+        } else {
+            self.rebuild();
+        }
+
+        Ok(id)
+    }
+
+    /// All facets of the lower hull, that contain at least two points.
+    ///
+    /// The lower hull's normals have no positive component.
+    /// Hence their negated normals are valid (non-negative) alpha-vectors.
+    /// Facets of only one point would lead to an alpha-vector, where only one metric is set.
+    pub fn lower_cells(&self) -> impl Iterator<Item = &Facet> {
+        self.facets.iter().filter(|facet| {
+            facet.vertices.len() > 1
+                // Components of rays are slightly positive,
+                // because ghost-points are only approximating rays.
+                && facet
+                    .normal
+                    .iter()
+                    .enumerate()
+                    .filter(|(i, _)| !facet.rays.contains(i))
+                    .all(|(_, &n)| n <= accuracy::F64_ABS)
+        })
+    }
+
+    fn ghost_id(&self, dim_idx: usize) -> usize {
+        usize::MAX - dim_idx
+    }
+
+    fn point(&self, raw_id: usize) -> &DimVec<f64> {
+        if raw_id < self.points.len() {
+            &self.points[raw_id]
+        } else {
+            &self.ghosts[usize::MAX - raw_id]
+        }
+    }
+
+    /// Places the ghost-points according to all points and inserts all points again.
+    fn rebuild(&mut self) {
+        // bounding-box
+
+        self.bbox_min = self.points[0].clone();
+        self.bbox_max = self.points[0].clone();
+        for point in &self.points {
+            for i in 0..self.dim {
+                self.bbox_min[i] = self.bbox_min[i].min(point[i]);
+                self.bbox_max[i] = self.bbox_max[i].max(point[i]);
+            }
+        }
+        let spread = helpers::sub(&self.bbox_max, &self.bbox_min)
+            .into_iter()
+            .fold(0.0, f64::max);
+        // relative to the spread, so the hull doesn't depend on the points' scale
+        let distance = GHOST_SCALE * if spread > 0.0 { spread } else { 1.0 };
+        self.tolerance = accuracy::F64_ABS * spread;
+
+        self.ghosts = (0..self.dim)
+            .map(|i| {
+                let mut ghost = self.bbox_max.clone();
+                ghost[i] += distance;
+                ghost
+            })
+            .collect();
+
+        // Initial simplex of all ghost-points and the first point,
+        // which is affinely independent, because every point is below the ghosts' hyperplane.
+
+        let mut simplex: Vec<_> = (0..self.dim).map(|i| self.ghost_id(i)).collect();
+        simplex.push(0);
+
+        self.interior = smallvec![0.0; self.dim];
+        for &raw_id in &simplex {
+            let point = self.point(raw_id).clone();
+            helpers::add_assign(&mut self.interior, &point);
+        }
+        let n = simplex.len() as f64;
+        self.interior.iter_mut().for_each(|x| *x /= n);
+
+        self.facets.clear();
+        for i in 0..simplex.len() {
+            let raw_vertices = simplex
+                .iter()
+                .enumerate()
+                .filter(|&(j, _)| i != j)
+                .map(|(_, &raw_id)| raw_id)
+                .collect();
+            let facet = self.create_facet(raw_vertices).expect(
+                "The initial simplex is not degenerate, since the first point is below the ghosts.",
+            );
+            self.facets.push(facet);
+        }
+
+        // insert remaining points
+
+        for id in 1..self.points.len() {
+            self.insert(id);
+        }
+    }
+
+    fn insert(&mut self, id: usize) {
+        let point = self.points[id].clone();
+        let tolerance = self.tolerance;
+        let (visible, invisible): (Vec<_>, Vec<_>) = self
+            .facets
+            .drain(..)
+            .partition(|facet| facet.distance(&point) > tolerance);
+        self.facets = invisible;
+
+        if visible.is_empty() {
+            // point is inside (or on) the hull
+            return;
+        }
+
+        // Ridges of visible facets, that are shared with invisible facets, build the horizon.
+        // In a simplicial hull, every ridge is shared by exactly two facets,
+        // so horizon-ridges occur only once in the visible facets.
+
+        let mut ridge_counts: HashMap<Vec<usize>, usize> = HashMap::new();
+        for facet in &visible {
+            for i in 0..facet.raw_vertices.len() {
+                let mut ridge: Vec<usize> = facet
+                    .raw_vertices
+                    .iter()
+                    .enumerate()
+                    .filter(|&(j, _)| i != j)
+                    .map(|(_, &v)| v)
+                    .collect();
+                ridge.sort();
+                *ridge_counts.entry(ridge).or_insert(0) += 1;
+            }
+        }
+
+        let mut horizon: Vec<_> = ridge_counts
+            .into_iter()
+            .filter(|&(_, count)| count == 1)
+            .map(|(ridge, _)| ridge)
+            .collect();
+        // deterministic order of facets
+        horizon.sort();
+
+        let mut new_facets = Vec::with_capacity(horizon.len());
+        for mut raw_vertices in horizon {
+            raw_vertices.push(id);
+            match self.create_facet(raw_vertices) {
+                Some(facet) => new_facets.push(facet),
+                None => {
+                    // The point is (nearly) in the plane of a horizon-ridge and of its invisible facet,
+                    // hence it is on the hull and the hull is kept as it is (without holes).
+                    self.facets.extend(visible);
+                    return;
+                }
+            }
+        }
+        self.facets.extend(new_facets);
+    }
+
+    /// Returns None for degenerate facets (e.g. of collinear or duplicate points), which have no volume and hence no normal.
+    /// Degeneracy is checked relative to the facet's edges, so it doesn't depend on the points' scale.
+    fn create_facet(&mut self, mut raw_vertices: Vec<usize>) -> Option<Facet> {
+        raw_vertices.sort();
+        let v_0 = self.point(raw_vertices[0]).clone();
+
+        // The normal is orthogonal to all edges (v_i - v_0) of the facet
+        // and is computed as generalized cross-product.
+
+        let edges: Vec<DimVec<f64>> = raw_vertices[1..]
+            .iter()
+            .map(|&v_i| helpers::sub(self.point(v_i), &v_0))
+            .collect();
+        let mut normal: DimVec<f64> = if self.dim == 1 {
+            smallvec![1.0]
+        } else {
+            (0..self.dim)
+                .map(|j| {
+                    let minor: DimVec<DimVec<f64>> = edges
+                        .iter()
+                        .map(|edge| {
+                            edge.iter()
+                                .enumerate()
+                                .filter(|&(k, _)| k != j)
+                                .map(|(_, &x)| x)
+                                .collect()
+                        })
+                        .collect();
+                    let sign = if j % 2 == 0 { 1.0 } else { -1.0 };
+                    sign * algebra::Matrix::from_rows(minor).determinant()
+                })
+                .collect()
+        };
+        let norm = helpers::dot_product(&normal, &normal).sqrt();
+        let edge_norms: f64 = edges
+            .iter()
+            .map(|edge| helpers::dot_product(edge, edge).sqrt())
+            .product();
+        // NaN-norms are degenerate as well
+        if norm.is_nan() || norm <= DEGENERACY_TOLERANCE * edge_norms {
+            return None;
+        }
+        normal.iter_mut().for_each(|n| *n /= norm);
+        let mut offset = helpers::dot_product(&normal, &v_0);
+
+        // orient outwards
+
+        if helpers::dot_product(&normal, &self.interior) > offset {
+            normal.iter_mut().for_each(|n| *n = -*n);
+            offset = -offset;
+        }
+
+        // get stable id
+
+        let next_id = self.facet_ids.len();
+        let id = *self
+            .facet_ids
+            .entry(raw_vertices.clone())
+            .or_insert(next_id);
+
+        let (vertices, rays) = {
+            let mut vertices = Vec::new();
+            let mut rays = Vec::new();
+            for &raw_id in &raw_vertices {
+                if raw_id < self.points.len() {
+                    vertices.push(raw_id);
+                } else {
+                    rays.push(usize::MAX - raw_id);
+                }
+            }
+            rays.sort();
+            (vertices, rays)
+        };
+
+        Some(Facet {
+            id,
+            raw_vertices,
+            vertices,
+            rays,
+            normal,
+            offset,
+        })
+    }
+}
@@ -2,7 +2,10 @@ use crate::defaults::capacity::DimVec;
 use std::str::FromStr;
 
 pub mod algebra;
+pub mod convex_hull;
 pub mod err;
+pub mod parallel;
+pub mod spilling;
 
 pub fn is_line_functional(line: &String) -> bool {
     line.len() > 0 && line.chars().next() != Some('#')
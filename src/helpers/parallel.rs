@@ -0,0 +1,156 @@
+use std::{ops::Range, thread};
+
+/// Splits `0..len` into (at most) `num_threads` consecutive, non-empty ranges of similar length.
+pub fn ranges(len: usize, num_threads: usize) -> Vec<Range<usize>> {
+    let num_threads = num_threads.max(1).min(len.max(1));
+    let chunk_len = len.div_ceil(num_threads);
+    (0..num_threads)
+        .map(|i| (i * chunk_len).min(len)..((i + 1) * chunk_len).min(len))
+        .filter(|range| !range.is_empty())
+        .collect()
+}
+
+/// Calls `f` for every range of `ranges(len, num_threads)` in its own thread.
+/// The results are returned in the order of the ranges.
+pub fn map_ranges<R, F>(len: usize, num_threads: usize, f: F) -> Vec<R>
+where
+    R: Send,
+    F: Fn(Range<usize>) -> R + Sync,
+{
+    let ranges = ranges(len, num_threads);
+
+    // no need for spawning threads
+    if ranges.len() <= 1 {
+        return ranges.into_iter().map(f).collect();
+    }
+
+    let f = &f;
+    thread::scope(|scope| {
+        let handles: Vec<_> = ranges
+            .into_iter()
+            .map(|range| scope.spawn(move || f(range)))
+            .collect();
+        handles
+            .into_iter()
+            .map(|handle| match handle.join() {
+                Ok(result) => result,
+                Err(e) => std::panic::resume_unwind(e),
+            })
+            .collect()
+    })
+}
+
+/// Maps every item with `num_threads` threads, keeping the order of the items.
+pub fn map<T, R, F>(items: &[T], num_threads: usize, f: F) -> Vec<R>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(&T) -> R + Sync,
+{
+    let mut mapped = Vec::with_capacity(items.len());
+    for chunk in map_ranges(items.len(), num_threads, |range| {
+        items[range].iter().map(&f).collect::<Vec<_>>()
+    }) {
+        mapped.extend(chunk);
+    }
+    mapped
+}
+
+/// Like `map(...)`, but returns the first error (in the order of the items).
+pub fn try_map<T, R, E, F>(items: &[T], num_threads: usize, f: F) -> Result<Vec<R>, E>
+where
+    T: Sync,
+    R: Send,
+    E: Send,
+    F: Fn(&T) -> Result<R, E> + Sync,
+{
+    let mut mapped = Vec::with_capacity(items.len());
+    for chunk in map_ranges(items.len(), num_threads, |range| {
+        items[range].iter().map(&f).collect::<Result<Vec<_>, E>>()
+    }) {
+        mapped.extend(chunk?);
+    }
+    Ok(mapped)
+}
+
+/// Calls `f` for every item with `num_threads` threads.
+/// Besides the item, `f` gets the item's index.
+pub fn for_each_mut<T, F>(items: &mut [T], num_threads: usize, f: F)
+where
+    T: Send,
+    F: Fn(usize, &mut T) + Sync,
+{
+    let ranges = ranges(items.len(), num_threads);
+    let chunk_len = match ranges.first() {
+        Some(range) => range.len(),
+        None => return,
+    };
+
+    let f = &f;
+    thread::scope(|scope| {
+        for (chunk_idx, chunk) in items.chunks_mut(chunk_len).enumerate() {
+            scope.spawn(move || {
+                let offset = chunk_idx * chunk_len;
+                for (i, item) in chunk.iter_mut().enumerate() {
+                    f(offset + i, item);
+                }
+            });
+        }
+    });
+}
+
+/// Sorts the items stably (like `Vec::sort_by_key(...)`) via merge-sort, where every half is sorted in its own thread until `num_threads` are used.
+///
+/// Merging needs additional memory for the merged items.
+pub fn sort_by_key<T, K, F>(items: Vec<T>, num_threads: usize, key: F) -> Vec<T>
+where
+    T: Send,
+    K: Ord,
+    F: Fn(&T) -> K + Sync,
+{
+    sort_rec(items, num_threads, &key)
+}
+
+fn sort_rec<T, K, F>(mut items: Vec<T>, num_threads: usize, key: &F) -> Vec<T>
+where
+    T: Send,
+    K: Ord,
+    F: Fn(&T) -> K + Sync,
+{
+    if num_threads <= 1 || items.len() < 2 {
+        items.sort_by_key(key);
+        return items;
+    }
+
+    let right = items.split_off(items.len() / 2);
+    let right_threads = num_threads / 2;
+    let left_threads = num_threads - right_threads;
+    let (left, right) = thread::scope(|scope| {
+        let handle = scope.spawn(move || sort_rec(right, right_threads, key));
+        let left = sort_rec(items, left_threads, key);
+        match handle.join() {
+            Ok(right) => (left, right),
+            Err(e) => std::panic::resume_unwind(e),
+        }
+    });
+
+    // merge stably, meaning equal items of the left half come first
+    let mut merged = Vec::with_capacity(left.len() + right.len());
+    let mut left = left.into_iter().peekable();
+    let mut right = right.into_iter().peekable();
+    loop {
+        let is_taking_right = match (left.peek(), right.peek()) {
+            (Some(l), Some(r)) => key(r) < key(l),
+            (Some(_), None) => false,
+            (None, Some(_)) => true,
+            (None, None) => break,
+        };
+        let item = if is_taking_right {
+            right.next()
+        } else {
+            left.next()
+        };
+        merged.extend(item);
+    }
+    merged
+}
@@ -0,0 +1,307 @@
+use crate::helpers::{err, MemSize};
+use log::debug;
+use std::{
+    cmp::{max, Reverse},
+    collections::BinaryHeap,
+    fs::{self, File, OpenOptions},
+    io::{self, BufReader, BufWriter, Read, Write},
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+static TMP_DIR_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+/// A new directory for temporary files, which is removed (with its files) when being dropped.
+#[derive(Debug)]
+pub struct TmpDir {
+    path: PathBuf,
+}
+
+impl TmpDir {
+    /// Creates a new subdirectory in the given parent-directory, which is unique for this process.
+    pub fn new<P: AsRef<Path> + ?Sized>(parent: &P) -> err::Result<TmpDir> {
+        let path = parent.as_ref().join(format!(
+            "osmgraphing-{}-{}",
+            std::process::id(),
+            TMP_DIR_COUNT.fetch_add(1, Ordering::SeqCst)
+        ));
+        match fs::create_dir_all(&path) {
+            Ok(()) => Ok(TmpDir { path }),
+            Err(e) => Err(format!(
+                "Couldn't create temporary directory {} due to error: {}",
+                path.display(),
+                e
+            )
+            .into()),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the path of a file in this directory, which is unique per given idx.
+    pub fn file(&self, name: &str, idx: usize) -> PathBuf {
+        self.path.join(format!("{}-{}.bin", name, idx))
+    }
+}
+
+impl Drop for TmpDir {
+    fn drop(&mut self) {
+        // files are temporary anyways, so errors are ignored
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
+
+/// Items, which can be spilled into sorted runs (see `SortedRuns`).
+pub trait Spillable: MemSize + Sized {
+    type Key: Ord;
+
+    /// Items are returned sorted by this key.
+    fn key(&self) -> Self::Key;
+
+    fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()>;
+
+    /// Returns None at the end of the run.
+    fn read_from<R: Read>(reader: &mut R) -> io::Result<Option<Self>>;
+}
+
+/// Items, which are buffered until the memory-budget is reached.
+/// Then, the buffer is sorted by key and spilled as run into a temporary file.
+/// When merging, all runs are merged (k-way), so the items are returned sorted without loading all of them.
+/// Since every merged run is an open file, at most `max_merged_runs` runs are merged at once,
+/// hence more runs are merged in several passes.
+///
+/// Items with equal keys are returned in order of insertion.
+pub struct SortedRuns<T> {
+    tmp_dir: TmpDir,
+    name: &'static str,
+    max_buffer_len: usize,
+    max_merged_runs: usize,
+    buffer: Vec<T>,
+    // in order of insertion
+    run_files: Vec<PathBuf>,
+    file_count: usize,
+    len: usize,
+}
+
+impl<T: Spillable> SortedRuns<T> {
+    /// The name is used for the run-files and for messages.
+    pub fn new(
+        tmp_dir: TmpDir,
+        name: &'static str,
+        memory_budget_b: usize,
+        max_merged_runs: usize,
+    ) -> SortedRuns<T> {
+        let max_buffer_len = max(1, memory_budget_b / T::mem_size_b());
+        debug!("max-buffer-len of {}: {}", name, max_buffer_len);
+        SortedRuns {
+            tmp_dir,
+            name,
+            max_buffer_len,
+            max_merged_runs: max(2, max_merged_runs),
+            buffer: Vec::new(),
+            run_files: Vec::new(),
+            file_count: 0,
+            len: 0,
+        }
+    }
+
+    /// Number of pushed items
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn run_count(&self) -> usize {
+        self.run_files.len()
+    }
+
+    pub fn push(&mut self, item: T) -> err::Feedback {
+        self.buffer.push(item);
+        self.len += 1;
+        if self.buffer.len() >= self.max_buffer_len {
+            self.spill()?;
+        }
+        Ok(())
+    }
+
+    /// Spills the remaining buffer and merges all runs.
+    pub fn into_merged(mut self) -> err::Result<MergedRuns<T>> {
+        if !self.buffer.is_empty() {
+            self.spill()?;
+        }
+
+        // The first runs are merged into one run, which replaces them,
+        // so equal keys keep their order of insertion.
+        while self.run_files.len() > self.max_merged_runs {
+            let run_files: Vec<PathBuf> = self.run_files.drain(..self.max_merged_runs).collect();
+            debug!("Merge {} runs of {}", run_files.len(), self.name);
+            let (run_file, mut writer) = self.create_run_file()?;
+            for item in Merger::<T>::open(self.name, &run_files)? {
+                item?
+                    .write_to(&mut writer)
+                    .map_err(|e| io_err(self.name, e))?;
+            }
+            writer.flush().map_err(|e| io_err(self.name, e))?;
+            for run_file in &run_files {
+                if let Err(e) = fs::remove_file(run_file) {
+                    return Err(format!(
+                        "Couldn't remove {} due to error: {}",
+                        run_file.display(),
+                        e
+                    )
+                    .into());
+                }
+            }
+            self.run_files.insert(0, run_file);
+        }
+
+        Ok(MergedRuns {
+            merger: Merger::open(self.name, &self.run_files)?,
+            _tmp_dir: self.tmp_dir,
+        })
+    }
+
+    fn spill(&mut self) -> err::Feedback {
+        // stable, so equal keys keep their order
+        self.buffer.sort_by_key(T::key);
+
+        let (run_file, mut writer) = self.create_run_file()?;
+        debug!(
+            "Spill {} {} into {}",
+            self.buffer.len(),
+            self.name,
+            run_file.display()
+        );
+        let name = self.name;
+        for item in self.buffer.drain(..) {
+            item.write_to(&mut writer).map_err(|e| io_err(name, e))?;
+        }
+        writer.flush().map_err(|e| io_err(name, e))?;
+
+        self.run_files.push(run_file);
+        Ok(())
+    }
+
+    fn create_run_file(&mut self) -> err::Result<(PathBuf, BufWriter<File>)> {
+        let run_file = self.tmp_dir.file(self.name, self.file_count);
+        self.file_count += 1;
+        match OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&run_file)
+        {
+            Ok(file) => Ok((run_file, BufWriter::new(file))),
+            Err(e) => {
+                Err(format!("Couldn't create {} due to error: {}", run_file.display(), e).into())
+            }
+        }
+    }
+}
+
+/// Iterates over the items of all runs, sorted by their key.
+/// The temporary files are removed when dropping this iterator.
+pub struct MergedRuns<T: Spillable> {
+    merger: Merger<T>,
+    _tmp_dir: TmpDir,
+}
+
+impl<T: Spillable> Iterator for MergedRuns<T> {
+    type Item = err::Result<T>;
+
+    fn next(&mut self) -> Option<err::Result<T>> {
+        self.merger.next()
+    }
+}
+
+/// K-way merge of the given runs, where every run is an open file.
+struct Merger<T: Spillable> {
+    name: &'static str,
+    readers: Vec<BufReader<File>>,
+    heads: Vec<Option<T>>,
+    // ties are broken by the run-idx, hence by order of insertion
+    heap: BinaryHeap<Reverse<(T::Key, usize)>>,
+}
+
+impl<T: Spillable> Merger<T> {
+    fn open(name: &'static str, run_files: &[PathBuf]) -> err::Result<Merger<T>> {
+        let mut readers = Vec::with_capacity(run_files.len());
+        let mut heads = Vec::with_capacity(run_files.len());
+        let mut heap = BinaryHeap::with_capacity(run_files.len());
+        for (run_idx, run_file) in run_files.iter().enumerate() {
+            let mut reader = match OpenOptions::new().read(true).open(run_file) {
+                Ok(file) => BufReader::new(file),
+                Err(e) => {
+                    return Err(
+                        format!("Couldn't open {} due to error: {}", run_file.display(), e).into(),
+                    )
+                }
+            };
+            let head = T::read_from(&mut reader).map_err(|e| io_err(name, e))?;
+            if let Some(item) = &head {
+                heap.push(Reverse((item.key(), run_idx)));
+            }
+            readers.push(reader);
+            heads.push(head);
+        }
+
+        Ok(Merger {
+            name,
+            readers,
+            heads,
+            heap,
+        })
+    }
+}
+
+impl<T: Spillable> Iterator for Merger<T> {
+    type Item = err::Result<T>;
+
+    fn next(&mut self) -> Option<err::Result<T>> {
+        let Reverse((_key, run_idx)) = self.heap.pop()?;
+        let next_head = match T::read_from(&mut self.readers[run_idx]) {
+            Ok(next_head) => next_head,
+            Err(e) => return Some(Err(io_err(self.name, e))),
+        };
+        if let Some(item) = &next_head {
+            self.heap.push(Reverse((item.key(), run_idx)));
+        }
+        let head = std::mem::replace(&mut self.heads[run_idx], next_head);
+        head.map(Ok)
+    }
+}
+
+/// Reads a record of `buf.len()` bytes, as needed by `Spillable::read_from(...)`.
+///
+/// Returns false, if the reader has ended before the record.
+/// A record, which has been started but is incomplete (e.g. of a truncated file), is an error.
+pub fn read_record<R: Read>(reader: &mut R, buf: &mut [u8]) -> io::Result<bool> {
+    let mut read_count = 0;
+    while read_count < buf.len() {
+        match reader.read(&mut buf[read_count..]) {
+            Ok(0) if read_count == 0 => return Ok(false),
+            Ok(0) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    format!(
+                        "The record is truncated after {} of {} bytes.",
+                        read_count,
+                        buf.len()
+                    ),
+                ))
+            }
+            Ok(n) => read_count += n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => (),
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(true)
+}
+
+fn io_err(name: &str, e: io::Error) -> err::Msg {
+    err::Msg::from(format!("Spilling {} failed due to error: {}", name, e))
+}
@@ -4,6 +4,12 @@ use std::path::Path;
 mod parsing;
 mod writing;
 
+pub mod diff {
+    pub use crate::io::writing::diff::Writer;
+}
+pub mod geojson {
+    pub use crate::io::writing::geojson::Writer;
+}
 pub mod smarts {
     pub use crate::io::writing::smarts::Writer;
 }
@@ -16,12 +22,17 @@ pub mod network {
         pub use crate::io::parsing::network::edges::Parser;
         pub use crate::io::writing::network::edges::Writer;
     }
+    pub mod updates {
+        pub use crate::io::parsing::network::updates::Parser;
+    }
+}
+pub mod matching {
+    pub use crate::io::parsing::matching::Parser;
 }
 pub mod routing {
     pub use crate::io::parsing::routing::Parser;
-    pub use crate::io::writing::routing::Writer;
+    pub use crate::io::writing::routing::{PathsWriter, Writer};
 }
-#[cfg(feature = "gpl")]
 pub mod evaluating_balance {
     pub use crate::io::writing::evaluating_balance::Writer;
 }
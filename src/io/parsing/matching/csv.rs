@@ -0,0 +1,112 @@
+use crate::{helpers::err, routing::matching::GpsPoint};
+use kissunits::geo::Coordinate;
+use std::{
+    fs::OpenOptions,
+    io::{BufRead, BufReader},
+    path::Path,
+};
+
+/// Parses lines of `lat,lon[,time]`, where the time is given as RFC 3339 or as seconds since the unix-epoch.
+///
+/// An optional header (e.g. `lat,lon,time`) defines the columns' order by the names `lat`, `lon` and `time`.
+/// Lines starting with `#` are ignored.
+pub fn parse(path: &Path) -> err::Result<Vec<GpsPoint>> {
+    let file = OpenOptions::new().read(true).open(path).map_err(|e| {
+        err::Msg::from(format!(
+            "Couldn't open {} due to error: {}",
+            path.display(),
+            e
+        ))
+    })?;
+
+    let mut trace = Vec::new();
+    let mut columns: Option<Columns> = None;
+    for (line_idx, line) in BufReader::new(file).lines().enumerate() {
+        let line = line?;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let params: Vec<&str> = line.split(',').map(str::trim).collect();
+
+        let columns = match &columns {
+            Some(columns) => columns,
+            None => {
+                // The first line is either the header or already a gps-point.
+                if params[0].parse::<f64>().is_err() {
+                    columns = Some(Columns::from_header(&params)?);
+                    continue;
+                }
+                columns.get_or_insert(Columns::default())
+            }
+        };
+
+        let param = |idx: usize| -> err::Result<&str> {
+            params.get(idx).copied().ok_or_else(|| {
+                err::Msg::from(format!(
+                    "Line {} of {} should have at least {} values.",
+                    line_idx + 1,
+                    path.display(),
+                    idx + 1
+                ))
+            })
+        };
+        let parse_f64 = |snippet: &str| {
+            snippet
+                .parse::<f64>()
+                .map_err(|_| err::Msg::from(format!("Couldn't parse f64-value {}", snippet)))
+        };
+        // the time is optional, even per line
+        let timestamp = match columns.time.and_then(|idx| params.get(idx)) {
+            Some(&"") | None => None,
+            Some(snippet) => Some(super::parse_timestamp(snippet)?),
+        };
+        trace.push(GpsPoint {
+            coord: Coordinate {
+                lat: parse_f64(param(columns.lat)?)?,
+                lon: parse_f64(param(columns.lon)?)?,
+            },
+            timestamp,
+        });
+    }
+
+    Ok(trace)
+}
+
+struct Columns {
+    lat: usize,
+    lon: usize,
+    time: Option<usize>,
+}
+
+impl Default for Columns {
+    fn default() -> Columns {
+        Columns {
+            lat: 0,
+            lon: 1,
+            time: Some(2),
+        }
+    }
+}
+
+impl Columns {
+    fn from_header(names: &[&str]) -> err::Result<Columns> {
+        let position = |name: &str| {
+            names
+                .iter()
+                .position(|other| other.eq_ignore_ascii_case(name))
+        };
+
+        match (position("lat"), position("lon")) {
+            (Some(lat), Some(lon)) => Ok(Columns {
+                lat,
+                lon,
+                time: position("time"),
+            }),
+            _ => Err(err::Msg::from(format!(
+                "The header {:?} should contain the columns lat and lon.",
+                names
+            ))),
+        }
+    }
+}
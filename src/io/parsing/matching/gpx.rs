@@ -0,0 +1,101 @@
+use crate::{helpers::err, routing::matching::GpsPoint};
+use kissunits::geo::Coordinate;
+use quick_xml::{
+    events::{BytesStart, Event},
+    Reader,
+};
+use std::{io::BufRead, path::Path};
+
+/// Parses the track-points (`trkpt`) and route-points (`rtept`) of all tracks and routes in order.
+/// Their optional element `time` is taken as timestamp.
+pub fn parse(path: &Path) -> err::Result<Vec<GpsPoint>> {
+    let mut reader = Reader::from_file(path).map_err(|e| {
+        err::Msg::from(format!(
+            "Couldn't open {} due to error: {}",
+            path.display(),
+            e
+        ))
+    })?;
+    reader.trim_text(true);
+
+    let mut trace = Vec::new();
+    let mut current_point: Option<GpsPoint> = None;
+    let mut is_in_time = false;
+    let mut buf = Vec::new();
+    loop {
+        match reader.read_event(&mut buf) {
+            Ok(Event::Start(ref e)) => match e.name() {
+                b"trkpt" | b"rtept" => current_point = Some(parse_point(e, &reader)?),
+                b"time" => is_in_time = current_point.is_some(),
+                _ => (),
+            },
+            Ok(Event::Empty(ref e)) => {
+                if let b"trkpt" | b"rtept" = e.name() {
+                    trace.push(parse_point(e, &reader)?);
+                }
+            }
+            Ok(Event::Text(ref e)) if is_in_time => {
+                let snippet = e.unescape_and_decode(&reader).map_err(|e| {
+                    err::Msg::from(format!("Couldn't decode time due to error: {}", e))
+                })?;
+                if let Some(point) = current_point.as_mut() {
+                    point.timestamp = Some(super::parse_timestamp(&snippet)?);
+                }
+            }
+            Ok(Event::End(ref e)) => match e.name() {
+                b"trkpt" | b"rtept" => {
+                    if let Some(point) = current_point.take() {
+                        trace.push(point);
+                    }
+                }
+                b"time" => is_in_time = false,
+                _ => (),
+            },
+            Ok(Event::Eof) => break,
+            Err(e) => {
+                return Err(err::Msg::from(format!(
+                    "Couldn't read {} at position {} due to error: {}",
+                    path.display(),
+                    reader.buffer_position(),
+                    e
+                )))
+            }
+            _ => (),
+        }
+        buf.clear();
+    }
+
+    Ok(trace)
+}
+
+fn parse_point<B: BufRead>(e: &BytesStart, reader: &Reader<B>) -> err::Result<GpsPoint> {
+    let mut lat = None;
+    let mut lon = None;
+    for attribute in e.attributes() {
+        let attribute = attribute
+            .map_err(|e| err::Msg::from(format!("Couldn't read attribute due to error: {}", e)))?;
+        let value = || -> err::Result<f64> {
+            let value = attribute.unescape_and_decode_value(reader).map_err(|e| {
+                err::Msg::from(format!("Couldn't decode attribute due to error: {}", e))
+            })?;
+            value
+                .parse::<f64>()
+                .map_err(|_| err::Msg::from(format!("Couldn't parse f64-value {}", value)))
+        };
+        match attribute.key {
+            b"lat" => lat = Some(value()?),
+            b"lon" => lon = Some(value()?),
+            _ => (),
+        }
+    }
+
+    match (lat, lon) {
+        (Some(lat), Some(lon)) => Ok(GpsPoint {
+            coord: Coordinate { lat, lon },
+            timestamp: None,
+        }),
+        _ => Err(err::Msg::from(
+            "Every gps-point should have the attributes lat and lon.",
+        )),
+    }
+}
@@ -0,0 +1,52 @@
+use crate::{configs, helpers::err, io::SupportingFileExts, routing::matching::GpsPoint};
+use chrono::DateTime;
+use log::info;
+
+mod csv;
+mod gpx;
+
+pub struct Parser;
+
+impl Parser {
+    /// Parses the config's trace-file, which is either a gpx-file (its track-points) or a csv-file (lines of `lat,lon[,time]`).
+    pub fn parse(cfg: &configs::matching::Config) -> err::Result<Vec<GpsPoint>> {
+        let trace_file = cfg.trace_file.as_ref().expect("No trace-file specified.");
+
+        info!("DO Parse gps-trace");
+        let trace = match Parser::find_supported_ext(trace_file) {
+            Ok("gpx") => gpx::parse(trace_file),
+            Ok("csv") => csv::parse(trace_file),
+            Ok(_) => Err(err::Msg::from(
+                "Should not happen, since 'find_supported_ext(...)' should cover this.",
+            )),
+            Err(msg) => Err(format!("Wrong trace-file in parser: {}", msg).into()),
+        }?;
+        info!("FINISHED Parsed {} gps-points", trace.len());
+
+        Ok(trace)
+    }
+}
+
+impl SupportingFileExts for Parser {
+    fn supported_exts<'a>() -> &'a [&'a str] {
+        &["gpx", "csv"]
+    }
+}
+
+/// Parses timestamps given as RFC 3339 (e.g. `2020-06-01T12:00:00Z`) or as seconds since the unix-epoch.
+fn parse_timestamp(snippet: &str) -> Result<f64, String> {
+    let snippet = snippet.trim();
+    if let Ok(seconds) = snippet.parse::<f64>() {
+        return Ok(seconds);
+    }
+
+    match DateTime::parse_from_rfc3339(snippet) {
+        Ok(date_time) => {
+            Ok(date_time.timestamp() as f64 + f64::from(date_time.timestamp_subsec_nanos()) * 1e-9)
+        }
+        Err(e) => Err(format!(
+            "Couldn't parse timestamp {} due to error: {}",
+            snippet, e
+        )),
+    }
+}
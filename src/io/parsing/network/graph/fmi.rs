@@ -302,6 +302,7 @@ impl ProtoShortcut {
                 src_id: src_id.ok_or("Proto-edge should have a src-id, but doesn't.".to_owned())?,
                 dst_id: dst_id.ok_or("Proto-edge should have a dst-id, but doesn't.".to_owned())?,
                 metrics: metric_values,
+                street_idx: None,
             },
             sc_edges,
         })
@@ -2,13 +2,12 @@ pub mod fmi;
 pub mod pbf;
 
 use crate::{
-    configs::parsing::{self, generating},
-    defaults::capacity,
+    configs::parsing,
     helpers::err,
     io::{MapFileExt, SupportingFileExts, SupportingMapFileExts},
     network::{EdgeBuilder, Graph, GraphBuilder, NodeBuilder},
 };
-use log::{info, warn};
+use log::info;
 use std::path::Path;
 
 /// The parser parsing `*.osm.pbf`- and `*.fmi`-files into a graphbuilder or a graph.
@@ -77,19 +76,32 @@ trait Parsing {
 
         info!("START Process given file");
         self.preprocess(builder.cfg())?;
-        self.parse_ways(&mut builder)?;
-        let mut builder = builder.next();
-        self.parse_nodes(&mut builder)?;
-        let builder = builder.next();
+        let builder = if builder.cfg().external_memory.is_some() {
+            self.parse_with_external_memory(builder)?
+        } else {
+            self.parse_ways(&mut builder)?;
+            let mut builder = builder.next();
+            self.parse_nodes(&mut builder)?;
+            builder.next()?
+        };
         info!("FINISHED");
 
-        builder
+        Ok(builder)
     }
 
     fn parse_ways(&self, builder: &mut EdgeBuilder) -> err::Feedback;
 
     fn parse_nodes(&self, builder: &mut NodeBuilder) -> err::Feedback;
 
+    /// Parses ways and nodes with bounded memory (see `configs::parsing::external_memory`).
+    /// The builder spills its proto-edges anyways, but parsers may reduce the memory further, e.g. by reading the map-file only once.
+    fn parse_with_external_memory(&self, mut builder: EdgeBuilder) -> err::Result<GraphBuilder> {
+        self.parse_ways(&mut builder)?;
+        let mut builder = builder.next();
+        self.parse_nodes(&mut builder)?;
+        builder.next()
+    }
+
     fn parse_and_finalize(&mut self, cfg: parsing::Config) -> err::Result<Graph> {
         let path = Path::new(&cfg.map_file);
         info!("START Parse from given path {}", path.display());
@@ -141,67 +153,6 @@ fn check_config(cfg: &parsing::Config) -> err::Feedback {
         return Err("The provided config-file doesn't contain a longitude, but needs to.".into());
     }
 
-    // check edges' metric-memory-capacity
-
-    let dim = cfg.edges.metrics.units.len()
-        + if let Some(generating_cfg) = &cfg.generating {
-            generating_cfg
-                .edges
-                .categories
-                .iter()
-                .map(|category| match category {
-                    generating::edges::Category::Meta { info: _, id: _ } => 0,
-                    generating::edges::Category::Convert { from: _, to: _ } => 0,
-                    generating::edges::Category::Calc {
-                        result: _,
-                        a: _,
-                        b: _,
-                    } => 1,
-                    generating::edges::Category::Copy { from: _, to: _ } => 1,
-                    generating::edges::Category::Haversine { unit: _, id: _ } => 1,
-                    generating::edges::Category::Custom {
-                        unit: _,
-                        id: _,
-                        default: _,
-                    } => 1,
-                    generating::edges::Category::Merge {
-                        from: _,
-                        is_file_with_header: _,
-                        edge_id: _,
-                        edges_info: _,
-                    } => 0,
-                })
-                .sum()
-        } else {
-            0
-        };
-
-    if dim > capacity::SMALL_VEC_INLINE_SIZE {
-        return Err(format!(
-            "{}{}{}{}{}{}{}",
-            "The provided config-file has more metrics for the graph (",
-            dim,
-            ") than the parser has been compiled to (",
-            capacity::SMALL_VEC_INLINE_SIZE,
-            "). You can set the graph's dimension via 'GRAPH_DIM=",
-            dim,
-            " cargo build ...'"
-        )
-        .into());
-    } else if dim < capacity::SMALL_VEC_INLINE_SIZE {
-        warn!(
-            "{}{}{}{}{}{}{}{}",
-            "The provided config-file has less metrics for the graph (",
-            dim,
-            ") than the parser has been compiled to (",
-            capacity::SMALL_VEC_INLINE_SIZE,
-            "). Compiling accordingly saves memory. You can set the graph's dimension via ",
-            "'GRAPH_DIM=",
-            dim,
-            " cargo build ...'"
-        );
-    }
-
     // check count of shortcut-edge-indices
     // -> only 0 or 2 allowed
 
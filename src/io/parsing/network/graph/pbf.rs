@@ -1,14 +1,28 @@
 use crate::{
     configs::parsing::{self, edges},
     defaults::capacity::DimVec,
-    helpers::err,
-    network::{EdgeBuilder, NodeBuilder, ProtoEdge, ProtoNode, StreetCategory},
+    helpers::{
+        err,
+        spilling::{self, SortedRuns, Spillable, TmpDir},
+        MemSize,
+    },
+    network::{EdgeBuilder, GraphBuilder, NodeBuilder, ProtoEdge, ProtoNode, StreetCategory},
 };
 use kissunits::geo::Coordinate;
 use log::info;
-use osmpbfreader::{reader::OsmPbfReader, OsmObj};
+use osmpbfreader::{
+    blocks, osmformat::PrimitiveBlock, primitive_block_from_blob, reader::OsmPbfReader, OsmObj, Way,
+};
 use smallvec::smallvec;
-use std::fs::OpenOptions;
+use std::{
+    collections::BTreeMap,
+    fs::OpenOptions,
+    io::{self, Read, Write},
+    mem,
+    path::Path,
+    sync::{mpsc, Arc, Condvar, Mutex},
+    thread,
+};
 
 pub struct Parser;
 
@@ -47,6 +61,7 @@ impl super::Parsing for Parser {
                     | edges::metrics::UnitInfo::Seconds
                     | edges::metrics::UnitInfo::Minutes
                     | edges::metrics::UnitInfo::Hours
+                    | edges::metrics::UnitInfo::DiscomfortKilometers
                     | edges::metrics::UnitInfo::F64 => {
                         return Err(format!(
                             "The {:?} of an edge in a pbf-file has to be calculated, \
@@ -56,7 +71,8 @@ impl super::Parsing for Parser {
                         .into());
                     }
                     edges::metrics::UnitInfo::KilometersPerHour
-                    | edges::metrics::UnitInfo::LaneCount => {
+                    | edges::metrics::UnitInfo::LaneCount
+                    | edges::metrics::UnitInfo::Discomfort => {
                         // irrelevant
                     }
                 },
@@ -64,137 +80,428 @@ impl super::Parsing for Parser {
             }
         }
 
+        for (surface, factor) in cfg.vehicles.surface_speed_factors.iter() {
+            if *factor <= 0.0 {
+                return Err(format!(
+                    "The speed-factor {} of surface `{}` has to be positive.",
+                    factor, surface
+                )
+                .into());
+            }
+        }
+
         info!("FINISHED");
         Ok(())
     }
 
+    /// Ways are processed per block of the map-file with one builder per block.
+    /// These builders are appended in the order of the map-file, so the result doesn't depend on the number of threads.
     fn parse_ways(&self, builder: &mut EdgeBuilder) -> err::Feedback {
-        info!("START Create edges from input-file.");
-        let file = OpenOptions::new()
-            .read(true)
-            .open(&builder.cfg().map_file)
-            .expect(&format!(
-                "Couldn't open {}",
-                builder.cfg().map_file.display()
-            ));
-
-        for mut way in OsmPbfReader::new(file)
-            .par_iter()
-            .filter_map(Result::ok)
-            .filter_map(|obj| match obj {
-                OsmObj::Way(way) => Some(way),
-                _ => None,
-            })
+        info!(
+            "START Create edges from input-file using {} threads.",
+            builder.cfg().num_threads
+        );
+        let block_cfg = block_cfg_of(builder.cfg());
+        for_each_block(
+            &block_cfg.map_file,
+            block_cfg.num_threads,
+            |block| {
+                let mut block_builder = GraphBuilder::new(block_cfg.clone());
+                for way in blocks::ways(block) {
+                    insert_way(&mut block_builder, way)?;
+                }
+                Ok(block_builder)
+            },
+            |block_builder| builder.append(block_builder),
+        )?;
+        info!("FINISHED");
+        Ok(())
+    }
+
+    /// Nodes are looked up per block of the map-file, so only nodes of edges are inserted afterwards.
+    fn parse_nodes(&self, builder: &mut NodeBuilder) -> err::Feedback {
+        info!(
+            "START Create nodes from input-file using {} threads.",
+            builder.cfg().num_threads
+        );
+        // Workers look up nodes while sharing the builder, hence found nodes are inserted afterwards.
+        let mut proto_nodes = Vec::new();
         {
-            if way.nodes.len() < 2 {
-                continue;
-            }
+            let builder: &NodeBuilder = builder;
+            for_each_block(
+                &builder.cfg().map_file,
+                builder.cfg().num_threads,
+                |block| {
+                    Ok(blocks::nodes(block)
+                        // add node to graph if it's part of an edge
+                        .filter(|node| builder.contains(node.id.0))
+                        .map(|node| ProtoNode {
+                            id: node.id.0,
+                            coord: Coordinate::from_decimicro(
+                                node.decimicro_lat,
+                                node.decimicro_lon,
+                            ),
+                            ch_level: None,
+                        })
+                        .collect::<Vec<_>>())
+                },
+                |block_nodes| {
+                    proto_nodes.extend(block_nodes);
+                    Ok(())
+                },
+            )?;
+        }
+        for proto_node in proto_nodes {
+            builder.insert(proto_node);
+        }
+        info!("FINISHED");
+        Ok(())
+    }
 
-            // collect relevant data from file, if way-type is as expected by user
-            let highway_tag = match StreetCategory::from(&way) {
-                Some(highway_tag) => highway_tag,
-                None => continue,
-            };
-            if !highway_tag.is_for(
-                &builder.cfg().vehicles.category,
-                builder.cfg().vehicles.are_drivers_picky,
-            ) {
-                continue;
-            }
+    /// Reads the map-file only once.
+    /// All nodes' coordinates are spilled as runs sorted by id, because ways refer to nodes, which are not known before reading all ways.
+    /// Afterwards, the merged runs are joined with the (sorted) node-ids of the edges, resolving their coordinates.
+    fn parse_with_external_memory(&self, mut builder: EdgeBuilder) -> err::Result<GraphBuilder> {
+        info!("START Create edges and spill nodes from input-file in one pass.");
+        let external_memory_cfg = builder
+            .cfg()
+            .external_memory
+            .clone()
+            .expect("Parsing with external memory needs its config.");
+        let mut coords = SortedRuns::new(
+            TmpDir::new(&external_memory_cfg.dir)?,
+            "nodes",
+            external_memory_cfg.memory_budget_b,
+            external_memory_cfg.max_merged_runs,
+        );
 
-            // get nodes of way to create proto-edges later
-            let (is_oneway, is_reverse) = highway_tag.parse_oneway(&way);
-            if is_reverse {
-                way.nodes.reverse();
-            }
-            let iter_range = if is_oneway {
-                0..0
-            } else {
-                // if not oneway
-                // -> add node-IDs reversed to generate edges forwards and backwards
-                // -> don't use last one, to not use it twice (a->b->c->c->b->a)
-                0..(way.nodes.len() - 1)
-            };
-            let nodes: Vec<i64> = way
-                .nodes
-                .iter()
-                .chain(way.nodes[iter_range].iter().rev())
-                .map(|id| id.0)
-                .collect();
-
-            // Collect metrics as expected by user-config
-            // ATTENTION: A way contains multiple edges, thus be careful when adding new metrics.
-
-            let mut metrics: DimVec<_> = smallvec![];
-
-            for category in builder.cfg().edges.categories.iter() {
-                match category {
-                    edges::Category::Meta { info: _, id: _ } => {
-                        // already checked in preprocessing
-                    }
-                    edges::Category::Metric { unit, id: _ } => match unit {
-                        edges::metrics::UnitInfo::KilometersPerHour => {
-                            let maxspeed = highway_tag.parse_maxspeed(&way);
-                            metrics.push(*maxspeed);
-                        }
-                        edges::metrics::UnitInfo::LaneCount => {
-                            let lane_count = highway_tag.parse_lane_count(&way);
-                            metrics.push(lane_count as f64);
-                        }
-                        edges::metrics::UnitInfo::Meters
-                        | edges::metrics::UnitInfo::Kilometers
-                        | edges::metrics::UnitInfo::Seconds
-                        | edges::metrics::UnitInfo::Minutes
-                        | edges::metrics::UnitInfo::Hours
-                        | edges::metrics::UnitInfo::F64 => {
-                            // already checked in preprocessing
-                        }
-                    },
-                    edges::Category::Ignored => {
-                        // already checked in preprocessing
+        let block_cfg = block_cfg_of(builder.cfg());
+        for_each_block(
+            &block_cfg.map_file,
+            block_cfg.num_threads,
+            |block| {
+                let mut block_builder = GraphBuilder::new(block_cfg.clone());
+                let mut block_nodes = Vec::new();
+                for obj in blocks::iter(block) {
+                    match obj {
+                        OsmObj::Node(node) => block_nodes.push(SpilledNode {
+                            id: node.id.0,
+                            decimicro_lat: node.decimicro_lat,
+                            decimicro_lon: node.decimicro_lon,
+                        }),
+                        OsmObj::Way(way) => insert_way(&mut block_builder, way)?,
+                        OsmObj::Relation(_) => (),
                     }
                 }
+                Ok((block_builder, block_nodes))
+            },
+            |(block_builder, block_nodes)| {
+                for spilled_node in block_nodes {
+                    coords.push(spilled_node)?;
+                }
+                builder.append(block_builder)
+            },
+        )?;
+        info!("FINISHED");
+
+        info!(
+            "START Resolve coordinates by merging {} spilled nodes from {} sorted runs.",
+            coords.len(),
+            coords.run_count()
+        );
+        let mut builder = builder.next();
+        // add nodes to graph if they are part of an edge
+        builder.insert_sorted(coords.into_merged()?.map(|spilled_node| {
+            spilled_node.map(|spilled_node| ProtoNode {
+                id: spilled_node.id,
+                coord: Coordinate::from_decimicro(
+                    spilled_node.decimicro_lat,
+                    spilled_node.decimicro_lon,
+                ),
+                ch_level: None,
+            })
+        }))?;
+        info!("FINISHED");
+
+        builder.next()
+    }
+}
+
+/// Builders per block don't spill, since they are appended to the spilling builder.
+fn block_cfg_of(cfg: &parsing::Config) -> parsing::Config {
+    let mut block_cfg = cfg.clone();
+    block_cfg.external_memory = None;
+    block_cfg
+}
+
+/// Reads the map-file's blobs in one thread, while `num_threads` workers decode them into blocks and process them via `work`.
+/// The workers' results are merged via `merge` in the order of the map-file, so the graph doesn't depend on the threads' scheduling.
+/// Blobs and blocks, which can't be read, are errors, since the graph would be incomplete otherwise.
+fn for_each_block<T, W, M>(
+    map_file: &Path,
+    num_threads: usize,
+    work: W,
+    mut merge: M,
+) -> err::Feedback
+where
+    T: Send,
+    W: Fn(&PrimitiveBlock) -> err::Result<T> + Sync,
+    M: FnMut(T) -> err::Feedback,
+{
+    let file = match OpenOptions::new().read(true).open(map_file) {
+        Ok(file) => file,
+        Err(e) => {
+            return Err(format!("Couldn't open {} due to error: {}", map_file.display(), e).into())
+        }
+    };
+    let mut reader = OsmPbfReader::new(file);
+    let num_threads = num_threads.max(1);
+    // Results are merged in order, hence results of later blobs are pending, while an earlier blob is processed.
+    // Blobs are read at most this far ahead of the next merged blob, bounding the pending results.
+    let max_ahead = 2 * num_threads;
+    // idx of the next merged blob, or None if merging has stopped
+    let next_merged: (Mutex<Option<usize>>, Condvar) = (Mutex::new(Some(0)), Condvar::new());
+
+    thread::scope(|scope| {
+        // Channels are bounded to bound memory, if reading is faster than processing.
+        let (blob_sender, blob_receiver) = mpsc::sync_channel(max_ahead);
+        let (result_sender, result_receiver) = mpsc::sync_channel(max_ahead);
+
+        // Senders stop, when their receivers are dropped, e.g. due to an error.
+        let next_merged = &next_merged;
+        scope.spawn(move || {
+            for (blob_idx, blob) in reader.blobs().enumerate() {
+                let (next_merged_idx, is_merged) = next_merged;
+                let mut next_merged_idx = match next_merged_idx.lock() {
+                    Ok(next_merged_idx) => next_merged_idx,
+                    Err(_) => break,
+                };
+                while next_merged_idx.is_some_and(|idx| blob_idx >= idx + max_ahead) {
+                    next_merged_idx = match is_merged.wait(next_merged_idx) {
+                        Ok(next_merged_idx) => next_merged_idx,
+                        Err(_) => return,
+                    };
+                }
+                if next_merged_idx.is_none() {
+                    break;
+                }
+                drop(next_merged_idx);
+
+                let is_err = blob.is_err();
+                let blob = blob.map_err(|e| {
+                    err::Msg::from(format!(
+                        "Couldn't read blob {} of map-file due to error: {}",
+                        blob_idx, e
+                    ))
+                });
+                if blob_sender.send((blob_idx, blob)).is_err() || is_err {
+                    break;
+                }
             }
+        });
+        let blob_receiver = Arc::new(Mutex::new(blob_receiver));
+        for _ in 0..num_threads {
+            let blob_receiver = Arc::clone(&blob_receiver);
+            let result_sender = result_sender.clone();
+            let work = &work;
+            scope.spawn(move || loop {
+                let next_blob = match blob_receiver.lock() {
+                    Ok(blob_receiver) => blob_receiver.recv(),
+                    Err(_) => break,
+                };
+                let (blob_idx, blob) = match next_blob {
+                    Ok(next_blob) => next_blob,
+                    Err(_) => break,
+                };
+                let result = blob.and_then(|blob| match primitive_block_from_blob(&blob) {
+                    Ok(block) => work(&block),
+                    Err(e) => Err(format!(
+                        "Couldn't decode block {} of map-file due to error: {}",
+                        blob_idx, e
+                    )
+                    .into()),
+                });
+                if result_sender.send((blob_idx, result)).is_err() {
+                    break;
+                }
+            });
+        }
+        // workers have their own senders
+        drop(result_sender);
 
-            // for n nodes in a way, you can create (n-1) edges
-            for node_idx in 0..(nodes.len() - 1) {
-                // add proto-edge to graph
-                builder.insert(ProtoEdge {
-                    id: None,
-                    src_id: nodes[node_idx],
-                    dst_id: nodes[node_idx + 1],
-                    metrics: metrics.clone(),
-                })?;
+        // merge in order of the blobs
+        let merge_all = || -> err::Feedback {
+            let mut pending = BTreeMap::new();
+            let mut next_blob_idx = 0;
+            for (blob_idx, result) in result_receiver {
+                pending.insert(blob_idx, result);
+                while let Some(result) = pending.remove(&next_blob_idx) {
+                    merge(result?)?;
+                    next_blob_idx += 1;
+                    if let Ok(mut next_merged_idx) = next_merged.0.lock() {
+                        *next_merged_idx = Some(next_blob_idx);
+                    }
+                    next_merged.1.notify_all();
+                }
             }
+            Ok(())
+        };
+        let result = merge_all();
+
+        // stop reading, e.g. due to an error
+        if let Ok(mut next_merged_idx) = next_merged.0.lock() {
+            *next_merged_idx = None;
         }
-        info!("FINISHED");
-        Ok(())
+        next_merged.1.notify_all();
+        result
+    })
+}
+
+/// A node's coordinate, as it is spilled when parsing with external memory.
+struct SpilledNode {
+    id: i64,
+    decimicro_lat: i32,
+    decimicro_lon: i32,
+}
+
+impl MemSize for SpilledNode {
+    fn mem_size_b() -> usize {
+        mem::size_of::<i64>() + 2 * mem::size_of::<i32>()
+    }
+}
+
+impl Spillable for SpilledNode {
+    type Key = i64;
+
+    fn key(&self) -> i64 {
+        self.id
     }
 
-    fn parse_nodes(&self, builder: &mut NodeBuilder) -> err::Feedback {
-        info!("START Create nodes from input-file.");
-        let cfg = builder.cfg();
-
-        let file = OpenOptions::new()
-            .read(true)
-            .open(&cfg.map_file)
-            .expect(&format!("Couldn't open {}", cfg.map_file.display()));
-        for node in OsmPbfReader::new(file)
-            .par_iter()
-            .filter_map(Result::ok)
-            .filter_map(|obj| match obj {
-                OsmObj::Node(node) => Some(node),
-                _ => None,
-            })
-        {
-            // add node to graph if it's part of an edge
-            builder.insert(ProtoNode {
-                id: node.id.0,
-                coord: Coordinate::from_decimicro(node.decimicro_lat, node.decimicro_lon),
-                ch_level: None,
-            });
+    fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(&self.id.to_le_bytes())?;
+        writer.write_all(&self.decimicro_lat.to_le_bytes())?;
+        writer.write_all(&self.decimicro_lon.to_le_bytes())
+    }
+
+    fn read_from<R: Read>(reader: &mut R) -> io::Result<Option<SpilledNode>> {
+        let mut bytes = [0u8; 16];
+        if !spilling::read_record(reader, &mut bytes)? {
+            return Ok(None);
         }
-        info!("FINISHED");
-        Ok(())
+        let mut id = [0u8; 8];
+        id.copy_from_slice(&bytes[0..8]);
+        let mut lat = [0u8; 4];
+        lat.copy_from_slice(&bytes[8..12]);
+        let mut lon = [0u8; 4];
+        lon.copy_from_slice(&bytes[12..16]);
+        Ok(Some(SpilledNode {
+            id: i64::from_le_bytes(id),
+            decimicro_lat: i32::from_le_bytes(lat),
+            decimicro_lon: i32::from_le_bytes(lon),
+        }))
+    }
+}
+
+/// Creates the way's proto-edges, if the way is relevant for the configured vehicle.
+fn insert_way(builder: &mut EdgeBuilder, mut way: Way) -> err::Feedback {
+    if way.nodes.len() < 2 {
+        return Ok(());
+    }
+
+    // collect relevant data from file, if way-type is as expected by user
+    let highway_tag = match StreetCategory::from(&way) {
+        Some(highway_tag) => highway_tag,
+        None => return Ok(()),
+    };
+    if !highway_tag.is_for(
+        &builder.cfg().vehicles.category,
+        builder.cfg().vehicles.are_drivers_picky,
+    ) {
+        return Ok(());
+    }
+
+    // get nodes of way to create proto-edges later
+    let (is_oneway, is_reverse) = highway_tag.parse_oneway(&way);
+    if is_reverse {
+        way.nodes.reverse();
+    }
+    let iter_range = if is_oneway {
+        0..0
+    } else {
+        // if not oneway
+        // -> add node-IDs reversed to generate edges forwards and backwards
+        // -> don't use last one, to not use it twice (a->b->c->c->b->a)
+        0..(way.nodes.len() - 1)
+    };
+    let nodes: Vec<i64> = way
+        .nodes
+        .iter()
+        .chain(way.nodes[iter_range].iter().rev())
+        .map(|id| id.0)
+        .collect();
+
+    // Collect metrics as expected by user-config
+    // ATTENTION: A way contains multiple edges, thus be careful when adding new metrics.
+
+    let mut metrics: DimVec<_> = smallvec![];
+
+    for category in builder.cfg().edges.categories.iter() {
+        match category {
+            edges::Category::Meta { info: _, id: _ } => {
+                // already checked in preprocessing
+            }
+            edges::Category::Metric { unit, id: _ } => match unit {
+                edges::metrics::UnitInfo::KilometersPerHour => {
+                    let maxspeed = highway_tag.parse_maxspeed(&way);
+                    let factor = builder
+                        .cfg()
+                        .vehicles
+                        .surface_speed_factor_of(way.tags.get("surface").map(|s| s.as_str()));
+                    metrics.push(*maxspeed * factor);
+                }
+                edges::metrics::UnitInfo::LaneCount => {
+                    let lane_count = highway_tag.parse_lane_count(&way);
+                    metrics.push(lane_count as f64);
+                }
+                edges::metrics::UnitInfo::Discomfort => {
+                    let discomfort = highway_tag.parse_discomfort(&way);
+                    metrics.push(discomfort);
+                }
+                edges::metrics::UnitInfo::Meters
+                | edges::metrics::UnitInfo::Kilometers
+                | edges::metrics::UnitInfo::Seconds
+                | edges::metrics::UnitInfo::Minutes
+                | edges::metrics::UnitInfo::Hours
+                | edges::metrics::UnitInfo::DiscomfortKilometers
+                | edges::metrics::UnitInfo::F64 => {
+                    // already checked in preprocessing
+                }
+            },
+            edges::Category::Ignored => {
+                // already checked in preprocessing
+            }
+        }
+    }
+
+    // osm-ways are kept as streets, hence every way is stored on its own
+    let edges_cfg = &builder.cfg().edges;
+    let street_idx = if edges_cfg.is_keeping_streets || edges_cfg.ways.is_some() {
+        let street = highway_tag.parse_street(&way, edges_cfg.ways.as_ref());
+        Some(builder.insert_street(street))
+    } else {
+        None
+    };
+
+    // for n nodes in a way, you can create (n-1) edges
+    for node_idx in 0..(nodes.len() - 1) {
+        // add proto-edge to graph
+        builder.insert(ProtoEdge {
+            id: None,
+            src_id: nodes[node_idx],
+            dst_id: nodes[node_idx + 1],
+            metrics: metrics.clone(),
+            street_idx,
+        })?;
     }
+    Ok(())
 }
@@ -1,2 +1,3 @@
 pub mod edges;
 pub mod graph;
+pub mod updates;
@@ -0,0 +1,115 @@
+use super::{RawUpdate, RawValue};
+use crate::helpers::err;
+use std::{
+    fs::OpenOptions,
+    io::{BufRead, BufReader},
+    path::Path,
+};
+
+/// Parses lines of comma-separated values, whose order is defined by the (necessary) header.
+/// The header contains `edge-id` or `edge-idx`, `metric-id` and `value`.
+/// Lines starting with `#` are ignored.
+pub(super) fn parse(path: &Path) -> err::Result<Vec<RawUpdate>> {
+    let file = OpenOptions::new().read(true).open(path).map_err(|e| {
+        err::Msg::from(format!(
+            "Couldn't open {} due to error: {}",
+            path.display(),
+            e
+        ))
+    })?;
+
+    let mut raw_updates = Vec::new();
+    let mut columns: Option<Columns> = None;
+    for (line_idx, line) in BufReader::new(file).lines().enumerate() {
+        let line = line?;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let params: Vec<&str> = line.split(',').map(str::trim).collect();
+
+        let columns = match &columns {
+            Some(columns) => columns,
+            None => {
+                columns = Some(Columns::from_header(&params)?);
+                continue;
+            }
+        };
+
+        let param = |idx: usize| -> err::Result<&str> {
+            params.get(idx).copied().ok_or_else(|| {
+                err::Msg::from(format!(
+                    "Line {} of {} should have at least {} values.",
+                    line_idx + 1,
+                    path.display(),
+                    idx + 1
+                ))
+            })
+        };
+        let parse_usize = |snippet: &str| {
+            snippet
+                .parse::<usize>()
+                .map_err(|_| err::Msg::from(format!("Couldn't parse usize-value {}", snippet)))
+        };
+
+        let edge = match columns.edge {
+            EdgeColumn::Id(idx) => RawUpdate::edge_from(Some(parse_usize(param(idx)?)?), None)?,
+            EdgeColumn::Idx(idx) => RawUpdate::edge_from(None, Some(parse_usize(param(idx)?)?))?,
+        };
+        let metric_id = match columns.metric_id.and_then(|idx| params.get(idx)) {
+            Some(&"") | None => None,
+            Some(metric_id) => Some(String::from(*metric_id)),
+        };
+        let value = {
+            let snippet = param(columns.value)?;
+            match snippet.parse::<f64>() {
+                Ok(value) => RawValue::Number(value),
+                Err(_) => RawValue::Keyword(String::from(snippet)),
+            }
+        };
+        raw_updates.push(RawUpdate {
+            edge,
+            metric_id,
+            value,
+        });
+    }
+
+    Ok(raw_updates)
+}
+
+enum EdgeColumn {
+    Id(usize),
+    Idx(usize),
+}
+
+struct Columns {
+    edge: EdgeColumn,
+    metric_id: Option<usize>,
+    value: usize,
+}
+
+impl Columns {
+    fn from_header(params: &[&str]) -> err::Result<Columns> {
+        let position = |name: &str| params.iter().position(|param| *param == name);
+
+        let edge = match (position("edge-id"), position("edge-idx")) {
+            (Some(idx), None) => EdgeColumn::Id(idx),
+            (None, Some(idx)) => EdgeColumn::Idx(idx),
+            (Some(_), Some(_)) | (None, None) => {
+                return Err(err::Msg::from(format!(
+                    "The header {:?} should contain exactly one of `edge-id` or `edge-idx`.",
+                    params
+                )))
+            }
+        };
+        let value = position("value").ok_or_else(|| {
+            err::Msg::from(format!("The header {:?} should contain `value`.", params))
+        })?;
+
+        Ok(Columns {
+            edge,
+            metric_id: position("metric-id"),
+            value,
+        })
+    }
+}
@@ -0,0 +1,57 @@
+use super::{RawUpdate, RawValue};
+use crate::helpers::err;
+use serde::Deserialize;
+use std::{fs::OpenOptions, io::BufReader, path::Path};
+
+/// Parses an array of objects with `edge-id` or `edge-idx`, an optional `metric-id` and a `value`.
+pub(super) fn parse(path: &Path) -> err::Result<Vec<RawUpdate>> {
+    let file = OpenOptions::new().read(true).open(path).map_err(|e| {
+        err::Msg::from(format!(
+            "Couldn't open {} due to error: {}",
+            path.display(),
+            e
+        ))
+    })?;
+
+    let json_updates: Vec<JsonUpdate> =
+        serde_json::from_reader(BufReader::new(file)).map_err(|e| {
+            err::Msg::from(format!(
+                "Serde couldn't read {} due to error: {}",
+                path.display(),
+                e
+            ))
+        })?;
+
+    json_updates
+        .into_iter()
+        .map(|json_update| {
+            Ok(RawUpdate {
+                edge: RawUpdate::edge_from(json_update.edge_id, json_update.edge_idx)?,
+                metric_id: json_update.metric_id,
+                value: match json_update.value {
+                    JsonValue::Number(value) => RawValue::Number(value),
+                    JsonValue::Keyword(keyword) => RawValue::Keyword(keyword),
+                },
+            })
+        })
+        .collect()
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct JsonUpdate {
+    #[serde(rename = "edge-id")]
+    edge_id: Option<usize>,
+    #[serde(rename = "edge-idx")]
+    edge_idx: Option<usize>,
+    #[serde(rename = "metric-id")]
+    metric_id: Option<String>,
+    value: JsonValue,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum JsonValue {
+    Number(f64),
+    Keyword(String),
+}
@@ -0,0 +1,105 @@
+use crate::{
+    configs,
+    helpers::err,
+    io::SupportingFileExts,
+    network::{EdgeIdx, EdgeRef, MetricUpdate},
+};
+use log::info;
+use std::path::Path;
+
+mod csv;
+mod json;
+
+/// Parses batches of metric-updates (see `network::MetricUpdate`) from a csv- or json-file.
+///
+/// Every update refers to an edge by its `edge-id` or by its `edge-idx`.
+/// Its `value` is either a new value for the metric of the given `metric-id`,
+/// or one of the keywords `closed` or `open`, which don't need a `metric-id`.
+///
+/// - csv: A header (e.g. `edge-id,metric-id,value`) defines the columns' order.
+/// - json: An array of objects, e.g. `[{ "edge-id": 42, "metric-id": "kmph", "value": 30.0 }, { "edge-idx": 3, "value": "closed" }]`.
+pub struct Parser;
+
+impl Parser {
+    pub fn parse<P: AsRef<Path> + ?Sized>(
+        path: &P,
+        cfg: &configs::parsing::Config,
+    ) -> err::Result<Vec<MetricUpdate>> {
+        let path = path.as_ref();
+
+        info!("DO Parse metric-updates from {}", path.display());
+        let raw_updates = match Parser::find_supported_ext(path) {
+            Ok("csv") => csv::parse(path),
+            Ok("json") => json::parse(path),
+            Ok(_) => Err(err::Msg::from(
+                "Should not happen, since 'find_supported_ext(...)' should cover this.",
+            )),
+            Err(msg) => Err(format!("Wrong update-file in parser: {}", msg).into()),
+        }?;
+        let updates = raw_updates
+            .into_iter()
+            .map(|raw_update| raw_update.into_update(cfg))
+            .collect::<err::Result<Vec<_>>>()?;
+        info!("FINISHED Parsed {} metric-updates", updates.len());
+
+        Ok(updates)
+    }
+}
+
+impl SupportingFileExts for Parser {
+    fn supported_exts<'a>() -> &'a [&'a str] {
+        &["csv", "json"]
+    }
+}
+
+/// An update as given in the file, whose metric-id is not mapped yet.
+struct RawUpdate {
+    edge: EdgeRef,
+    metric_id: Option<String>,
+    value: RawValue,
+}
+
+enum RawValue {
+    Number(f64),
+    Keyword(String),
+}
+
+impl RawUpdate {
+    fn edge_from(edge_id: Option<usize>, edge_idx: Option<usize>) -> err::Result<EdgeRef> {
+        match (edge_id, edge_idx) {
+            (Some(edge_id), None) => Ok(EdgeRef::Id(edge_id)),
+            (None, Some(edge_idx)) => Ok(EdgeRef::Idx(EdgeIdx(edge_idx))),
+            (Some(_), Some(_)) | (None, None) => Err(err::Msg::from(
+                "Every metric-update needs exactly one of `edge-id` or `edge-idx`.",
+            )),
+        }
+    }
+
+    fn into_update(self, cfg: &configs::parsing::Config) -> err::Result<MetricUpdate> {
+        let edge = self.edge;
+        match self.value {
+            RawValue::Number(value) => {
+                let metric_id = self.metric_id.ok_or_else(|| {
+                    err::Msg::from(format!(
+                        "The metric-update of edge {:?} with value {} needs a metric-id.",
+                        edge, value
+                    ))
+                })?;
+                Ok(MetricUpdate::Set {
+                    edge,
+                    metric_idx: cfg.edges.metrics.try_idx_of(&metric_id)?,
+                    value,
+                })
+            }
+            RawValue::Keyword(keyword) => match keyword.trim().to_ascii_lowercase().as_ref() {
+                "closed" => Ok(MetricUpdate::Close { edge }),
+                "open" => Ok(MetricUpdate::Reopen { edge }),
+                _ => Err(format!(
+                    "The value `{}` of edge {:?} is neither a number nor `closed` or `open`.",
+                    keyword, edge
+                )
+                .into()),
+            },
+        }
+    }
+}
@@ -1,4 +1,5 @@
 use crate::{configs, helpers::err, io::SupportingFileExts, network::RoutePair};
+use kissunits::geo::Coordinate;
 use log::info;
 
 mod routes;
@@ -6,6 +7,7 @@ mod routes;
 pub struct Parser;
 
 impl Parser {
+    /// Parses route-pairs given as node-ids, meaning lines of `src-id dst-id count`.
     pub fn parse(cfg: &configs::routing::Config) -> err::Result<Vec<(RoutePair<i64>, usize)>> {
         let route_pairs_file = cfg
             .route_pairs_file
@@ -13,7 +15,24 @@ impl Parser {
             .expect("No routes-file specified.");
 
         match Parser::find_supported_ext(route_pairs_file) {
-            Ok(_) => routes::Parser::new().parse(cfg),
+            Ok(_) => routes::Parser::new().parse(cfg, routes::parse_id_line),
+            Err(msg) => Err(format!("Wrong parser-routes-file: {}", msg).into()),
+        }
+    }
+
+    /// Parses route-pairs given as coordinates, meaning lines of `src-lat src-lon dst-lat dst-lon count`.
+    ///
+    /// The coordinates can be snapped to nodes via `RoutePair::<Coordinate>::try_into_idx(...)`.
+    pub fn parse_coords(
+        cfg: &configs::routing::Config,
+    ) -> err::Result<Vec<(RoutePair<Coordinate>, usize)>> {
+        let route_pairs_file = cfg
+            .route_pairs_file
+            .as_ref()
+            .expect("No routes-file specified.");
+
+        match Parser::find_supported_ext(route_pairs_file) {
+            Ok(_) => routes::Parser::new().parse(cfg, routes::parse_coord_line),
             Err(msg) => Err(format!("Wrong parser-routes-file: {}", msg).into()),
         }
     }
@@ -25,6 +44,9 @@ impl SupportingFileExts for Parser {
     }
 }
 
+/// Parses the whitespace-separated values of a route-line into a route-pair and its count.
+type LineParser<T> = fn(&[&str]) -> Result<(RoutePair<T>, usize), String>;
+
 trait Parsing {
     fn preprocess(&mut self, cfg: &configs::routing::Config) -> err::Feedback {
         let route_pairs_file = cfg
@@ -40,18 +62,20 @@ trait Parsing {
         Ok(())
     }
 
-    fn parse_route_pairs(
+    fn parse_route_pairs<T>(
         &self,
         cfg: &configs::routing::Config,
-    ) -> Result<Vec<(RoutePair<i64>, usize)>, String>;
+        parse_line: LineParser<T>,
+    ) -> Result<Vec<(RoutePair<T>, usize)>, String>;
 
-    fn parse(
+    fn parse<T>(
         &mut self,
         cfg: &configs::routing::Config,
-    ) -> err::Result<Vec<(RoutePair<i64>, usize)>> {
+        parse_line: LineParser<T>,
+    ) -> err::Result<Vec<(RoutePair<T>, usize)>> {
         info!("DO Parse route-pairs");
         self.preprocess(cfg)?;
-        let routes = self.parse_route_pairs(cfg)?;
+        let routes = self.parse_route_pairs(cfg, parse_line)?;
 
         Ok(routes)
     }
@@ -3,6 +3,7 @@ use crate::{
     helpers::{self, err},
     network::RoutePair,
 };
+use kissunits::geo::Coordinate;
 use std::{
     fs::OpenOptions,
     io::{BufRead, BufReader},
@@ -73,10 +74,11 @@ impl super::Parsing for Parser {
         Ok(())
     }
 
-    fn parse_route_pairs(
+    fn parse_route_pairs<T>(
         &self,
         cfg: &configs::routing::Config,
-    ) -> Result<Vec<(RoutePair<i64>, usize)>, String> {
+        parse_line: super::LineParser<T>,
+    ) -> Result<Vec<(RoutePair<T>, usize)>, String> {
         let mut route_pairs = Vec::with_capacity(self.route_lines.len());
         let route_pairs_file = cfg
             .route_pairs_file
@@ -103,39 +105,83 @@ impl super::Parsing for Parser {
             // create route
 
             let params: Vec<&str> = line.split_whitespace().collect();
-            if params.len() != 3 {
-                return Err(format!(
-                    "A route-line is expected to consist of (src-id, dst-id, count), \
-                     but {} values are provided.",
-                    params.len()
-                ));
-            }
-
-            let param = params[0];
-            let src_id = param
-                .parse::<i64>()
-                .ok()
-                .ok_or(format!("Could not parse route's src-id {}", param))?;
-            let param = params[1];
-            let dst_id = param
-                .parse::<i64>()
-                .ok()
-                .ok_or(format!("Could not parse route's dst-id {}", param))?;
-            let param = params[2];
-            let n = param
-                .parse::<usize>()
-                .ok()
-                .ok_or(format!("Could not parse route's count {}", param))?;
-
-            route_pairs.push((
-                RoutePair {
-                    src: src_id,
-                    dst: dst_id,
-                },
-                n,
-            ));
+            route_pairs.push(parse_line(&params)?);
         }
 
         Ok(route_pairs)
     }
 }
+
+pub fn parse_id_line(params: &[&str]) -> Result<(RoutePair<i64>, usize), String> {
+    if params.len() != 3 {
+        return Err(format!(
+            "A route-line is expected to consist of (src-id, dst-id, count), \
+             but {} values are provided.",
+            params.len()
+        ));
+    }
+
+    let param = params[0];
+    let src_id = param
+        .parse::<i64>()
+        .ok()
+        .ok_or(format!("Could not parse route's src-id {}", param))?;
+    let param = params[1];
+    let dst_id = param
+        .parse::<i64>()
+        .ok()
+        .ok_or(format!("Could not parse route's dst-id {}", param))?;
+    let n = parse_count(params[2])?;
+
+    Ok((
+        RoutePair {
+            src: src_id,
+            dst: dst_id,
+        },
+        n,
+    ))
+}
+
+pub fn parse_coord_line(params: &[&str]) -> Result<(RoutePair<Coordinate>, usize), String> {
+    if params.len() != 5 {
+        return Err(format!(
+            "A route-line is expected to consist of (src-lat, src-lon, dst-lat, dst-lon, count), \
+             but {} values are provided.",
+            params.len()
+        ));
+    }
+
+    let mut values = [0.0; 4];
+    for (value, (param, name)) in values.iter_mut().zip(
+        params
+            .iter()
+            .zip(&["src-lat", "src-lon", "dst-lat", "dst-lon"]),
+    ) {
+        *value = param
+            .parse::<f64>()
+            .ok()
+            .ok_or(format!("Could not parse route's {} {}", name, param))?;
+    }
+    let n = parse_count(params[4])?;
+
+    Ok((
+        RoutePair {
+            src: Coordinate {
+                lat: values[0],
+                lon: values[1],
+            },
+            dst: Coordinate {
+                lat: values[2],
+                lon: values[3],
+            },
+        },
+        n,
+    ))
+}
+
+fn parse_count(param: &str) -> Result<usize, String> {
+    param
+        .parse::<usize>()
+        .ok()
+        .ok_or(format!("Could not parse route's count {}", param))
+}
@@ -0,0 +1,162 @@
+use super::geojson::{feature, write_feature_collection};
+use crate::{
+    configs::writing::diff::Config as WritingConfig, defaults, helpers::err,
+    io::SupportingFileExts, network::GraphDiff,
+};
+use log::info;
+use serde_json::{json, Map};
+use std::{
+    fmt::Display,
+    fs::OpenOptions,
+    io::{BufWriter, Write},
+};
+
+/// Writes the differences between two graphs (see `Graph::diff(...)`) as `csv` or GeoJSON, depending on the file's extension.
+///
+/// Every node and every edge is a line (or Feature) with the kind of its difference (`added`, `removed` or `changed`).
+/// Edges have their old and new values and the deltas per metric-id.
+/// In `csv`, columns are separated by spaces and missing values are written as `_`, while GeoJSON omits them.
+pub struct Writer;
+
+impl Writer {
+    pub fn write(diff: &GraphDiff, writing_cfg: &WritingConfig) -> err::Feedback {
+        info!(
+            "START Write the graphs' diff with {}",
+            writing_cfg.file.display()
+        );
+
+        let ext = Self::find_supported_ext(&writing_cfg.file)?;
+        let mut writer = match OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(&writing_cfg.file)
+        {
+            Ok(file) => BufWriter::new(file),
+            Err(e) => {
+                return Err(err::Msg::from(format!(
+                    "Couldn't open {} due to error: {}",
+                    writing_cfg.file.display(),
+                    e
+                )))
+            }
+        };
+
+        match ext {
+            "csv" => write_csv(&mut writer, diff)?,
+            _ => write_geojson(&mut writer, diff)?,
+        }
+        writer.flush()?;
+
+        info!("FINISHED");
+        Ok(())
+    }
+}
+
+impl SupportingFileExts for Writer {
+    fn supported_exts<'a>() -> &'a [&'a str] {
+        &["csv", "geojson", "json"]
+    }
+}
+
+fn write_csv<W: Write>(writer: &mut W, diff: &GraphDiff) -> err::Feedback {
+    // header
+
+    write!(writer, "element kind id src-id dst-id")?;
+    for metric_id in &diff.metric_ids {
+        write!(writer, " {id}-old {id}-new {id}-delta", id = metric_id.0)?;
+    }
+    writeln!(writer)?;
+
+    // nodes have no metrics
+
+    for node in &diff.nodes {
+        write!(
+            writer,
+            "node {} {} {ignore} {ignore}",
+            node.kind.name(),
+            node.id,
+            ignore = defaults::writing::IGNORE_STR
+        )?;
+        for _ in 0..(3 * diff.metric_ids.len()) {
+            write!(writer, " {}", defaults::writing::IGNORE_STR)?;
+        }
+        writeln!(writer)?;
+    }
+
+    for edge in &diff.edges {
+        write!(
+            writer,
+            "edge {} {} {} {}",
+            edge.kind.name(),
+            or_ignore(edge.id),
+            edge.src_id,
+            edge.dst_id
+        )?;
+        let deltas = edge.deltas();
+        for i in 0..diff.metric_ids.len() {
+            write!(
+                writer,
+                " {} {} {}",
+                or_ignore(edge.old_metrics.as_ref().map(|values| values[i])),
+                or_ignore(edge.new_metrics.as_ref().map(|values| values[i])),
+                or_ignore(deltas.as_ref().map(|values| values[i])),
+            )?;
+        }
+        writeln!(writer)?;
+    }
+
+    Ok(())
+}
+
+fn write_geojson<W: Write>(writer: &mut W, diff: &GraphDiff) -> err::Feedback {
+    let node_features = diff.nodes.iter().map(|node| {
+        json!({
+            "type": "Feature",
+            "geometry": {
+                "type": "Point",
+                "coordinates": [node.coord.lon, node.coord.lat],
+            },
+            "properties": {
+                "element": "node",
+                "kind": node.kind.name(),
+                "node-id": node.id,
+            },
+        })
+    });
+
+    let edge_features = diff.edges.iter().map(|edge| {
+        let mut properties = Map::new();
+        properties.insert(String::from("element"), json!("edge"));
+        properties.insert(String::from("kind"), json!(edge.kind.name()));
+        if let Some(edge_id) = edge.id {
+            properties.insert(String::from("edge-id"), json!(edge_id));
+        }
+        properties.insert(String::from("src-id"), json!(edge.src_id));
+        properties.insert(String::from("dst-id"), json!(edge.dst_id));
+
+        let deltas = edge.deltas();
+        for (i, metric_id) in diff.metric_ids.iter().enumerate() {
+            for (suffix, values) in &[
+                ("old", &edge.old_metrics),
+                ("new", &edge.new_metrics),
+                ("delta", &deltas),
+            ] {
+                if let Some(values) = values {
+                    properties.insert(format!("{}-{}", metric_id.0, suffix), json!(values[i]));
+                }
+            }
+        }
+
+        feature(&[edge.src_coord, edge.dst_coord], properties)
+    });
+
+    write_feature_collection(writer, node_features.chain(edge_features))
+}
+
+fn or_ignore<T: Display>(value: Option<T>) -> String {
+    match value {
+        Some(value) => value.to_string(),
+        None => String::from(defaults::writing::IGNORE_STR),
+    }
+}
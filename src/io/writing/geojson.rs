@@ -0,0 +1,305 @@
+use crate::{
+    configs::writing::geojson::Config as WritingConfig,
+    helpers::err,
+    io::SupportingFileExts,
+    network::{EdgeIdx, Graph, MetricIdx},
+    routing::paths::Path,
+};
+use kissunits::geo::Coordinate;
+use log::info;
+use serde_json::{json, Map, Value};
+use std::{
+    fs::OpenOptions,
+    io::{BufWriter, Write},
+};
+
+/// Writes paths and edges as [GeoJSON](https://tools.ietf.org/html/rfc7946), e.g. for QGIS or web-maps.
+///
+/// Paths and edges are LineStrings, with their costs (or metrics) per metric-id as properties.
+/// Coloured edges have the property `stroke` (see [simplestyle-spec](https://github.com/mapbox/simplestyle-spec)), going from green (low) to red (high).
+pub struct Writer;
+
+impl Writer {
+    /// Writes the path as single Feature with the properties `src-id`, `dst-id` and its costs.
+    /// Shortcuts are flattened.
+    pub fn write_path(path: &Path, graph: &Graph, writing_cfg: &WritingConfig) -> err::Feedback {
+        let mut writer = Writer::open(writing_cfg)?;
+        serde_json::to_writer(&mut writer, &path_feature(path, None, graph, writing_cfg))
+            .map_err(|e| err::Msg::from(format!("Couldn't write path due to error: {}", e)))?;
+        writeln!(writer)?;
+        Ok(())
+    }
+
+    /// Writes the paths (e.g. found by an explorator) as FeatureCollection.
+    /// In addition to `write_path(...)`, every Feature has the property `path-idx`.
+    pub fn write_paths(
+        paths: &[Path],
+        graph: &Graph,
+        writing_cfg: &WritingConfig,
+    ) -> err::Feedback {
+        let mut writer = Writer::open(writing_cfg)?;
+        write_feature_collection(
+            &mut writer,
+            paths
+                .iter()
+                .enumerate()
+                .map(|(path_idx, path)| path_feature(path, Some(path_idx), graph, writing_cfg)),
+        )
+    }
+
+    /// Writes the graph's edges (within the bounding-box, if any) as FeatureCollection,
+    /// coloured by the metric `color-by` (if any).
+    pub fn write_graph(graph: &Graph, writing_cfg: &WritingConfig) -> err::Feedback {
+        info!(
+            "START Write the graph's edges as GeoJSON with {}",
+            writing_cfg.file.display()
+        );
+
+        let colors = match &writing_cfg.color_id {
+            Some(color_id) => {
+                let metric_idx = graph.cfg().edges.metrics.try_idx_of(color_id)?;
+                let values: Vec<f64> = graph
+                    .fwd_edges()
+                    .iter()
+                    .map(|edge_idx| metric_value(graph, edge_idx, metric_idx, writing_cfg))
+                    .collect();
+                Some(values)
+            }
+            None => None,
+        };
+        write_edges(graph, None, colors.as_deref(), writing_cfg)?;
+
+        info!("FINISHED");
+        Ok(())
+    }
+
+    /// Like `write_graph(...)`, but the edges are coloured by external values (one per edge, e.g. the balancer's workloads).
+    /// The values are written as property `values_id`.
+    pub fn write_graph_with_values(
+        values: &[f64],
+        values_id: &str,
+        graph: &Graph,
+        writing_cfg: &WritingConfig,
+    ) -> err::Feedback {
+        info!(
+            "START Write the graph's edges with {} as GeoJSON with {}",
+            values_id,
+            writing_cfg.file.display()
+        );
+
+        if values.len() != graph.fwd_edges().count() {
+            return Err(err::Msg::from(format!(
+                "There should be one value per edge ({}), but there are {}.",
+                graph.fwd_edges().count(),
+                values.len()
+            )));
+        }
+        write_edges(graph, Some((values_id, values)), Some(values), writing_cfg)?;
+
+        info!("FINISHED");
+        Ok(())
+    }
+
+    fn open(writing_cfg: &WritingConfig) -> err::Result<BufWriter<std::fs::File>> {
+        if !Self::is_file_supported(&writing_cfg.file) {
+            return Err(format!("No support for writing {}.", writing_cfg.file.display()).into());
+        }
+
+        match OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(&writing_cfg.file)
+        {
+            Ok(file) => Ok(BufWriter::new(file)),
+            Err(e) => Err(err::Msg::from(format!(
+                "Couldn't open {} due to error: {}",
+                writing_cfg.file.display(),
+                e
+            ))),
+        }
+    }
+}
+
+impl SupportingFileExts for Writer {
+    fn supported_exts<'a>() -> &'a [&'a str] {
+        &["geojson", "json"]
+    }
+}
+
+fn write_edges(
+    graph: &Graph,
+    values: Option<(&str, &[f64])>,
+    colors: Option<&[f64]>,
+    writing_cfg: &WritingConfig,
+) -> err::Feedback {
+    let nodes = graph.nodes();
+    let fwd_edges = graph.fwd_edges();
+    let bwd_edges = graph.bwd_edges();
+    let metric_ids = &graph.cfg().edges.metrics.ids;
+
+    let edge_idxs: Vec<EdgeIdx> = fwd_edges
+        .iter()
+        .filter(|&edge_idx| writing_cfg.is_writing_shortcuts || !fwd_edges.is_shortcut(edge_idx))
+        .filter(|&edge_idx| match &writing_cfg.bbox {
+            Some(bbox) => {
+                bbox.contains(&nodes.coord(bwd_edges.dst_idx(edge_idx)))
+                    || bbox.contains(&nodes.coord(fwd_edges.dst_idx(edge_idx)))
+            }
+            None => true,
+        })
+        .collect();
+
+    // colors are scaled between the written edges' extreme values
+    let color_range = colors.map(|colors| {
+        edge_idxs
+            .iter()
+            .map(|edge_idx| colors[**edge_idx])
+            .filter(|value| value.is_finite())
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), value| {
+                (min.min(value), max.max(value))
+            })
+    });
+
+    let features = edge_idxs.iter().map(|&edge_idx| {
+        let mut properties = Map::new();
+        if let Some(edge_id) = fwd_edges.try_id(edge_idx) {
+            properties.insert(String::from("edge-id"), json!(edge_id));
+        }
+        properties.insert(
+            String::from("src-id"),
+            json!(nodes.id(bwd_edges.dst_idx(edge_idx))),
+        );
+        properties.insert(
+            String::from("dst-id"),
+            json!(nodes.id(fwd_edges.dst_idx(edge_idx))),
+        );
+        properties.insert(
+            String::from("is-shortcut"),
+            json!(fwd_edges.is_shortcut(edge_idx)),
+        );
+        for (metric_idx, metric_id) in metric_ids.iter().enumerate() {
+            properties.insert(
+                metric_id.0.clone(),
+                json!(metric_value(
+                    graph,
+                    edge_idx,
+                    MetricIdx(metric_idx),
+                    writing_cfg
+                )),
+            );
+        }
+        if let Some((values_id, values)) = values {
+            properties.insert(String::from(values_id), json!(values[*edge_idx]));
+        }
+        if let (Some(colors), Some((min, max))) = (colors, color_range) {
+            properties.insert(
+                String::from("stroke"),
+                json!(color_of(colors[*edge_idx], min, max)),
+            );
+        }
+
+        feature(
+            &[
+                nodes.coord(bwd_edges.dst_idx(edge_idx)),
+                nodes.coord(fwd_edges.dst_idx(edge_idx)),
+            ],
+            properties,
+        )
+    });
+
+    let mut writer = Writer::open(writing_cfg)?;
+    write_feature_collection(&mut writer, features)
+}
+
+pub(super) fn write_feature_collection<W, I>(writer: &mut W, features: I) -> err::Feedback
+where
+    W: Write,
+    I: Iterator<Item = Value>,
+{
+    writeln!(writer, "{{\"type\":\"FeatureCollection\",\"features\":[")?;
+    for (i, feature) in features.enumerate() {
+        if i > 0 {
+            writeln!(writer, ",")?;
+        }
+        serde_json::to_writer(&mut *writer, &feature)
+            .map_err(|e| err::Msg::from(format!("Couldn't write feature due to error: {}", e)))?;
+    }
+    writeln!(writer)?;
+    writeln!(writer, "]}}")?;
+    Ok(())
+}
+
+fn path_feature(
+    path: &Path,
+    path_idx: Option<usize>,
+    graph: &Graph,
+    writing_cfg: &WritingConfig,
+) -> Value {
+    let nodes = graph.nodes();
+    let mut path = path.clone().flatten(graph);
+
+    let mut properties = Map::new();
+    if let Some(path_idx) = path_idx {
+        properties.insert(String::from("path-idx"), json!(path_idx));
+    }
+    properties.insert(String::from("src-id"), json!(nodes.id(path.src_idx())));
+    properties.insert(String::from("dst-id"), json!(nodes.id(path.dst_idx())));
+    let costs = path.calc_costs(graph).clone();
+    for (metric_idx, metric_id) in graph.cfg().edges.metrics.ids.iter().enumerate() {
+        let mut cost = costs[metric_idx];
+        if writing_cfg.is_denormalizing {
+            if let Some(mean) = graph.metrics().mean(MetricIdx(metric_idx)) {
+                cost *= mean;
+            }
+        }
+        properties.insert(metric_id.0.clone(), json!(cost));
+    }
+
+    feature(&path.coords(graph), properties)
+}
+
+pub(super) fn feature(coords: &[Coordinate], properties: Map<String, Value>) -> Value {
+    let mut coordinates: Vec<[f64; 2]> =
+        coords.iter().map(|coord| [coord.lon, coord.lat]).collect();
+    // LineStrings need at least two positions
+    if coordinates.len() == 1 {
+        coordinates.push(coordinates[0]);
+    }
+
+    json!({
+        "type": "Feature",
+        "geometry": {
+            "type": "LineString",
+            "coordinates": coordinates,
+        },
+        "properties": properties,
+    })
+}
+
+fn metric_value(
+    graph: &Graph,
+    edge_idx: EdgeIdx,
+    metric_idx: MetricIdx,
+    writing_cfg: &WritingConfig,
+) -> f64 {
+    let mut value = graph.metrics()[metric_idx].get(edge_idx);
+    if writing_cfg.is_denormalizing {
+        if let Some(mean) = graph.metrics().mean(metric_idx) {
+            value *= mean;
+        }
+    }
+    value
+}
+
+/// Interpolates from green (min) over yellow to red (max) as hex-string.
+fn color_of(value: f64, min: f64, max: f64) -> String {
+    let t = if max > min && value.is_finite() {
+        ((value - min) / (max - min)).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    let red = (255.0 * (2.0 * t).min(1.0)).round() as u8;
+    let green = (255.0 * (2.0 * (1.0 - t)).min(1.0)).round() as u8;
+    format!("#{:02x}{:02x}00", red, green)
+}
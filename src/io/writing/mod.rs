@@ -1,5 +1,6 @@
-#[cfg(feature = "gpl")]
+pub mod diff;
 pub mod evaluating_balance;
+pub mod geojson;
 pub mod network;
 pub mod routing;
 pub mod smarts;
@@ -174,7 +174,7 @@ fn write_edges_to_file<W: Write>(
 
                             // denormalize metric if wished
 
-                            let mut metric_value = graph.metrics()[edge_idx][*metric_idx];
+                            let mut metric_value = graph.metrics()[metric_idx].get(edge_idx);
                             if writing_cfg.is_denormalizing {
                                 // check if graph is normalized
                                 if let Some(mean) = graph.metrics().mean(metric_idx) {
@@ -193,6 +193,26 @@ fn write_edges_to_file<W: Write>(
                     break;
                 }
 
+                // look for osm-way-id of same id
+                if !has_been_written {
+                    if let Some(ways_cfg) = &graph.cfg().edges.ways {
+                        if &ways_cfg.id == next_id {
+                            match graph.streets().way_id(edge_idx) {
+                                Some(way_id) => write!(writer, "{}", way_id)?,
+                                None => write!(writer, "{}", defaults::writing::IGNORE_STR)?,
+                            }
+                            has_been_written = true;
+                        } else if ways_cfg.tag_idx_of(&next_id.0).is_some() {
+                            // tags may contain spaces, which can't be parsed back
+                            return Err(format!(
+                                "Tag {} of osm-ways is free text and can't be written as edge-column.",
+                                next_id
+                            )
+                            .into());
+                        }
+                    }
+                }
+
                 // if nothing has been written
                 // -> id is not in config
                 if !has_been_written {
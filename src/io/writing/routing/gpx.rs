@@ -0,0 +1,102 @@
+use super::{escape_xml, Legs};
+use crate::{
+    configs::writing::paths::{Config, GpxKind},
+    defaults,
+    helpers::err,
+    network::Graph,
+};
+use kissunits::geo::Coordinate;
+use std::io::Write;
+
+/// Writes the paths as [gpx 1.1](https://www.topografix.com/GPX/1/1/), one `trk` or `rte` per path.
+///
+/// The metrics of a leg are written as `extensions` of the point, where the leg ends.
+/// Since gpx allows only foreign elements in `extensions`, they are in the namespace `defaults::writing::paths::GPX_NAMESPACE`.
+pub(super) fn write<W: Write>(
+    writer: &mut W,
+    paths: &[Legs],
+    graph: &Graph,
+    writing_cfg: &Config,
+) -> err::Feedback {
+    writeln!(writer, "<?xml version=\"1.0\" encoding=\"UTF-8\"?>")?;
+    writeln!(
+        writer,
+        "<gpx version=\"1.1\" creator=\"{}\" xmlns=\"http://www.topografix.com/GPX/1/1\" \
+         xmlns:{}=\"{}\">",
+        defaults::writing::paths::CREATOR,
+        defaults::writing::paths::GPX_NAMESPACE_PREFIX,
+        defaults::writing::paths::GPX_NAMESPACE
+    )?;
+
+    let metric_ids: Vec<String> = graph
+        .cfg()
+        .edges
+        .metrics
+        .ids
+        .iter()
+        .map(|id| escape_xml(&id.0))
+        .collect();
+
+    for legs in paths {
+        let (path_tag, point_tag) = match writing_cfg.gpx_kind {
+            GpxKind::Track => ("trk", "trkpt"),
+            GpxKind::Route => ("rte", "rtept"),
+        };
+
+        writeln!(writer, "  <{}>", path_tag)?;
+        writeln!(writer, "    <name>{}</name>", escape_xml(&legs.name))?;
+        write_extensions(writer, "    ", &metric_ids, &legs.costs)?;
+        if writing_cfg.gpx_kind == GpxKind::Track {
+            writeln!(writer, "    <trkseg>")?;
+        }
+
+        let indent = match writing_cfg.gpx_kind {
+            GpxKind::Track => "      ",
+            GpxKind::Route => "    ",
+        };
+        for (i, coord) in legs.coords.iter().enumerate() {
+            // the leg arriving at this point
+            match i.checked_sub(1).map(|leg_idx| &legs.metrics[leg_idx]) {
+                Some(metrics) => {
+                    writeln!(writer, "{}{}>", indent, point_open(point_tag, coord))?;
+                    write_extensions(writer, &format!("{}  ", indent), &metric_ids, metrics)?;
+                    writeln!(writer, "{}</{}>", indent, point_tag)?;
+                }
+                None => writeln!(writer, "{}{}/>", indent, point_open(point_tag, coord))?,
+            }
+        }
+
+        if writing_cfg.gpx_kind == GpxKind::Track {
+            writeln!(writer, "    </trkseg>")?;
+        }
+        writeln!(writer, "  </{}>", path_tag)?;
+    }
+
+    writeln!(writer, "</gpx>")?;
+    Ok(())
+}
+
+fn point_open(point_tag: &str, coord: &Coordinate) -> String {
+    format!("<{} lat=\"{}\" lon=\"{}\"", point_tag, coord.lat, coord.lon)
+}
+
+fn write_extensions<W: Write>(
+    writer: &mut W,
+    indent: &str,
+    metric_ids: &[String],
+    metrics: &[f64],
+) -> err::Feedback {
+    writeln!(writer, "{}<extensions>", indent)?;
+    for (metric_id, value) in metric_ids.iter().zip(metrics) {
+        writeln!(
+            writer,
+            "{}  <{prefix}:metric id=\"{}\">{}</{prefix}:metric>",
+            indent,
+            metric_id,
+            value,
+            prefix = defaults::writing::paths::GPX_NAMESPACE_PREFIX
+        )?;
+    }
+    writeln!(writer, "{}</extensions>", indent)?;
+    Ok(())
+}
@@ -0,0 +1,79 @@
+use super::{escape_xml, Legs};
+use crate::{helpers::err, network::Graph};
+use kissunits::geo::Coordinate;
+use std::io::Write;
+
+/// Writes the paths as [kml 2.2](https://developers.google.com/kml/documentation/kmlreference), one `Folder` per path.
+///
+/// A folder contains the whole path as `Placemark` with its costs, followed by one `Placemark` per leg with its metrics.
+pub(super) fn write<W: Write>(writer: &mut W, paths: &[Legs], graph: &Graph) -> err::Feedback {
+    writeln!(writer, "<?xml version=\"1.0\" encoding=\"UTF-8\"?>")?;
+    writeln!(writer, "<kml xmlns=\"http://www.opengis.net/kml/2.2\">")?;
+    writeln!(writer, "<Document>")?;
+
+    let metric_ids: Vec<String> = graph
+        .cfg()
+        .edges
+        .metrics
+        .ids
+        .iter()
+        .map(|id| escape_xml(&id.0))
+        .collect();
+
+    for legs in paths {
+        let name = escape_xml(&legs.name);
+        writeln!(writer, "  <Folder>")?;
+        writeln!(writer, "    <name>{}</name>", name)?;
+        write_placemark(writer, &name, &metric_ids, &legs.costs, &legs.coords)?;
+        for (leg_idx, metrics) in legs.metrics.iter().enumerate() {
+            write_placemark(
+                writer,
+                &format!("{} (leg {})", name, leg_idx),
+                &metric_ids,
+                metrics,
+                &legs.coords[leg_idx..leg_idx + 2],
+            )?;
+        }
+        writeln!(writer, "  </Folder>")?;
+    }
+
+    writeln!(writer, "</Document>")?;
+    writeln!(writer, "</kml>")?;
+    Ok(())
+}
+
+fn write_placemark<W: Write>(
+    writer: &mut W,
+    name: &str,
+    metric_ids: &[String],
+    metrics: &[f64],
+    coords: &[Coordinate],
+) -> err::Feedback {
+    writeln!(writer, "    <Placemark>")?;
+    writeln!(writer, "      <name>{}</name>", name)?;
+    writeln!(writer, "      <ExtendedData>")?;
+    for (metric_id, value) in metric_ids.iter().zip(metrics) {
+        writeln!(
+            writer,
+            "        <Data name=\"{}\"><value>{}</value></Data>",
+            metric_id, value
+        )?;
+    }
+    writeln!(writer, "      </ExtendedData>")?;
+
+    // LineStrings need at least two positions
+    let mut coordinates: Vec<String> = coords
+        .iter()
+        .map(|coord| format!("{},{}", coord.lon, coord.lat))
+        .collect();
+    if coordinates.len() == 1 {
+        coordinates.push(coordinates[0].clone());
+    }
+    writeln!(
+        writer,
+        "      <LineString><coordinates>{}</coordinates></LineString>",
+        coordinates.join(" ")
+    )?;
+    writeln!(writer, "    </Placemark>")?;
+    Ok(())
+}
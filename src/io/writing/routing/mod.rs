@@ -1,6 +1,20 @@
-use crate::{configs, helpers::err, io::SupportingFileExts, network::Graph};
+use crate::{
+    configs,
+    defaults::capacity::DimVec,
+    helpers::err,
+    io::SupportingFileExts,
+    network::{Graph, MetricIdx},
+    routing::paths::{PartialEdge, Path},
+};
+use kissunits::geo::Coordinate;
 use log::info;
+use std::{
+    fs::OpenOptions,
+    io::{BufWriter, Write},
+};
 
+mod gpx;
+mod kml;
 mod random_or_all;
 
 pub struct Writer;
@@ -31,3 +45,135 @@ impl SupportingFileExts for Writer {
         &["route-pairs"]
     }
 }
+
+/// Writes computed paths as gpx or kml, depending on the file's extension.
+///
+/// Every path consists of legs (its edges, where partial edges are shortened), which are written with their metrics.
+/// Paths with shortcuts are flattened before.
+pub struct PathsWriter;
+
+impl PathsWriter {
+    pub fn write(
+        paths: &[Path],
+        graph: &Graph,
+        writing_cfg: &configs::writing::paths::Config,
+    ) -> err::Feedback {
+        info!(
+            "START Write {} paths with {}",
+            paths.len(),
+            writing_cfg.file.display()
+        );
+
+        let ext = PathsWriter::find_supported_ext(&writing_cfg.file)?;
+        let output_file = match OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(&writing_cfg.file)
+        {
+            Ok(file) => file,
+            Err(e) => {
+                return Err(err::Msg::from(format!(
+                    "Couldn't open {} due to error: {}",
+                    writing_cfg.file.display(),
+                    e
+                )))
+            }
+        };
+        let mut writer = BufWriter::new(output_file);
+
+        let legs: Vec<Legs> = paths
+            .iter()
+            .map(|path| Legs::from(path, graph, writing_cfg))
+            .collect();
+        match ext {
+            "gpx" => gpx::write(&mut writer, &legs, graph, writing_cfg)?,
+            "kml" => kml::write(&mut writer, &legs, graph)?,
+            _ => {
+                return Err(err::Msg::from(
+                    "Should not happen, since 'find_supported_ext(...)' should cover this.",
+                ))
+            }
+        }
+        writer.flush()?;
+
+        info!("FINISHED");
+        Ok(())
+    }
+}
+
+impl SupportingFileExts for PathsWriter {
+    fn supported_exts<'a>() -> &'a [&'a str] {
+        &["gpx", "kml"]
+    }
+}
+
+/// A path's coordinates with the metrics of every leg in between, so `coords.len() == metrics.len() + 1`.
+struct Legs {
+    name: String,
+    coords: Vec<Coordinate>,
+    metrics: Vec<DimVec<f64>>,
+    costs: DimVec<f64>,
+}
+
+impl Legs {
+    fn from(path: &Path, graph: &Graph, writing_cfg: &configs::writing::paths::Config) -> Legs {
+        let fwd_edges = graph.fwd_edges();
+        let nodes = graph.nodes();
+
+        let mut path = if path.iter().any(|&edge_idx| fwd_edges.is_shortcut(edge_idx)) {
+            path.clone().flatten(graph)
+        } else {
+            path.clone()
+        };
+
+        let denormalize = |mut metrics: DimVec<f64>| {
+            if writing_cfg.is_denormalizing {
+                for (metric_idx, metric) in metrics.iter_mut().enumerate() {
+                    if let Some(mean) = graph.metrics().mean(MetricIdx(metric_idx)) {
+                        *metric *= mean;
+                    }
+                }
+            }
+            metrics
+        };
+        let partial_metrics = |partial_edge: &PartialEdge| {
+            graph
+                .metrics()
+                .get(partial_edge.edge_idx)
+                .iter()
+                .map(|metric| partial_edge.fraction() * metric)
+                .collect::<DimVec<f64>>()
+        };
+
+        let mut metrics = Vec::with_capacity(path.iter().len() + 2);
+        if let Some(partial_edge) = path.first_partial_edge() {
+            metrics.push(partial_metrics(partial_edge));
+        }
+        for &edge_idx in path.iter() {
+            metrics.push(graph.metrics().get(edge_idx));
+        }
+        if let Some(partial_edge) = path.last_partial_edge() {
+            metrics.push(partial_metrics(partial_edge));
+        }
+
+        Legs {
+            name: format!(
+                "{} to {}",
+                nodes.id(path.src_idx()),
+                nodes.id(path.dst_idx())
+            ),
+            coords: path.coords(graph),
+            metrics: metrics.into_iter().map(denormalize).collect(),
+            costs: denormalize(path.calc_costs(graph).clone()),
+        }
+    }
+}
+
+/// Escapes the xml-special characters in texts and attribute-values.
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
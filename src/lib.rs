@@ -6,7 +6,4 @@ pub mod io;
 pub mod multi_ch_constructor;
 pub mod network;
 pub mod routing;
-
-pub mod compiler {
-    include!(concat!(env!("OUT_DIR"), "/", "compiler.rs"));
-}
+pub mod server;
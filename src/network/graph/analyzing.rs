@@ -0,0 +1,301 @@
+use super::{super::spatial, Graph};
+use crate::{defaults, network::BoundingBox};
+use kissunits::geo::Coordinate;
+use log::info;
+use serde_json::{json, Value};
+use std::fmt::{self, Display};
+
+/// Statistics of a graph, which report suspicious data as well, e.g. self-loops or negative metrics (see `Graph::analyze()`).
+///
+/// Degrees, metrics, self-loops and parallel edges consider original edges only, since shortcuts are parallel to their edges by design.
+/// Removed nodes and edges are ignored.
+#[derive(Clone, Debug)]
+pub struct GraphStats {
+    pub node_count: usize,
+    /// Number of (forward-)edges, including shortcuts
+    pub edge_count: usize,
+    pub shortcut_count: usize,
+    /// Number of nodes per out-degree (as idx)
+    pub out_degree_histogram: Vec<usize>,
+    /// Number of nodes per in-degree (as idx)
+    pub in_degree_histogram: Vec<usize>,
+    /// Number of strongly connected components
+    pub component_count: usize,
+    /// Sizes of the largest strongly connected components (descending), see `defaults::analyzing::MAX_LISTED_COMPONENTS`
+    pub largest_component_sizes: Vec<usize>,
+    /// Number of nodes per ch-level (as idx)
+    pub ch_level_histogram: Vec<usize>,
+    /// One entry per metric, in the order of the graph's metrics
+    pub metrics: Vec<MetricStats>,
+    pub self_loop_count: usize,
+    /// Number of edges, whose src and dst are connected by a previous edge already
+    pub parallel_edge_count: usize,
+    /// None, if the graph has no nodes
+    pub bounding_box: Option<BoundingBox>,
+}
+
+/// Statistics of one metric, given in the graph's units (normalized, if the graph's metrics are normalized).
+/// Without any edges, the values are NaN.
+#[derive(Clone, Debug)]
+pub struct MetricStats {
+    pub id: String,
+    pub min: f64,
+    pub max: f64,
+    pub mean: f64,
+    /// (percent, value) by nearest rank, see `defaults::analyzing::PERCENTILES`
+    pub percentiles: Vec<(usize, f64)>,
+    pub zero_count: usize,
+    pub negative_count: usize,
+}
+
+impl GraphStats {
+    /// Shortcuts per edge, which is 0 for graphs without edges.
+    pub fn shortcut_ratio(&self) -> f64 {
+        if self.edge_count > 0 {
+            self.shortcut_count as f64 / self.edge_count as f64
+        } else {
+            0.0
+        }
+    }
+
+    /// Describes suspicious data of the graph, which is empty for a clean graph.
+    pub fn issues(&self) -> Vec<String> {
+        let mut issues = Vec::new();
+        if self.self_loop_count > 0 {
+            issues.push(format!("{} edges are self-loops.", self.self_loop_count));
+        }
+        if self.parallel_edge_count > 0 {
+            issues.push(format!(
+                "{} edges are parallel to other edges.",
+                self.parallel_edge_count
+            ));
+        }
+        for metric in &self.metrics {
+            if metric.zero_count > 0 {
+                issues.push(format!(
+                    "{} edges have metric {} of 0.",
+                    metric.zero_count, metric.id
+                ));
+            }
+            if metric.negative_count > 0 {
+                issues.push(format!(
+                    "{} edges have a negative metric {}.",
+                    metric.negative_count, metric.id
+                ));
+            }
+        }
+        issues
+    }
+
+    /// Returns the statistics and their issues as json, e.g. for dashboards.
+    pub fn to_json(&self) -> Value {
+        json!({
+            "node-count": self.node_count,
+            "edge-count": self.edge_count,
+            "shortcut-count": self.shortcut_count,
+            "shortcut-ratio": self.shortcut_ratio(),
+            "out-degree-histogram": self.out_degree_histogram,
+            "in-degree-histogram": self.in_degree_histogram,
+            "component-count": self.component_count,
+            "largest-component-sizes": self.largest_component_sizes,
+            "ch-level-histogram": self.ch_level_histogram,
+            "metrics": self.metrics.iter().map(|metric| json!({
+                "id": metric.id,
+                "min": metric.min,
+                "max": metric.max,
+                "mean": metric.mean,
+                "percentiles": metric.percentiles.iter().map(|&(percent, value)| json!({
+                    "percent": percent,
+                    "value": value,
+                })).collect::<Vec<_>>(),
+                "zero-count": metric.zero_count,
+                "negative-count": metric.negative_count,
+            })).collect::<Vec<_>>(),
+            "self-loop-count": self.self_loop_count,
+            "parallel-edge-count": self.parallel_edge_count,
+            "bounding-box": self.bounding_box.map(|bounding_box| json!({
+                "min": { "lat": bounding_box.min.lat, "lon": bounding_box.min.lon },
+                "max": { "lat": bounding_box.max.lat, "lon": bounding_box.max.lon },
+            })),
+            "issues": self.issues(),
+        })
+    }
+}
+
+impl Display for GraphStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "GraphStats: {{ number of nodes: {}, number of edges: {} ({} shortcuts) }}",
+            self.node_count, self.edge_count, self.shortcut_count
+        )?;
+        writeln!(
+            f,
+            "Components: {} (largest: {:?})",
+            self.component_count, self.largest_component_sizes
+        )?;
+        for metric in &self.metrics {
+            writeln!(
+                f,
+                "Metric {}: {{ min: {}, max: {}, mean: {} }}",
+                metric.id, metric.min, metric.max, metric.mean
+            )?;
+        }
+        write!(
+            f,
+            "Self-loops: {}, parallel edges: {}",
+            self.self_loop_count, self.parallel_edge_count
+        )
+    }
+}
+
+/// Analysis of the graph's structure and metrics
+impl Graph {
+    /// Collects statistics of the graph and reports suspicious data (see `GraphStats`).
+    pub fn analyze(&self) -> GraphStats {
+        info!("START Analyze graph.");
+        let nodes = self.nodes();
+        let fwd_edges = self.fwd_edges();
+        let bwd_edges = self.bwd_edges();
+
+        let mut node_count = 0;
+        let mut edge_count = 0;
+        let mut shortcut_count = 0;
+        let mut out_degree_histogram = Vec::new();
+        let mut in_degree_histogram = Vec::new();
+        let mut ch_level_histogram = Vec::new();
+        let mut metric_values = vec![Vec::new(); self.metrics().dim()];
+        let mut self_loop_count = 0;
+        let mut parallel_edge_count = 0;
+        let mut bounding_box: Option<BoundingBox> = None;
+
+        for node_idx in nodes.iter() {
+            if self.is_removed_node(node_idx) {
+                continue;
+            }
+            node_count += 1;
+            increment(&mut ch_level_histogram, nodes.level(node_idx));
+
+            let coord = nodes.coord(node_idx);
+            bounding_box = Some(match bounding_box {
+                Some(BoundingBox { min, max }) => BoundingBox {
+                    min: Coordinate {
+                        lat: min.lat.min(coord.lat),
+                        lon: min.lon.min(coord.lon),
+                    },
+                    max: Coordinate {
+                        lat: max.lat.max(coord.lat),
+                        lon: max.lon.max(coord.lon),
+                    },
+                },
+                None => BoundingBox {
+                    min: coord,
+                    max: coord,
+                },
+            });
+
+            let mut dst_idxs = Vec::new();
+            for edge in fwd_edges.starting_from(node_idx) {
+                edge_count += 1;
+                if edge.is_shortcut() {
+                    shortcut_count += 1;
+                    continue;
+                }
+
+                if edge.dst_idx() == node_idx {
+                    self_loop_count += 1;
+                }
+                dst_idxs.push(edge.dst_idx());
+                for (values, value) in metric_values.iter_mut().zip(edge.metrics()) {
+                    values.push(value);
+                }
+            }
+            increment(&mut out_degree_histogram, dst_idxs.len());
+            let out_degree = dst_idxs.len();
+            dst_idxs.sort_unstable();
+            dst_idxs.dedup();
+            parallel_edge_count += out_degree - dst_idxs.len();
+
+            let in_degree = bwd_edges
+                .starting_from(node_idx)
+                .filter(|edge| !edge.is_shortcut())
+                .count();
+            increment(&mut in_degree_histogram, in_degree);
+        }
+
+        // strongly connected components without removed nodes
+
+        let (component_ids, component_sizes) = spatial::strongly_connected_components(self);
+        let mut component_sizes = vec![0; component_sizes.len()];
+        for node_idx in nodes.iter() {
+            if let (false, Some(component_id)) =
+                (self.is_removed_node(node_idx), component_ids[*node_idx])
+            {
+                component_sizes[component_id] += 1;
+            }
+        }
+        component_sizes.retain(|&size| size > 0);
+        component_sizes.sort_unstable_by(|a, b| b.cmp(a));
+        let component_count = component_sizes.len();
+        component_sizes.truncate(defaults::analyzing::MAX_LISTED_COMPONENTS);
+
+        let metrics = metric_values
+            .into_iter()
+            .zip(self.cfg().edges.metrics.ids.iter())
+            .map(|(values, id)| MetricStats::from_values(id.0.clone(), values))
+            .collect();
+
+        info!("FINISHED");
+        GraphStats {
+            node_count,
+            edge_count,
+            shortcut_count,
+            out_degree_histogram,
+            in_degree_histogram,
+            component_count,
+            largest_component_sizes: component_sizes,
+            ch_level_histogram,
+            metrics,
+            self_loop_count,
+            parallel_edge_count,
+            bounding_box,
+        }
+    }
+}
+
+impl MetricStats {
+    fn from_values(id: String, mut values: Vec<f64>) -> MetricStats {
+        values.sort_unstable_by(f64::total_cmp);
+
+        let percentiles = defaults::analyzing::PERCENTILES
+            .iter()
+            .map(|&percent| {
+                // nearest rank
+                let rank = (percent * values.len()).div_ceil(100);
+                let value = match values.get(rank.max(1) - 1) {
+                    Some(&value) => value,
+                    None => f64::NAN,
+                };
+                (percent, value)
+            })
+            .collect();
+
+        MetricStats {
+            id,
+            min: values.first().copied().unwrap_or(f64::NAN),
+            max: values.last().copied().unwrap_or(f64::NAN),
+            mean: values.iter().sum::<f64>() / values.len() as f64,
+            percentiles,
+            zero_count: values.iter().filter(|&&value| value == 0.0).count(),
+            negative_count: values.iter().filter(|&&value| value < 0.0).count(),
+        }
+    }
+}
+
+/// Increments the histogram's entry of the given idx, growing the histogram if needed.
+fn increment(histogram: &mut Vec<usize>, idx: usize) {
+    if histogram.len() <= idx {
+        histogram.resize(idx + 1, 0);
+    }
+    histogram[idx] += 1;
+}
@@ -1,4 +1,6 @@
-use super::{EdgeIdx, Graph, NodeIdx};
+use super::{
+    ordering, spilling::EdgeRuns, EdgeIdx, Graph, MetricIdx, MetricValues, NodeIdx, NodeOrdering,
+};
 use crate::{
     approximating::Approx,
     configs::parsing::{self, generating},
@@ -7,15 +9,16 @@ use crate::{
         capacity::{self, DimVec},
         routing::IS_USING_CH_LEVEL_SPEEDUP,
     },
-    helpers::{self, err, MemSize},
+    helpers::{err, parallel, MemSize},
     io,
+    network::Street,
 };
 use kissunits::geo::Coordinate;
 use log::{debug, info, trace};
 use progressing::{mapping::Bar as MappingBar, Baring};
-use smallvec::smallvec;
 use std::{
-    cmp::{min, Reverse},
+    cmp::{max, min, Reverse},
+    collections::HashMap,
     mem,
 };
 
@@ -26,6 +29,7 @@ impl Graph {
             cfg,
             // nodes
             node_ids: Vec::new(),
+            node_ids_to_idx_map: Vec::new(),
             // node-metrics
             node_coords: Vec::new(),
             node_ch_levels: Vec::new(),
@@ -42,15 +46,46 @@ impl Graph {
             // edge-ids
             edge_ids: Vec::new(),
             edge_ids_to_idx_map: Vec::new(),
+            // streets
+            streets: Vec::new(),
+            street_idxs: Vec::new(),
+            // updates
+            closed_edges: HashMap::new(),
+            // editing
+            delta: None,
             // shortcuts (contraction-hierarchies)
-            sc_offsets: Vec::new(),
+            // like every offset-array, it ends with the upper bound (even without edges)
+            sc_offsets: vec![0],
             sc_edges: Vec::new(),
         }
     }
 
+    /// Permutes the nodes, which are sorted by id, according to their new indices.
+    /// The id-to-idx-mapping keeps the ids' order, so it is sorted by id.
+    fn reorder_nodes(&mut self, new_idxs: &[NodeIdx]) {
+        let mut old_idxs = vec![0; new_idxs.len()];
+        for (old_idx, new_idx) in new_idxs.iter().enumerate() {
+            old_idxs[**new_idx] = old_idx;
+        }
+
+        self.node_ids_to_idx_map = self
+            .node_ids
+            .iter()
+            .zip(new_idxs)
+            .map(|(&id, &idx)| (id, idx))
+            .collect();
+        self.node_ids = old_idxs.iter().map(|&idx| self.node_ids[idx]).collect();
+        self.node_coords = old_idxs.iter().map(|&idx| self.node_coords[idx]).collect();
+        self.node_ch_levels = old_idxs
+            .iter()
+            .map(|&idx| self.node_ch_levels[idx])
+            .collect();
+    }
+
     /// Optimizes capacity of used data-structures.
     fn shrink_to_fit(&mut self) {
         self.node_ids.shrink_to_fit();
+        self.node_ids_to_idx_map.shrink_to_fit();
         self.node_coords.shrink_to_fit();
         self.fwd_dsts.shrink_to_fit();
         self.fwd_offsets.shrink_to_fit();
@@ -59,16 +94,33 @@ impl Graph {
         self.bwd_offsets.shrink_to_fit();
         self.bwd_to_fwd_map.shrink_to_fit();
         self.metrics.shrink_to_fit();
+        self.metrics
+            .iter_mut()
+            .for_each(MetricValues::shrink_to_fit);
         self.edge_ids.shrink_to_fit();
         self.edge_ids_to_idx_map.shrink_to_fit();
+        self.streets.shrink_to_fit();
+        self.street_idxs.shrink_to_fit();
         self.sc_offsets.shrink_to_fit();
         self.sc_edges.shrink_to_fit();
     }
 
     /// The provided edge is interpreted as forward-edge.
-    fn add_metrics(&mut self, proto_edge: &mut ProtoEdgeB) -> err::Feedback {
+    /// The metrics are added to the given columns, which are converted into the metrics' precisions when finalizing.
+    fn add_metrics(&self, metrics: &mut [Vec<f64>], proto_edge: &mut ProtoEdgeB) -> err::Feedback {
         let cfg = &self.cfg;
 
+        if proto_edge.metrics.len() != metrics.len() {
+            return Err(format!(
+                "Proto-edge (id:{}->id:{}) has {} metrics, but {} are expected.",
+                self.nodes().id(proto_edge.src_idx),
+                self.nodes().id(proto_edge.dst_idx),
+                proto_edge.metrics.len(),
+                metrics.len()
+            )
+            .into());
+        }
+
         for metric_idx in 0..proto_edge.metrics.len() {
             if Approx(proto_edge.metrics[metric_idx]) == Approx(0.0) {
                 trace!(
@@ -82,7 +134,9 @@ impl Graph {
             }
         }
 
-        self.metrics.push(proto_edge.metrics.clone());
+        for (values, &metric) in metrics.iter_mut().zip(proto_edge.metrics.iter()) {
+            values.push(metric);
+        }
 
         Ok(())
     }
@@ -118,6 +172,8 @@ pub struct ProtoEdge {
     pub src_id: i64,
     pub dst_id: i64,
     pub metrics: DimVec<f64>,
+    /// Index returned by `EdgeBuilder::insert_street(...)`
+    pub street_idx: Option<usize>,
 }
 
 impl Into<ProtoShortcut> for ProtoEdge {
@@ -142,16 +198,37 @@ impl MemSize for ProtoEdge {
         + 2 * mem::size_of::<i64>()
         // metrics: DimVec<f64>
         + capacity::SMALL_VEC_INLINE_SIZE * mem::size_of::<f64>()
+        // street_idx: Option<usize>
+        + mem::size_of::<Option<usize>>()
     }
 }
 
-struct ProtoEdgeA {
+pub(super) struct ProtoEdgeA {
     pub idx: usize,
     pub id: Option<usize>,
     pub src_id: i64,
     pub dst_id: i64,
     pub metrics: DimVec<f64>,
     pub sc_edges: Option<usize>,
+    pub street_idx: Option<usize>,
+}
+
+impl MemSize for ProtoEdgeA {
+    fn mem_size_b() -> usize {
+        // idx
+        mem::size_of::<usize>()
+        // id: usize
+        + mem::size_of::<Option<usize>>()
+        // src_id: i64
+        // dst_id: i64
+        + 2 * mem::size_of::<i64>()
+        // metrics
+        + capacity::SMALL_VEC_INLINE_SIZE * mem::size_of::<f64>()
+        // sc_edges
+        + mem::size_of::<Option<usize>>()
+        // street_idx
+        + mem::size_of::<Option<usize>>()
+    }
 }
 
 struct ProtoEdgeB {
@@ -161,6 +238,7 @@ struct ProtoEdgeB {
     pub dst_idx: NodeIdx,
     pub metrics: DimVec<f64>,
     pub sc_edges: Option<usize>,
+    pub street_idx: Option<usize>,
 }
 
 impl MemSize for ProtoEdgeB {
@@ -176,7 +254,35 @@ impl MemSize for ProtoEdgeB {
         + capacity::SMALL_VEC_INLINE_SIZE * mem::size_of::<f64>()
         // sc_edges
         + mem::size_of::<usize>()
+        // street_idx
+        + mem::size_of::<Option<usize>>()
+    }
+}
+
+/// Creates the offset-array of the given edges, which are sorted by the node returned by `src_idx_of`.
+/// The offset of a node is the index of its first leaving edge,
+/// so nodes without leaving edges get the offset of the next node.
+/// The array ends with the last node having leaving edges, followed by the number of edges.
+fn offsets_of<F>(proto_edges: &[ProtoEdgeC], num_threads: usize, src_idx_of: F) -> Vec<usize>
+where
+    F: Fn(&ProtoEdgeC) -> NodeIdx + Sync,
+{
+    let node_count = match proto_edges.last() {
+        Some(proto_edge) => *src_idx_of(proto_edge) + 1,
+        // first node needs an offset anyways
+        None => 1,
+    };
+    let mut offsets = Vec::with_capacity(node_count + 1);
+    for chunk in parallel::map_ranges(node_count, num_threads, |node_range| {
+        node_range
+            .map(|node_idx| proto_edges.partition_point(|edge| *src_idx_of(edge) < node_idx))
+            .collect::<Vec<_>>()
+    }) {
+        offsets.extend(chunk);
     }
+    // last node needs an upper bound as well for `leaving_edges(...)`
+    offsets.push(proto_edges.len());
+    offsets
 }
 
 /// handy for remembering indices after sorting backwards
@@ -186,13 +292,20 @@ struct ProtoEdgeC {
     dst_idx: NodeIdx,
     idx: usize,
     id: Option<usize>,
+    street_idx: Option<usize>,
 }
 
 pub struct EdgeBuilder {
     cfg: parsing::Config,
     node_ids: Vec<i64>,
+    // number of node-ids after their last deduplication (only used with external memory)
+    dedup_node_count: usize,
     proto_edges: Vec<ProtoEdgeA>,
+    // spilled proto-edges, if building with external memory
+    edge_runs: Option<EdgeRuns>,
     proto_shortcuts: Vec<[EdgeIdx; 2]>,
+    streets: Vec<Street>,
+    street_idxs: HashMap<Street, usize>,
 }
 
 impl EdgeBuilder {
@@ -200,6 +313,17 @@ impl EdgeBuilder {
         &self.cfg
     }
 
+    /// Stores the street (if not stored already) and returns its index for proto-edges.
+    pub fn insert_street(&mut self, street: Street) -> usize {
+        if let Some(&street_idx) = self.street_idxs.get(&street) {
+            return street_idx;
+        }
+        let street_idx = self.streets.len();
+        self.streets.push(street.clone());
+        self.street_idxs.insert(street, street_idx);
+        street_idx
+    }
+
     pub fn insert<E>(&mut self, proto_edge: E) -> err::Feedback
     where
         E: Into<ProtoShortcut>,
@@ -240,6 +364,39 @@ impl EdgeBuilder {
         }
 
         // add edges
+        if let Some(external_memory_cfg) = &self.cfg.external_memory {
+            // Node-ids are deduplicated from time to time,
+            // since they would take lots of memory for continent-scale maps otherwise.
+            if self.node_ids.len()
+                >= 2 * max(
+                    self.dedup_node_count,
+                    defaults::parsing::external_memory::MIN_DEDUP_NODE_COUNT,
+                )
+            {
+                self.node_ids.sort_unstable();
+                self.node_ids.dedup();
+                self.dedup_node_count = self.node_ids.len();
+            }
+
+            if self.edge_runs.is_none() {
+                self.edge_runs = Some(EdgeRuns::new(external_memory_cfg)?);
+            }
+            let edge_runs = self
+                .edge_runs
+                .as_mut()
+                .expect("Edge-runs have just been created.");
+            return edge_runs.push(ProtoEdgeA {
+                idx: edge_runs.len(),
+                id: proto_edge.id,
+                src_id: proto_edge.src_id,
+                dst_id: proto_edge.dst_id,
+                metrics: proto_edge.metrics,
+                // rejected by edge-runs
+                sc_edges: sc_edges.map(|_| 0),
+                street_idx: proto_edge.street_idx,
+            });
+        }
+
         let idx = self.proto_edges.len();
         if let Some(sc_edges) = sc_edges {
             // save index to shortcut in proto-edge
@@ -250,6 +407,7 @@ impl EdgeBuilder {
                 dst_id: proto_edge.dst_id,
                 metrics: proto_edge.metrics,
                 sc_edges: Some(self.proto_shortcuts.len()),
+                street_idx: proto_edge.street_idx,
             });
             self.proto_shortcuts.push(sc_edges);
         } else {
@@ -260,18 +418,59 @@ impl EdgeBuilder {
                 dst_id: proto_edge.dst_id,
                 metrics: proto_edge.metrics,
                 sc_edges: None,
+                street_idx: proto_edge.street_idx,
             });
         }
 
         Ok(())
     }
 
+    /// Inserts the streets and proto-edges of the other builder, e.g. of a builder per thread.
+    /// The result is the same as if the other builder's proto-edges had been inserted into this builder directly.
+    pub fn append(&mut self, other: EdgeBuilder) -> err::Feedback {
+        let street_idxs: Vec<usize> = other
+            .streets
+            .into_iter()
+            .map(|street| self.insert_street(street))
+            .collect();
+        // shortcuts refer to the order of insertion
+        let edge_offset = self.proto_edges.len();
+        let proto_shortcuts = other.proto_shortcuts;
+
+        for proto_edge in other.proto_edges {
+            let sc_edges = proto_edge.sc_edges.map(|sc_idx| {
+                let [sc_edge_0, sc_edge_1] = proto_shortcuts[sc_idx];
+                [
+                    EdgeIdx(*sc_edge_0 + edge_offset),
+                    EdgeIdx(*sc_edge_1 + edge_offset),
+                ]
+            });
+            self.insert(ProtoShortcut {
+                proto_edge: ProtoEdge {
+                    id: proto_edge.id,
+                    src_id: proto_edge.src_id,
+                    dst_id: proto_edge.dst_id,
+                    metrics: proto_edge.metrics,
+                    street_idx: proto_edge
+                        .street_idx
+                        .map(|street_idx| street_idxs[street_idx]),
+                },
+                sc_edges,
+            })?;
+        }
+
+        Ok(())
+    }
+
     pub fn next(mut self) -> NodeBuilder {
         self.proto_edges.shrink_to_fit();
         self.proto_shortcuts.shrink_to_fit();
 
         // sort nodes, remove duplicates and shrink array since it can only shrink from now on
-        self.node_ids.sort_unstable();
+        self.node_ids =
+            parallel::sort_by_key(mem::take(&mut self.node_ids), self.cfg.num_threads, |&id| {
+                id
+            });
         self.node_ids.dedup();
         self.node_ids.shrink_to_fit();
 
@@ -285,7 +484,9 @@ impl EdgeBuilder {
             node_coords,
             node_ch_levels,
             proto_edges: self.proto_edges,
+            edge_runs: self.edge_runs,
             proto_shortcuts: self.proto_shortcuts,
+            streets: self.streets,
         }
     }
 }
@@ -296,7 +497,9 @@ pub struct NodeBuilder {
     node_coords: Vec<Option<Coordinate>>,
     node_ch_levels: Vec<usize>,
     proto_edges: Vec<ProtoEdgeA>,
+    edge_runs: Option<EdgeRuns>,
     proto_shortcuts: Vec<[EdgeIdx; 2]>,
+    streets: Vec<Street>,
 }
 
 impl NodeBuilder {
@@ -304,6 +507,11 @@ impl NodeBuilder {
         &self.cfg
     }
 
+    /// Returns true if node is part of edge and hence would be added.
+    pub fn contains(&self, id: i64) -> bool {
+        self.node_ids.binary_search(&id).is_ok()
+    }
+
     /// Returns true if node is part of edge and hence has been added.
     pub fn insert(&mut self, proto_node: ProtoNode) -> bool {
         if let Ok(idx) = self.node_ids.binary_search(&proto_node.id) {
@@ -317,6 +525,35 @@ impl NodeBuilder {
         }
     }
 
+    /// Like `insert(...)`, but for nodes sorted by id, which are merged with the (sorted) node-ids instead of searching every id.
+    /// Nodes after the last node-id are not read anymore.
+    ///
+    /// Returns the number of added nodes.
+    pub fn insert_sorted<I>(&mut self, proto_nodes: I) -> err::Result<usize>
+    where
+        I: IntoIterator<Item = err::Result<ProtoNode>>,
+    {
+        let mut idx = 0;
+        let mut count = 0;
+        for proto_node in proto_nodes {
+            let proto_node = proto_node?;
+            while idx < self.node_ids.len() && self.node_ids[idx] < proto_node.id {
+                idx += 1;
+            }
+            if idx == self.node_ids.len() {
+                break;
+            }
+            if self.node_ids[idx] == proto_node.id {
+                self.node_coords[idx] = Some(proto_node.coord);
+                if let Some(ch_level) = proto_node.ch_level {
+                    self.node_ch_levels[idx] = ch_level;
+                }
+                count += 1;
+            }
+        }
+        Ok(count)
+    }
+
     pub fn next(self) -> err::Result<GraphBuilder> {
         Ok(GraphBuilder {
             cfg: self.cfg,
@@ -324,7 +561,9 @@ impl NodeBuilder {
             node_coords: self.node_coords,
             node_ch_levels: self.node_ch_levels,
             proto_edges: self.proto_edges,
+            edge_runs: self.edge_runs,
             proto_shortcuts: self.proto_shortcuts,
+            streets: self.streets,
         })
     }
 }
@@ -335,7 +574,9 @@ pub struct GraphBuilder {
     node_coords: Vec<Option<Coordinate>>,
     node_ch_levels: Vec<usize>,
     proto_edges: Vec<ProtoEdgeA>,
+    edge_runs: Option<EdgeRuns>,
     proto_shortcuts: Vec<[EdgeIdx; 2]>,
+    streets: Vec<Street>,
 }
 
 impl GraphBuilder {
@@ -343,8 +584,12 @@ impl GraphBuilder {
         EdgeBuilder {
             cfg,
             node_ids: Vec::new(),
+            dedup_node_count: 0,
             proto_edges: Vec::new(),
+            edge_runs: None,
             proto_shortcuts: Vec::new(),
+            streets: Vec::new(),
+            street_idxs: HashMap::new(),
         }
     }
 
@@ -355,9 +600,14 @@ impl GraphBuilder {
         info!(
             "START Finalize graph with {} proto-nodes and {} proto-edges.",
             self.node_ids.len(),
-            self.proto_edges.len()
+            match &self.edge_runs {
+                Some(edge_runs) => edge_runs.len(),
+                None => self.proto_edges.len(),
+            }
         );
         let mut graph = Graph::new(self.cfg);
+        // streets are only stored, if any has been parsed
+        graph.streets = self.streets;
 
         //----------------------------------------------------------------------------------------//
         // add nodes to graph which belong to edges (sorted by asc id)
@@ -381,10 +631,18 @@ impl GraphBuilder {
             graph.shrink_to_fit();
         }
 
+        if let Some(edge_runs) = self.edge_runs.take() {
+            return GraphBuilder::finalize_external(graph, edge_runs);
+        }
+
         //----------------------------------------------------------------------------------------//
         // replace edges' node-ids by node-indizes for better performance
 
-        info!("DO Replace edges' node-ids by node-indizes.");
+        let num_threads = graph.cfg.num_threads;
+        info!(
+            "DO Replace edges' node-ids by node-indizes using {} threads.",
+            num_threads
+        );
         let mut proto_edges = {
             let nodes = graph.nodes();
 
@@ -416,28 +674,28 @@ impl GraphBuilder {
                 new_proto_edges.reserve_exact(chunk.len());
                 debug!("chunk-len: {}", chunk.len());
 
-                for edge in chunk.into_iter() {
-                    new_proto_edges.push(ProtoEdgeB {
+                let new_chunk = parallel::try_map(&chunk, num_threads, |edge| {
+                    err::Result::Ok(ProtoEdgeB {
                         idx: edge.idx,
                         id: edge.id,
-                        src_idx: nodes.idx_from(edge.src_id).expect(&format!(
-                            "The given src-id `{:?}` doesn't exist as node",
-                            edge.src_id
-                        )),
-                        dst_idx: nodes.idx_from(edge.dst_id).expect(&format!(
-                            "The given dst-id `{:?}` doesn't exist as node",
-                            edge.dst_id
-                        )),
-                        metrics: edge.metrics,
+                        src_idx: nodes.idx_from(edge.src_id).map_err(|_| {
+                            format!("The given src-id `{:?}` doesn't exist as node", edge.src_id)
+                        })?,
+                        dst_idx: nodes.idx_from(edge.dst_id).map_err(|_| {
+                            format!("The given dst-id `{:?}` doesn't exist as node", edge.dst_id)
+                        })?,
+                        metrics: edge.metrics.clone(),
                         sc_edges: edge.sc_edges,
-                    });
+                        street_idx: edge.street_idx,
+                    })
+                })?;
+                new_proto_edges.extend(new_chunk);
 
-                    // print progress
-                    progress_bar.add(1usize);
-                    if progress_bar.has_progressed_significantly() {
-                        progress_bar.remember_significant_progress();
-                        info!("{}", progress_bar);
-                    }
+                // print progress
+                progress_bar.set(new_proto_edges.len());
+                if progress_bar.has_progressed_significantly() {
+                    progress_bar.remember_significant_progress();
+                    info!("{}", progress_bar);
                 }
             }
             progress_bar.set(new_proto_edges.len());
@@ -451,28 +709,49 @@ impl GraphBuilder {
             new_proto_edges
         };
 
+        //----------------------------------------------------------------------------------------//
+        // reorder nodes for better cache-behaviour when routing
+
+        if let Some(new_idxs) = ordering::new_idxs_of(
+            graph.cfg.node_ordering,
+            &graph.node_coords,
+            &graph.node_ch_levels,
+            &proto_edges,
+            |edge| (edge.src_idx, edge.dst_idx),
+            num_threads,
+        ) {
+            info!("DO Reorder nodes by {:?}.", graph.cfg.node_ordering);
+            graph.reorder_nodes(&new_idxs);
+            parallel::for_each_mut(&mut proto_edges, num_threads, |_, edge| {
+                edge.src_idx = new_idxs[*edge.src_idx];
+                edge.dst_idx = new_idxs[*edge.dst_idx];
+            });
+        }
+
         //----------------------------------------------------------------------------------------//
         // sort forward-edges by ascending src-id, then by ascending dst-id -> offset-array
 
         info!("DO Sort proto-forward-edges by their src/dst-IDs.");
-        {
+        let mut proto_edges = {
             // - memory-peak is here when sorting
             // - sort by src-id, then level of dst, then dst-id
             //   -> branch prediction in dijkstra when breaking after level is reached
             if !IS_USING_CH_LEVEL_SPEEDUP {
-                proto_edges.sort_by_key(|edge| (edge.src_idx, edge.dst_idx, edge.id));
+                parallel::sort_by_key(proto_edges, num_threads, |edge| {
+                    (edge.src_idx, edge.dst_idx, edge.id)
+                })
             } else {
                 let nodes = graph.nodes();
-                proto_edges.sort_by_key(|edge| {
+                parallel::sort_by_key(proto_edges, num_threads, |edge| {
                     (
                         edge.src_idx,
                         Reverse(nodes.level(edge.dst_idx)),
                         edge.dst_idx,
                         edge.id,
                     )
-                });
+                })
             }
-        }
+        };
 
         //----------------------------------------------------------------------------------------//
         // shortcuts: map usize to EdgeIdx
@@ -522,8 +801,14 @@ impl GraphBuilder {
                     is_eq &= e0.id == e1.id;
                     is_eq &= (e0.src_idx, e0.dst_idx) == (e1.src_idx, e1.dst_idx);
                     if is_eq {
-                        for (e0_metric, e1_metric) in e0.metrics.iter().zip(e1.metrics.iter()) {
-                            if Approx(e0_metric) == Approx(e1_metric) {
+                        for (metric_idx, (&e0_metric, &e1_metric)) in
+                            e0.metrics.iter().zip(e1.metrics.iter()).enumerate()
+                        {
+                            // metrics stored with less precision are equal within their tolerance
+                            let precision =
+                                graph.cfg.edges.metrics.precision_of(MetricIdx(metric_idx));
+                            let tolerance = precision.tolerance(e0_metric);
+                            if Approx(e0_metric).eq_within(&Approx(e1_metric), tolerance) {
                                 continue;
                             }
                             // values are different
@@ -578,8 +863,13 @@ impl GraphBuilder {
         // building is reduced.
 
         info!("START Store metrics.");
+        // Metrics are stored as f64 while finalizing (e.g. for generating and normalizing them)
+        // and converted into their precisions at the end.
+        // init metrics, one array per metric
+        let mut metrics: Vec<Vec<f64>> = vec![Vec::new(); graph.cfg.edges.metrics.units.len()];
+        debug!("graph-metric-dim: {}", metrics.len());
         let mut new_sc_edges = Vec::with_capacity(sc_count);
-        let mut proto_edges = {
+        let proto_edges = {
             let mut new_proto_edges = vec![];
 
             let mut progress_bar = MappingBar::with_range(0, proto_edges.len()).timed();
@@ -588,12 +878,6 @@ impl GraphBuilder {
             // Work off proto-edges in chunks to keep memory-usage lower.
             let max_chunk_size = capacity::MAX_BYTE_PER_CHUNK / ProtoShortcut::mem_size_b();
             debug!("max-chunk-size: {}", max_chunk_size);
-            // init metrics
-            graph.metrics = Vec::new();
-            debug!(
-                "initial graph-metric-capacity: {}",
-                graph.metrics.capacity()
-            );
 
             // sort reversed to make splice efficient
             proto_edges.reverse();
@@ -610,20 +894,22 @@ impl GraphBuilder {
 
                 // allocate new memory-needs
                 proto_edges.shrink_to_fit();
-                graph.metrics.reserve_exact(chunk.len());
+                for values in metrics.iter_mut() {
+                    values.reserve_exact(chunk.len());
+                }
                 new_proto_edges.reserve_exact(chunk.len());
                 debug!("chunk-len: {}", chunk.len());
-                debug!("graph-metric-capacity: {}", graph.metrics.capacity());
 
                 for mut edge in chunk.into_iter() {
                     // add to graph and remember ids
                     // -> nodes are needed to be finished here to map NodeId -> NodeIdx
-                    graph.add_metrics(&mut edge)?;
+                    graph.add_metrics(&mut metrics, &mut edge)?;
                     new_proto_edges.push(ProtoEdgeC {
                         src_idx: edge.src_idx,
                         dst_idx: edge.dst_idx,
                         idx: 0, // used later for offset-arrays
                         id: edge.id,
+                        street_idx: edge.street_idx,
                     });
 
                     // remember sc-edges for setting offsets later
@@ -649,14 +935,137 @@ impl GraphBuilder {
             }
             // reduce and optimize memory-usage
             graph.shrink_to_fit();
+            metrics.iter_mut().for_each(Vec::shrink_to_fit);
             new_proto_edges.shrink_to_fit();
             // last node needs an upper bound as well for `leaving_edges(...)`
 
             new_proto_edges
         };
 
-        for metrics in &graph.metrics {
-            for metric in metrics {
+        GraphBuilder::finalize_edges(graph, metrics, proto_edges, new_sc_edges, sc_count)
+    }
+
+    /// Merges the spilled proto-edges, which are sorted by (src-id, dst-id) already.
+    /// Since shortcuts and ch-levels are not supported, this is the order of the forward-edges.
+    fn finalize_external(graph: Graph, edge_runs: EdgeRuns) -> err::Result<Graph> {
+        if graph.cfg.node_ordering != NodeOrdering::Id {
+            return Err(err::Msg::from(format!(
+                "Node-ordering {:?} is not supported when building with external memory.",
+                graph.cfg.node_ordering
+            )));
+        }
+        if graph
+            .node_ch_levels
+            .iter()
+            .any(|&level| level != defaults::network::nodes::LEVEL)
+        {
+            return Err(err::Msg::from(
+                "Ch-levels are not supported when building with external memory.",
+            ));
+        }
+
+        info!(
+            "START Merge {} proto-edges from {} sorted runs.",
+            edge_runs.len(),
+            edge_runs.run_count()
+        );
+        let mut metrics: Vec<Vec<f64>> = vec![Vec::new(); graph.cfg.edges.metrics.units.len()];
+        let mut proto_edges = Vec::new();
+        let edge_count = edge_runs.len();
+        let mut progress_bar = MappingBar::with_range(0, edge_count).timed();
+        let mut removed_count = 0;
+        {
+            let nodes = graph.nodes();
+            // id, src-idx, dst-idx and metrics of last stored edge to remove duplicates
+            let mut last_edge: Option<(Option<usize>, NodeIdx, NodeIdx, DimVec<f64>)> = None;
+
+            for proto_edge in edge_runs.into_merged()? {
+                let proto_edge = proto_edge?;
+                let mut edge = ProtoEdgeB {
+                    idx: proto_edge.idx,
+                    id: proto_edge.id,
+                    src_idx: nodes.idx_from(proto_edge.src_id).map_err(|_| {
+                        format!(
+                            "The given src-id `{:?}` doesn't exist as node",
+                            proto_edge.src_id
+                        )
+                    })?,
+                    dst_idx: nodes.idx_from(proto_edge.dst_id).map_err(|_| {
+                        format!(
+                            "The given dst-id `{:?}` doesn't exist as node",
+                            proto_edge.dst_id
+                        )
+                    })?,
+                    metrics: proto_edge.metrics,
+                    sc_edges: None,
+                    street_idx: proto_edge.street_idx,
+                };
+
+                // print progress
+                progress_bar.add(1usize);
+                if progress_bar.has_progressed_significantly() {
+                    progress_bar.remember_significant_progress();
+                    info!("{}", progress_bar);
+                }
+
+                // duplicates are next to each other (like in memory)
+                let is_duplicate = match &last_edge {
+                    Some((id, src_idx, dst_idx, last_metrics)) => {
+                        (*id, *src_idx, *dst_idx) == (edge.id, edge.src_idx, edge.dst_idx)
+                            && last_metrics
+                                .iter()
+                                .zip(edge.metrics.iter())
+                                .enumerate()
+                                .all(|(metric_idx, (&m0, &m1))| {
+                                    let precision =
+                                        graph.cfg.edges.metrics.precision_of(MetricIdx(metric_idx));
+                                    Approx(m0).eq_within(&Approx(m1), precision.tolerance(m0))
+                                })
+                    }
+                    None => false,
+                };
+                if is_duplicate {
+                    removed_count += 1;
+                    continue;
+                }
+                last_edge = Some((edge.id, edge.src_idx, edge.dst_idx, edge.metrics.clone()));
+
+                graph.add_metrics(&mut metrics, &mut edge)?;
+                proto_edges.push(ProtoEdgeC {
+                    src_idx: edge.src_idx,
+                    dst_idx: edge.dst_idx,
+                    idx: 0, // used later for offset-arrays
+                    id: edge.id,
+                    street_idx: edge.street_idx,
+                });
+            }
+        }
+        progress_bar.set(edge_count);
+        if progress_bar.has_progressed_significantly() {
+            progress_bar.remember_significant_progress();
+            info!("{}", progress_bar);
+        }
+        info!("Removed {} duplicates.", removed_count);
+        proto_edges.shrink_to_fit();
+        metrics.iter_mut().for_each(Vec::shrink_to_fit);
+        info!("FINISHED");
+
+        GraphBuilder::finalize_edges(graph, metrics, proto_edges, Vec::new(), 0)
+    }
+
+    /// Builds the offset-arrays from the proto-edges, which are sorted like the forward-edges, and generates metrics.
+    fn finalize_edges(
+        mut graph: Graph,
+        mut metrics: Vec<Vec<f64>>,
+        mut proto_edges: Vec<ProtoEdgeC>,
+        new_sc_edges: Vec<(usize, [EdgeIdx; 2])>,
+        sc_count: usize,
+    ) -> err::Result<Graph> {
+        let num_threads = graph.cfg.num_threads;
+        let is_storing_streets = !graph.streets.is_empty();
+
+        for values in &metrics {
+            for metric in values {
                 if metric < &defaults::accuracy::F64_ABS {
                     return Err(err::Msg::from(
                         "A metric is smaller than accuracy allows it.",
@@ -708,65 +1117,31 @@ impl GraphBuilder {
         //----------------------------------------------------------------------------------------//
         // build forward-offset-array and edges
 
-        // logging
-        info!("START Create the forward-offset-array and the forward-mapping.");
+        info!("DO Create the forward-offset-array and the forward-mapping.");
         {
-            let mut progress_bar = MappingBar::with_range(0, proto_edges.len()).timed();
-            // start looping
-            let mut src_idx = NodeIdx(0);
-            let mut offset = 0;
-            graph.fwd_offsets.push(offset);
-            // high-level-idea
-            // count offset for each proto_edge (sorted)
-            // and apply offset as far as src doesn't change
-            let mut edge_idx = 0;
-            for proto_edge in proto_edges.iter_mut() {
-                // Add edge-idx here to remember it for indirect mapping bwd->fwd.
-                // Update it at the end of the loop.
-                proto_edge.idx = edge_idx;
-
-                // do not swap src and dst since this is a forward-edge
-                let edge_src_idx = proto_edge.src_idx;
-                let edge_dst_idx = proto_edge.dst_idx;
-
-                // If coming edges have new src, then update offset of new src.
-                // Loop because of nodes with no leaving edges.
-                // Nodes of id y with no leaving edge must have the same offset as the node of id (y+1)
-                // to remember it.
-                while src_idx != edge_src_idx.into() {
-                    *src_idx += 1;
-                    graph.fwd_offsets.push(offset);
-                }
-                offset += 1;
-                graph.bwd_dsts.push(edge_src_idx);
-                graph.fwd_dsts.push(edge_dst_idx);
-                // mapping fwd to fwd is just the identity
-                graph.fwd_to_fwd_map.push(EdgeIdx(edge_idx));
-                // edge-ids
-                graph.edge_ids.push(proto_edge.id);
-                if let Some(id) = proto_edge.id {
-                    graph.edge_ids_to_idx_map.push((id, EdgeIdx(edge_idx)));
-                }
-
-                // print progress
-                progress_bar.set(edge_idx);
-                if progress_bar.has_progressed_significantly() {
-                    progress_bar.remember_significant_progress();
-                    info!("{}", progress_bar);
-                }
+            // Remember edge-idx for indirect mapping bwd->fwd.
+            parallel::for_each_mut(&mut proto_edges, num_threads, |edge_idx, proto_edge| {
+                proto_edge.idx = edge_idx
+            });
 
-                // update edge-idx
-                edge_idx += 1;
-            }
-            // last node needs an upper bound as well for `leaving_edges(...)`
-            graph.fwd_offsets.push(offset);
-            progress_bar.set(offset);
-            if progress_bar.has_progressed_significantly() {
-                progress_bar.remember_significant_progress();
-                info!("{}", progress_bar);
+            // do not swap src and dst since this is a forward-edge
+            graph.fwd_offsets = offsets_of(&proto_edges, num_threads, |edge| edge.src_idx);
+            graph.bwd_dsts = parallel::map(&proto_edges, num_threads, |edge| edge.src_idx);
+            graph.fwd_dsts = parallel::map(&proto_edges, num_threads, |edge| edge.dst_idx);
+            // mapping fwd to fwd is just the identity
+            graph.fwd_to_fwd_map = (0..proto_edges.len()).map(EdgeIdx).collect();
+            // edge-ids
+            graph.edge_ids = parallel::map(&proto_edges, num_threads, |edge| edge.id);
+            graph.edge_ids_to_idx_map = proto_edges
+                .iter()
+                .filter_map(|edge| edge.id.map(|id| (id, EdgeIdx(edge.idx))))
+                .collect();
+            // streets
+            if is_storing_streets {
+                graph.street_idxs =
+                    parallel::map(&proto_edges, num_threads, |edge| edge.street_idx);
             }
             // reduce and optimize memory-usage
-            // already dropped via iterator: drop(self.proto_edges);
             graph.shrink_to_fit();
         }
 
@@ -775,9 +1150,11 @@ impl GraphBuilder {
         if graph.edge_ids_to_idx_map.len() > 0 {
             let old_len = graph.edge_ids_to_idx_map.len();
             info!("DO Sort mapping from edge-ids to indices.");
-            graph
-                .edge_ids_to_idx_map
-                .sort_unstable_by_key(|&(id, _idx)| id);
+            graph.edge_ids_to_idx_map = parallel::sort_by_key(
+                mem::take(&mut graph.edge_ids_to_idx_map),
+                num_threads,
+                |&(id, _idx)| id,
+            );
             graph.edge_ids_to_idx_map.dedup_by_key(|&mut (id, _idx)| id);
             if graph.edge_ids_to_idx_map.len() != old_len {
                 return Err(err::Msg::from(
@@ -811,74 +1188,37 @@ impl GraphBuilder {
         // sort backward-edges by ascending dst-id, then by ascending src-id -> offset-array
 
         info!("DO Sort proto-backward-edges by their dst/src-IDs.");
-        {
+        let proto_edges = {
             if !IS_USING_CH_LEVEL_SPEEDUP {
-                proto_edges.sort_by_key(|edge| (edge.dst_idx, edge.src_idx));
+                parallel::sort_by_key(proto_edges, num_threads, |edge| {
+                    (edge.dst_idx, edge.src_idx)
+                })
             } else {
                 let nodes = graph.nodes();
-                proto_edges.sort_by_key(|edge| {
+                parallel::sort_by_key(proto_edges, num_threads, |edge| {
                     (
                         edge.dst_idx,
                         Reverse(nodes.level(edge.src_idx)),
                         edge.src_idx,
                         edge.id,
                     )
-                });
+                })
             }
-        }
+        };
 
         //----------------------------------------------------------------------------------------//
         // build backward-offset-array
 
-        info!("START Create the backward-offset-array.");
+        info!("DO Create the backward-offset-array.");
         {
-            let mut progress_bar = MappingBar::with_range(0, proto_edges.len()).timed();
-            // start looping
-            let mut src_idx = NodeIdx(0);
-            let mut offset = 0;
-            graph.bwd_offsets.push(offset);
-            // high-level-idea
-            // count offset for each proto_edge (sorted) and apply offset as far as src doesn't change
-            for edge_idx in 0..proto_edges.len() {
-                let proto_edge = &mut proto_edges[edge_idx];
-
-                // swap src and dst since this is the backward-edge
-                let edge_src_idx = proto_edge.dst_idx;
-
-                // If coming edges have new src, then update offset of new src.
-                // Loop because of nodes with no leaving edges.
-                // Nodes of id y with no leaving edge must have the same offset as the node of id (y+1)
-                // to remember it.
-                while src_idx != edge_src_idx {
-                    *src_idx += 1;
-                    graph.bwd_offsets.push(offset);
-                }
-                offset += 1;
-                // For the backward-mapping, bwd-indices have been remembered above,
-                // but applied to forward-sorted-edges.
-                // Now, that's used to generate the mapping from backward to forward,
-                // which is needed for the offset-arrays.
-                graph.bwd_to_fwd_map.push(EdgeIdx(proto_edge.idx));
-
-                // print progress
-                progress_bar.set(edge_idx);
-                if progress_bar.has_progressed_significantly() {
-                    progress_bar.remember_significant_progress();
-                    info!("{}", progress_bar);
-                }
-            }
-            // last node needs an upper bound as well for `leaving_edges(...)`
-            debug_assert_eq!(
-                offset,
-                proto_edges.len(),
-                "Last offset-value should be as big as the number of proto-edges."
-            );
-            graph.bwd_offsets.push(offset);
-            progress_bar.set(graph.fwd_dsts.len());
-            if progress_bar.has_progressed_significantly() {
-                progress_bar.remember_significant_progress();
-                info!("{}", progress_bar);
-            }
+            // swap src and dst since this is the backward-edge
+            graph.bwd_offsets = offsets_of(&proto_edges, num_threads, |edge| edge.dst_idx);
+            // For the backward-mapping, bwd-indices have been remembered above,
+            // but applied to forward-sorted-edges.
+            // Now, that's used to generate the mapping from backward to forward,
+            // which is needed for the offset-arrays.
+            graph.bwd_to_fwd_map =
+                parallel::map(&proto_edges, num_threads, |edge| EdgeIdx(edge.idx));
             // reduce and optimize memory-usage
             graph.shrink_to_fit();
         }
@@ -1129,10 +1469,7 @@ impl GraphBuilder {
                     }
                     generating::edges::Category::Custom { unit, id, default } => {
                         // update graph
-                        graph
-                            .metrics
-                            .iter_mut()
-                            .for_each(|metric| metric.push(*default));
+                        metrics.push(vec![*default; graph.fwd_dsts.len()]);
 
                         // update config
 
@@ -1158,6 +1495,8 @@ impl GraphBuilder {
                             generating::edges::metrics::UnitInfo::Hours => false,
                             generating::edges::metrics::UnitInfo::KilometersPerHour => false,
                             generating::edges::metrics::UnitInfo::LaneCount => false,
+                            generating::edges::metrics::UnitInfo::Discomfort => false,
+                            generating::edges::metrics::UnitInfo::DiscomfortKilometers => false,
                             generating::edges::metrics::UnitInfo::F64 => false,
                         } {
                             return Err(format!(
@@ -1170,28 +1509,36 @@ impl GraphBuilder {
 
                         // calculate haversine-distance and update graph and config
 
-                        for edge_idx in (0..graph.metrics.len()).map(EdgeIdx) {
-                            // get positions
-
-                            let (src_coord, dst_coord) = {
-                                let src_idx = graph.bwd_edges().dst_idx(edge_idx);
-                                let dst_idx = graph.fwd_edges().dst_idx(edge_idx);
-                                let nodes = graph.nodes();
-                                (nodes.coord(src_idx), nodes.coord(dst_idx))
-                            };
-
-                            // calculate distance
-                            let distance = {
-                                let km =
-                                    kissunits::geo::haversine_distance_km(&src_coord, &dst_coord);
-                                generating::edges::metrics::UnitInfo::Kilometers
-                                    .try_convert(unit, *km)?
-                            };
+                        let distances = {
+                            let nodes = graph.nodes();
+                            let (src_idxs, dst_idxs) = (&graph.bwd_dsts, &graph.fwd_dsts);
+                            let mut distances = Vec::with_capacity(dst_idxs.len());
+                            for chunk in
+                                parallel::map_ranges(dst_idxs.len(), num_threads, |edge_range| {
+                                    edge_range
+                                        .map(|edge_idx| {
+                                            // get positions
+                                            let src_coord = nodes.coord(src_idxs[edge_idx]);
+                                            let dst_coord = nodes.coord(dst_idxs[edge_idx]);
+
+                                            // calculate distance
+                                            let km = kissunits::geo::haversine_distance_km(
+                                                &src_coord, &dst_coord,
+                                            );
+                                            generating::edges::metrics::UnitInfo::Kilometers
+                                                .try_convert(unit, *km)
+                                        })
+                                        .collect::<err::Result<Vec<_>>>()
+                                })
+                            {
+                                distances.extend(chunk?);
+                            }
+                            distances
+                        };
 
-                            // update graph
+                        // update graph
 
-                            graph.metrics[*edge_idx].push(distance);
-                        }
+                        metrics.push(distances);
 
                         // update config
 
@@ -1211,19 +1558,15 @@ impl GraphBuilder {
                         // and add to their metrics
 
                         let metric_idx = graph.cfg.edges.metrics.idx_of(&from.id);
-                        for edge_idx in 0..graph.metrics.len() {
-                            // get old value
-                            // and generate new value
-
-                            let new_raw_value = {
-                                let old_raw_value = graph.metrics[edge_idx][*metric_idx];
-                                from.unit.try_convert(&to.unit, old_raw_value)?
-                            };
+                        let new_raw_values = parallel::try_map(
+                            &metrics[*metric_idx],
+                            num_threads,
+                            |&old_raw_value| from.unit.try_convert(&to.unit, old_raw_value),
+                        )?;
 
-                            // update graph
+                        // update graph
 
-                            graph.metrics[edge_idx].push(new_raw_value);
-                        }
+                        metrics.push(new_raw_values);
 
                         // update config
 
@@ -1243,19 +1586,12 @@ impl GraphBuilder {
                         // and replace their existing metrics
 
                         let metric_idx = graph.cfg.edges.metrics.idx_of(&from.id);
-                        for edge_idx in 0..graph.metrics.len() {
-                            // get old value
-                            // and generate new value
-
-                            let new_raw_value = {
-                                let old_raw_value = graph.metrics[edge_idx][*metric_idx];
-                                from.unit.try_convert(&to.unit, old_raw_value)?
-                            };
-
-                            // update graph
-
-                            graph.metrics[edge_idx][*metric_idx] = new_raw_value;
-                        }
+                        // generate new values from old values
+                        // and update graph
+                        metrics[*metric_idx] =
+                            parallel::try_map(&metrics[*metric_idx], num_threads, |&raw_value| {
+                                from.unit.try_convert(&to.unit, raw_value)
+                            })?;
 
                         // update config
 
@@ -1286,22 +1622,29 @@ impl GraphBuilder {
 
                         let metric_idx_a = graph.cfg.edges.metrics.idx_of(&a.id);
                         let metric_idx_b = graph.cfg.edges.metrics.idx_of(&b.id);
-                        for edge_idx in 0..graph.metrics.len() {
-                            // get old value
-                            // and generate new value
-
-                            let new_raw_value = {
-                                let old_raw_a = graph.metrics[edge_idx][*metric_idx_a];
-                                let old_raw_b = graph.metrics[edge_idx][*metric_idx_b];
-                                result
-                                    .unit
-                                    .try_calc(&a.unit, old_raw_a, &b.unit, old_raw_b)?
-                            };
+                        let (raw_values_a, raw_values_b) =
+                            (&metrics[*metric_idx_a], &metrics[*metric_idx_b]);
+                        let mut new_raw_values = Vec::with_capacity(graph.fwd_dsts.len());
+                        for chunk in
+                            parallel::map_ranges(raw_values_a.len(), num_threads, |edge_range| {
+                                edge_range
+                                    .map(|edge_idx| {
+                                        result.unit.try_calc(
+                                            &a.unit,
+                                            raw_values_a[edge_idx],
+                                            &b.unit,
+                                            raw_values_b[edge_idx],
+                                        )
+                                    })
+                                    .collect::<err::Result<Vec<_>>>()
+                            })
+                        {
+                            new_raw_values.extend(chunk?);
+                        }
 
-                            // update graph
+                        // update graph
 
-                            graph.metrics[edge_idx].push(new_raw_value);
-                        }
+                        metrics.push(new_raw_values);
 
                         // update config
 
@@ -1374,7 +1717,7 @@ impl GraphBuilder {
 
                                         let param = params[col_idx];
                                         if let Ok(raw_value) = param.parse::<f64>() {
-                                            graph.metrics[*edge_idx][*metric_idx] = raw_value;
+                                            metrics[*metric_idx][*edge_idx] = raw_value;
                                         } else {
                                             return Err(err::Msg::from(format!(
                                                 "Parsing '{}' didn't work.",
@@ -1411,15 +1754,12 @@ impl GraphBuilder {
 
             // compute mean
 
-            let means: DimVec<_> = graph
-                .metrics
-                .iter()
-                .fold(smallvec![0.0; graph.metrics().dim()], |acc, b| {
-                    helpers::add(&acc, b)
-                })
-                .iter_mut()
-                .map(|sum| *sum / n)
-                .collect();
+            // Every metric is summed up by one thread, so the means don't depend on the number of threads.
+            let means: DimVec<_> = parallel::map(&metrics, num_threads, |values| {
+                values.iter().sum::<f64>() / n
+            })
+            .into_iter()
+            .collect();
 
             // print mean
 
@@ -1437,16 +1777,13 @@ impl GraphBuilder {
 
             // normalize
 
-            for edge_metrics in graph.metrics.iter_mut() {
-                edge_metrics
-                    .iter_mut()
-                    .enumerate()
-                    .for_each(|(metric_idx, metric)| {
-                        *metric /= means[metric_idx];
-                        if Approx(*metric) == Approx(0.0) {
-                            *metric = defaults::accuracy::F64_ABS
-                        }
-                    });
+            for (values, mean) in metrics.iter_mut().zip(&means) {
+                parallel::for_each_mut(values, num_threads, |_, metric| {
+                    *metric /= mean;
+                    if Approx(*metric) == Approx(0.0) {
+                        *metric = defaults::accuracy::F64_ABS
+                    }
+                });
             }
 
             // and remember means
@@ -1454,6 +1791,37 @@ impl GraphBuilder {
             graph.means = Some(means);
         }
 
+        //----------------------------------------------------------------------------------------//
+        // store metrics with their precisions
+
+        info!("DO Store metrics with their precisions.");
+        {
+            let metrics_cfg = &graph.cfg.edges.metrics;
+            if let Some(id) = metrics_cfg
+                .precisions
+                .keys()
+                .find(|id| !metrics_cfg.ids.contains(id))
+            {
+                return Err(format!(
+                    "The precision of metric {} is given, but the graph has no such metric.",
+                    id
+                )
+                .into());
+            }
+
+            graph.metrics = Vec::with_capacity(metrics.len());
+            for (metric_idx, values) in metrics.into_iter().enumerate() {
+                let precision = graph.cfg.edges.metrics.precision_of(MetricIdx(metric_idx));
+                let values = MetricValues::try_from_f64s(precision, values).map_err(|msg| {
+                    format!(
+                        "Storing metric {} failed: {}",
+                        graph.cfg.edges.metrics.ids[metric_idx], msg
+                    )
+                })?;
+                graph.metrics.push(values);
+            }
+        }
+
         info!("FINISHED Finalizing graph has finished.");
         Ok(graph)
     }
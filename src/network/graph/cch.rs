@@ -0,0 +1,587 @@
+use super::{
+    building::{GraphBuilder, ProtoEdge, ProtoNode, ProtoShortcut},
+    EdgeIdx, Graph, NodeIdx,
+};
+use crate::{
+    approximating::Approx,
+    configs,
+    defaults::{self, capacity::DimVec},
+    helpers::{self, err},
+};
+use kissunits::geo::Coordinate;
+use log::info;
+use std::mem;
+
+/// Parts of the nested dissection with at most this many nodes are not split anymore.
+const MAX_LEAF_SIZE: usize = 1;
+
+// Every arc of the chordal supergraph has two directions.
+const UP: usize = 0;
+const DOWN: usize = 1;
+
+/// Customizable contraction-hierarchies (CCH)
+///
+/// The preprocessing is split into two phases.
+/// The first phase depends only on the graph's structure and is done once, when creating this struct.
+/// It computes a node-ordering via nested dissection (using the nodes' coordinates) and contracts the graph wrt this ordering, resulting in the chordal supergraph of the graph.
+///
+/// The second phase (the customization) depends on the edges' metrics and is cheap in comparison to a full ch-construction.
+/// Every arc of the supergraph gets all shortcuts, which are pareto-optimal wrt the metrics considered in the routing.
+/// Hence the resulting ch-graph is correct for every personalized query over these metrics, e.g. in the explorator.
+/// To bound the customization, an arc keeps at most `defaults::routing::CCH_MAX_PARETO_SET_SIZE` shortcuts,
+/// but always the best one per metric.
+///
+/// The ch-levels are the nodes' ranks in the ordering, so they are the same in every customization.
+/// Nodes are identified by their ids, because customized graphs may be reordered when finalizing (see `parsing.node-ordering`).
+pub struct CustomizableCH {
+    // rank (used as ch-level) of every node-id, sorted by id
+    ranks: Vec<(i64, usize)>,
+    // node-ids sorted by rank
+    ids: Vec<i64>,
+    // upward arcs (low rank -> high rank) of the chordal supergraph as offset-graph in rank-space
+    up_offsets: Vec<usize>,
+    up_heads: Vec<usize>,
+}
+
+#[derive(Clone)]
+struct Entry {
+    metrics: DimVec<f64>,
+    // proto-edge-indices of the shortcut's sub-edges
+    sc_edges: Option<[usize; 2]>,
+    // proto-edge-index, which is set when the entry is added to the graph-builder
+    edge: Option<usize>,
+}
+
+impl CustomizableCH {
+    /// Computes the metric-independent part of the CCH.
+    /// Shortcuts of the provided graph are ignored.
+    pub fn new(graph: &Graph) -> CustomizableCH {
+        let nodes = graph.nodes();
+        let neighbours = undirected_neighbours(graph);
+
+        info!("START Compute node-ordering via nested dissection.");
+        let order = {
+            let mut order = Vec::with_capacity(nodes.count());
+            let mut part_ids = vec![0; nodes.count()];
+            let mut next_part_id = 1;
+            dissect(
+                nodes.iter().collect(),
+                graph,
+                &neighbours,
+                &mut part_ids,
+                &mut next_part_id,
+                &mut order,
+            );
+            order
+        };
+        let mut ranks = vec![0; nodes.count()];
+        for (rank, node_idx) in order.iter().enumerate() {
+            ranks[**node_idx] = rank;
+        }
+        let ids: Vec<i64> = order.iter().map(|&node_idx| nodes.id(node_idx)).collect();
+        info!("FINISHED");
+
+        info!("START Contract graph wrt node-ordering.");
+        let mut up_arcs: Vec<Vec<usize>> = vec![Vec::new(); nodes.count()];
+        for node_idx in nodes.iter() {
+            let rank = ranks[*node_idx];
+            for neighbour in &neighbours[*node_idx] {
+                if rank < ranks[**neighbour] {
+                    up_arcs[rank].push(ranks[**neighbour]);
+                }
+            }
+        }
+        // Contracting a node connects all its upper neighbours.
+        // Adding them to its lowest upper neighbour is sufficient,
+        // because this neighbour is contracted next and passes them on.
+        for rank in 0..nodes.count() {
+            let mut heads = mem::take(&mut up_arcs[rank]);
+            heads.sort_unstable();
+            heads.dedup();
+            if let Some((&parent, others)) = heads.split_first() {
+                up_arcs[parent].extend_from_slice(others);
+            }
+            up_arcs[rank] = heads;
+        }
+        let mut up_offsets = Vec::with_capacity(nodes.count() + 1);
+        let mut up_heads = Vec::new();
+        up_offsets.push(0);
+        for heads in up_arcs {
+            up_heads.extend(heads);
+            up_offsets.push(up_heads.len());
+        }
+        info!("FINISHED Chordal supergraph has {} arcs.", up_heads.len());
+
+        let mut ranks: Vec<(i64, usize)> = ids
+            .iter()
+            .enumerate()
+            .map(|(rank, &id)| (id, rank))
+            .collect();
+        ranks.sort_unstable();
+
+        CustomizableCH {
+            ranks,
+            ids,
+            up_offsets,
+            up_heads,
+        }
+    }
+
+    /// Number of (undirected) arcs in the chordal supergraph
+    pub fn arc_count(&self) -> usize {
+        self.up_heads.len()
+    }
+
+    /// The node's ch-level in every customized graph, or None for unknown node-ids
+    pub fn level(&self, id: i64) -> Option<usize> {
+        match self
+            .ranks
+            .binary_search_by_key(&id, |&(node_id, _rank)| node_id)
+        {
+            Ok(i) => Some(self.ranks[i].1),
+            Err(_) => None,
+        }
+    }
+
+    /// Creates a ch-graph from the provided graph's edges (ignoring its shortcuts) and metrics.
+    ///
+    /// Pareto-optimality of shortcuts is checked for all metrics, which are considered in the routing-config, meaning they have a non-zero alpha or a finite tolerated scale.
+    pub fn customize(
+        &self,
+        graph: &Graph,
+        routing_cfg: &configs::routing::Config,
+    ) -> err::Result<Graph> {
+        let nodes = graph.nodes();
+        let fwd_edges = graph.fwd_edges();
+
+        if nodes.count() != self.ranks.len() {
+            return Err(format!(
+                "The CCH has been built for {} nodes, but the graph has {} nodes.",
+                self.ranks.len(),
+                nodes.count()
+            )
+            .into());
+        }
+        // ranks per node-idx of the provided graph
+        let ranks = nodes
+            .iter()
+            .map(|node_idx| {
+                self.level(nodes.id(node_idx)).ok_or_else(|| {
+                    err::Msg::from(format!(
+                        "The CCH has not been built for node-id {}.",
+                        nodes.id(node_idx)
+                    ))
+                })
+            })
+            .collect::<err::Result<Vec<usize>>>()?;
+
+        let considered_metrics: Vec<usize> = (0..graph.metrics().dim())
+            .filter(|&i| {
+                routing_cfg.alphas[i] != 0.0
+                    || routing_cfg.tolerated_scales[i] != defaults::routing::TOLERATED_SCALE_INF
+            })
+            .collect();
+        info!(
+            "START Customize ch-graph wrt metrics {:?}",
+            considered_metrics
+                .iter()
+                .map(|&i| &graph.cfg().edges.metrics.ids[i])
+                .collect::<Vec<_>>()
+        );
+
+        // The graph's metrics are already normalized (if wanted),
+        // so normalizing them again would destroy the means.
+        let mut builder = {
+            let mut cfg = graph.cfg().clone();
+            cfg.edges.metrics.are_normalized = false;
+            GraphBuilder::new(cfg)
+        };
+        let mut arc_sets: Vec<Vec<Entry>> = vec![Vec::new(); 2 * self.up_heads.len()];
+        let mut edge_count = 0;
+
+        // add original edges
+
+        for src_idx in nodes.iter() {
+            for edge in fwd_edges.starting_from(src_idx) {
+                if edge.is_shortcut() {
+                    continue;
+                }
+
+                let street_idx = graph
+                    .streets()
+                    .of(edge.idx())
+                    .map(|street| builder.insert_street(street.clone()));
+                builder.insert(ProtoEdge {
+                    id: fwd_edges.try_id(edge.idx()),
+                    src_id: nodes.id(src_idx),
+                    dst_id: nodes.id(edge.dst_idx()),
+                    metrics: edge.metrics().clone(),
+                    street_idx,
+                })?;
+
+                let (src_rank, dst_rank) = (ranks[*src_idx], ranks[*edge.dst_idx()]);
+                if src_rank != dst_rank {
+                    let set_idx = if src_rank < dst_rank {
+                        2 * self.arc_idx(src_rank, dst_rank)? + UP
+                    } else {
+                        2 * self.arc_idx(dst_rank, src_rank)? + DOWN
+                    };
+                    insert_pareto_optimal(
+                        &mut arc_sets[set_idx],
+                        Entry {
+                            metrics: edge.metrics().clone(),
+                            sc_edges: None,
+                            edge: Some(edge_count),
+                        },
+                        &considered_metrics,
+                    );
+                }
+
+                edge_count += 1;
+            }
+        }
+        let original_edge_count = edge_count;
+
+        // Shortcuts get new ids (if the original edges have ids) to keep them writable.
+        let mut next_sc_id = {
+            let mut max_id = Some(0);
+            for edge_idx in fwd_edges.iter() {
+                if !fwd_edges.is_shortcut(edge_idx) {
+                    max_id = match (max_id, fwd_edges.try_id(edge_idx)) {
+                        (Some(max_id), Some(id)) => Some(max_id.max(id)),
+                        _ => None,
+                    };
+                }
+            }
+            max_id.map(|max_id| max_id + 1)
+        };
+
+        // Process nodes bottom-up.
+        // When a node is processed, all its upward arcs are final,
+        // because they can only be improved by lower triangles.
+
+        for low in 0..self.ranks.len() {
+            let arcs = self.up_offsets[low]..self.up_offsets[low + 1];
+
+            // add final shortcuts to graph
+
+            for arc in arcs.clone() {
+                let high = self.up_heads[arc];
+                for &dir in &[UP, DOWN] {
+                    let (src_rank, dst_rank) = if dir == UP { (low, high) } else { (high, low) };
+                    for entry in arc_sets[2 * arc + dir].iter_mut() {
+                        if let (None, Some(sc_edges)) = (entry.edge, entry.sc_edges) {
+                            builder.insert(ProtoShortcut {
+                                proto_edge: ProtoEdge {
+                                    id: next_sc_id,
+                                    src_id: self.ids[src_rank],
+                                    dst_id: self.ids[dst_rank],
+                                    metrics: entry.metrics.clone(),
+                                    street_idx: None,
+                                },
+                                sc_edges: Some([EdgeIdx(sc_edges[0]), EdgeIdx(sc_edges[1])]),
+                            })?;
+                            entry.edge = Some(edge_count);
+                            edge_count += 1;
+                            next_sc_id = next_sc_id.map(|id| id + 1);
+                        }
+                    }
+                }
+            }
+
+            // relax lower triangles (low, u, v) into arc (u, v)
+
+            for arc_u in arcs.clone() {
+                let u = self.up_heads[arc_u];
+                for arc_v in (arc_u + 1)..arcs.end {
+                    let v = self.up_heads[arc_v];
+                    let arc_uv = self.arc_idx(u, v)?;
+
+                    // u -> low -> v
+                    relax(
+                        &mut arc_sets,
+                        2 * arc_uv + UP,
+                        [2 * arc_u + DOWN, 2 * arc_v + UP],
+                        &considered_metrics,
+                    );
+                    // v -> low -> u
+                    relax(
+                        &mut arc_sets,
+                        2 * arc_uv + DOWN,
+                        [2 * arc_v + DOWN, 2 * arc_u + UP],
+                        &considered_metrics,
+                    );
+                }
+            }
+
+            // arcs of processed nodes are not needed anymore
+
+            for arc in arcs {
+                arc_sets[2 * arc + UP] = Vec::new();
+                arc_sets[2 * arc + DOWN] = Vec::new();
+            }
+        }
+        info!(
+            "FINISHED Customization created {} shortcuts.",
+            edge_count - original_edge_count
+        );
+
+        // build ch-graph
+
+        let mut builder = builder.next();
+        for node_idx in nodes.iter() {
+            builder.insert(ProtoNode {
+                id: nodes.id(node_idx),
+                coord: nodes.coord(node_idx),
+                ch_level: Some(ranks[*node_idx]),
+            });
+        }
+        let mut ch_graph = builder.next()?.finalize()?;
+        ch_graph.cfg = graph.cfg.clone();
+        ch_graph.means = graph.means.clone();
+
+        Ok(ch_graph)
+    }
+
+    fn arc_idx(&self, low: usize, high: usize) -> err::Result<usize> {
+        let offset = self.up_offsets[low];
+        match self.up_heads[offset..self.up_offsets[low + 1]].binary_search(&high) {
+            Ok(i) => Ok(offset + i),
+            Err(_) => Err(format!(
+                "The chordal supergraph should contain the arc of ranks ({}, {}).",
+                low, high
+            )
+            .into()),
+        }
+    }
+}
+
+/// Adds all combinations of the sub-arcs' entries to the target-arc.
+fn relax(
+    arc_sets: &mut [Vec<Entry>],
+    target: usize,
+    sub_arcs: [usize; 2],
+    considered_metrics: &[usize],
+) {
+    let mut target_set = mem::take(&mut arc_sets[target]);
+    for entry_0 in &arc_sets[sub_arcs[0]] {
+        for entry_1 in &arc_sets[sub_arcs[1]] {
+            insert_pareto_optimal(
+                &mut target_set,
+                Entry {
+                    metrics: helpers::add(&entry_0.metrics, &entry_1.metrics),
+                    sc_edges: Some([
+                        entry_0.edge.expect("Sub-arcs should be final."),
+                        entry_1.edge.expect("Sub-arcs should be final."),
+                    ]),
+                    edge: None,
+                },
+                considered_metrics,
+            );
+        }
+    }
+    arc_sets[target] = target_set;
+}
+
+/// Keeps only entries, which are not dominated (or equal) wrt the considered metrics.
+///
+/// If the set exceeds `defaults::routing::CCH_MAX_PARETO_SET_SIZE`, the entry with the largest (range-normalized) sum of metrics is dropped,
+/// while the best entry per metric is kept.
+fn insert_pareto_optimal(set: &mut Vec<Entry>, new_entry: Entry, considered_metrics: &[usize]) {
+    let is_dominating = |a: &DimVec<f64>, b: &DimVec<f64>| {
+        considered_metrics
+            .iter()
+            .all(|&i| Approx(a[i]) <= Approx(b[i]))
+    };
+
+    if set
+        .iter()
+        .any(|entry| is_dominating(&entry.metrics, &new_entry.metrics))
+    {
+        return;
+    }
+    set.retain(|entry| !is_dominating(&new_entry.metrics, &entry.metrics));
+    set.push(new_entry);
+
+    if set.len() > defaults::routing::CCH_MAX_PARETO_SET_SIZE {
+        drop_worst(set, considered_metrics);
+    }
+}
+
+/// Removes the entry with the largest sum of metrics (normalized by their ranges in the set),
+/// which is not the best entry of any considered metric.
+fn drop_worst(set: &mut Vec<Entry>, considered_metrics: &[usize]) {
+    let ranges: Vec<(f64, f64)> = considered_metrics
+        .iter()
+        .map(|&i| {
+            set.iter()
+                .map(|entry| entry.metrics[i])
+                .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), value| {
+                    (min.min(value), max.max(value))
+                })
+        })
+        .collect();
+    let is_best = |entry: &Entry| {
+        considered_metrics
+            .iter()
+            .zip(&ranges)
+            .any(|(&i, &(min, _))| Approx(entry.metrics[i]) == Approx(min))
+    };
+    let score = |entry: &Entry| -> f64 {
+        considered_metrics
+            .iter()
+            .zip(&ranges)
+            .filter(|(_, &(min, max))| max > min)
+            .map(|(&i, &(min, max))| (entry.metrics[i] - min) / (max - min))
+            .sum()
+    };
+
+    let worst_idx = set
+        .iter()
+        .enumerate()
+        .filter(|(_, entry)| !is_best(entry))
+        .map(|(idx, entry)| (idx, score(entry)))
+        .fold(
+            None,
+            |worst: Option<(usize, f64)>, (idx, score)| match worst {
+                Some((_, worst_score)) if worst_score >= score => worst,
+                _ => Some((idx, score)),
+            },
+        );
+    if let Some((idx, _)) = worst_idx {
+        set.remove(idx);
+    }
+}
+
+fn undirected_neighbours(graph: &Graph) -> Vec<Vec<NodeIdx>> {
+    let fwd_edges = graph.fwd_edges();
+    let mut neighbours = vec![Vec::new(); graph.nodes().count()];
+
+    for src_idx in graph.nodes().iter() {
+        for edge in fwd_edges.starting_from(src_idx) {
+            if edge.is_shortcut() || edge.dst_idx() == src_idx {
+                continue;
+            }
+            neighbours[*src_idx].push(edge.dst_idx());
+            neighbours[*edge.dst_idx()].push(src_idx);
+        }
+    }
+
+    for node_neighbours in neighbours.iter_mut() {
+        node_neighbours.sort_unstable();
+        node_neighbours.dedup();
+    }
+
+    neighbours
+}
+
+/// Orders the provided nodes recursively, where separators get the highest ranks.
+///
+/// The nodes are split at the median of latitude or longitude, whichever leads to the smaller separator.
+/// The separator is the boundary of one side.
+fn dissect(
+    nodes: Vec<NodeIdx>,
+    graph: &Graph,
+    neighbours: &[Vec<NodeIdx>],
+    part_ids: &mut [usize],
+    next_part_id: &mut usize,
+    order: &mut Vec<NodeIdx>,
+) {
+    if nodes.len() <= MAX_LEAF_SIZE {
+        order.extend(nodes);
+        return;
+    }
+
+    let axes: [fn(Coordinate) -> f64; 2] = [|coord| coord.lat, |coord| coord.lon];
+    let (parts, separator) = axes
+        .iter()
+        .map(|axis| {
+            bisect(
+                nodes.clone(),
+                *axis,
+                graph,
+                neighbours,
+                part_ids,
+                next_part_id,
+            )
+        })
+        .min_by_key(|(_parts, separator)| separator.len())
+        .expect("There should be two axes.");
+
+    let [part_0, part_1] = parts;
+    dissect(part_0, graph, neighbours, part_ids, next_part_id, order);
+    dissect(part_1, graph, neighbours, part_ids, next_part_id, order);
+    order.extend(separator);
+}
+
+/// Returns both parts (without separator) and the separator.
+fn bisect(
+    mut nodes: Vec<NodeIdx>,
+    axis: fn(Coordinate) -> f64,
+    graph: &Graph,
+    neighbours: &[Vec<NodeIdx>],
+    part_ids: &mut [usize],
+    next_part_id: &mut usize,
+) -> ([Vec<NodeIdx>; 2], Vec<NodeIdx>) {
+    let graph_nodes = graph.nodes();
+    let mid = nodes.len() / 2;
+    nodes.sort_unstable_by(|&a, &b| {
+        axis(graph_nodes.coord(a))
+            .partial_cmp(&axis(graph_nodes.coord(b)))
+            .expect("Coordinates should be comparable.")
+    });
+    let part_1 = nodes.split_off(mid);
+    let part_0 = nodes;
+
+    // Fresh part-ids are used to avoid resetting them.
+    let ids = [*next_part_id, *next_part_id + 1];
+    *next_part_id += 2;
+    for (part, &id) in [&part_0, &part_1].iter().zip(&ids) {
+        for node_idx in part.iter() {
+            part_ids[**node_idx] = id;
+        }
+    }
+
+    // boundaries of both parts
+    let boundaries: Vec<Vec<bool>> = [&part_0, &part_1]
+        .iter()
+        .zip(ids.iter().rev())
+        .map(|(part, &other_id)| {
+            part.iter()
+                .map(|node_idx| {
+                    neighbours[**node_idx]
+                        .iter()
+                        .any(|neighbour| part_ids[**neighbour] == other_id)
+                })
+                .collect()
+        })
+        .collect();
+    let boundary_sizes: Vec<usize> = boundaries
+        .iter()
+        .map(|boundary| boundary.iter().filter(|&&is_boundary| is_boundary).count())
+        .collect();
+
+    // use smaller boundary as separator
+    let sep_part = if boundary_sizes[0] <= boundary_sizes[1] {
+        0
+    } else {
+        1
+    };
+    let mut parts = [part_0, part_1];
+    let (separator, remaining): (Vec<_>, Vec<_>) = mem::take(&mut parts[sep_part])
+        .into_iter()
+        .zip(&boundaries[sep_part])
+        .partition(|(_node_idx, &is_boundary)| is_boundary);
+    parts[sep_part] = remaining
+        .into_iter()
+        .map(|(node_idx, _)| node_idx)
+        .collect();
+
+    (
+        parts,
+        separator
+            .into_iter()
+            .map(|(node_idx, _)| node_idx)
+            .collect(),
+    )
+}
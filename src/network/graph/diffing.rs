@@ -0,0 +1,345 @@
+use super::Graph;
+use crate::{approximating::Approx, configs::SimpleId, defaults::capacity::DimVec, helpers::err};
+use kissunits::geo::Coordinate;
+use log::info;
+use serde::Deserialize;
+use std::cmp::Ordering;
+
+/// Defines how edges of two graphs are matched (see `Graph::diff(...)`).
+#[derive(Copy, Clone, Debug, Deserialize, PartialEq, Eq)]
+pub enum EdgeMatching {
+    /// Edges are matched by their external edge-ids, which every edge needs.
+    EdgeIds,
+    /// Edges are matched by their src- and dst-ids.
+    /// Parallel edges are matched in the order of the graphs.
+    NodeIds,
+}
+
+/// Whether an element has been added, removed or changed in the new graph.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DiffKind {
+    Added,
+    Removed,
+    Changed,
+}
+
+impl DiffKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            DiffKind::Added => "added",
+            DiffKind::Removed => "removed",
+            DiffKind::Changed => "changed",
+        }
+    }
+}
+
+/// A node, which is only in one graph, or whose coordinate has changed.
+#[derive(Clone, Debug)]
+pub struct NodeDiff {
+    pub kind: DiffKind,
+    pub id: i64,
+    /// The coordinate in the new graph, except for removed nodes
+    pub coord: Coordinate,
+}
+
+/// An edge, which is only in one graph, or whose metrics have changed.
+#[derive(Clone, Debug)]
+pub struct EdgeDiff {
+    pub kind: DiffKind,
+    pub id: Option<usize>,
+    pub src_id: i64,
+    pub dst_id: i64,
+    /// The coordinates in the new graph, except for removed edges
+    pub src_coord: Coordinate,
+    pub dst_coord: Coordinate,
+    /// Metrics (in the order of `GraphDiff::metric_ids`) in the old graph, None for added edges
+    pub old_metrics: Option<DimVec<f64>>,
+    /// Metrics (in the order of `GraphDiff::metric_ids`) in the new graph, None for removed edges
+    pub new_metrics: Option<DimVec<f64>>,
+}
+
+impl EdgeDiff {
+    /// New minus old metrics, which is None for added or removed edges.
+    pub fn deltas(&self) -> Option<DimVec<f64>> {
+        match (&self.old_metrics, &self.new_metrics) {
+            (Some(old_metrics), Some(new_metrics)) => Some(
+                old_metrics
+                    .iter()
+                    .zip(new_metrics.iter())
+                    .map(|(old_value, new_value)| new_value - old_value)
+                    .collect(),
+            ),
+            _ => None,
+        }
+    }
+}
+
+/// Differences from an old to a new graph, e.g. of two osm-extracts or two balancer-iterations (see `Graph::diff(...)`).
+///
+/// Nodes and edges are sorted by their ids.
+/// The diff owns its data, so it can be written without the graphs (see `io::diff::Writer`).
+#[derive(Clone, Debug)]
+pub struct GraphDiff {
+    /// Ids of the metrics both graphs have, in the order of the old graph
+    pub metric_ids: Vec<SimpleId>,
+    pub nodes: Vec<NodeDiff>,
+    pub edges: Vec<EdgeDiff>,
+}
+
+impl GraphDiff {
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty() && self.edges.is_empty()
+    }
+
+    pub fn node_count(&self, kind: DiffKind) -> usize {
+        self.nodes.iter().filter(|node| node.kind == kind).count()
+    }
+
+    pub fn edge_count(&self, kind: DiffKind) -> usize {
+        self.edges.iter().filter(|edge| edge.kind == kind).count()
+    }
+}
+
+/// Sorting-key of matched edges
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum EdgeKey {
+    Id(usize),
+    /// src-id, dst-id and occurrence of parallel edges
+    NodeIds(i64, i64, usize),
+}
+
+struct KeyedEdge {
+    key: EdgeKey,
+    id: Option<usize>,
+    src_id: i64,
+    dst_id: i64,
+    src_coord: Coordinate,
+    dst_coord: Coordinate,
+    metrics: DimVec<f64>,
+}
+
+/// Comparing two graphs
+impl Graph {
+    /// Compares this (old) graph with the new graph.
+    ///
+    /// Nodes are matched by their ids, edges according to the edge-matching.
+    /// Shortcuts, removed nodes and removed edges are ignored.
+    /// Metrics are compared (approximately) by their ids, so metrics of only one graph are ignored.
+    /// Values are compared as stored, hence normalized graphs should have the same means.
+    pub fn diff(&self, new: &Graph, edge_matching: EdgeMatching) -> err::Result<GraphDiff> {
+        info!(
+            "START Diff graphs by matching edges by {:?}.",
+            edge_matching
+        );
+
+        // metrics of both graphs
+        let mut metric_ids = Vec::new();
+        let mut old_metric_idxs = Vec::new();
+        let mut new_metric_idxs = Vec::new();
+        for (old_idx, id) in self.cfg().edges.metrics.ids.iter().enumerate() {
+            if let Ok(new_idx) = new.cfg().edges.metrics.try_idx_of(id) {
+                metric_ids.push(id.clone());
+                old_metric_idxs.push(old_idx);
+                new_metric_idxs.push(*new_idx);
+            } else {
+                info!(
+                    "Metric {} is ignored, because the new graph doesn't have it.",
+                    id
+                );
+            }
+        }
+
+        // nodes
+
+        let mut nodes = Vec::new();
+        let (old_nodes, new_nodes) = (self.nodes(), new.nodes());
+        for old_idx in old_nodes.iter() {
+            if self.is_removed_node(old_idx) {
+                continue;
+            }
+            let id = old_nodes.id(old_idx);
+            let old_coord = old_nodes.coord(old_idx);
+            match new_nodes.idx_from(id) {
+                Ok(new_idx) if !new.is_removed_node(new_idx) => {
+                    let new_coord = new_nodes.coord(new_idx);
+                    if Approx(old_coord.lat) != Approx(new_coord.lat)
+                        || Approx(old_coord.lon) != Approx(new_coord.lon)
+                    {
+                        nodes.push(NodeDiff {
+                            kind: DiffKind::Changed,
+                            id,
+                            coord: new_coord,
+                        });
+                    }
+                }
+                _ => nodes.push(NodeDiff {
+                    kind: DiffKind::Removed,
+                    id,
+                    coord: old_coord,
+                }),
+            }
+        }
+        for new_idx in new_nodes.iter() {
+            if new.is_removed_node(new_idx) {
+                continue;
+            }
+            let id = new_nodes.id(new_idx);
+            let is_added = match old_nodes.idx_from(id) {
+                Ok(old_idx) => self.is_removed_node(old_idx),
+                Err(_) => true,
+            };
+            if is_added {
+                nodes.push(NodeDiff {
+                    kind: DiffKind::Added,
+                    id,
+                    coord: new_nodes.coord(new_idx),
+                });
+            }
+        }
+        nodes.sort_by_key(|node| node.id);
+
+        // edges, merged by their sorted keys
+
+        let old_edges = keyed_edges(self, edge_matching, &old_metric_idxs)?;
+        let new_edges = keyed_edges(new, edge_matching, &new_metric_idxs)?;
+        let mut edges = Vec::new();
+        let mut old_edges = old_edges.into_iter().peekable();
+        let mut new_edges = new_edges.into_iter().peekable();
+        loop {
+            let ordering = match (old_edges.peek(), new_edges.peek()) {
+                (Some(old_edge), Some(new_edge)) => old_edge.key.cmp(&new_edge.key),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => break,
+            };
+            match ordering {
+                Ordering::Less => {
+                    let old_edge = old_edges.next().expect("Old edge has been peeked.");
+                    let old_metrics = Some(old_edge.metrics.clone());
+                    edges.push(edge_diff(DiffKind::Removed, &old_edge, old_metrics, None));
+                }
+                Ordering::Greater => {
+                    let new_edge = new_edges.next().expect("New edge has been peeked.");
+                    let new_metrics = Some(new_edge.metrics.clone());
+                    edges.push(edge_diff(DiffKind::Added, &new_edge, None, new_metrics));
+                }
+                Ordering::Equal => {
+                    let old_edge = old_edges.next().expect("Old edge has been peeked.");
+                    let new_edge = new_edges.next().expect("New edge has been peeked.");
+                    let is_changed =
+                        old_edge.src_id != new_edge.src_id
+                            || old_edge.dst_id != new_edge.dst_id
+                            || old_edge.metrics.iter().zip(new_edge.metrics.iter()).any(
+                                |(old_value, new_value)| Approx(old_value) != Approx(new_value),
+                            );
+                    if is_changed {
+                        edges.push(edge_diff(
+                            DiffKind::Changed,
+                            &new_edge,
+                            Some(old_edge.metrics),
+                            Some(new_edge.metrics.clone()),
+                        ));
+                    }
+                }
+            }
+        }
+
+        let diff = GraphDiff {
+            metric_ids,
+            nodes,
+            edges,
+        };
+        info!(
+            "FINISHED Nodes: {} added, {} removed, {} changed. Edges: {} added, {} removed, {} changed.",
+            diff.node_count(DiffKind::Added),
+            diff.node_count(DiffKind::Removed),
+            diff.node_count(DiffKind::Changed),
+            diff.edge_count(DiffKind::Added),
+            diff.edge_count(DiffKind::Removed),
+            diff.edge_count(DiffKind::Changed),
+        );
+        Ok(diff)
+    }
+}
+
+/// The edge's ids and coordinates are taken from the given (preferably new) edge.
+fn edge_diff(
+    kind: DiffKind,
+    edge: &KeyedEdge,
+    old_metrics: Option<DimVec<f64>>,
+    new_metrics: Option<DimVec<f64>>,
+) -> EdgeDiff {
+    EdgeDiff {
+        kind,
+        id: edge.id,
+        src_id: edge.src_id,
+        dst_id: edge.dst_id,
+        src_coord: edge.src_coord,
+        dst_coord: edge.dst_coord,
+        old_metrics,
+        new_metrics,
+    }
+}
+
+/// Returns the graph's original edges sorted by their keys.
+fn keyed_edges(
+    graph: &Graph,
+    edge_matching: EdgeMatching,
+    metric_idxs: &[usize],
+) -> err::Result<Vec<KeyedEdge>> {
+    let nodes = graph.nodes();
+    let fwd_edges = graph.fwd_edges();
+
+    let mut keyed_edges = Vec::new();
+    for src_idx in nodes.iter() {
+        for edge in fwd_edges.starting_from(src_idx) {
+            if edge.is_shortcut() {
+                continue;
+            }
+
+            let id = fwd_edges.try_id(edge.idx());
+            let (src_id, dst_id) = (nodes.id(src_idx), nodes.id(edge.dst_idx()));
+            let key = match edge_matching {
+                EdgeMatching::EdgeIds => match id {
+                    Some(id) => EdgeKey::Id(id),
+                    None => {
+                        return Err(format!(
+                            "Edge from node {} to node {} has no id, but edges are matched by ids.",
+                            src_id, dst_id
+                        )
+                        .into())
+                    }
+                },
+                // occurrences are set after sorting
+                EdgeMatching::NodeIds => EdgeKey::NodeIds(src_id, dst_id, 0),
+            };
+            let metrics = edge.metrics();
+            keyed_edges.push(KeyedEdge {
+                key,
+                id,
+                src_id,
+                dst_id,
+                src_coord: nodes.coord(src_idx),
+                dst_coord: nodes.coord(edge.dst_idx()),
+                metrics: metric_idxs.iter().map(|&i| metrics[i]).collect(),
+            });
+        }
+    }
+
+    // stable, so parallel edges keep the graph's order
+    keyed_edges.sort_by_key(|edge| edge.key);
+    for i in 1..keyed_edges.len() {
+        match (keyed_edges[i - 1].key, keyed_edges[i].key) {
+            (EdgeKey::Id(prev_id), EdgeKey::Id(id)) if prev_id == id => {
+                return Err(format!("Edge-id {} is not unique.", id).into());
+            }
+            (EdgeKey::NodeIds(prev_src, prev_dst, k), EdgeKey::NodeIds(src, dst, _))
+                if (prev_src, prev_dst) == (src, dst) =>
+            {
+                keyed_edges[i].key = EdgeKey::NodeIds(src, dst, k + 1);
+            }
+            _ => (),
+        }
+    }
+    Ok(keyed_edges)
+}
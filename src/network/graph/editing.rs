@@ -0,0 +1,409 @@
+use super::{
+    building::{GraphBuilder, ProtoEdge, ProtoNode, ProtoShortcut},
+    updating::{EdgeRef, MetricUpdate},
+    EdgeIdx, Graph, NodeIdx,
+};
+use crate::{
+    defaults::{self, capacity::DimVec},
+    helpers::err,
+};
+use kissunits::geo::Coordinate;
+use log::info;
+use std::collections::{HashMap, HashSet};
+
+/// Structural changes of a finalized graph, which are not part of its offset-arrays.
+///
+/// Added nodes and edges are appended to the graph's arrays, hence they get the next free indices.
+/// Since the offset-arrays can't be extended, added edges are remembered per node and chained to the edges of the offset-arrays, when accessing a node's edges.
+/// Removed nodes and edges keep their indices, but are skipped when accessing a node's edges.
+#[derive(Debug, Default)]
+pub struct GraphDelta {
+    // ids of added nodes, because they are not sorted into the graph's node-ids
+    added_node_ids: HashMap<i64, NodeIdx>,
+    // added edges per src-idx and per dst-idx
+    fwd_edges: HashMap<NodeIdx, Vec<EdgeIdx>>,
+    bwd_edges: HashMap<NodeIdx, Vec<EdgeIdx>>,
+    removed_nodes: HashSet<NodeIdx>,
+    removed_edges: HashSet<EdgeIdx>,
+}
+
+impl GraphDelta {
+    pub(super) fn added_node_ids(&self) -> &HashMap<i64, NodeIdx> {
+        &self.added_node_ids
+    }
+
+    pub(super) fn fwd_edges(&self) -> &HashMap<NodeIdx, Vec<EdgeIdx>> {
+        &self.fwd_edges
+    }
+
+    pub(super) fn bwd_edges(&self) -> &HashMap<NodeIdx, Vec<EdgeIdx>> {
+        &self.bwd_edges
+    }
+
+    pub(super) fn removed_edges(&self) -> &HashSet<EdgeIdx> {
+        &self.removed_edges
+    }
+}
+
+/// Structural editing, e.g. for planning scenarios like closed bridges or new bypass roads.
+///
+/// Edits are stored as delta on top of the offset-arrays, which is transparent to the accessors, e.g. `EdgeAccessor::starting_from(...)`.
+/// Hence routing works on an edited graph without rebuilding it.
+/// Since edits would invalidate the contraction, graphs with shortcuts can't be edited.
+///
+/// Removed nodes and edges keep their indices until the graph is compacted (see `Graph::compact()`), so iterating over all indices still returns them.
+impl Graph {
+    /// Adds a node without any edges.
+    /// The node's id has to be new, also wrt removed nodes.
+    pub fn add_node(&mut self, id: i64, coord: Coordinate) -> err::Result<NodeIdx> {
+        self.check_editability()?;
+        if self.nodes().idx_from(id).is_ok() {
+            return Err(format!("The graph contains already a node with id {}.", id).into());
+        }
+
+        let idx = NodeIdx(self.node_ids.len());
+        self.node_ids.push(id);
+        self.node_coords.push(coord);
+        self.node_ch_levels.push(defaults::network::nodes::LEVEL);
+        self.delta
+            .get_or_insert_with(GraphDelta::default)
+            .added_node_ids
+            .insert(id, idx);
+        Ok(idx)
+    }
+
+    /// Adds an edge from src to dst.
+    ///
+    /// The metrics are given in the graph's units (see `cfg.edges.metrics`) and are normalized by the (unchanged) means, if the graph is normalized.
+    /// If an id is given, it has to be new.
+    pub fn add_edge(
+        &mut self,
+        id: Option<usize>,
+        src_idx: NodeIdx,
+        dst_idx: NodeIdx,
+        metrics: DimVec<f64>,
+    ) -> err::Result<EdgeIdx> {
+        self.check_editability()?;
+        for &node_idx in &[src_idx, dst_idx] {
+            if *node_idx >= self.nodes().count() || self.is_removed_node(node_idx) {
+                return Err(format!(
+                    "The node-idx {} is not part of the graph's nodes.",
+                    node_idx
+                )
+                .into());
+            }
+        }
+        if metrics.len() != self.metrics().dim() {
+            return Err(format!(
+                "The new edge has {} metrics, but the graph's metrics have dimension {}.",
+                metrics.len(),
+                self.metrics().dim()
+            )
+            .into());
+        }
+        if let Some(value) = metrics
+            .iter()
+            .find(|value| !value.is_finite() || **value < 0.0)
+        {
+            return Err(format!(
+                "The new edge's metric {} has to be finite and non-negative.",
+                value
+            )
+            .into());
+        }
+        let metrics: DimVec<f64> = match &self.means {
+            Some(means) => metrics
+                .iter()
+                .zip(means)
+                .map(|(value, mean)| value / mean)
+                .collect(),
+            None => metrics,
+        };
+        if let Some((values, value)) = self
+            .metrics
+            .iter()
+            .zip(&metrics)
+            .find(|(values, value)| !values.can_store(**value))
+        {
+            return Err(format!(
+                "The new edge's metric {} can't be stored with precision {:?}.",
+                value,
+                values.precision()
+            )
+            .into());
+        }
+        let id_pos = match id {
+            Some(id) => match self
+                .edge_ids_to_idx_map
+                .binary_search_by_key(&id, |(edge_id, _edge_idx)| *edge_id)
+            {
+                Ok(_) => {
+                    return Err(
+                        format!("The graph contains already an edge with id {}.", id).into(),
+                    )
+                }
+                Err(pos) => Some(pos),
+            },
+            None => None,
+        };
+
+        let idx = EdgeIdx(self.fwd_dsts.len());
+        self.fwd_dsts.push(dst_idx);
+        self.bwd_dsts.push(src_idx);
+        for (values, metric) in self.metrics.iter_mut().zip(metrics) {
+            values.try_push(metric)?;
+        }
+        self.edge_ids.push(id);
+        if let (Some(id), Some(pos)) = (id, id_pos) {
+            self.edge_ids_to_idx_map.insert(pos, (id, idx));
+        }
+        // streets are only stored, if any exists
+        if !self.street_idxs.is_empty() {
+            self.street_idxs.push(None);
+        }
+        // no shortcut
+        let sc_offset = *self
+            .sc_offsets
+            .last()
+            .expect("Shortcut-offsets should end with the number of shortcuts.");
+        self.sc_offsets.push(sc_offset);
+
+        let delta = self.delta.get_or_insert_with(GraphDelta::default);
+        delta.fwd_edges.entry(src_idx).or_default().push(idx);
+        delta.bwd_edges.entry(dst_idx).or_default().push(idx);
+        Ok(idx)
+    }
+
+    /// Removes the edge, so it isn't leaving or entering any node anymore.
+    /// The edge's id can't be used for accessing it anymore.
+    pub fn remove_edge(&mut self, edge: EdgeRef) -> err::Result<()> {
+        self.check_editability()?;
+        let idx = self.try_edge_idx_of(edge)?;
+        if self.is_removed_edge(idx) {
+            return Err(format!("The edge (idx: {}) has already been removed.", idx).into());
+        }
+
+        if let Some(id) = self.edge_ids[*idx] {
+            if let Ok(pos) = self
+                .edge_ids_to_idx_map
+                .binary_search_by_key(&id, |(edge_id, _edge_idx)| *edge_id)
+            {
+                self.edge_ids_to_idx_map.remove(pos);
+            }
+        }
+        self.delta
+            .get_or_insert_with(GraphDelta::default)
+            .removed_edges
+            .insert(idx);
+        Ok(())
+    }
+
+    /// Removes the node and all of its leaving and entering edges.
+    /// Returns the number of removed edges.
+    pub fn remove_node(&mut self, idx: NodeIdx) -> err::Result<usize> {
+        self.check_editability()?;
+        if *idx >= self.nodes().count() || self.is_removed_node(idx) {
+            return Err(format!("The node-idx {} is not part of the graph's nodes.", idx).into());
+        }
+
+        let mut edge_idxs: Vec<_> = self
+            .fwd_edges()
+            .starting_from(idx)
+            .chain(self.bwd_edges().starting_from(idx))
+            .map(|edge| edge.idx())
+            .collect();
+        // self-loops are leaving and entering
+        edge_idxs.sort();
+        edge_idxs.dedup();
+        for &edge_idx in &edge_idxs {
+            self.remove_edge(EdgeRef::Idx(edge_idx))?;
+        }
+        self.delta
+            .get_or_insert_with(GraphDelta::default)
+            .removed_nodes
+            .insert(idx);
+        Ok(edge_idxs.len())
+    }
+
+    /// Returns true if nodes or edges have been added or removed since finalizing.
+    pub fn is_edited(&self) -> bool {
+        self.delta.is_some()
+    }
+
+    pub fn is_removed_node(&self, idx: NodeIdx) -> bool {
+        match &self.delta {
+            Some(delta) => delta.removed_nodes.contains(&idx),
+            None => false,
+        }
+    }
+
+    pub fn is_removed_edge(&self, idx: EdgeIdx) -> bool {
+        match &self.delta {
+            Some(delta) => delta.removed_edges.contains(&idx),
+            None => false,
+        }
+    }
+
+    /// Folds all edits into a fresh graph with new offset-arrays, hence indices may change.
+    ///
+    /// Removed nodes and edges are dropped, as well as nodes without any edges (like when parsing).
+    /// Streets, osm-ways, means and closures (see `Graph::update_metrics(...)`) are kept.
+    pub fn compact(&self) -> err::Result<Graph> {
+        self.check_editability()?;
+        info!(
+            "START Compact graph with {} nodes and {} edges.",
+            self.nodes().count(),
+            self.fwd_edges().count()
+        );
+        let graph = self.rebuild(
+            &vec![true; self.nodes().count()],
+            &vec![true; self.fwd_edges().count()],
+        )?;
+        info!(
+            "FINISHED Compacted graph has {} nodes and {} edges.",
+            graph.nodes().count(),
+            graph.fwd_edges().count()
+        );
+        Ok(graph)
+    }
+
+    /// Builds a fresh graph from the kept nodes and edges (indexed by their idx), hence indices may change.
+    ///
+    /// Removed nodes and edges are dropped, as well as nodes without any kept edges (like when parsing).
+    /// Kept shortcuts need their sc-edges to be kept.
+    /// Streets, osm-ways, means and closures (see `Graph::update_metrics(...)`) are kept.
+    pub(super) fn rebuild(
+        &self,
+        is_kept_node: &[bool],
+        is_kept_edge: &[bool],
+    ) -> err::Result<Graph> {
+        // The graph's metrics are already normalized (if wanted),
+        // so normalizing them again would destroy the means.
+        let mut builder = {
+            let mut cfg = self.cfg().clone();
+            cfg.edges.metrics.are_normalized = false;
+            GraphBuilder::new(cfg)
+        };
+        // closed edges are inserted with their original metrics and closed again afterwards
+        let mut closed_edges = Vec::new();
+
+        let nodes = self.nodes();
+        let fwd_edges = self.fwd_edges();
+        let is_kept =
+            |src_idx: NodeIdx, edge_idx: EdgeIdx| is_kept_node[*src_idx] && is_kept_edge[*edge_idx];
+
+        // shortcuts refer to the order of insertion
+        let mut new_idxs = vec![None; fwd_edges.count()];
+        let mut edge_count = 0;
+        for src_idx in nodes.iter() {
+            for edge in fwd_edges.starting_from(src_idx) {
+                if is_kept(src_idx, edge.idx()) {
+                    new_idxs[*edge.idx()] = Some(EdgeIdx(edge_count));
+                    edge_count += 1;
+                }
+            }
+        }
+
+        for src_idx in nodes.iter() {
+            for edge in fwd_edges.starting_from(src_idx) {
+                if !is_kept(src_idx, edge.idx()) {
+                    continue;
+                }
+
+                let street_idx = self
+                    .streets()
+                    .of(edge.idx())
+                    .map(|street| builder.insert_street(street.clone()));
+                let proto_edge = ProtoEdge {
+                    id: fwd_edges.try_id(edge.idx()),
+                    src_id: nodes.id(src_idx),
+                    dst_id: nodes.id(edge.dst_idx()),
+                    metrics: match self.closed_edges.get(&edge.idx()) {
+                        Some(metrics) => metrics.clone(),
+                        None => edge.metrics(),
+                    },
+                    street_idx,
+                };
+                if self.is_closed(edge.idx()) {
+                    closed_edges.push((
+                        proto_edge.id,
+                        proto_edge.src_id,
+                        proto_edge.dst_id,
+                        proto_edge.metrics.clone(),
+                    ));
+                }
+                let sc_edges = match edge.sc_edges() {
+                    Some(&[sc_edge_0, sc_edge_1]) => {
+                        match (new_idxs[*sc_edge_0], new_idxs[*sc_edge_1]) {
+                            (Some(sc_edge_0), Some(sc_edge_1)) => Some([sc_edge_0, sc_edge_1]),
+                            _ => {
+                                return Err(format!(
+                                    "The shortcut (idx: {}) is kept without its sc-edges.",
+                                    edge.idx()
+                                )
+                                .into())
+                            }
+                        }
+                    }
+                    None => None,
+                };
+                builder.insert(ProtoShortcut {
+                    proto_edge,
+                    sc_edges,
+                })?;
+            }
+        }
+
+        let mut builder = builder.next();
+        for node_idx in nodes.iter() {
+            if self.is_removed_node(node_idx) || !is_kept_node[*node_idx] {
+                continue;
+            }
+            builder.insert(ProtoNode {
+                id: nodes.id(node_idx),
+                coord: nodes.coord(node_idx),
+                ch_level: Some(nodes.level(node_idx)),
+            });
+        }
+        let mut graph = builder.next()?.finalize()?;
+        graph.cfg = self.cfg.clone();
+        graph.means = self.means.clone();
+
+        // close edges again
+        let mut updates = Vec::with_capacity(closed_edges.len());
+        {
+            let nodes = graph.nodes();
+            let fwd_edges = graph.fwd_edges();
+            for (id, src_id, dst_id, metrics) in closed_edges {
+                let (src_idx, dst_idx) = match (nodes.idx_from(src_id), nodes.idx_from(dst_id)) {
+                    (Ok(src_idx), Ok(dst_idx)) => (src_idx, dst_idx),
+                    _ => continue,
+                };
+                if let Some(edge) = fwd_edges.starting_from(src_idx).find(|edge| {
+                    edge.dst_idx() == dst_idx
+                        && fwd_edges.try_id(edge.idx()) == id
+                        && edge.metrics() == metrics
+                }) {
+                    updates.push(MetricUpdate::Close {
+                        edge: EdgeRef::Idx(edge.idx()),
+                    });
+                }
+            }
+        }
+        if !updates.is_empty() {
+            graph.update_metrics(&updates)?;
+        }
+
+        Ok(graph)
+    }
+
+    fn check_editability(&self) -> err::Feedback {
+        if self.sc_edges.is_empty() {
+            Ok(())
+        } else {
+            Err(err::Msg::from(
+                "The graph contains shortcuts, which would be invalidated by structural edits.",
+            ))
+        }
+    }
+}
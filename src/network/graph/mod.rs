@@ -1,10 +1,31 @@
+mod analyzing;
 pub mod building;
+pub mod cch;
+mod diffing;
+mod editing;
 mod indexing;
+mod ordering;
+mod spilling;
+mod storing;
+mod subgraphing;
+pub mod updating;
+pub use analyzing::{GraphStats, MetricStats};
+pub use diffing::{DiffKind, EdgeDiff, EdgeMatching, GraphDiff, NodeDiff};
+use editing::GraphDelta;
 pub use indexing::{EdgeIdx, EdgeIdxIterator, MetricIdx, NodeIdx, NodeIdxIterator};
-
-use crate::{configs::parsing::Config, defaults::capacity::DimVec, helpers::err};
+pub use ordering::NodeOrdering;
+pub use storing::MetricValues;
+pub use subgraphing::SubgraphPredicate;
+
+use crate::{
+    configs::parsing::{edges::ways, Config},
+    defaults::capacity::DimVec,
+    helpers::err,
+    network::Street,
+};
 use kissunits::geo::Coordinate;
 use std::{
+    collections::{HashMap, HashSet},
     fmt,
     fmt::Display,
     iter::Iterator,
@@ -78,8 +99,10 @@ use std::{
 #[derive(Debug)]
 pub struct Graph {
     cfg: Config,
-    // nodes, ids sorted
+    // nodes, ids sorted (except for added nodes) if the nodes are ordered by id (see `cfg.node_ordering`)
     node_ids: Vec<i64>,
+    // mapping from id to NodeIdx, sorted by id, empty if the node-ids are sorted
+    node_ids_to_idx_map: Vec<(i64, NodeIdx)>,
     // node-metrics
     node_coords: Vec<Coordinate>,
     node_ch_levels: Vec<usize>,
@@ -91,12 +114,20 @@ pub struct Graph {
     bwd_dsts: Vec<NodeIdx>,
     bwd_offsets: Vec<usize>,
     bwd_to_fwd_map: Vec<EdgeIdx>,
-    // edge-metrics (sorted according to fwd_dsts)
-    metrics: Vec<DimVec<f64>>,
+    // edge-metrics, one array per metric (each sorted according to fwd_dsts)
+    // stored with the metric's precision
+    metrics: Vec<MetricValues>,
     means: Option<DimVec<f64>>,
     // mapping from id to EdgeIdx, sorted by id
     edge_ids: Vec<Option<usize>>,
     edge_ids_to_idx_map: Vec<(usize, EdgeIdx)>,
+    // streets (stored once) and their indices per edge, empty if no streets are kept
+    streets: Vec<Street>,
+    street_idxs: Vec<Option<usize>>,
+    // closed edges and their metrics before closing them (see `Graph::update_metrics(...)`)
+    closed_edges: HashMap<EdgeIdx, DimVec<f64>>,
+    // added and removed nodes and edges (see `Graph::add_edge(...)` and others)
+    delta: Option<GraphDelta>,
     // shortcuts (contraction-hierarchies)
     sc_offsets: Vec<usize>,
     sc_edges: Vec<[EdgeIdx; 2]>,
@@ -111,8 +142,10 @@ impl Graph {
     pub fn nodes<'a>(&'a self) -> NodeAccessor<'a> {
         NodeAccessor {
             node_ids: &self.node_ids,
+            node_ids_to_idx_map: &self.node_ids_to_idx_map,
             node_coords: &self.node_coords,
             node_ch_levels: &self.node_ch_levels,
+            added_node_ids: self.delta.as_ref().map(GraphDelta::added_node_ids),
         }
     }
 
@@ -123,6 +156,8 @@ impl Graph {
             edge_dsts: &self.fwd_dsts,
             offsets: &self.fwd_offsets,
             xwd_to_fwd_map: &self.fwd_to_fwd_map,
+            added_edges: self.delta.as_ref().map(GraphDelta::fwd_edges),
+            removed_edges: self.delta.as_ref().map(GraphDelta::removed_edges),
             metrics: self.metrics(),
             sc_offsets: &self.sc_offsets,
             sc_edges: &self.sc_edges,
@@ -136,12 +171,22 @@ impl Graph {
             edge_dsts: &(self.bwd_dsts),
             offsets: &(self.bwd_offsets),
             xwd_to_fwd_map: &(self.bwd_to_fwd_map),
+            added_edges: self.delta.as_ref().map(GraphDelta::bwd_edges),
+            removed_edges: self.delta.as_ref().map(GraphDelta::removed_edges),
             metrics: self.metrics(),
             sc_offsets: &self.sc_offsets,
             sc_edges: &self.sc_edges,
         }
     }
 
+    pub fn streets<'a>(&'a self) -> StreetAccessor<'a> {
+        StreetAccessor {
+            cfg: self.cfg.edges.ways.as_ref(),
+            streets: &self.streets,
+            street_idxs: &self.street_idxs,
+        }
+    }
+
     pub fn metrics<'a>(&'a self) -> MetricAccessor<'a> {
         MetricAccessor {
             cfg: &self.cfg,
@@ -399,8 +444,13 @@ impl<'a> HalfEdge<'a> {
         self.edge_accessor.sc_edges(self.idx)
     }
 
-    pub fn metrics(&self) -> &DimVec<f64> {
-        &self.edge_accessor.metrics[self.idx]
+    pub fn metrics(&self) -> DimVec<f64> {
+        self.edge_accessor.metrics.get(self.idx)
+    }
+
+    /// Weights the edge's metrics with the given alphas (see `MetricAccessor::dot_product(...)`).
+    pub fn dot_product(&self, alphas: &[f64]) -> f64 {
+        self.edge_accessor.metrics.dot_product(alphas, self.idx)
     }
 }
 
@@ -417,7 +467,7 @@ impl<'a> Display for HalfEdge<'a> {
         write!(
             f,
             "{{ (src)-{:?}->(idx: {}) }}",
-            self.edge_accessor.metrics[self.idx],
+            self.edge_accessor.metrics.get(self.idx),
             self.dst_idx(),
         )
     }
@@ -428,8 +478,11 @@ impl<'a> Display for HalfEdge<'a> {
 #[derive(Debug)]
 pub struct NodeAccessor<'a> {
     node_ids: &'a Vec<i64>,
+    node_ids_to_idx_map: &'a Vec<(i64, NodeIdx)>,
     node_coords: &'a Vec<Coordinate>,
     node_ch_levels: &'a Vec<usize>,
+    // added nodes are not sorted into node-ids
+    added_node_ids: Option<&'a HashMap<i64, NodeIdx>>,
 }
 
 impl IntoIterator for NodeAccessor<'_> {
@@ -472,7 +525,29 @@ impl<'a> NodeAccessor<'a> {
     }
 
     pub fn idx_from(&self, id: i64) -> Result<NodeIdx, NodeIdx> {
-        match self.node_ids.binary_search(&id) {
+        // added nodes are appended, hence only the other node-ids are sorted
+        let sorted_count = match self.added_node_ids {
+            Some(added_node_ids) => {
+                if let Some(&idx) = added_node_ids.get(&id) {
+                    return Ok(idx);
+                }
+                self.node_ids.len() - added_node_ids.len()
+            }
+            None => self.node_ids.len(),
+        };
+
+        // nodes are reordered, hence the ids are sorted in the mapping
+        if !self.node_ids_to_idx_map.is_empty() {
+            return match self
+                .node_ids_to_idx_map
+                .binary_search_by_key(&id, |&(id, _idx)| id)
+            {
+                Ok(pos) => Ok(self.node_ids_to_idx_map[pos].1),
+                Err(pos) => Err(NodeIdx(pos)),
+            };
+        }
+
+        match self.node_ids[..sorted_count].binary_search(&id) {
             Ok(idx) => Ok(NodeIdx(idx)),
             Err(idx) => Err(NodeIdx(idx)),
         }
@@ -499,6 +574,62 @@ impl<'a> NodeAccessor<'a> {
     }
 }
 
+/// A shallow container for accessing the edges' streets (see `network::Street`).
+/// Graphs parsed without streets (e.g. from fmi-files) return `None` for every edge.
+#[derive(Debug)]
+pub struct StreetAccessor<'a> {
+    cfg: Option<&'a ways::Config>,
+    streets: &'a Vec<Street>,
+    street_idxs: &'a Vec<Option<usize>>,
+}
+
+impl<'a> StreetAccessor<'a> {
+    pub fn is_empty(&self) -> bool {
+        self.streets.is_empty()
+    }
+
+    /// Number of distinct streets
+    pub fn count(&self) -> usize {
+        self.streets.len()
+    }
+
+    /// Edges of equal streets return the same street-idx.
+    pub fn street_idx(&self, edge_idx: EdgeIdx) -> Option<usize> {
+        *self.street_idxs.get(*edge_idx)?
+    }
+
+    pub fn of(&self, edge_idx: EdgeIdx) -> Option<&'a Street> {
+        Some(&self.streets[self.street_idx(edge_idx)?])
+    }
+
+    /// The osm-id of the edge's way, if osm-ways are kept (`edges.ways`)
+    pub fn way_id(&self, edge_idx: EdgeIdx) -> Option<i64> {
+        self.of(edge_idx)?.way_id
+    }
+
+    /// The value of the edge's way-tag, if the key is configured in `edges.ways.tags`.
+    pub fn tag<S>(&self, edge_idx: EdgeIdx, key: S) -> Option<&'a str>
+    where
+        S: AsRef<str>,
+    {
+        let tag_idx = self.cfg?.tag_idx_of(key)?;
+        self.of(edge_idx)?.tags.get(tag_idx)?.as_deref()
+    }
+
+    /// All edges, which have been created from the osm-way of the given id.
+    pub fn edges_of_way(&self, way_id: i64) -> Vec<EdgeIdx> {
+        self.street_idxs
+            .iter()
+            .enumerate()
+            .filter(|(_, street_idx)| match street_idx {
+                Some(street_idx) => self.streets[*street_idx].way_id == Some(way_id),
+                None => false,
+            })
+            .map(|(edge_idx, _)| EdgeIdx(edge_idx))
+            .collect()
+    }
+}
+
 /// A shallow container for accessing edges.
 /// Shallow means that it does only contain references to the graph's data-arrays.
 #[derive(Debug)]
@@ -509,6 +640,9 @@ pub struct EdgeAccessor<'a> {
     offsets: &'a Vec<usize>,
     // indirect mapping to save memory
     xwd_to_fwd_map: &'a Vec<EdgeIdx>,
+    // edges added or removed after finalizing (see `Graph::add_edge(...)`)
+    added_edges: Option<&'a HashMap<NodeIdx, Vec<EdgeIdx>>>,
+    removed_edges: Option<&'a HashSet<EdgeIdx>>,
     metrics: MetricAccessor<'a>,
     // shortcuts
     sc_offsets: &'a Vec<usize>,
@@ -624,20 +758,38 @@ impl<'a> EdgeAccessor<'a> {
 
     fn offset_indices(&'a self, idx: NodeIdx) -> impl Iterator<Item = EdgeIdx> + 'a {
         // Use offset-array to get indices for the graph's edges belonging to the given node
-        // (idx + 1) guaranteed by offset-array-length
+        // (idx + 1) guaranteed by offset-array-length, except for nodes added after finalizing
         // i0 <= i1 <-> node has 0 or more leaving edges
-        (self.offsets[*idx]..self.offsets[*idx + 1])
+        let offset_range = match self.offsets.get(*idx + 1) {
+            Some(&i1) => self.offsets[*idx]..i1,
+            None => 0..0,
+        };
+        // edges added after finalizing are not part of the offset-array
+        let added_indices = self
+            .added_edges
+            .and_then(|added_edges| added_edges.get(&idx))
             .into_iter()
+            .flatten()
+            .copied();
+        offset_range
             .map(move |i| self.xwd_to_fwd_map[i])
+            .chain(added_indices)
+            .filter(move |edge_idx| match self.removed_edges {
+                Some(removed_edges) => !removed_edges.contains(edge_idx),
+                None => true,
+            })
     }
 }
 
 /// A shallow container for accessing metrics.
 /// Shallow means that it does only contain references to the graph's data-arrays.
+///
+/// Metrics are stored as one array per metric (struct-of-arrays), hence indexing by metric-idx returns the values of all edges.
+/// Every metric is stored with its precision (see `cfg.edges.metrics.precisions`), but values are always returned as `f64`.
 #[derive(Debug)]
 pub struct MetricAccessor<'a> {
     cfg: &'a Config,
-    metrics: &'a Vec<DimVec<f64>>,
+    metrics: &'a Vec<MetricValues>,
     means: Option<&'a DimVec<f64>>,
 }
 
@@ -649,37 +801,65 @@ impl<'a> MetricAccessor<'a> {
     pub fn mean(&self, idx: MetricIdx) -> Option<f64> {
         Some(self.means?[*idx])
     }
+
+    /// Collects all metrics of the given edge.
+    pub fn get(&self, edge_idx: EdgeIdx) -> DimVec<f64> {
+        self.metrics
+            .iter()
+            .map(|values| values.get(edge_idx))
+            .collect()
+    }
+
+    /// Iterates over the metrics' arrays, each containing the values of all edges.
+    pub fn iter(&self) -> impl Iterator<Item = &'a MetricValues> {
+        self.metrics.iter()
+    }
+
+    /// Number of bytes needed for storing the metrics of all edges
+    pub fn mem_size_b(&self) -> usize {
+        self.metrics.iter().map(MetricValues::mem_size_b).sum()
+    }
+
+    /// Weights the edge's metrics with the given alphas without collecting them.
+    pub fn dot_product(&self, alphas: &[f64], edge_idx: EdgeIdx) -> f64 {
+        alphas
+            .iter()
+            .zip(self.metrics)
+            .fold(0.0, |start, (alpha, values)| {
+                start + alpha * values.get(edge_idx)
+            })
+    }
 }
 
-impl<'a> Index<EdgeIdx> for MetricAccessor<'a> {
-    type Output = DimVec<f64>;
+impl<'a> Index<MetricIdx> for MetricAccessor<'a> {
+    type Output = MetricValues;
 
-    fn index(&self, edge_idx: EdgeIdx) -> &DimVec<f64> {
-        &self.metrics[*edge_idx]
+    fn index(&self, metric_idx: MetricIdx) -> &MetricValues {
+        &self.metrics[*metric_idx]
     }
 }
 
-impl<'a> Index<EdgeIdx> for &MetricAccessor<'a> {
-    type Output = DimVec<f64>;
+impl<'a> Index<MetricIdx> for &MetricAccessor<'a> {
+    type Output = MetricValues;
 
-    fn index(&self, edge_idx: EdgeIdx) -> &DimVec<f64> {
-        &self.metrics[*edge_idx]
+    fn index(&self, metric_idx: MetricIdx) -> &MetricValues {
+        &self.metrics[*metric_idx]
     }
 }
 
-impl<'a> Index<&EdgeIdx> for MetricAccessor<'a> {
-    type Output = DimVec<f64>;
+impl<'a> Index<&MetricIdx> for MetricAccessor<'a> {
+    type Output = MetricValues;
 
-    fn index(&self, edge_idx: &EdgeIdx) -> &DimVec<f64> {
-        &self.metrics[**edge_idx]
+    fn index(&self, metric_idx: &MetricIdx) -> &MetricValues {
+        &self.metrics[**metric_idx]
     }
 }
 
-impl<'a> Index<&EdgeIdx> for &MetricAccessor<'a> {
-    type Output = DimVec<f64>;
+impl<'a> Index<&MetricIdx> for &MetricAccessor<'a> {
+    type Output = MetricValues;
 
-    fn index(&self, edge_idx: &EdgeIdx) -> &DimVec<f64> {
-        &self.metrics[**edge_idx]
+    fn index(&self, metric_idx: &MetricIdx) -> &MetricValues {
+        &self.metrics[**metric_idx]
     }
 }
 
@@ -688,7 +868,7 @@ impl<'a> Index<&EdgeIdx> for &MetricAccessor<'a> {
 #[derive(Debug)]
 pub struct MetricAccessorMut<'a> {
     cfg: &'a Config,
-    metrics: &'a mut Vec<DimVec<f64>>,
+    metrics: &'a mut Vec<MetricValues>,
     means: Option<&'a mut DimVec<f64>>,
 }
 
@@ -704,76 +884,40 @@ impl<'a> MetricAccessorMut<'a> {
     pub fn means(&mut self) -> Option<&mut DimVec<f64>> {
         Some(self.means.as_mut()?)
     }
-}
-
-impl<'a> Index<EdgeIdx> for MetricAccessorMut<'a> {
-    type Output = DimVec<f64>;
-
-    fn index(&self, edge_idx: EdgeIdx) -> &DimVec<f64> {
-        &self.metrics[*edge_idx]
-    }
-}
 
-impl<'a> IndexMut<EdgeIdx> for MetricAccessorMut<'a> {
-    fn index_mut(&mut self, edge_idx: EdgeIdx) -> &mut DimVec<f64> {
-        &mut self.metrics[*edge_idx]
+    /// Collects all metrics of the given edge.
+    pub fn get(&self, edge_idx: EdgeIdx) -> DimVec<f64> {
+        self.metrics
+            .iter()
+            .map(|values| values.get(edge_idx))
+            .collect()
     }
 }
 
-impl<'a> Index<EdgeIdx> for &MetricAccessorMut<'a> {
-    type Output = DimVec<f64>;
+impl<'a> Index<MetricIdx> for MetricAccessorMut<'a> {
+    type Output = MetricValues;
 
-    fn index(&self, edge_idx: EdgeIdx) -> &DimVec<f64> {
-        &self.metrics[*edge_idx]
+    fn index(&self, metric_idx: MetricIdx) -> &MetricValues {
+        &self.metrics[*metric_idx]
     }
 }
 
-impl<'a> Index<EdgeIdx> for &mut MetricAccessorMut<'a> {
-    type Output = DimVec<f64>;
-
-    fn index(&self, edge_idx: EdgeIdx) -> &DimVec<f64> {
-        &self.metrics[*edge_idx]
-    }
-}
-
-impl<'a> IndexMut<EdgeIdx> for &mut MetricAccessorMut<'a> {
-    fn index_mut(&mut self, edge_idx: EdgeIdx) -> &mut DimVec<f64> {
-        &mut self.metrics[*edge_idx]
-    }
-}
-
-impl<'a> Index<&EdgeIdx> for MetricAccessorMut<'a> {
-    type Output = DimVec<f64>;
-
-    fn index(&self, edge_idx: &EdgeIdx) -> &DimVec<f64> {
-        &self.metrics[**edge_idx]
-    }
-}
-
-impl<'a> IndexMut<&EdgeIdx> for MetricAccessorMut<'a> {
-    fn index_mut(&mut self, edge_idx: &EdgeIdx) -> &mut DimVec<f64> {
-        &mut self.metrics[**edge_idx]
-    }
-}
-
-impl<'a> Index<&EdgeIdx> for &MetricAccessorMut<'a> {
-    type Output = DimVec<f64>;
-
-    fn index(&self, edge_idx: &EdgeIdx) -> &DimVec<f64> {
-        &self.metrics[**edge_idx]
+impl<'a> IndexMut<MetricIdx> for MetricAccessorMut<'a> {
+    fn index_mut(&mut self, metric_idx: MetricIdx) -> &mut MetricValues {
+        &mut self.metrics[*metric_idx]
     }
 }
 
-impl<'a> Index<&EdgeIdx> for &mut MetricAccessorMut<'a> {
-    type Output = DimVec<f64>;
+impl<'a> Index<&MetricIdx> for MetricAccessorMut<'a> {
+    type Output = MetricValues;
 
-    fn index(&self, edge_idx: &EdgeIdx) -> &DimVec<f64> {
-        &self.metrics[**edge_idx]
+    fn index(&self, metric_idx: &MetricIdx) -> &MetricValues {
+        &self.metrics[**metric_idx]
     }
 }
 
-impl<'a> IndexMut<&EdgeIdx> for &mut MetricAccessorMut<'a> {
-    fn index_mut(&mut self, edge_idx: &EdgeIdx) -> &mut DimVec<f64> {
-        &mut self.metrics[**edge_idx]
+impl<'a> IndexMut<&MetricIdx> for MetricAccessorMut<'a> {
+    fn index_mut(&mut self, metric_idx: &MetricIdx) -> &mut MetricValues {
+        &mut self.metrics[**metric_idx]
     }
 }
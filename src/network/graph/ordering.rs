@@ -0,0 +1,196 @@
+use super::NodeIdx;
+use crate::helpers::parallel;
+use kissunits::geo::Coordinate;
+use serde::Deserialize;
+use std::{cmp::Reverse, collections::VecDeque};
+
+/// The order of the graph's nodes, which is applied when finalizing the graph (see `parsing.node-ordering`).
+///
+/// Routing-algorithms access nodes together, which are close in the graph.
+/// Storing these nodes close in memory as well improves the cache-behaviour.
+/// Node-indices depend on the ordering, but `NodeAccessor::idx_from(id)` works for every ordering.
+#[derive(Copy, Clone, Debug, Deserialize, PartialEq, Eq)]
+pub enum NodeOrdering {
+    /// Ascending node-ids, which scatters spatial neighbours across memory.
+    Id,
+    /// Hilbert-curve over the nodes' coordinates, so spatial neighbours are stored close to each other.
+    Hilbert,
+    /// Depth-first-search over the (undirected) edges, starting at the next unvisited node (by id).
+    Dfs,
+    /// Breadth-first-search over the (undirected) edges, starting at the next unvisited node (by id).
+    Bfs,
+    /// Descending ch-levels, so the nodes of the ch-dijkstra's upward-searches are stored together.
+    ChLevel,
+}
+
+/// Returns the new idx of every node (indexed by its old idx), or None for `NodeOrdering::Id`.
+/// Ties are broken by the old idx, so the result is the same for every number of threads.
+pub(super) fn new_idxs_of<T, F>(
+    ordering: NodeOrdering,
+    coords: &[Coordinate],
+    levels: &[usize],
+    edges: &[T],
+    endpoints_of: F,
+    num_threads: usize,
+) -> Option<Vec<NodeIdx>>
+where
+    T: Sync,
+    F: Fn(&T) -> (NodeIdx, NodeIdx),
+{
+    let node_count = coords.len();
+    let old_idxs: Vec<usize> = (0..node_count).collect();
+
+    // old idx per new idx
+    let order = match ordering {
+        NodeOrdering::Id => return None,
+        NodeOrdering::Hilbert => {
+            let hilbert_idxs = hilbert_idxs_of(coords, num_threads);
+            parallel::sort_by_key(old_idxs, num_threads, |&idx| hilbert_idxs[idx])
+        }
+        NodeOrdering::ChLevel => {
+            parallel::sort_by_key(old_idxs, num_threads, |&idx| Reverse(levels[idx]))
+        }
+        NodeOrdering::Dfs | NodeOrdering::Bfs => {
+            let (offsets, neighbours) = undirected_adjacency(node_count, edges, endpoints_of);
+            let neighbours_of = |idx: usize| &neighbours[offsets[idx]..offsets[idx + 1]];
+
+            let mut is_visited = vec![false; node_count];
+            let mut order = Vec::with_capacity(node_count);
+            for start_idx in 0..node_count {
+                if is_visited[start_idx] {
+                    continue;
+                }
+
+                if ordering == NodeOrdering::Bfs {
+                    let mut queue = VecDeque::new();
+                    is_visited[start_idx] = true;
+                    queue.push_back(start_idx);
+                    while let Some(idx) = queue.pop_front() {
+                        order.push(idx);
+                        for &neighbour in neighbours_of(idx) {
+                            if !is_visited[neighbour] {
+                                is_visited[neighbour] = true;
+                                queue.push_back(neighbour);
+                            }
+                        }
+                    }
+                } else {
+                    // reversed, so neighbours are visited in the order of the edges
+                    let mut stack = vec![start_idx];
+                    while let Some(idx) = stack.pop() {
+                        if is_visited[idx] {
+                            continue;
+                        }
+                        is_visited[idx] = true;
+                        order.push(idx);
+                        for &neighbour in neighbours_of(idx).iter().rev() {
+                            if !is_visited[neighbour] {
+                                stack.push(neighbour);
+                            }
+                        }
+                    }
+                }
+            }
+            order
+        }
+    };
+
+    let mut new_idxs = vec![NodeIdx(0); node_count];
+    for (new_idx, &old_idx) in order.iter().enumerate() {
+        new_idxs[old_idx] = NodeIdx(new_idx);
+    }
+    Some(new_idxs)
+}
+
+/// Returns the offsets and the neighbours of every node, where edges are added in both directions.
+fn undirected_adjacency<T, F>(
+    node_count: usize,
+    edges: &[T],
+    endpoints_of: F,
+) -> (Vec<usize>, Vec<usize>)
+where
+    F: Fn(&T) -> (NodeIdx, NodeIdx),
+{
+    let mut offsets = vec![0; node_count + 1];
+    for edge in edges {
+        let (src_idx, dst_idx) = endpoints_of(edge);
+        offsets[*src_idx + 1] += 1;
+        offsets[*dst_idx + 1] += 1;
+    }
+    for idx in 0..node_count {
+        offsets[idx + 1] += offsets[idx];
+    }
+
+    let mut neighbours = vec![0; offsets[node_count]];
+    let mut next_offsets = offsets.clone();
+    for edge in edges {
+        let (src_idx, dst_idx) = endpoints_of(edge);
+        neighbours[next_offsets[*src_idx]] = *dst_idx;
+        next_offsets[*src_idx] += 1;
+        neighbours[next_offsets[*dst_idx]] = *src_idx;
+        next_offsets[*dst_idx] += 1;
+    }
+
+    (offsets, neighbours)
+}
+
+/// Number of cells of the hilbert-curve's grid per dimension (as power of 2)
+const HILBERT_ORDER: u32 = 16;
+
+/// Maps the coordinates onto a grid over their bounding-box and returns their indices along the hilbert-curve.
+fn hilbert_idxs_of(coords: &[Coordinate], num_threads: usize) -> Vec<u64> {
+    let (mut min, mut max) = (
+        Coordinate {
+            lat: f64::INFINITY,
+            lon: f64::INFINITY,
+        },
+        Coordinate {
+            lat: f64::NEG_INFINITY,
+            lon: f64::NEG_INFINITY,
+        },
+    );
+    for coord in coords {
+        min.lat = min.lat.min(coord.lat);
+        min.lon = min.lon.min(coord.lon);
+        max.lat = max.lat.max(coord.lat);
+        max.lon = max.lon.max(coord.lon);
+    }
+
+    let max_cell = ((1u64 << HILBERT_ORDER) - 1) as f64;
+    let cell_of = |value: f64, min: f64, max: f64| {
+        if max > min {
+            ((value - min) / (max - min) * max_cell).round() as u32
+        } else {
+            0
+        }
+    };
+    parallel::map(coords, num_threads, |coord| {
+        hilbert_idx(
+            cell_of(coord.lon, min.lon, max.lon),
+            cell_of(coord.lat, min.lat, max.lat),
+        )
+    })
+}
+
+/// Returns the idx of the given cell along the hilbert-curve (see [wikipedia](https://en.wikipedia.org/wiki/Hilbert_curve)).
+fn hilbert_idx(mut x: u32, mut y: u32) -> u64 {
+    let n = 1u32 << HILBERT_ORDER;
+    let mut idx = 0;
+    let mut s = n / 2;
+    while s > 0 {
+        let rx = (x & s > 0) as u32;
+        let ry = (y & s > 0) as u32;
+        idx += u64::from(s) * u64::from(s) * u64::from((3 * rx) ^ ry);
+
+        // rotate quadrant
+        if ry == 0 {
+            if rx == 1 {
+                x = n - 1 - x;
+                y = n - 1 - y;
+            }
+            std::mem::swap(&mut x, &mut y);
+        }
+        s /= 2;
+    }
+    idx
+}
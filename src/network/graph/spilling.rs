@@ -0,0 +1,122 @@
+use super::building::ProtoEdgeA;
+use crate::{
+    configs::parsing::external_memory,
+    defaults::capacity::DimVec,
+    helpers::{
+        err,
+        spilling::{self, MergedRuns, SortedRuns, Spillable, TmpDir},
+    },
+};
+use std::io::{self, Read, Write};
+
+/// Sorting-key of proto-edges, which is the order of the final forward-edges.
+/// The proto-edge's idx keeps the order of insertion for equal edges, so duplicates are removed like in memory.
+type EdgeKey = (i64, i64, Option<usize>, usize);
+
+/// Proto-edges, which are spilled as sorted runs until finalizing, when they are merged by src-id.
+pub(super) struct EdgeRuns {
+    runs: SortedRuns<ProtoEdgeA>,
+}
+
+impl EdgeRuns {
+    pub fn new(cfg: &external_memory::Config) -> err::Result<EdgeRuns> {
+        Ok(EdgeRuns {
+            runs: SortedRuns::new(
+                TmpDir::new(&cfg.dir)?,
+                "edges",
+                cfg.memory_budget_b,
+                cfg.max_merged_runs,
+            ),
+        })
+    }
+
+    /// Number of pushed proto-edges
+    pub fn len(&self) -> usize {
+        self.runs.len()
+    }
+
+    pub fn run_count(&self) -> usize {
+        self.runs.run_count()
+    }
+
+    pub fn push(&mut self, proto_edge: ProtoEdgeA) -> err::Feedback {
+        if proto_edge.sc_edges.is_some() {
+            return Err(err::Msg::from(
+                "Shortcuts are not supported when building with external memory.",
+            ));
+        }
+        self.runs.push(proto_edge)
+    }
+
+    /// Spills the remaining buffer and merges all runs, sorted by (src-id, dst-id).
+    pub fn into_merged(self) -> err::Result<MergedRuns<ProtoEdgeA>> {
+        self.runs.into_merged()
+    }
+}
+
+// Options are stored as u64, where u64::MAX is None.
+
+fn from_option(value: Option<usize>) -> u64 {
+    match value {
+        Some(value) => value as u64,
+        None => u64::MAX,
+    }
+}
+
+fn to_option(value: u64) -> Option<usize> {
+    match value {
+        u64::MAX => None,
+        value => Some(value as usize),
+    }
+}
+
+impl Spillable for ProtoEdgeA {
+    type Key = EdgeKey;
+
+    fn key(&self) -> EdgeKey {
+        (self.src_id, self.dst_id, self.id, self.idx)
+    }
+
+    fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(&(self.idx as u64).to_le_bytes())?;
+        writer.write_all(&from_option(self.id).to_le_bytes())?;
+        writer.write_all(&self.src_id.to_le_bytes())?;
+        writer.write_all(&self.dst_id.to_le_bytes())?;
+        writer.write_all(&from_option(self.street_idx).to_le_bytes())?;
+        writer.write_all(&(self.metrics.len() as u64).to_le_bytes())?;
+        for metric in &self.metrics {
+            writer.write_all(&metric.to_le_bytes())?;
+        }
+        Ok(())
+    }
+
+    fn read_from<R: Read>(reader: &mut R) -> io::Result<Option<ProtoEdgeA>> {
+        let mut header = [0u8; 6 * 8];
+        if !spilling::read_record(reader, &mut header)? {
+            return Ok(None);
+        }
+        let word = |i: usize| {
+            let mut bytes = [0u8; 8];
+            bytes.copy_from_slice(&header[(8 * i)..(8 * (i + 1))]);
+            bytes
+        };
+
+        let dim = u64::from_le_bytes(word(5)) as usize;
+        let mut metrics = DimVec::with_capacity(dim);
+        let mut bytes = [0u8; 8];
+        for _ in 0..dim {
+            reader.read_exact(&mut bytes)?;
+            metrics.push(f64::from_le_bytes(bytes));
+        }
+
+        Ok(Some(ProtoEdgeA {
+            idx: u64::from_le_bytes(word(0)) as usize,
+            id: to_option(u64::from_le_bytes(word(1))),
+            src_id: i64::from_le_bytes(word(2)),
+            dst_id: i64::from_le_bytes(word(3)),
+            metrics,
+            sc_edges: None,
+            street_idx: to_option(u64::from_le_bytes(word(4))),
+        }))
+    }
+}
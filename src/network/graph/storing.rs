@@ -0,0 +1,151 @@
+use super::EdgeIdx;
+use crate::{configs::parsing::edges::metrics::Precision, helpers::err};
+
+/// The values of one metric for all edges, stored with the metric's precision.
+///
+/// Values are always accessed as `f64`.
+/// Infinite values (e.g. of closed edges) are supported by every precision.
+#[derive(Clone, Debug)]
+pub enum MetricValues {
+    F64(Vec<f64>),
+    F32(Vec<f32>),
+    /// `u32::MAX` is stored for infinity.
+    U32 {
+        values: Vec<u32>,
+        scale: f64,
+    },
+}
+
+impl MetricValues {
+    /// Converts the values into the given precision.
+    /// Returns an error, if a value can't be stored, e.g. because it is too big for the fixed-point-range.
+    pub fn try_from_f64s(precision: Precision, values: Vec<f64>) -> err::Result<MetricValues> {
+        let mut metric_values = MetricValues::with_capacity(precision, values.len());
+        for value in values {
+            metric_values.try_push(value)?;
+        }
+        Ok(metric_values)
+    }
+
+    pub fn with_capacity(precision: Precision, capacity: usize) -> MetricValues {
+        match precision {
+            Precision::F64 => MetricValues::F64(Vec::with_capacity(capacity)),
+            Precision::F32 => MetricValues::F32(Vec::with_capacity(capacity)),
+            Precision::U32 { scale } => MetricValues::U32 {
+                values: Vec::with_capacity(capacity),
+                scale,
+            },
+        }
+    }
+
+    pub fn precision(&self) -> Precision {
+        match self {
+            MetricValues::F64(_) => Precision::F64,
+            MetricValues::F32(_) => Precision::F32,
+            MetricValues::U32 { values: _, scale } => Precision::U32 { scale: *scale },
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        match self {
+            MetricValues::F64(values) => values.len(),
+            MetricValues::F32(values) => values.len(),
+            MetricValues::U32 { values, scale: _ } => values.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Number of bytes needed for storing the values
+    pub fn mem_size_b(&self) -> usize {
+        match self {
+            MetricValues::F64(values) => values.len() * std::mem::size_of::<f64>(),
+            MetricValues::F32(values) => values.len() * std::mem::size_of::<f32>(),
+            MetricValues::U32 { values, scale: _ } => values.len() * std::mem::size_of::<u32>(),
+        }
+    }
+
+    #[inline]
+    pub fn get(&self, edge_idx: EdgeIdx) -> f64 {
+        match self {
+            MetricValues::F64(values) => values[*edge_idx],
+            MetricValues::F32(values) => values[*edge_idx] as f64,
+            MetricValues::U32 { values, scale } => match values[*edge_idx] {
+                u32::MAX => f64::INFINITY,
+                value => value as f64 * scale,
+            },
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = f64> + '_ {
+        (0..self.len()).map(move |i| self.get(EdgeIdx(i)))
+    }
+
+    /// Returns true if the value can be stored.
+    /// Every value can be stored as `f64`, while the lossy precisions need non-negative values in their range.
+    pub fn can_store(&self, value: f64) -> bool {
+        match self {
+            MetricValues::F64(_) => true,
+            MetricValues::F32(_) => {
+                value >= 0.0 && (value.is_infinite() || value <= f32::MAX as f64)
+            }
+            MetricValues::U32 { values: _, scale } => quantize(value, *scale).is_some(),
+        }
+    }
+
+    /// Sets the value, where values beyond the precision's range are stored as infinity.
+    pub fn set(&mut self, edge_idx: EdgeIdx, value: f64) {
+        match self {
+            MetricValues::F64(values) => values[*edge_idx] = value,
+            MetricValues::F32(values) => values[*edge_idx] = value as f32,
+            MetricValues::U32 { values, scale } => {
+                values[*edge_idx] = quantize(value, *scale).unwrap_or(u32::MAX)
+            }
+        }
+    }
+
+    pub fn try_push(&mut self, value: f64) -> err::Feedback {
+        if !self.can_store(value) {
+            return Err(format!(
+                "The metric-value {} can't be stored with precision {:?}.",
+                value,
+                self.precision()
+            )
+            .into());
+        }
+
+        match self {
+            MetricValues::F64(values) => values.push(value),
+            MetricValues::F32(values) => values.push(value as f32),
+            MetricValues::U32 { values, scale } => {
+                values.push(quantize(value, *scale).unwrap_or(u32::MAX))
+            }
+        }
+        Ok(())
+    }
+
+    pub fn shrink_to_fit(&mut self) {
+        match self {
+            MetricValues::F64(values) => values.shrink_to_fit(),
+            MetricValues::F32(values) => values.shrink_to_fit(),
+            MetricValues::U32 { values, scale: _ } => values.shrink_to_fit(),
+        }
+    }
+}
+
+/// Returns `value / scale` as fixed-point-value, if it is in range.
+fn quantize(value: f64, scale: f64) -> Option<u32> {
+    if value == f64::INFINITY {
+        return Some(u32::MAX);
+    }
+
+    let fixed_point = (value / scale).round();
+    // u32::MAX is reserved for infinity
+    if fixed_point >= 0.0 && fixed_point < u32::MAX as f64 {
+        Some(fixed_point as u32)
+    } else {
+        None
+    }
+}
@@ -0,0 +1,194 @@
+use super::{Graph, MetricIdx, NodeIdx};
+use crate::{helpers::err, network::BoundingBox};
+use log::info;
+use std::collections::VecDeque;
+
+/// Defines which nodes and edges are kept in a subgraph (see `Graph::subgraph(...)`).
+///
+/// Node-predicates keep the edges between kept nodes, while edge-predicates keep all nodes.
+#[derive(Clone, Debug, PartialEq)]
+pub enum SubgraphPredicate {
+    /// Nodes inside the bounding-box
+    BoundingBox(BoundingBox),
+    /// The given nodes
+    NodeIdxs(Vec<NodeIdx>),
+    /// Nodes, which are at most `hops` edges away from the center.
+    /// Edges are used in both directions, but shortcuts are ignored.
+    Neighbourhood { center: NodeIdx, hops: usize },
+    /// Nodes with a ch-level of at least this level
+    MinChLevel(usize),
+    /// Edges, whose metric is in `[min, max]`.
+    /// Values are compared as stored, hence in normalized units if the graph is normalized.
+    Metric { idx: MetricIdx, min: f64, max: f64 },
+    /// Nodes and edges fulfilling every predicate
+    All(Vec<SubgraphPredicate>),
+}
+
+impl SubgraphPredicate {
+    /// Returns for every node (by idx), whether it fulfills the predicate.
+    fn kept_nodes(&self, graph: &Graph) -> err::Result<Vec<bool>> {
+        let nodes = graph.nodes();
+        let check_idx = |idx: NodeIdx| {
+            if *idx < nodes.count() {
+                Ok(())
+            } else {
+                Err(err::Msg::from(format!(
+                    "The node-idx {} is not part of the graph's nodes.",
+                    idx
+                )))
+            }
+        };
+
+        let is_kept_node = match self {
+            SubgraphPredicate::BoundingBox(bbox) => nodes
+                .iter()
+                .map(|idx| bbox.contains(&nodes.coord(idx)))
+                .collect(),
+            SubgraphPredicate::NodeIdxs(idxs) => {
+                let mut is_kept_node = vec![false; nodes.count()];
+                for &idx in idxs {
+                    check_idx(idx)?;
+                    is_kept_node[*idx] = true;
+                }
+                is_kept_node
+            }
+            SubgraphPredicate::Neighbourhood { center, hops } => {
+                check_idx(*center)?;
+                let (fwd_edges, bwd_edges) = (graph.fwd_edges(), graph.bwd_edges());
+
+                // bfs, remembering the number of hops
+                let mut is_kept_node = vec![false; nodes.count()];
+                let mut queue = VecDeque::new();
+                is_kept_node[**center] = true;
+                queue.push_back((*center, 0));
+                while let Some((idx, hop_count)) = queue.pop_front() {
+                    if hop_count == *hops {
+                        continue;
+                    }
+                    for edge in fwd_edges
+                        .starting_from(idx)
+                        .chain(bwd_edges.starting_from(idx))
+                        .filter(|edge| !edge.is_shortcut())
+                    {
+                        if !is_kept_node[*edge.dst_idx()] {
+                            is_kept_node[*edge.dst_idx()] = true;
+                            queue.push_back((edge.dst_idx(), hop_count + 1));
+                        }
+                    }
+                }
+                is_kept_node
+            }
+            SubgraphPredicate::MinChLevel(level) => {
+                nodes.iter().map(|idx| nodes.level(idx) >= *level).collect()
+            }
+            SubgraphPredicate::Metric { idx, .. } => {
+                if **idx >= graph.metrics().dim() {
+                    return Err(format!(
+                        "The metric-idx {} is not part of the graph's {} metrics.",
+                        idx,
+                        graph.metrics().dim()
+                    )
+                    .into());
+                }
+                vec![true; nodes.count()]
+            }
+            SubgraphPredicate::All(predicates) => {
+                let mut is_kept_node = vec![true; nodes.count()];
+                for predicate in predicates {
+                    for (is_kept, is_kept_by_predicate) in
+                        is_kept_node.iter_mut().zip(predicate.kept_nodes(graph)?)
+                    {
+                        *is_kept &= is_kept_by_predicate;
+                    }
+                }
+                is_kept_node
+            }
+        };
+        Ok(is_kept_node)
+    }
+
+    /// Returns whether an edge with these metrics fulfills the predicate, ignoring node-predicates.
+    fn keeps_edge(&self, metrics: &[f64]) -> bool {
+        match self {
+            SubgraphPredicate::Metric { idx, min, max } => {
+                *min <= metrics[**idx] && metrics[**idx] <= *max
+            }
+            SubgraphPredicate::All(predicates) => predicates
+                .iter()
+                .all(|predicate| predicate.keeps_edge(metrics)),
+            SubgraphPredicate::BoundingBox(_)
+            | SubgraphPredicate::NodeIdxs(_)
+            | SubgraphPredicate::Neighbourhood { .. }
+            | SubgraphPredicate::MinChLevel(_) => true,
+        }
+    }
+}
+
+/// Extracting smaller graphs, e.g. for debugging a route of a huge graph
+impl Graph {
+    /// Returns a new graph with the nodes and edges fulfilling the predicate, hence indices are remapped, while ids are kept.
+    ///
+    /// Edges are kept, if both of their nodes are kept.
+    /// Shortcuts are kept, if both of their sc-edges are kept (recursively).
+    /// Nodes without any kept edges are dropped (like when parsing), as well as removed nodes and edges.
+    /// The subgraph can be written like every graph, e.g. via `io::network::graph::Writer`.
+    pub fn subgraph(&self, predicate: &SubgraphPredicate) -> err::Result<Graph> {
+        info!(
+            "START Extract subgraph of graph with {} nodes and {} edges.",
+            self.nodes().count(),
+            self.fwd_edges().count()
+        );
+        let is_kept_node = predicate.kept_nodes(self)?;
+
+        // None for shortcuts, which depend on their sc-edges
+        let nodes = self.nodes();
+        let fwd_edges = self.fwd_edges();
+        let mut is_kept_edge = vec![Some(false); fwd_edges.count()];
+        for src_idx in nodes.iter() {
+            for edge in fwd_edges.starting_from(src_idx) {
+                is_kept_edge[*edge.idx()] = if !is_kept_node[*src_idx]
+                    || !is_kept_node[*edge.dst_idx()]
+                    || !predicate.keeps_edge(&edge.metrics())
+                {
+                    Some(false)
+                } else if edge.is_shortcut() {
+                    None
+                } else {
+                    Some(true)
+                };
+            }
+        }
+
+        // Resolve shortcuts iteratively, since the ch-hierarchy can be deep.
+        for edge_idx in fwd_edges.iter() {
+            let mut stack = vec![edge_idx];
+            while let Some(&sc_idx) = stack.last() {
+                if is_kept_edge[*sc_idx].is_some() {
+                    stack.pop();
+                    continue;
+                }
+                let [sc_edge_0, sc_edge_1] = *fwd_edges
+                    .sc_edges(sc_idx)
+                    .expect("Only shortcuts are unresolved.");
+                match (is_kept_edge[*sc_edge_0], is_kept_edge[*sc_edge_1]) {
+                    (Some(false), _) | (_, Some(false)) => is_kept_edge[*sc_idx] = Some(false),
+                    (Some(true), Some(true)) => is_kept_edge[*sc_idx] = Some(true),
+                    (None, _) => stack.push(sc_edge_0),
+                    (_, None) => stack.push(sc_edge_1),
+                }
+            }
+        }
+        let is_kept_edge: Vec<bool> = is_kept_edge
+            .into_iter()
+            .map(|is_kept| is_kept == Some(true))
+            .collect();
+
+        let graph = self.rebuild(&is_kept_node, &is_kept_edge)?;
+        info!(
+            "FINISHED Subgraph has {} nodes and {} edges.",
+            graph.nodes().count(),
+            graph.fwd_edges().count()
+        );
+        Ok(graph)
+    }
+}
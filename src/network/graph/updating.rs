@@ -0,0 +1,247 @@
+use super::{EdgeIdx, Graph, MetricIdx};
+use crate::{defaults::capacity::DimVec, helpers::err};
+use log::info;
+use std::collections::{HashMap, HashSet};
+
+/// Refers to an edge by its index or by its (external) id, which is mapped to its index.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum EdgeRef {
+    Idx(EdgeIdx),
+    Id(usize),
+}
+
+/// A change of an edge's metrics, e.g. due to live-traffic or closures.
+///
+/// Only original edges can be updated, since shortcuts get their metrics from their replaced edges.
+#[derive(Clone, Debug, PartialEq)]
+pub enum MetricUpdate {
+    /// Sets the edge's metric to the value, which is given in the metric's unit.
+    /// If the graph is normalized, the value is normalized by the metric's (unchanged) mean.
+    Set {
+        edge: EdgeRef,
+        metric_idx: MetricIdx,
+        value: f64,
+    },
+    /// Sets all metrics of the edge to infinity, so routing doesn't use it anymore.
+    Close { edge: EdgeRef },
+    /// Restores the metrics of a closed edge, including values set while it has been closed.
+    Reopen { edge: EdgeRef },
+}
+
+impl MetricUpdate {
+    pub fn edge(&self) -> EdgeRef {
+        match self {
+            MetricUpdate::Set {
+                edge,
+                metric_idx: _,
+                value: _,
+            }
+            | MetricUpdate::Close { edge }
+            | MetricUpdate::Reopen { edge } => *edge,
+        }
+    }
+}
+
+/// Describes what has been changed by `Graph::update_metrics(...)`.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct UpdateStats {
+    pub updated_edge_count: usize,
+    pub closed_edge_count: usize,
+    pub reopened_edge_count: usize,
+    /// Shortcuts, whose metrics have changed due to their replaced edges
+    pub updated_shortcut_count: usize,
+}
+
+impl Graph {
+    /// Applies the updates in the given order without rebuilding the graph.
+    ///
+    /// All updates are checked before any of them is applied, so an invalid update leaves the graph unchanged.
+    /// Changed metrics are propagated to all shortcuts replacing the changed edges (recursively), hence path-costs stay correct.
+    /// Nevertheless, shortcuts are not re-contracted, so ch-queries may miss better paths after big changes.
+    /// For optimal ch-queries, a ch-graph has to be customized again (see `network::CustomizableCH`).
+    pub fn update_metrics(&mut self, updates: &[MetricUpdate]) -> err::Result<UpdateStats> {
+        info!(
+            "START Update metrics of graph with {} updates.",
+            updates.len()
+        );
+
+        // check updates before changing anything
+
+        let mut edge_idxs = Vec::with_capacity(updates.len());
+        for update in updates {
+            let edge_idx = self.try_edge_idx_of(update.edge())?;
+            if self.fwd_edges().is_shortcut(edge_idx) {
+                return Err(format!(
+                    "Edge (idx: {}) is a shortcut, which can't be updated directly.",
+                    edge_idx
+                )
+                .into());
+            }
+            if let MetricUpdate::Set {
+                edge: _,
+                metric_idx,
+                value,
+            } = update
+            {
+                if **metric_idx >= self.metrics().dim() {
+                    return Err(format!(
+                        "The metric-idx {} is out of the graph's metrics' dimension {}.",
+                        metric_idx,
+                        self.metrics().dim()
+                    )
+                    .into());
+                }
+                if !value.is_finite() || *value < 0.0 {
+                    return Err(format!(
+                        "The new value {} of edge (idx: {}) has to be finite and non-negative.",
+                        value, edge_idx
+                    )
+                    .into());
+                }
+                let values = &self.metrics[**metric_idx];
+                let normalized_value = match self.metrics().mean(*metric_idx) {
+                    Some(mean) => value / mean,
+                    None => *value,
+                };
+                if !values.can_store(normalized_value) {
+                    return Err(format!(
+                        "The new value {} of edge (idx: {}) can't be stored with precision {:?}.",
+                        value,
+                        edge_idx,
+                        values.precision()
+                    )
+                    .into());
+                }
+            }
+            edge_idxs.push(edge_idx);
+        }
+
+        // apply updates
+
+        let mut stats = UpdateStats::default();
+        let mut changed_edges = Vec::new();
+        for (update, edge_idx) in updates.iter().zip(edge_idxs) {
+            match update {
+                MetricUpdate::Set {
+                    edge: _,
+                    metric_idx,
+                    value,
+                } => {
+                    let value = match self.metrics().mean(*metric_idx) {
+                        Some(mean) => value / mean,
+                        None => *value,
+                    };
+                    // closed edges get their value when being reopened
+                    match self.closed_edges.get_mut(&edge_idx) {
+                        Some(metrics) => metrics[**metric_idx] = value,
+                        None => {
+                            self.metrics[**metric_idx].set(edge_idx, value);
+                            changed_edges.push(edge_idx);
+                        }
+                    }
+                    stats.updated_edge_count += 1;
+                }
+                MetricUpdate::Close { edge: _ } => {
+                    if !self.closed_edges.contains_key(&edge_idx) {
+                        let metrics: DimVec<_> = self
+                            .metrics
+                            .iter_mut()
+                            .map(|values| {
+                                let value = values.get(edge_idx);
+                                values.set(edge_idx, f64::INFINITY);
+                                value
+                            })
+                            .collect();
+                        self.closed_edges.insert(edge_idx, metrics);
+                        changed_edges.push(edge_idx);
+                        stats.closed_edge_count += 1;
+                    }
+                }
+                MetricUpdate::Reopen { edge: _ } => {
+                    if let Some(metrics) = self.closed_edges.remove(&edge_idx) {
+                        for (values, metric) in self.metrics.iter_mut().zip(metrics) {
+                            values.set(edge_idx, metric);
+                        }
+                        changed_edges.push(edge_idx);
+                        stats.reopened_edge_count += 1;
+                    }
+                }
+            }
+        }
+
+        stats.updated_shortcut_count = self.propagate_to_shortcuts(changed_edges);
+
+        info!("FINISHED {:?}", stats);
+        Ok(stats)
+    }
+
+    /// Returns true if the edge has been closed via `Graph::update_metrics(...)`.
+    /// Shortcuts are never closed themselves, but their metrics are infinite if they replace closed edges.
+    pub fn is_closed(&self, edge_idx: EdgeIdx) -> bool {
+        self.closed_edges.contains_key(&edge_idx)
+    }
+
+    pub(super) fn try_edge_idx_of(&self, edge: EdgeRef) -> err::Result<EdgeIdx> {
+        match edge {
+            EdgeRef::Idx(edge_idx) => {
+                if *edge_idx < self.fwd_edges().count() {
+                    Ok(edge_idx)
+                } else {
+                    Err(format!(
+                        "The edge-idx {} is out of the graph's {} edges.",
+                        edge_idx,
+                        self.fwd_edges().count()
+                    )
+                    .into())
+                }
+            }
+            EdgeRef::Id(edge_id) => self.fwd_edges().try_idx_from(edge_id),
+        }
+    }
+
+    /// Recomputes the metrics of all shortcuts, which replace (recursively) the changed edges.
+    /// Returns the number of changed shortcuts.
+    fn propagate_to_shortcuts(&mut self, changed_edges: Vec<EdgeIdx>) -> usize {
+        if self.sc_edges.is_empty() || changed_edges.is_empty() {
+            return 0;
+        }
+
+        // map replaced edges to their shortcuts
+        let mut shortcuts_of: HashMap<EdgeIdx, Vec<EdgeIdx>> = HashMap::new();
+        {
+            let fwd_edges = self.fwd_edges();
+            for sc_idx in fwd_edges.iter() {
+                if let Some(sc_edges) = fwd_edges.sc_edges(sc_idx) {
+                    for &edge_idx in sc_edges {
+                        shortcuts_of.entry(edge_idx).or_default().push(sc_idx);
+                    }
+                }
+            }
+        }
+
+        // A shortcut may be recomputed multiple times, if both of its replaced edges change,
+        // but the graph is acyclic, so this terminates.
+        let mut updated_shortcuts = HashSet::new();
+        let mut queue = changed_edges;
+        while let Some(edge_idx) = queue.pop() {
+            for &sc_idx in shortcuts_of.get(&edge_idx).into_iter().flatten() {
+                let [sc_edge_0, sc_edge_1] = self.sc_edges[self.sc_offsets[*sc_idx]];
+                let mut has_changed = false;
+                for values in self.metrics.iter_mut() {
+                    // compare stored values, which may have less precision than f64
+                    let old_value = values.get(sc_idx);
+                    values.set(sc_idx, values.get(sc_edge_0) + values.get(sc_edge_1));
+                    if values.get(sc_idx) != old_value {
+                        has_changed = true;
+                    }
+                }
+                if has_changed {
+                    updated_shortcuts.insert(sc_idx);
+                    queue.push(sc_idx);
+                }
+            }
+        }
+
+        updated_shortcuts.len()
+    }
+}
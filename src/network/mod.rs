@@ -1,12 +1,21 @@
 mod graph;
 pub use graph::{
     building::{EdgeBuilder, GraphBuilder, NodeBuilder, ProtoEdge, ProtoNode, ProtoShortcut},
-    EdgeAccessor, EdgeIdx, Graph, HalfEdge, MetricAccessor, MetricIdx, Node, NodeAccessor, NodeIdx,
+    cch::CustomizableCH,
+    updating::{EdgeRef, MetricUpdate, UpdateStats},
+    DiffKind, EdgeAccessor, EdgeDiff, EdgeIdx, EdgeMatching, Graph, GraphDiff, GraphStats,
+    HalfEdge, MetricAccessor, MetricIdx, MetricStats, MetricValues, Node, NodeAccessor, NodeDiff,
+    NodeIdx, NodeOrdering, StreetAccessor, SubgraphPredicate,
 };
 
 mod routes;
 pub use routes::RoutePair;
 
+mod spatial;
+pub use spatial::{
+    BoundingBox, EdgeIndex, EdgePosition, PhantomNode, SnappingFilter, SpatialIndex,
+};
+
 /// The street-type, collecting all kind of default-values.
 ///
 /// ## Street-types
@@ -82,6 +91,40 @@ pub enum StreetCategory {
     Path,
 }
 
+/// Street-information of an osm-way, which is kept for its edges when parsing pbf-files with `edges.will_keep_streets`.
+///
+/// Equal streets are stored only once in the graph, so consecutive ways of the same street can't be distinguished.
+/// With `edges.ways`, the osm-way's id and tags are kept as well, hence every osm-way is its own street.
+#[derive(Clone, Debug, Default, Hash, PartialEq, Eq)]
+pub struct Street {
+    /// The tag `name`
+    pub name: Option<String>,
+    /// The tag `ref`, e.g. `A 81`
+    pub reference: Option<String>,
+    /// Motorways and trunks (and their links), which are left via exits
+    pub is_motorway: bool,
+    /// Links like `highway=motorway_link`
+    pub is_link: bool,
+    /// The tag `junction=roundabout` (or `circular`)
+    pub is_roundabout: bool,
+    /// The osm-id of the way, if `edges.ways` is configured
+    pub way_id: Option<i64>,
+    /// Values of the way's tags, aligned to the configured tag-keys (`edges.ways.tags`)
+    pub tags: Vec<Option<String>>,
+}
+
+impl Street {
+    /// The name for displaying, which is the name, the ref or both (`name (ref)`).
+    pub fn label(&self) -> Option<String> {
+        match (&self.name, &self.reference) {
+            (Some(name), Some(reference)) => Some(format!("{} ({})", name, reference)),
+            (Some(name), None) => Some(name.clone()),
+            (None, Some(reference)) => Some(reference.clone()),
+            (None, None) => None,
+        }
+    }
+}
+
 pub mod vehicles {
     use serde::Deserialize;
 
@@ -1,4 +1,8 @@
-use crate::network::{Graph, Node, NodeIdx};
+use crate::{
+    helpers::err,
+    network::{Graph, Node, NodeIdx, SpatialIndex},
+};
+use kissunits::geo::Coordinate;
 
 #[derive(Copy, Clone)]
 pub struct RoutePair<T> {
@@ -51,3 +55,25 @@ impl RoutePair<NodeIdx> {
         }
     }
 }
+
+impl RoutePair<Coordinate> {
+    /// Snaps src and dst to their nearest nodes in the spatial index.
+    pub fn try_into_idx(self, spatial_index: &SpatialIndex) -> err::Result<RoutePair<NodeIdx>> {
+        let snap = |coord: &Coordinate| {
+            spatial_index
+                .nearest_node(coord)
+                .map(|(node_idx, _)| node_idx)
+                .ok_or_else(|| {
+                    err::Msg::from(format!(
+                        "RoutePair<Coordinate> can't snap {}, because no node is indexed.",
+                        coord
+                    ))
+                })
+        };
+
+        Ok(RoutePair {
+            src: snap(&self.src)?,
+            dst: snap(&self.dst)?,
+        })
+    }
+}
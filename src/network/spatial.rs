@@ -0,0 +1,635 @@
+use crate::network::{EdgeIdx, Graph, NodeIdx};
+use kissunits::{
+    distance::Kilometers,
+    geo::{self, Coordinate},
+};
+use serde::Deserialize;
+use std::{cmp::Ordering, f64::consts::PI};
+
+/// Defines which nodes can be snapped to.
+#[derive(Copy, Clone, Debug, Deserialize, PartialEq, Eq)]
+pub enum SnappingFilter {
+    /// Every node of the graph
+    All,
+    /// Nodes having at least one (leaving or incoming) edge.
+    /// Since the parser only keeps edges accessible to the configured vehicle-category, these are the nodes accessible to this vehicle.
+    Accessible,
+    /// Nodes of the largest strongly connected component, so routes between snapped nodes exist.
+    LargestComponent,
+}
+
+/// An axis-aligned box of coordinates, e.g. for selecting the edges of a region.
+/// Boxes crossing the antimeridian are not supported.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct BoundingBox {
+    pub min: Coordinate,
+    pub max: Coordinate,
+}
+
+impl BoundingBox {
+    pub fn contains(&self, coord: &Coordinate) -> bool {
+        self.min.lat <= coord.lat
+            && coord.lat <= self.max.lat
+            && self.min.lon <= coord.lon
+            && coord.lon <= self.max.lon
+    }
+}
+
+/// A spatial index over the node-coordinates, answering nearest-node-queries.
+///
+/// The index is a kd-tree over the nodes' positions on the unit-sphere (3d), which is stored implicitly:
+/// the median of every (sub-)slice is the root of this slice's subtree.
+/// The euclidean distance in 3d (the chord) grows strictly with the great-circle-distance, hence queries are exact, even at the poles or the antimeridian.
+/// Returned distances are haversine-distances.
+///
+/// The index owns its data, so it can be shared with the graph, e.g. in a server.
+pub struct SpatialIndex {
+    points: Vec<Point>,
+    filter: SnappingFilter,
+}
+
+#[derive(Copy, Clone)]
+struct Point {
+    idx: NodeIdx,
+    coord: Coordinate,
+    xyz: [f64; 3],
+}
+
+impl SpatialIndex {
+    /// Indexes all nodes passing the filter.
+    pub fn new(graph: &Graph, filter: SnappingFilter) -> SpatialIndex {
+        let nodes = graph.nodes();
+        let is_snappable = match filter {
+            SnappingFilter::All => vec![true; nodes.count()],
+            SnappingFilter::Accessible => accessible_nodes(graph),
+            SnappingFilter::LargestComponent => largest_component(graph),
+        };
+
+        let mut points: Vec<Point> = nodes
+            .iter()
+            .filter(|node_idx| is_snappable[**node_idx])
+            .map(|node_idx| {
+                let coord = nodes.coord(node_idx);
+                Point {
+                    idx: node_idx,
+                    coord,
+                    xyz: to_xyz(&coord),
+                }
+            })
+            .collect();
+        build(&mut points, 0);
+
+        SpatialIndex { points, filter }
+    }
+
+    pub fn filter(&self) -> SnappingFilter {
+        self.filter
+    }
+
+    /// Number of indexed nodes
+    pub fn count(&self) -> usize {
+        self.points.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    /// Returns the nearest indexed node and its distance, or `None` if no node is indexed.
+    pub fn nearest_node(&self, coord: &Coordinate) -> Option<(NodeIdx, Kilometers)> {
+        self.k_nearest(coord, 1).into_iter().next()
+    }
+
+    /// Returns the `k` nearest indexed nodes with their distances, sorted by distance.
+    pub fn k_nearest(&self, coord: &Coordinate, k: usize) -> Vec<(NodeIdx, Kilometers)> {
+        if k == 0 {
+            return Vec::new();
+        }
+
+        // sorted by squared chord-distance
+        let mut best: Vec<(f64, Point)> = Vec::with_capacity(k + 1);
+        search_k_nearest(&self.points, 0, &to_xyz(coord), k, &mut best);
+        best.into_iter()
+            .map(|(_, point)| (point.idx, geo::haversine_distance_km(coord, &point.coord)))
+            .collect()
+    }
+
+    /// Returns all indexed nodes with at most the provided distance, sorted by distance.
+    pub fn within_radius(
+        &self,
+        coord: &Coordinate,
+        radius: Kilometers,
+    ) -> Vec<(NodeIdx, Kilometers)> {
+        if radius.0.is_nan() || radius.0 < 0.0 {
+            return Vec::new();
+        }
+
+        // convert great-circle-distance to squared chord-distance
+        let max_sq_chord = {
+            let angle = (radius.0 / EARTH_MEAN_RADIUS_KM).min(PI);
+            let chord = 2.0 * (angle / 2.0).sin();
+            // tolerate rounding-errors, the exact distance is checked below
+            chord * chord * (1.0 + 1e-9) + 1e-15
+        };
+
+        let mut found = Vec::new();
+        search_within(&self.points, 0, &to_xyz(coord), max_sq_chord, &mut found);
+
+        let mut found: Vec<_> = found
+            .into_iter()
+            .map(|point| (point.idx, geo::haversine_distance_km(coord, &point.coord)))
+            .filter(|(_, distance)| distance.0 <= radius.0)
+            .collect();
+        found.sort_by(|(_, a), (_, b)| a.0.partial_cmp(&b.0).unwrap_or(Ordering::Equal));
+        found
+    }
+}
+
+/// A position on an edge, given as fraction of the edge from its src (`0.0`) to its dst (`1.0`).
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct EdgePosition {
+    pub edge_idx: EdgeIdx,
+    pub fraction: f64,
+}
+
+/// A coordinate, which is projected onto its nearest edge.
+/// It can be used as src or dst of a query instead of a node (see `routing::dijkstra::PhantomQuery`).
+#[derive(Clone, Debug)]
+pub struct PhantomNode {
+    /// The projected coordinate on the edge
+    pub coord: Coordinate,
+    /// Distance from the provided coordinate to the projected coordinate
+    pub distance: Kilometers,
+    /// The nearest edge and its reverse edge (if existing) with the respective positions of the projected coordinate
+    pub positions: Vec<EdgePosition>,
+}
+
+/// A spatial index over the edges (as straight lines between their nodes), snapping coordinates onto their nearest edge.
+///
+/// Like the `SpatialIndex`, it is an implicit kd-tree on the unit-sphere, but over the edges' midpoints.
+/// Every midpoint knows its edge's radius and every subtree its maximum radius, which bounds the distance of the subtree's edges.
+/// Shortcuts are not indexed, and an edge is only indexed if both of its nodes pass the filter.
+pub struct EdgeIndex {
+    segments: Vec<Segment>,
+    filter: SnappingFilter,
+}
+
+#[derive(Copy, Clone)]
+struct Segment {
+    edge_idx: EdgeIdx,
+    src: Coordinate,
+    dst: Coordinate,
+    mid_xyz: [f64; 3],
+    // every point of the edge's arc is at most this far away from mid_xyz (chord-distance)
+    radius: f64,
+    // maximum radius of this segment's subtree
+    max_radius: f64,
+}
+
+impl EdgeIndex {
+    pub fn new(graph: &Graph, filter: SnappingFilter) -> EdgeIndex {
+        let nodes = graph.nodes();
+        let fwd_edges = graph.fwd_edges();
+        let is_snappable = match filter {
+            SnappingFilter::All => vec![true; nodes.count()],
+            SnappingFilter::Accessible => accessible_nodes(graph),
+            SnappingFilter::LargestComponent => largest_component(graph),
+        };
+
+        let mut segments = Vec::new();
+        for src_idx in nodes.iter().filter(|node_idx| is_snappable[**node_idx]) {
+            for edge in fwd_edges.starting_from(src_idx) {
+                if edge.is_shortcut() || !is_snappable[*edge.dst_idx()] {
+                    continue;
+                }
+
+                let src = nodes.coord(src_idx);
+                let dst = nodes.coord(edge.dst_idx());
+                let (src_xyz, dst_xyz) = (to_xyz(&src), to_xyz(&dst));
+                let half_chord = sq_distance(&src_xyz, &dst_xyz).sqrt() / 2.0;
+                segments.push(Segment {
+                    edge_idx: edge.idx(),
+                    src,
+                    dst,
+                    mid_xyz: [
+                        (src_xyz[0] + dst_xyz[0]) / 2.0,
+                        (src_xyz[1] + dst_xyz[1]) / 2.0,
+                        (src_xyz[2] + dst_xyz[2]) / 2.0,
+                    ],
+                    // The arc bulges out of the chord by at most its sagitta,
+                    // which is less than half_chord^2 for the unit-sphere.
+                    radius: half_chord + half_chord * half_chord + 1e-12,
+                    max_radius: 0.0,
+                });
+            }
+        }
+        build_segments(&mut segments, 0);
+
+        EdgeIndex { segments, filter }
+    }
+
+    pub fn filter(&self) -> SnappingFilter {
+        self.filter
+    }
+
+    /// Number of indexed edges
+    pub fn count(&self) -> usize {
+        self.segments.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.segments.is_empty()
+    }
+
+    /// Projects the coordinate onto its nearest indexed edge.
+    /// The reverse edge is added to the phantom-node's positions, if it exists (and isn't a shortcut).
+    ///
+    /// Returns `None` if no edge is indexed.
+    pub fn snap(&self, coord: &Coordinate, graph: &Graph) -> Option<PhantomNode> {
+        let mut best: Option<(f64, Segment, f64, Coordinate)> = None;
+        search_nearest_segment(&self.segments, 0, coord, &to_xyz(coord), &mut best);
+        let (_, segment, fraction, projected) = best?;
+
+        let fwd_edges = graph.fwd_edges();
+        let bwd_edges = graph.bwd_edges();
+        let src_idx = bwd_edges.dst_idx(segment.edge_idx);
+        let dst_idx = fwd_edges.dst_idx(segment.edge_idx);
+        let mut positions = vec![EdgePosition {
+            edge_idx: segment.edge_idx,
+            fraction,
+        }];
+        if let Some(reverse_edge) = fwd_edges
+            .starting_from(dst_idx)
+            .find(|edge| edge.dst_idx() == src_idx && !edge.is_shortcut())
+        {
+            positions.push(EdgePosition {
+                edge_idx: reverse_edge.idx(),
+                fraction: 1.0 - fraction,
+            });
+        }
+
+        Some(PhantomNode {
+            coord: projected,
+            distance: geo::haversine_distance_km(coord, &projected),
+            positions,
+        })
+    }
+
+    /// Projects the coordinate onto all indexed edges within the radius, but at most onto the `max_count` closest ones.
+    /// In contrast to `snap(...)`, every direction of an edge is its own candidate, so every phantom-node has exactly one position.
+    ///
+    /// The candidates are sorted by distance.
+    pub fn candidates(
+        &self,
+        coord: &Coordinate,
+        radius: Kilometers,
+        max_count: usize,
+    ) -> Vec<PhantomNode> {
+        let mut found = Vec::new();
+        search_segments_within(
+            &self.segments,
+            0,
+            coord,
+            &to_xyz(coord),
+            km_to_chord(radius.0),
+            &mut found,
+        );
+        found.sort_by(|(a, _, _, _), (b, _, _, _)| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+        found.truncate(max_count);
+
+        found
+            .into_iter()
+            .map(|(_, segment, fraction, projected)| PhantomNode {
+                coord: projected,
+                distance: geo::haversine_distance_km(coord, &projected),
+                positions: vec![EdgePosition {
+                    edge_idx: segment.edge_idx,
+                    fraction,
+                }],
+            })
+            .collect()
+    }
+}
+
+/// Used by `kissunits::geo::haversine_distance_km`
+const EARTH_MEAN_RADIUS_KM: f64 = 6_371.0;
+
+fn km_to_chord(km: f64) -> f64 {
+    2.0 * (km / EARTH_MEAN_RADIUS_KM / 2.0).min(PI / 2.0).sin()
+}
+
+/// Projects the coordinate onto the segment in a local equirectangular projection, which is precise for edges of usual lengths.
+///
+/// Returns the fraction of the projected coordinate (from src to dst) and the projected coordinate.
+fn project(coord: &Coordinate, src: &Coordinate, dst: &Coordinate) -> (f64, Coordinate) {
+    let lon_scale = ((src.lat + dst.lat) / 2.0).to_radians().cos();
+    let (dx, dy) = ((dst.lon - src.lon) * lon_scale, dst.lat - src.lat);
+    let (px, py) = ((coord.lon - src.lon) * lon_scale, coord.lat - src.lat);
+
+    let sq_len = dx * dx + dy * dy;
+    let fraction = if sq_len > 0.0 {
+        ((px * dx + py * dy) / sq_len).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+
+    (
+        fraction,
+        Coordinate {
+            lat: src.lat + fraction * (dst.lat - src.lat),
+            lon: src.lon + fraction * (dst.lon - src.lon),
+        },
+    )
+}
+
+fn to_xyz(coord: &Coordinate) -> [f64; 3] {
+    let lat = coord.lat.to_radians();
+    let lon = coord.lon.to_radians();
+    [lat.cos() * lon.cos(), lat.cos() * lon.sin(), lat.sin()]
+}
+
+fn sq_distance(a: &[f64; 3], b: &[f64; 3]) -> f64 {
+    (0..3).map(|i| (a[i] - b[i]) * (a[i] - b[i])).sum()
+}
+
+fn build(points: &mut [Point], depth: usize) {
+    if points.len() <= 1 {
+        return;
+    }
+
+    let axis = depth % 3;
+    let mid = points.len() / 2;
+    points.select_nth_unstable_by(mid, |a, b| {
+        a.xyz[axis]
+            .partial_cmp(&b.xyz[axis])
+            .unwrap_or(Ordering::Equal)
+    });
+    let (left, right) = points.split_at_mut(mid);
+    build(left, depth + 1);
+    build(&mut right[1..], depth + 1);
+}
+
+fn search_k_nearest(
+    points: &[Point],
+    depth: usize,
+    target: &[f64; 3],
+    k: usize,
+    best: &mut Vec<(f64, Point)>,
+) {
+    if points.is_empty() {
+        return;
+    }
+
+    let mid = points.len() / 2;
+    let point = points[mid];
+
+    // insert sorted and keep the k best
+    let sq_dist = sq_distance(target, &point.xyz);
+    if best.len() < k || sq_dist < best[best.len() - 1].0 {
+        let pos = best
+            .iter()
+            .position(|(other, _)| sq_dist < *other)
+            .unwrap_or(best.len());
+        best.insert(pos, (sq_dist, point));
+        best.truncate(k);
+    }
+
+    let axis = depth % 3;
+    let diff = target[axis] - point.xyz[axis];
+    let (near, far) = if diff < 0.0 {
+        (&points[..mid], &points[mid + 1..])
+    } else {
+        (&points[mid + 1..], &points[..mid])
+    };
+    search_k_nearest(near, depth + 1, target, k, best);
+    // the far side can only contain better points, if the splitting-plane is close enough
+    if best.len() < k || diff * diff < best[best.len() - 1].0 {
+        search_k_nearest(far, depth + 1, target, k, best);
+    }
+}
+
+fn search_within(
+    points: &[Point],
+    depth: usize,
+    target: &[f64; 3],
+    max_sq_dist: f64,
+    found: &mut Vec<Point>,
+) {
+    if points.is_empty() {
+        return;
+    }
+
+    let mid = points.len() / 2;
+    let point = points[mid];
+    if sq_distance(target, &point.xyz) <= max_sq_dist {
+        found.push(point);
+    }
+
+    let axis = depth % 3;
+    let diff = target[axis] - point.xyz[axis];
+    if diff <= 0.0 || diff * diff <= max_sq_dist {
+        search_within(&points[..mid], depth + 1, target, max_sq_dist, found);
+    }
+    if diff >= 0.0 || diff * diff <= max_sq_dist {
+        search_within(&points[mid + 1..], depth + 1, target, max_sq_dist, found);
+    }
+}
+
+fn build_segments(segments: &mut [Segment], depth: usize) {
+    if segments.is_empty() {
+        return;
+    }
+
+    let axis = depth % 3;
+    let mid = segments.len() / 2;
+    segments.select_nth_unstable_by(mid, |a, b| {
+        a.mid_xyz[axis]
+            .partial_cmp(&b.mid_xyz[axis])
+            .unwrap_or(Ordering::Equal)
+    });
+    let (left, right) = segments.split_at_mut(mid);
+    build_segments(left, depth + 1);
+    build_segments(&mut right[1..], depth + 1);
+
+    let max_radius = segments
+        .iter()
+        .map(|segment| segment.radius)
+        .fold(0.0, f64::max);
+    segments[mid].max_radius = max_radius;
+}
+
+fn search_nearest_segment(
+    segments: &[Segment],
+    depth: usize,
+    coord: &Coordinate,
+    target: &[f64; 3],
+    best: &mut Option<(f64, Segment, f64, Coordinate)>,
+) {
+    if segments.is_empty() {
+        return;
+    }
+
+    let mid = segments.len() / 2;
+    let segment = segments[mid];
+    let best_chord = |best: &Option<(f64, Segment, f64, Coordinate)>| {
+        best.as_ref()
+            .map_or(f64::INFINITY, |(chord, _, _, _)| *chord)
+    };
+
+    // lower bound of the segment's distance
+    if sq_distance(target, &segment.mid_xyz).sqrt() - segment.radius < best_chord(best) {
+        let (fraction, projected) = project(coord, &segment.src, &segment.dst);
+        let chord = km_to_chord(geo::haversine_distance_km(coord, &projected).0);
+        if chord < best_chord(best) {
+            *best = Some((chord, segment, fraction, projected));
+        }
+    }
+
+    let axis = depth % 3;
+    let diff = target[axis] - segment.mid_xyz[axis];
+    let (near, far) = if diff < 0.0 {
+        (&segments[..mid], &segments[mid + 1..])
+    } else {
+        (&segments[mid + 1..], &segments[..mid])
+    };
+    search_nearest_segment(near, depth + 1, coord, target, best);
+    // the far side's midpoints are at least |diff| away
+    if let Some(far_root) = far.get(far.len() / 2) {
+        if diff.abs() - far_root.max_radius < best_chord(best) {
+            search_nearest_segment(far, depth + 1, coord, target, best);
+        }
+    }
+}
+
+fn search_segments_within(
+    segments: &[Segment],
+    depth: usize,
+    coord: &Coordinate,
+    target: &[f64; 3],
+    max_chord: f64,
+    found: &mut Vec<(f64, Segment, f64, Coordinate)>,
+) {
+    if segments.is_empty() {
+        return;
+    }
+
+    let mid = segments.len() / 2;
+    let segment = segments[mid];
+    if sq_distance(target, &segment.mid_xyz).sqrt() - segment.radius <= max_chord {
+        let (fraction, projected) = project(coord, &segment.src, &segment.dst);
+        let chord = km_to_chord(geo::haversine_distance_km(coord, &projected).0);
+        if chord <= max_chord {
+            found.push((chord, segment, fraction, projected));
+        }
+    }
+
+    let axis = depth % 3;
+    let diff = target[axis] - segment.mid_xyz[axis];
+    for (side, side_diff) in &[
+        (&segments[..mid], diff.max(0.0)),
+        (&segments[mid + 1..], (-diff).max(0.0)),
+    ] {
+        if let Some(side_root) = side.get(side.len() / 2) {
+            if side_diff - side_root.max_radius <= max_chord {
+                search_segments_within(side, depth + 1, coord, target, max_chord, found);
+            }
+        }
+    }
+}
+
+/// Marks nodes with at least one leaving or incoming edge.
+fn accessible_nodes(graph: &Graph) -> Vec<bool> {
+    let fwd_edges = graph.fwd_edges();
+    let bwd_edges = graph.bwd_edges();
+    graph
+        .nodes()
+        .iter()
+        .map(|node_idx| {
+            fwd_edges.starting_from(node_idx).next().is_some()
+                || bwd_edges.starting_from(node_idx).next().is_some()
+        })
+        .collect()
+}
+
+/// Marks the nodes of the largest strongly connected component.
+fn largest_component(graph: &Graph) -> Vec<bool> {
+    let (component_ids, component_sizes) = strongly_connected_components(graph);
+    let largest_id = component_sizes
+        .iter()
+        .enumerate()
+        .max_by_key(|&(component_id, size)| (size, std::cmp::Reverse(component_id)))
+        .map(|(component_id, _)| component_id);
+    component_ids
+        .into_iter()
+        .map(|component_id| component_id.is_some() && component_id == largest_id)
+        .collect()
+}
+
+/// Returns the component-id of every node and the size of every strongly connected component.
+///
+/// Kosaraju's algorithm with iterative depth-first-searches, so large graphs don't overflow the stack:
+/// The first search (on fwd-edges) computes the nodes' finishing-order, the second search (on bwd-edges) collects the components in reversed finishing-order.
+pub(super) fn strongly_connected_components(graph: &Graph) -> (Vec<Option<usize>>, Vec<usize>) {
+    let nodes = graph.nodes();
+    let fwd_edges = graph.fwd_edges();
+    let bwd_edges = graph.bwd_edges();
+
+    // finishing-order
+
+    let mut is_visited = vec![false; nodes.count()];
+    let mut finished = Vec::with_capacity(nodes.count());
+    for root_idx in nodes.iter() {
+        if is_visited[*root_idx] {
+            continue;
+        }
+        is_visited[*root_idx] = true;
+
+        // (node, already processed leaving edges)
+        let mut stack = vec![(root_idx, 0)];
+        while let Some((node_idx, edge_offset)) = stack.pop() {
+            let next_dst = fwd_edges
+                .starting_from(node_idx)
+                .nth(edge_offset)
+                .map(|edge| edge.dst_idx());
+
+            match next_dst {
+                Some(dst_idx) => {
+                    stack.push((node_idx, edge_offset + 1));
+                    if !is_visited[*dst_idx] {
+                        is_visited[*dst_idx] = true;
+                        stack.push((dst_idx, 0));
+                    }
+                }
+                None => finished.push(node_idx),
+            }
+        }
+    }
+
+    // components
+
+    let mut component_ids: Vec<Option<usize>> = vec![None; nodes.count()];
+    let mut component_sizes = Vec::new();
+    for &root_idx in finished.iter().rev() {
+        if component_ids[*root_idx].is_some() {
+            continue;
+        }
+        let component_id = component_sizes.len();
+        component_ids[*root_idx] = Some(component_id);
+        let mut size = 0;
+
+        let mut stack = vec![root_idx];
+        while let Some(node_idx) = stack.pop() {
+            size += 1;
+            for edge in bwd_edges.starting_from(node_idx) {
+                let dst_idx = edge.dst_idx();
+                if component_ids[*dst_idx].is_none() {
+                    component_ids[*dst_idx] = Some(component_id);
+                    stack.push(dst_idx);
+                }
+            }
+        }
+        component_sizes.push(size);
+    }
+
+    (component_ids, component_sizes)
+}
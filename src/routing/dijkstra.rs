@@ -1,11 +1,16 @@
-use super::paths::Path;
+use super::paths::{PartialEdge, Path};
 use crate::{
+    approximating::Approx,
     configs::routing::{Config, RoutingAlgo},
-    defaults::routing::IS_USING_CH_LEVEL_SPEEDUP,
-    helpers,
-    network::{EdgeIdx, Graph, NodeIdx},
+    defaults::routing::{IS_USING_CH_LEVEL_SPEEDUP, IS_USING_STALL_ON_DEMAND},
+    network::{EdgeAccessor, EdgeIdx, Graph, NodeAccessor, NodeIdx, PhantomNode},
+};
+use std::{
+    cmp::Reverse,
+    collections::BinaryHeap,
+    fmt::{self, Display},
+    time::{Duration, Instant},
 };
-use std::{cmp::Reverse, collections::BinaryHeap};
 
 #[derive(Copy, Clone)]
 pub struct Query<'a> {
@@ -15,14 +20,38 @@ pub struct Query<'a> {
     pub routing_cfg: &'a Config,
 }
 
+/// Like `Query`, but src and dst are phantom-nodes on edges (see `network::EdgeIndex::snap(...)`).
+///
+/// The search starts at the nodes, which can be reached from the src's positions, with the partial costs of these edges.
+/// Accordingly, it ends at the nodes, from which the dst's positions can be reached.
+/// The resulting path reports these partial edges (see `Path`).
+#[derive(Copy, Clone)]
+pub struct PhantomQuery<'a> {
+    pub src: &'a PhantomNode,
+    pub dst: &'a PhantomNode,
+    pub graph: &'a Graph,
+    pub routing_cfg: &'a Config,
+}
+
 /// A bidirectional implementation of Dijkstra's algorithm.
 /// This implementation reuses the underlying datastructures to speedup multiple computations.
 ///
 /// This implementation is correct for contracted and non-contracted graphs.
 /// However, the performance highly depends on a flag in the config, which has to be provided when computing the best path.
+///
+/// For contracted graphs, the query stops as soon as no queued node can undercut the best meeting-node.
+/// Further, it uses stall-on-demand (if activated in the defaults).
+///
+/// Detailed stats and the settled search-space of the last query are opt-in (see `collect_stats(...)` and `record_search_space(...)`).
 pub struct Dijkstra {
     // general
     is_ch_dijkstra: bool,
+    is_collecting_stats: bool,
+    is_recording_search_space: bool,
+    // stats of the last query
+    counts: [DirectionStats; 2],
+    last_stats: Option<QueryStats>,
+    last_search_space: Option<SearchSpace>,
     // data-structures for a query
     queue: BinaryHeap<Reverse<CostNode>>,
     costs: [Vec<f64>; 2],
@@ -36,6 +65,11 @@ impl Dijkstra {
     pub fn new() -> Dijkstra {
         Dijkstra {
             is_ch_dijkstra: false,
+            is_collecting_stats: false,
+            is_recording_search_space: false,
+            counts: [DirectionStats::default(), DirectionStats::default()],
+            last_stats: None,
+            last_search_space: None,
             queue: BinaryHeap::new(),
             costs: [vec![], vec![]],
             predecessors: [vec![], vec![]],
@@ -45,6 +79,46 @@ impl Dijkstra {
         }
     }
 
+    /// Number of nodes, which have been settled in the last query (summed up for both directions).
+    /// Stalled nodes are included.
+    pub fn settled_node_count(&self) -> usize {
+        self.counts[0].settled_node_count + self.counts[1].settled_node_count
+    }
+
+    /// Number of settled nodes in the last query, whose leaving edges haven't been relaxed due to stall-on-demand.
+    /// This is always 0 for non-contracted queries.
+    pub fn stalled_node_count(&self) -> usize {
+        self.counts[0].stalled_node_count + self.counts[1].stalled_node_count
+    }
+
+    /// If activated, every following query collects stats, which can be accessed via `last_stats()`.
+    pub fn collect_stats(&mut self, is_collecting: bool) {
+        self.is_collecting_stats = is_collecting;
+        if !is_collecting {
+            self.last_stats = None;
+        }
+    }
+
+    /// If activated, every following query records its settled nodes, which can be accessed via `last_search_space()`.
+    ///
+    /// This costs memory proportional to the search-space, hence it should only be used for analyzing queries.
+    pub fn record_search_space(&mut self, is_recording: bool) {
+        self.is_recording_search_space = is_recording;
+        if !is_recording {
+            self.last_search_space = None;
+        }
+    }
+
+    /// Stats of the last query, if collecting stats is activated.
+    pub fn last_stats(&self) -> Option<&QueryStats> {
+        self.last_stats.as_ref()
+    }
+
+    /// Settled nodes of the last query, if recording the search-space is activated.
+    pub fn last_search_space(&self) -> Option<&SearchSpace> {
+        self.last_search_space.as_ref()
+    }
+
     fn fwd_idx(&self) -> usize {
         0
     }
@@ -94,6 +168,18 @@ impl Dijkstra {
         }
 
         self.queue.clear();
+        self.counts = [DirectionStats::default(), DirectionStats::default()];
+        self.last_stats = None;
+        self.last_search_space = if self.is_recording_search_space {
+            Some(SearchSpace::default())
+        } else {
+            None
+        };
+    }
+
+    fn push(&mut self, costnode: CostNode) {
+        self.counts[self.dir_idx(costnode.direction)].pushed_node_count += 1;
+        self.queue.push(Reverse(costnode));
     }
 
     fn visit(&mut self, costnode: &CostNode) {
@@ -132,6 +218,45 @@ impl Dijkstra {
         costnode.cost <= self.costs[self.dir_idx(costnode.direction)][*costnode.idx]
     }
 
+    /// A settled node can be stalled, if it can be reached cheaper from a higher node.
+    /// Then its cost is not the cost of a shortest path, hence the node can't be part of the best path in this direction.
+    ///
+    /// The provided edges have to be the opposite direction's edges, so their dsts are the node's predecessors in this direction.
+    fn is_stallable(
+        &self,
+        costnode: &CostNode,
+        opp_xwd_edges: &EdgeAccessor,
+        nodes: &NodeAccessor,
+        alphas: &[f64],
+    ) -> bool {
+        let dir = self.dir_idx(costnode.direction);
+        let level = nodes.level(costnode.idx);
+
+        for incoming_edge in opp_xwd_edges.starting_from(costnode.idx) {
+            let pred_idx = incoming_edge.dst_idx();
+            if nodes.level(pred_idx) < level {
+                if IS_USING_CH_LEVEL_SPEEDUP {
+                    // break because leaving-edges are sorted by level
+                    break;
+                } else {
+                    continue;
+                }
+            }
+
+            let pred_cost = self.costs[dir][*pred_idx];
+            if pred_cost.is_infinite() {
+                continue;
+            }
+
+            let new_cost = pred_cost + incoming_edge.dot_product(alphas);
+            if Approx(new_cost) < Approx(costnode.cost) {
+                return true;
+            }
+        }
+
+        false
+    }
+
     /// Returns the cost of a path, so cost(src->v) + cost(v->dst)
     fn total_cost(&self, costnode: &CostNode) -> f64 {
         self.costs[self.fwd_idx()][*costnode.idx] + self.costs[self.bwd_idx()][*costnode.idx]
@@ -142,12 +267,269 @@ impl Dijkstra {
     /// ATTENTION!
     /// If any alpha-value in the routing-config is negative, or any metric in the graph is negative, this method won't terminate.
     pub fn compute_best_path(&mut self, query: Query) -> Option<Path> {
+        self.compute_with_stats(|dijkstra| dijkstra.compute(query))
+    }
+
+    /// Like `compute_best_path(...)`, but between phantom-nodes, so the path may start and end on partial edges.
+    pub fn compute_best_phantom_path(&mut self, query: PhantomQuery) -> Option<Path> {
+        self.compute_with_stats(|dijkstra| dijkstra.compute_phantom(query))
+    }
+
+    fn compute_with_stats<F>(&mut self, compute: F) -> Option<Path>
+    where
+        F: FnOnce(&mut Dijkstra) -> Option<(Option<NodeIdx>, Path)>,
+    {
+        let now = if self.is_collecting_stats {
+            Some(Instant::now())
+        } else {
+            None
+        };
+
+        let best_path = compute(self);
+
+        if let Some(now) = now {
+            self.last_stats = Some(QueryStats {
+                fwd: self.counts[self.fwd_idx()].clone(),
+                bwd: self.counts[self.bwd_idx()].clone(),
+                meeting_node_idx: best_path
+                    .as_ref()
+                    .and_then(|(meeting_node_idx, _)| *meeting_node_idx),
+                elapsed: now.elapsed(),
+            });
+        }
+
+        best_path.map(|(_, path)| path)
+    }
+
+    /// Returns all nodes (with their costs), which are reachable from the src with costs of at most `max_cost`, sorted by costs.
+    /// This can be used for isochrones.
+    ///
+    /// Only forward-edges are relaxed (ignoring ch-levels), hence contracted graphs work as well, but without speedup.
+    /// Negative alphas lead to no reachable nodes.
+    pub fn compute_reachable_nodes(
+        &mut self,
+        src_idx: NodeIdx,
+        max_cost: f64,
+        graph: &Graph,
+        routing_cfg: &Config,
+    ) -> Vec<(NodeIdx, f64)> {
+        if routing_cfg.alphas.iter().any(|alpha| alpha < &0.0) {
+            return Vec::new();
+        }
+
+        self.is_ch_dijkstra = false;
+        let fwd_edges = graph.fwd_edges();
+        self.init_query(graph.nodes().count());
+        let dir = self.fwd_idx();
+
+        let mut reachable_nodes = Vec::new();
+        self.costs[dir][*src_idx] = 0.0;
+        self.touched[dir].push(*src_idx);
+        self.push(CostNode {
+            idx: src_idx,
+            cost: 0.0,
+            direction: Direction::FWD,
+        });
+
+        while let Some(Reverse(current)) = self.queue.pop() {
+            // skip outdated costnodes
+            if current.cost > self.costs[dir][*current.idx] || self.is_visited[dir][*current.idx] {
+                continue;
+            }
+            self.visit(&current);
+            self.counts[dir].settled_node_count += 1;
+            reachable_nodes.push((current.idx, current.cost));
+
+            for leaving_edge in fwd_edges.starting_from(current.idx) {
+                let new_cost = current.cost + leaving_edge.dot_product(&routing_cfg.alphas);
+                self.counts[dir].relaxed_edge_count += 1;
+
+                if new_cost <= max_cost && new_cost < self.costs[dir][*leaving_edge.dst_idx()] {
+                    self.predecessors[dir][*leaving_edge.dst_idx()] = Some(leaving_edge.idx());
+                    self.costs[dir][*leaving_edge.dst_idx()] = new_cost;
+                    self.touched[dir].push(*leaving_edge.dst_idx());
+                    self.push(CostNode {
+                        idx: leaving_edge.dst_idx(),
+                        cost: new_cost,
+                        direction: Direction::FWD,
+                    });
+                }
+            }
+        }
+
+        reachable_nodes
+    }
+
+    /// Returns the meeting-node and the best path.
+    fn compute(&mut self, query: Query) -> Option<(Option<NodeIdx>, Path)> {
+        let nodes = query.graph.nodes();
+        self.search(
+            [&[(query.src_idx, 0.0)], &[(query.dst_idx, 0.0)]],
+            query.graph,
+            query.routing_cfg,
+        )
+        .map(|found| {
+            (
+                Some(found.meeting_node_idx),
+                Path::new(
+                    query.src_idx,
+                    nodes.id(query.src_idx),
+                    query.dst_idx,
+                    nodes.id(query.dst_idx),
+                    found.edges,
+                ),
+            )
+        })
+    }
+
+    /// Returns the meeting-node (if any) and the best path between the phantom-nodes.
+    fn compute_phantom(&mut self, query: PhantomQuery) -> Option<(Option<NodeIdx>, Path)> {
+        let nodes = query.graph.nodes();
+        let fwd_edges = query.graph.fwd_edges();
+        let bwd_edges = query.graph.bwd_edges();
+        let alphas = &query.routing_cfg.alphas;
+        let graph_metrics = query.graph.metrics();
+        let edge_cost = |edge_idx: EdgeIdx| graph_metrics.dot_product(alphas, edge_idx);
+
+        // The src's positions lead to their edges' dst-nodes,
+        // and the dst's positions are reached from their edges' src-nodes.
+        // Per node, only the cheapest seed is needed.
+        let mut seeds: [Vec<(NodeIdx, f64, PartialEdge)>; 2] = [Vec::new(), Vec::new()];
+        let fwd_candidates = query.src.positions.iter().map(|position| {
+            (
+                fwd_edges.dst_idx(position.edge_idx),
+                (1.0 - position.fraction) * edge_cost(position.edge_idx),
+                PartialEdge {
+                    edge_idx: position.edge_idx,
+                    start: position.fraction,
+                    end: 1.0,
+                },
+            )
+        });
+        let bwd_candidates = query.dst.positions.iter().map(|position| {
+            (
+                bwd_edges.dst_idx(position.edge_idx),
+                position.fraction * edge_cost(position.edge_idx),
+                PartialEdge {
+                    edge_idx: position.edge_idx,
+                    start: 0.0,
+                    end: position.fraction,
+                },
+            )
+        });
+        for (dir_seeds, candidates) in seeds.iter_mut().zip(vec![
+            fwd_candidates.collect::<Vec<_>>(),
+            bwd_candidates.collect::<Vec<_>>(),
+        ]) {
+            for candidate in candidates {
+                match dir_seeds.iter_mut().find(|seed| seed.0 == candidate.0) {
+                    Some(seed) => {
+                        if candidate.1 < seed.1 {
+                            *seed = candidate;
+                        }
+                    }
+                    None => dir_seeds.push(candidate),
+                }
+            }
+        }
+
+        let mut best: Option<(f64, Option<NodeIdx>, Path)> = None;
+
+        // via nodes
+        let search_seeds: Vec<Vec<(NodeIdx, f64)>> = seeds
+            .iter()
+            .map(|dir_seeds| {
+                dir_seeds
+                    .iter()
+                    .map(|&(idx, cost, _)| (idx, cost))
+                    .collect()
+            })
+            .collect();
+        if let Some(found) = self.search(
+            [&search_seeds[0], &search_seeds[1]],
+            query.graph,
+            query.routing_cfg,
+        ) {
+            let partial_edge = |dir: usize, node_idx: NodeIdx| {
+                seeds[dir]
+                    .iter()
+                    .find(|(idx, _, _)| *idx == node_idx)
+                    .map(|(_, _, partial_edge)| *partial_edge)
+                    .expect("The path should start and end at seeds.")
+            };
+            let first_partial_edge = partial_edge(self.fwd_idx(), found.src_idx);
+            let last_partial_edge = partial_edge(self.bwd_idx(), found.dst_idx);
+            let src_idx = bwd_edges.dst_idx(first_partial_edge.edge_idx);
+            let dst_idx = fwd_edges.dst_idx(last_partial_edge.edge_idx);
+            best = Some((
+                found.cost,
+                Some(found.meeting_node_idx),
+                Path::new(
+                    src_idx,
+                    nodes.id(src_idx),
+                    dst_idx,
+                    nodes.id(dst_idx),
+                    found.edges,
+                )
+                .with_partial_edges(Some(first_partial_edge), Some(last_partial_edge)),
+            ));
+        }
+
+        // directly, if both phantom-nodes lie on the same edge in the right order
+        for src_position in &query.src.positions {
+            for dst_position in &query.dst.positions {
+                if src_position.edge_idx != dst_position.edge_idx
+                    || src_position.fraction > dst_position.fraction
+                {
+                    continue;
+                }
+
+                let cost = (dst_position.fraction - src_position.fraction)
+                    * edge_cost(src_position.edge_idx);
+                let is_better = match &best {
+                    Some((best_cost, _, _)) => cost < *best_cost,
+                    None => true,
+                };
+                if is_better {
+                    let src_idx = bwd_edges.dst_idx(src_position.edge_idx);
+                    let dst_idx = fwd_edges.dst_idx(src_position.edge_idx);
+                    let partial_edge = PartialEdge {
+                        edge_idx: src_position.edge_idx,
+                        start: src_position.fraction,
+                        end: dst_position.fraction,
+                    };
+                    best = Some((
+                        cost,
+                        None,
+                        Path::new(
+                            src_idx,
+                            nodes.id(src_idx),
+                            dst_idx,
+                            nodes.id(dst_idx),
+                            vec![],
+                        )
+                        .with_partial_edges(Some(partial_edge), None),
+                    ));
+                }
+            }
+        }
+
+        best.map(|(_, meeting_node_idx, path)| (meeting_node_idx, path))
+    }
+
+    /// Searches the best path from any fwd-seed to any bwd-seed, where seeds are nodes with initial costs.
+    fn search(
+        &mut self,
+        seeds: [&[(NodeIdx, f64)]; 2],
+        graph: &Graph,
+        routing_cfg: &Config,
+    ) -> Option<Found> {
         debug_assert!(
-            !query.routing_cfg.alphas.is_empty(),
+            !routing_cfg.alphas.is_empty(),
             "Best path should be computed, but no alphas are specified."
         );
 
-        for alpha in query.routing_cfg.alphas.iter() {
+        for alpha in routing_cfg.alphas.iter() {
             // Dijkstra would not terminate with negative weights
             // -> no path found
             if alpha < &0.0 {
@@ -155,10 +537,9 @@ impl Dijkstra {
             }
         }
 
-        self.is_ch_dijkstra = match query.routing_cfg.routing_algo {
+        self.is_ch_dijkstra = match routing_cfg.routing_algo {
             RoutingAlgo::Dijkstra => false,
             RoutingAlgo::CHDijkstra => true,
-            #[cfg(feature = "gpl")]
             RoutingAlgo::Explorator { algo } => panic!(
                 "Dijkstra is called with {:?} as specified routing-algorithm",
                 RoutingAlgo::Explorator { algo }
@@ -168,7 +549,7 @@ impl Dijkstra {
         //----------------------------------------------------------------------------------------//
         // initialization-stuff
 
-        let nodes = query.graph.nodes();
+        let nodes = graph.nodes();
         let xwd_edges = {
             debug_assert_eq!(
                 0,
@@ -180,7 +561,7 @@ impl Dijkstra {
                 self.dir_idx(Direction::BWD),
                 "Direction-Idx of BWD is expected to be 1."
             );
-            [query.graph.fwd_edges(), query.graph.bwd_edges()]
+            [graph.fwd_edges(), graph.bwd_edges()]
         };
         self.init_query(nodes.count());
         let mut best_meeting: Option<(NodeIdx, f64)> = None;
@@ -188,25 +569,22 @@ impl Dijkstra {
         //----------------------------------------------------------------------------------------//
         // prepare first iteration(s)
 
-        // push src-node
-        self.queue.push(Reverse(CostNode {
-            idx: query.src_idx,
-            cost: 0.0,
-            direction: Direction::FWD,
-        }));
-        // push dst-node
-        self.queue.push(Reverse(CostNode {
-            idx: query.dst_idx,
-            cost: 0.0,
-            direction: Direction::BWD,
-        }));
-        // update fwd-stats
-        self.costs[self.fwd_idx()][*query.src_idx] = 0.0;
-        self.touched[self.fwd_idx()].push(*query.src_idx);
-
-        // update bwd-stats
-        self.costs[self.bwd_idx()][*query.dst_idx] = 0.0;
-        self.touched[self.bwd_idx()].push(*query.dst_idx);
+        // push src-nodes and dst-nodes
+        for (&direction, dir_seeds) in [Direction::FWD, Direction::BWD].iter().zip(&seeds) {
+            let dir = self.dir_idx(direction);
+            for &(idx, cost) in dir_seeds.iter() {
+                if cost < self.costs[dir][*idx] {
+                    self.push(CostNode {
+                        idx,
+                        cost,
+                        direction,
+                    });
+                    // update stats
+                    self.costs[dir][*idx] = cost;
+                    self.touched[dir].push(*idx);
+                }
+            }
+        }
 
         //----------------------------------------------------------------------------------------//
         // search for shortest path
@@ -238,6 +616,10 @@ impl Dijkstra {
             }
             // otherwise, mark CostNode as visitted
             self.visit(&current);
+            self.counts[dir].settled_node_count += 1;
+            if let Some(search_space) = &mut self.last_search_space {
+                search_space.settled_nodes[dir].push((current.idx, current.cost));
+            }
 
             // if meeting-node is already found
             // -> check if new meeting-node is better
@@ -245,6 +627,11 @@ impl Dijkstra {
                 // if cost of single-queue is more expensive than best meeting-node
                 // -> This can't be improved anymore
                 if current.cost > best_total_cost {
+                    // Both directions share the queue,
+                    // hence all remaining costnodes are at least as expensive.
+                    if self.is_ch_dijkstra {
+                        break;
+                    }
                     self.has_found_best_meeting_node[dir] = true;
                     continue;
                 }
@@ -260,6 +647,20 @@ impl Dijkstra {
                 best_meeting = Some((current.idx, new_total_cost));
             }
 
+            // stall-on-demand
+            if self.is_ch_dijkstra
+                && IS_USING_STALL_ON_DEMAND
+                && self.is_stallable(
+                    &current,
+                    &xwd_edges[self.opp_dir_idx(current.direction)],
+                    &nodes,
+                    &routing_cfg.alphas,
+                )
+            {
+                self.counts[dir].stalled_node_count += 1;
+                continue;
+            }
+
             // update costs and add predecessors of nodes, which are dst of current's leaving edges
             for leaving_edge in xwd_edges[dir].starting_from(current.idx) {
                 if self.is_ch_dijkstra
@@ -273,13 +674,36 @@ impl Dijkstra {
                     }
                 }
 
-                let new_cost = current.cost
-                    + helpers::dot_product(&query.routing_cfg.alphas, &leaving_edge.metrics());
+                let new_cost = current.cost + leaving_edge.dot_product(&routing_cfg.alphas);
+                self.counts[dir].relaxed_edge_count += 1;
+
+                // prune nodes, which can't undercut the best meeting-node
+                if self.is_ch_dijkstra {
+                    if let Some((_meeting_node, best_total_cost)) = best_meeting {
+                        if new_cost > best_total_cost {
+                            continue;
+                        }
+                    }
+                }
+
                 if new_cost < self.costs[dir][*leaving_edge.dst_idx()] {
                     self.predecessors[dir][*leaving_edge.dst_idx()] = Some(leaving_edge.idx());
                     self.costs[dir][*leaving_edge.dst_idx()] = new_cost;
                     self.touched[dir].push(*leaving_edge.dst_idx());
 
+                    // For the ch-dijkstra, a better meeting-node can be found before settling,
+                    // which improves the pruning.
+                    if self.is_ch_dijkstra {
+                        let opp_cost = self.costs[self.opp_dir_idx(current.direction)]
+                            [*leaving_edge.dst_idx()];
+                        let new_total_cost = new_cost + opp_cost;
+                        if best_meeting
+                            .map_or(opp_cost.is_finite(), |(_, best)| new_total_cost < best)
+                        {
+                            best_meeting = Some((leaving_edge.dst_idx(), new_total_cost));
+                        }
+                    }
+
                     // if path is found
                     // -> Run until queue is empty
                     //    since the shortest path could have longer hop-distance
@@ -288,11 +712,11 @@ impl Dijkstra {
                     //    The CH-Dijkstra has to continue until the global best meeting-node has
                     //    been found (see above).
                     if self.is_ch_dijkstra || best_meeting.is_none() {
-                        self.queue.push(Reverse(CostNode {
+                        self.push(CostNode {
                             idx: leaving_edge.dst_idx(),
                             cost: new_cost,
                             direction: current.direction,
-                        }));
+                        });
                     }
                 }
             }
@@ -301,7 +725,7 @@ impl Dijkstra {
         //----------------------------------------------------------------------------------------//
         // create path if found
 
-        if let Some((meeting_node_idx, _best_total_cost)) = best_meeting {
+        if let Some((meeting_node_idx, best_total_cost)) = best_meeting {
             let mut proto_path = Vec::new();
 
             // iterate backwards over fwd-path
@@ -317,6 +741,7 @@ impl Dijkstra {
 
             // take fwd-part in the right order
             proto_path.reverse();
+            let src_idx = cur_idx;
 
             // iterate backwards over bwd-path
             let mut cur_idx = meeting_node_idx;
@@ -329,19 +754,95 @@ impl Dijkstra {
                 cur_idx = xwd_edges[opp_dir].dst_idx(leaving_idx);
             }
 
-            Some(Path::new(
-                query.src_idx,
-                nodes.id(query.src_idx),
-                query.dst_idx,
-                nodes.id(query.dst_idx),
-                proto_path,
-            ))
+            Some(Found {
+                meeting_node_idx,
+                cost: best_total_cost,
+                src_idx,
+                dst_idx: cur_idx,
+                edges: proto_path,
+            })
         } else {
             None
         }
     }
 }
 
+/// Result of a search between seeds
+struct Found {
+    meeting_node_idx: NodeIdx,
+    cost: f64,
+    /// fwd-seed, where the path starts
+    src_idx: NodeIdx,
+    /// bwd-seed, where the path ends
+    dst_idx: NodeIdx,
+    edges: Vec<EdgeIdx>,
+}
+
+/// Counts of one direction of a query.
+#[derive(Clone, Debug, Default)]
+pub struct DirectionStats {
+    /// Stalled nodes are included.
+    pub settled_node_count: usize,
+    pub stalled_node_count: usize,
+    /// Edges, whose costs have been computed when settling their src-node.
+    pub relaxed_edge_count: usize,
+    /// Pushes into the queue, including the initial node.
+    pub pushed_node_count: usize,
+}
+
+/// Stats of a query, collected if activated via `Dijkstra::collect_stats(true)`.
+#[derive(Clone, Debug)]
+pub struct QueryStats {
+    pub fwd: DirectionStats,
+    pub bwd: DirectionStats,
+    /// None, if no path has been found.
+    pub meeting_node_idx: Option<NodeIdx>,
+    pub elapsed: Duration,
+}
+
+impl QueryStats {
+    pub fn settled_node_count(&self) -> usize {
+        self.fwd.settled_node_count + self.bwd.settled_node_count
+    }
+}
+
+impl Display for QueryStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} ms, settled {}+{} (fwd+bwd) nodes ({}+{} stalled), relaxed {}+{} edges, pushed {}+{} nodes, meeting-node {}",
+            self.elapsed.as_micros() as f64 / 1_000.0,
+            self.fwd.settled_node_count,
+            self.bwd.settled_node_count,
+            self.fwd.stalled_node_count,
+            self.bwd.stalled_node_count,
+            self.fwd.relaxed_edge_count,
+            self.bwd.relaxed_edge_count,
+            self.fwd.pushed_node_count,
+            self.bwd.pushed_node_count,
+            self.meeting_node_idx
+                .map_or(String::from("none"), |idx| format!("{}", idx)),
+        )
+    }
+}
+
+/// Settled nodes of a query with their costs (from src for fwd, to dst for bwd), in the order of settling.
+/// It is recorded if activated via `Dijkstra::record_search_space(true)`.
+#[derive(Clone, Debug, Default)]
+pub struct SearchSpace {
+    settled_nodes: [Vec<(NodeIdx, f64)>; 2],
+}
+
+impl SearchSpace {
+    pub fn fwd(&self) -> &Vec<(NodeIdx, f64)> {
+        &self.settled_nodes[0]
+    }
+
+    pub fn bwd(&self) -> &Vec<(NodeIdx, f64)> {
+        &self.settled_nodes[1]
+    }
+}
+
 #[derive(Copy, Clone, Debug)]
 enum Direction {
     FWD,
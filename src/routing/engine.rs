@@ -0,0 +1,327 @@
+use super::{
+    dijkstra::{self, Dijkstra},
+    explorating::ConvexHullExplorator,
+    paths::Path,
+};
+use crate::{
+    configs::routing::{Config, RoutingAlgo},
+    defaults,
+    helpers::err,
+    network::{Graph, NodeIdx, RoutePair},
+};
+use std::{
+    ops::{Deref, DerefMut},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    thread,
+};
+
+/// Datastructures, which are needed for a query and which are reused for multiple queries.
+pub struct Workspace {
+    pub dijkstra: Dijkstra,
+    pub explorator: ConvexHullExplorator,
+}
+
+impl Workspace {
+    pub fn new() -> Workspace {
+        Workspace {
+            dijkstra: Dijkstra::new(),
+            explorator: ConvexHullExplorator::new(),
+        }
+    }
+}
+
+impl Default for Workspace {
+    fn default() -> Workspace {
+        Workspace::new()
+    }
+}
+
+/// A workspace, which is given back to its engine's pool when dropped.
+pub struct PooledWorkspace {
+    pool: Arc<Mutex<Vec<Workspace>>>,
+    workspace: Option<Workspace>,
+}
+
+impl Deref for PooledWorkspace {
+    type Target = Workspace;
+
+    fn deref(&self) -> &Workspace {
+        self.workspace
+            .as_ref()
+            .expect("Workspace should only be taken when dropped.")
+    }
+}
+
+impl DerefMut for PooledWorkspace {
+    fn deref_mut(&mut self) -> &mut Workspace {
+        self.workspace
+            .as_mut()
+            .expect("Workspace should only be taken when dropped.")
+    }
+}
+
+impl Drop for PooledWorkspace {
+    fn drop(&mut self) {
+        if let (Some(workspace), Ok(mut pool)) = (self.workspace.take(), self.pool.lock()) {
+            pool.push(workspace);
+        }
+    }
+}
+
+/// A thread-safe engine for routing-queries on a shared graph.
+///
+/// Every query needs its own `Dijkstra` (and `ConvexHullExplorator`), because they hold mutable per-query buffers.
+/// The engine hands out these workspaces from a pool, so the buffers are allocated only once per concurrent query.
+/// Cloning the engine is cheap, since clones share the graph and the pool.
+#[derive(Clone)]
+pub struct Engine {
+    arc_graph: Arc<Graph>,
+    pool: Arc<Mutex<Vec<Workspace>>>,
+    num_threads: usize,
+}
+
+impl Engine {
+    /// Batches are worked off with `defaults::routing::NUM_THREADS` threads.
+    pub fn new(arc_graph: Arc<Graph>) -> Engine {
+        Engine::with_num_threads(arc_graph, defaults::routing::NUM_THREADS)
+    }
+
+    /// Batches are worked off with the provided number of threads (at least one).
+    pub fn with_num_threads(arc_graph: Arc<Graph>, num_threads: usize) -> Engine {
+        Engine {
+            arc_graph,
+            pool: Arc::new(Mutex::new(Vec::new())),
+            num_threads: num_threads.max(1),
+        }
+    }
+
+    pub fn graph(&self) -> &Arc<Graph> {
+        &self.arc_graph
+    }
+
+    pub fn num_threads(&self) -> usize {
+        self.num_threads
+    }
+
+    /// Takes a workspace from the pool or creates a new one, if the pool is empty.
+    /// The workspace is given back when dropped.
+    pub fn workspace(&self) -> PooledWorkspace {
+        let workspace = self
+            .pool
+            .lock()
+            .ok()
+            .and_then(|mut pool| pool.pop())
+            .unwrap_or_default();
+        PooledWorkspace {
+            pool: Arc::clone(&self.pool),
+            workspace: Some(workspace),
+        }
+    }
+
+    /// Computes the best path with the routing-config's algorithm.
+    ///
+    /// If the routing-config's algorithm is an explorator, its underlying Dijkstra is used.
+    ///
+    /// The returned path is not flattened.
+    pub fn route(&self, route_pair: RoutePair<NodeIdx>, routing_cfg: &Config) -> Option<Path> {
+        Engine::route_with(
+            &mut self.workspace(),
+            &self.arc_graph,
+            routing_cfg,
+            route_pair,
+        )
+    }
+
+    /// Explorates all best paths between the route-pair.
+    ///
+    /// If the routing-config's algorithm is an explorator, its underlying Dijkstra is used.
+    /// Otherwise, the routing-config's algorithm is used directly.
+    pub fn explore(&self, route_pair: RoutePair<NodeIdx>, routing_cfg: &Config) -> Vec<Path> {
+        Engine::explore_with(
+            &mut self.workspace(),
+            &self.arc_graph,
+            routing_cfg,
+            route_pair,
+        )
+    }
+
+    /// Like `route(...)`, but for many route-pairs in parallel.
+    /// The returned paths are in the order of the provided route-pairs.
+    pub fn route_batch(
+        &self,
+        route_pairs: Vec<RoutePair<NodeIdx>>,
+        routing_cfg: &Config,
+    ) -> err::Result<Vec<Option<Path>>> {
+        self.work_off(route_pairs, routing_cfg, Engine::route_with)
+    }
+
+    /// Like `explore(...)`, but for many route-pairs in parallel.
+    /// The returned paths are in the order of the provided route-pairs.
+    pub fn explore_batch(
+        &self,
+        route_pairs: Vec<RoutePair<NodeIdx>>,
+        routing_cfg: &Config,
+    ) -> err::Result<Vec<Vec<Path>>> {
+        Ok(self
+            .explore_batch_counting_pruned(route_pairs, routing_cfg)?
+            .into_iter()
+            .map(|(found_paths, _)| found_paths)
+            .collect())
+    }
+
+    /// Like `explore_batch(...)`, but every exploration's paths come with the number of cells,
+    /// which have been pruned due to tolerances (see `ConvexHullExplorator::pruned_cell_count()`).
+    pub fn explore_batch_counting_pruned(
+        &self,
+        route_pairs: Vec<RoutePair<NodeIdx>>,
+        routing_cfg: &Config,
+    ) -> err::Result<Vec<(Vec<Path>, usize)>> {
+        self.work_off(
+            route_pairs,
+            routing_cfg,
+            |workspace, graph, routing_cfg, route_pair| {
+                let found_paths = Engine::explore_with(workspace, graph, routing_cfg, route_pair);
+                (found_paths, workspace.explorator.pruned_cell_count())
+            },
+        )
+    }
+
+    fn route_with(
+        workspace: &mut Workspace,
+        graph: &Graph,
+        routing_cfg: &Config,
+        route_pair: RoutePair<NodeIdx>,
+    ) -> Option<Path> {
+        let query = dijkstra::Query {
+            src_idx: route_pair.src,
+            dst_idx: route_pair.dst,
+            graph,
+            routing_cfg,
+        };
+
+        match routing_cfg.routing_algo {
+            RoutingAlgo::Dijkstra | RoutingAlgo::CHDijkstra => {
+                workspace.dijkstra.compute_best_path(query)
+            }
+            RoutingAlgo::Explorator { algo } => {
+                let mut routing_cfg = routing_cfg.clone();
+                routing_cfg.routing_algo = RoutingAlgo::from(algo);
+                workspace.dijkstra.compute_best_path(dijkstra::Query {
+                    routing_cfg: &routing_cfg,
+                    ..query
+                })
+            }
+        }
+    }
+
+    fn explore_with(
+        workspace: &mut Workspace,
+        graph: &Graph,
+        routing_cfg: &Config,
+        route_pair: RoutePair<NodeIdx>,
+    ) -> Vec<Path> {
+        let query = dijkstra::Query {
+            src_idx: route_pair.src,
+            dst_idx: route_pair.dst,
+            graph,
+            routing_cfg,
+        };
+
+        let Workspace {
+            dijkstra,
+            explorator,
+        } = workspace;
+        match routing_cfg.routing_algo {
+            RoutingAlgo::Dijkstra | RoutingAlgo::CHDijkstra => {
+                explorator.fully_explorate(query, dijkstra)
+            }
+            RoutingAlgo::Explorator { algo } => {
+                let mut routing_cfg = routing_cfg.clone();
+                routing_cfg.routing_algo = RoutingAlgo::from(algo);
+                explorator.fully_explorate(
+                    dijkstra::Query {
+                        routing_cfg: &routing_cfg,
+                        ..query
+                    },
+                    dijkstra,
+                )
+            }
+        }
+    }
+
+    /// Every thread takes the next unprocessed route-pair until all route-pairs are processed,
+    /// so expensive queries don't block the remaining work.
+    fn work_off<T: Send>(
+        &self,
+        route_pairs: Vec<RoutePair<NodeIdx>>,
+        routing_cfg: &Config,
+        query: fn(&mut Workspace, &Graph, &Config, RoutePair<NodeIdx>) -> T,
+    ) -> err::Result<Vec<T>> {
+        let num_of_route_pairs = route_pairs.len();
+        let num_threads = self.num_threads.min(num_of_route_pairs);
+
+        // no need for spawning threads
+        if num_threads <= 1 {
+            let mut workspace = self.workspace();
+            return Ok(route_pairs
+                .into_iter()
+                .map(|route_pair| query(&mut workspace, &self.arc_graph, routing_cfg, route_pair))
+                .collect());
+        }
+
+        let next_idx = AtomicUsize::new(0);
+
+        // Scoped threads borrow the engine, the route-pairs and the routing-config directly.
+        let thread_outcomes: Vec<Vec<(usize, T)>> = thread::scope(|scope| {
+            let handles: Vec<_> = (0..num_threads)
+                .map(|_| {
+                    scope.spawn(|| {
+                        let mut workspace = self.workspace();
+                        let mut outcomes = Vec::new();
+                        loop {
+                            let idx = next_idx.fetch_add(1, Ordering::Relaxed);
+                            if idx >= route_pairs.len() {
+                                break;
+                            }
+                            let outcome = query(
+                                &mut workspace,
+                                &self.arc_graph,
+                                routing_cfg,
+                                route_pairs[idx],
+                            );
+                            outcomes.push((idx, outcome));
+                        }
+                        outcomes
+                    })
+                })
+                .collect();
+
+            handles
+                .into_iter()
+                .map(|handle| {
+                    handle
+                        .join()
+                        .map_err(|e| err::Msg::from(format!("Joining stucks due to {:?}", e)))
+                })
+                .collect::<err::Result<_>>()
+        })?;
+
+        // restore the order of the route-pairs
+
+        let mut outcomes: Vec<Option<T>> = (0..num_of_route_pairs).map(|_| None).collect();
+        for (idx, outcome) in thread_outcomes.into_iter().flatten() {
+            outcomes[idx] = Some(outcome);
+        }
+
+        outcomes
+            .into_iter()
+            .map(|outcome| {
+                outcome
+                    .ok_or_else(|| err::Msg::from("Every route-pair should have been processed."))
+            })
+            .collect()
+    }
+}
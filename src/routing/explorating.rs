@@ -7,22 +7,24 @@ use crate::{
     approximating::Approx,
     configs,
     defaults::{self, capacity::DimVec},
-    helpers::{self, algebra},
-    network::{Graph, NodeIdx},
+    helpers::{
+        self, algebra,
+        convex_hull::{self, ConvexHull},
+    },
+    network::{Graph, MetricIdx, NodeIdx},
     routing::{
         dijkstra::{self, Dijkstra},
         paths::Path,
     },
 };
 use log::{debug, trace, warn};
-use nd_triangulation::Triangulation;
 use smallvec::smallvec;
 use std::{
     collections::{HashMap, HashSet},
     ops::Deref,
 };
 
-// needed because convex-hull has dim+1 points per cell
+// needed because a simplex of the convex-hull has up to dim+1 points
 type CHDimVec<T> = smallvec::SmallVec<[T; defaults::capacity::SMALL_VEC_INLINE_SIZE + 1]>;
 
 struct Query<'a> {
@@ -31,7 +33,7 @@ struct Query<'a> {
     graph: &'a Graph,
     routing_cfg: configs::routing::Config,
     graph_dim: usize,
-    triangulation_dim: usize,
+    convex_hull_dim: usize,
     tolerances: DimVec<f64>,
     is_metric_considered: DimVec<bool>,
 }
@@ -48,6 +50,8 @@ impl<'a> Query<'a> {
         // config and stuff
         let graph_dim = graph.metrics().dim();
         // Every cost-value has to be below this value.
+        // Finite tolerances are relative to the best path of each metric, hence they are set
+        // after the initial paths have been explored.
         let tolerances: DimVec<_> = smallvec![defaults::routing::TOLERATED_SCALE_INF; graph_dim];
         // don't consider ignored metrics
         let is_metric_considered: DimVec<_> = routing_cfg
@@ -63,7 +67,7 @@ impl<'a> Query<'a> {
             graph,
             routing_cfg,
             graph_dim,
-            triangulation_dim: is_metric_considered
+            convex_hull_dim: is_metric_considered
                 .iter()
                 .filter(|&&is_considered| is_considered)
                 .count(),
@@ -71,6 +75,18 @@ impl<'a> Query<'a> {
             is_metric_considered,
         }
     }
+
+    /// Checks if the cost doesn't exceed the tolerance, respecting the storage-precision
+    /// of the metric (e.g. fixed-point metrics can't be compared with `F64_ABS`).
+    fn is_tolerated(&self, dim_i: usize, cost: f64, tolerance: f64) -> bool {
+        let precision = self
+            .graph
+            .cfg()
+            .edges
+            .metrics
+            .precision_of(MetricIdx(dim_i));
+        Approx(cost).le_within(&Approx(tolerance), precision.tolerance(tolerance))
+    }
 }
 
 #[derive(Clone, Copy, Eq, Hash, Ord, PartialEq, PartialOrd)]
@@ -105,6 +121,8 @@ impl Deref for CellId {
 struct Cell<'a> {
     id: CellId,
     vertices: CHDimVec<Vertex<'a>>,
+    // metric-indices, whose alpha has to be zero
+    rays: DimVec<usize>,
 }
 
 impl<'a> Cell<'a> {
@@ -115,12 +133,17 @@ impl<'a> Cell<'a> {
     pub fn vertices(&self) -> &CHDimVec<Vertex<'a>> {
         &self.vertices
     }
+
+    pub fn rays(&self) -> &DimVec<usize> {
+        &self.rays
+    }
 }
 
 pub struct ConvexHullExplorator {
     found_paths: HashMap<VertexId, Path>,
     tolerated_found_paths: Vec<VertexId>,
     visited_cells: HashSet<CellId>,
+    pruned_cell_count: usize,
 }
 
 impl ConvexHullExplorator {
@@ -129,14 +152,22 @@ impl ConvexHullExplorator {
             found_paths: HashMap::new(),
             tolerated_found_paths: Vec::new(),
             visited_cells: HashSet::new(),
+            pruned_cell_count: 0,
         }
     }
 
-    // TODO cap exploration with epsilon for routing-costs (1 + eps) * costs[i]
-    //
-    // New paths of a facet are linear-combinations of its defining paths
-    // -> could not be better than the best of already defined paths
+    /// Number of cells, which have been skipped in the last exploration,
+    /// because their paths couldn't undercut the tolerated costs.
+    pub fn pruned_cell_count(&self) -> usize {
+        self.pruned_cell_count
+    }
 
+    /// The exploration is capped by the routing-config's tolerated scales.
+    /// For every metric `i`, a path is tolerated if `costs[i] <= tolerated_scale[i] * best_costs[i]`
+    /// (e.g. a tolerated scale of `1 + eps`).
+    /// New paths of a facet are linear-combinations of its defining paths
+    /// -> could not be better than the best of already defined paths
+    /// -> Facets, that can't undercut any tolerance, are pruned.
     pub fn fully_explorate(
         &mut self,
         query: dijkstra::Query,
@@ -155,40 +186,39 @@ impl ConvexHullExplorator {
             );
         }
 
-        let mut triangulation = Triangulation::new(query.triangulation_dim);
-        let mut is_triangulation_dirty = false;
+        let mut convex_hull = ConvexHull::new(query.convex_hull_dim);
+        let mut is_convex_hull_dirty = false;
 
         self.found_paths.clear();
         self.tolerated_found_paths.clear();
         self.visited_cells.clear();
+        self.pruned_cell_count = 0;
         let mut new_found_paths = Vec::new();
         ConvexHullExplorator::explore_initial_paths(&mut new_found_paths, &mut query, dijkstra);
+        ConvexHullExplorator::explore_remaining_tolerances(&mut query, dijkstra);
         self.update(
             &query,
-            &mut is_triangulation_dirty,
+            &mut is_convex_hull_dirty,
             &mut new_found_paths,
-            &mut triangulation,
+            &mut convex_hull,
         );
 
         // explore
 
-        // +1 because a convex-hull (volume) needs dim+1 points
-        // For imagination:
-        // - line vs triangle in 2D
-        // - triangle vs tetrahedron in 3D
-        if query.triangulation_dim > 1
-            && self.found_paths.len() + new_found_paths.len() > query.triangulation_dim
-        {
+        // The lower convex-hull is extended by rays of the positive orthant,
+        // so two paths are enough to span a cell.
+        if query.convex_hull_dim > 1 && self.found_paths.len() + new_found_paths.len() > 1 {
             // find new routes
 
             trace!(
-                "Start exploring new alternative routes, because triangulation of dim {} is ready.",
-                query.triangulation_dim
+                "Start exploring new alternative routes, because convex hull of dim {} is ready.",
+                query.convex_hull_dim
             );
             trace!("Use tolerances {:?}", query.tolerances);
-            while is_triangulation_dirty {
+            while is_convex_hull_dirty {
                 trace!("Found {} paths yet.", self.found_paths.len());
-                for raw_cell in triangulation.convex_hull_cells() {
+                // Only lower cells lead to non-negative alphas.
+                for raw_cell in convex_hull.lower_cells() {
                     // don't look at cells twice
                     if self.visited_cells.contains(&CellId(raw_cell.id())) {
                         trace!(
@@ -198,7 +228,7 @@ impl ConvexHullExplorator {
                         continue;
                     }
 
-                    let cell = ConvexHullExplorator::cell_from(raw_cell, &self.found_paths);
+                    let cell = ConvexHullExplorator::cell_from(raw_cell, &query, &self.found_paths);
                     self.visited_cells.insert(*cell.id());
 
                     // A correct convex-hull implies following statements.
@@ -215,12 +245,9 @@ impl ConvexHullExplorator {
                         .tolerances
                         .iter()
                         .enumerate()
-                        // ...check if it is considered at all,
-                        // which is unnecessary here, because unconsidered metrics
-                        // have a tolerance of infinity.
-                        // .filter(|(dim_i, _tolerance)| query.is_metric_considered[*dim_i])
-                        // If tolerance is considered, check if any tolerance can't be undercut
-                        // by any path's cost.
+                        // ...check if any tolerance can't be undercut by any path's cost.
+                        // Unconsidered metrics are checked as well, because path-costs
+                        // contain all metrics and their tolerances are infinite if not set.
                         .any(|(dim_i, tolerance)| {
                             // So test here, if the given tolerance can be undercut by any cost.
                             // If not, this cell should not be considered.
@@ -228,7 +255,7 @@ impl ConvexHullExplorator {
                                 .vertices()
                                 .iter()
                                 .map(|vertex| vertex.path.costs()[dim_i])
-                                .any(|dim_cost| &dim_cost <= tolerance)
+                                .any(|dim_cost| query.is_tolerated(dim_i, dim_cost, *tolerance))
                         })
                     {
                         trace!(
@@ -237,6 +264,7 @@ impl ConvexHullExplorator {
                             **cell.id(),
                             "), that can't undercut at least one tolerance."
                         );
+                        self.pruned_cell_count += 1;
                         continue;
                     }
                     trace!("Explore cell of cell-id {}", **cell.id());
@@ -323,13 +351,22 @@ impl ConvexHullExplorator {
 
                 self.update(
                     &query,
-                    &mut is_triangulation_dirty,
+                    &mut is_convex_hull_dirty,
                     &mut new_found_paths,
-                    &mut triangulation,
+                    &mut convex_hull,
                 );
             }
         }
 
+        debug!(
+            "{}{}{}{}{}",
+            "Pruned ",
+            self.pruned_cell_count,
+            " of ",
+            self.visited_cells.len(),
+            " visited cells due to tolerances."
+        );
+
         // if paths were found but no one is tolerated
         if self.found_paths.len() > 0 && self.tolerated_found_paths.len() == 0 {
             warn!(
@@ -456,23 +493,74 @@ impl ConvexHullExplorator {
         }
     }
 
+    /// Metrics, which are not considered in the exploration (alpha is 0.0),
+    /// could still have a finite tolerated scale.
+    /// Their best costs are not known from the initial paths, so they are computed here.
+    fn explore_remaining_tolerances(query: &mut Query, dijkstra: &mut Dijkstra) {
+        let old_alphas = query.routing_cfg.alphas.clone();
+
+        for metric_idx in 0..query.graph_dim {
+            let tolerated_scale = query.routing_cfg.tolerated_scales[metric_idx];
+            if query.is_metric_considered[metric_idx]
+                || tolerated_scale == defaults::routing::TOLERATED_SCALE_INF
+            {
+                continue;
+            }
+
+            let mut alphas = smallvec![0.0; query.graph_dim];
+            alphas[metric_idx] = 1.0;
+            query.routing_cfg.alphas = alphas;
+            trace!(
+                "Trying alpha {:?} for tolerance of unconsidered metric",
+                query.routing_cfg.alphas
+            );
+
+            if let Some(mut best_path) = dijkstra.compute_best_path(dijkstra::Query {
+                src_idx: query.src_idx,
+                dst_idx: query.dst_idx,
+                graph: query.graph,
+                routing_cfg: &query.routing_cfg,
+            }) {
+                best_path.calc_costs(query.graph);
+                query.tolerances[metric_idx] = best_path.costs()[metric_idx] * tolerated_scale;
+            }
+        }
+
+        query.routing_cfg.alphas = old_alphas;
+    }
+
     fn cell_from<'a>(
-        cell: nd_triangulation::Cell,
+        cell: &convex_hull::Facet,
+        query: &Query,
         found_paths: &'a HashMap<VertexId, Path>,
     ) -> Cell<'a> {
+        // convex-hull contains only considered metrics
+        let considered_metric_indices: DimVec<_> = query
+            .is_metric_considered
+            .iter()
+            .enumerate()
+            .filter(|&(_, &imc)| imc)
+            .map(|(metric_idx, _)| metric_idx)
+            .collect();
+
         Cell {
             id: CellId(cell.id()),
             vertices: cell
                 .vertices()
-                .into_iter()
-                .map(|vertex| VertexId(vertex.id()))
+                .iter()
+                .map(|&vertex_id| VertexId(vertex_id))
                 .map(|vertex_id| Vertex {
                     id: vertex_id,
                     path: found_paths.get(&vertex_id).expect(
-                        "For every vertex in the triangulation, a path should be registered.",
+                        "For every vertex in the convex hull, a path should be registered.",
                     ),
                 })
                 .collect(),
+            rays: cell
+                .rays()
+                .iter()
+                .map(|&dim_idx| considered_metric_indices[dim_idx])
+                .collect(),
         }
     }
 
@@ -513,6 +601,14 @@ impl ConvexHullExplorator {
             b.push(0.0);
         }
 
+        // and metrics of the cell's rays as well
+        for &i in cell.rays() {
+            let mut row = smallvec![0.0; query.graph_dim];
+            row[i] = 1.0;
+            rows.push(row);
+            b.push(0.0);
+        }
+
         // if one condition is missing (depending on convex-hull-implementation),
         match query.graph_dim - rows.len() {
             0 => (),
@@ -534,21 +630,21 @@ impl ConvexHullExplorator {
     fn update(
         &mut self,
         query: &Query,
-        is_triangulation_dirty: &mut bool,
+        is_convex_hull_dirty: &mut bool,
         new_found_paths: &mut Vec<Path>,
-        triangulation: &mut Triangulation,
+        convex_hull: &mut ConvexHull,
     ) {
         trace!(
-            "Updating triangulation with {} new found paths.",
+            "Updating convex hull with {} new found paths.",
             new_found_paths.len()
         );
-        *is_triangulation_dirty = new_found_paths.len() > 0;
+        *is_convex_hull_dirty = !new_found_paths.is_empty();
 
-        // add new paths to triangulation
+        // add new paths to convex hull
         // but only with considered metrics
 
         for path in new_found_paths.drain(..) {
-            let new_raw_id = triangulation
+            let new_raw_id = convex_hull
                 .add_vertex(
                     &path
                         .costs()
@@ -567,7 +663,13 @@ impl ConvexHullExplorator {
             let new_id = VertexId(new_raw_id);
 
             // Remember path if it can be returned in the end.
-            if Approx(path.costs()) <= Approx(&query.tolerances) {
+            if path
+                .costs()
+                .iter()
+                .zip(query.tolerances.iter())
+                .enumerate()
+                .all(|(dim_i, (cost, tolerance))| query.is_tolerated(dim_i, *cost, *tolerance))
+            {
                 self.tolerated_found_paths.push(new_id);
             }
 
@@ -578,8 +680,8 @@ impl ConvexHullExplorator {
             "All new found paths should be added by now."
         );
         trace!(
-            "Triangulation is {}dirty.",
-            if *is_triangulation_dirty { "" } else { "not " }
+            "Convex hull is {}dirty.",
+            if *is_convex_hull_dirty { "" } else { "not " }
         );
     }
 }
@@ -0,0 +1,350 @@
+use crate::{
+    configs::parsing::edges::metrics::UnitInfo,
+    defaults::{self, capacity::DimVec},
+    helpers,
+    network::{EdgeIdx, Graph, MetricIdx, NodeIdx},
+    routing::paths::Path,
+};
+use kissunits::{
+    distance::{Kilometers, Meters},
+    geo::Coordinate,
+    time::{Hours, Minutes, Seconds},
+};
+use smallvec::smallvec;
+use std::fmt::{self, Display};
+
+/// What to do at a maneuver's location.
+///
+/// Turns are classified by the angle between the arriving and the leaving edge (see `defaults::guidance`).
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ManeuverKind {
+    Depart,
+    Continue,
+    SlightLeft,
+    Left,
+    SharpLeft,
+    SlightRight,
+    Right,
+    SharpRight,
+    UTurn,
+    /// Leaving a motorway (or trunk) onto a link
+    TakeExit,
+    /// Entering a roundabout, which is left at the given exit (starting with 1)
+    Roundabout {
+        exit: usize,
+    },
+    Arrive,
+}
+
+impl ManeuverKind {
+    /// Classifies a turn-angle in degrees, where positive angles are right turns.
+    fn from_angle(angle: f64) -> ManeuverKind {
+        let abs_angle = angle.abs();
+        if abs_angle < defaults::guidance::MIN_SLIGHT_TURN_DEG {
+            ManeuverKind::Continue
+        } else if abs_angle >= defaults::guidance::MIN_U_TURN_DEG {
+            ManeuverKind::UTurn
+        } else if abs_angle >= defaults::guidance::MIN_SHARP_TURN_DEG {
+            if angle < 0.0 {
+                ManeuverKind::SharpLeft
+            } else {
+                ManeuverKind::SharpRight
+            }
+        } else if abs_angle >= defaults::guidance::MIN_TURN_DEG {
+            if angle < 0.0 {
+                ManeuverKind::Left
+            } else {
+                ManeuverKind::Right
+            }
+        } else if angle < 0.0 {
+            ManeuverKind::SlightLeft
+        } else {
+            ManeuverKind::SlightRight
+        }
+    }
+}
+
+impl Display for ManeuverKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ManeuverKind::Depart => write!(f, "Depart"),
+            ManeuverKind::Continue => write!(f, "Continue"),
+            ManeuverKind::SlightLeft => write!(f, "Turn slightly left"),
+            ManeuverKind::Left => write!(f, "Turn left"),
+            ManeuverKind::SharpLeft => write!(f, "Turn sharply left"),
+            ManeuverKind::SlightRight => write!(f, "Turn slightly right"),
+            ManeuverKind::Right => write!(f, "Turn right"),
+            ManeuverKind::SharpRight => write!(f, "Turn sharply right"),
+            ManeuverKind::UTurn => write!(f, "Make a U-turn"),
+            ManeuverKind::TakeExit => write!(f, "Take the exit"),
+            ManeuverKind::Roundabout { exit } => {
+                write!(f, "At the roundabout, take exit {}", exit)
+            }
+            ManeuverKind::Arrive => write!(f, "Arrive at the destination"),
+        }
+    }
+}
+
+/// A single instruction of a path's guidance.
+#[derive(Clone, Debug)]
+pub struct Maneuver {
+    pub kind: ManeuverKind,
+    /// The street (see `network::Street::label()`), which is followed after this maneuver
+    pub street: Option<String>,
+    /// Where the maneuver takes place
+    pub coord: Coordinate,
+    /// The (denormalized) metrics until the next maneuver
+    pub costs: DimVec<f64>,
+    /// Taken from the first metric in kilometers or meters, if any
+    pub distance: Option<Kilometers>,
+    /// Taken from the first metric in seconds, minutes or hours, if any
+    pub duration: Option<Seconds>,
+}
+
+impl Maneuver {
+    /// Returns e.g. `Turn left onto Hauptstraße`.
+    pub fn instruction(&self) -> String {
+        match (&self.kind, &self.street) {
+            (ManeuverKind::Depart, Some(street)) | (ManeuverKind::Arrive, Some(street)) => {
+                format!("{} on {}", self.kind, street)
+            }
+            (_, Some(street)) => format!("{} onto {}", self.kind, street),
+            (_, None) => format!("{}", self.kind),
+        }
+    }
+}
+
+impl Display for Maneuver {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.instruction())?;
+        if let Some(distance) = self.distance {
+            write!(f, " ({:.3} km", distance.0)?;
+            if let Some(duration) = self.duration {
+                write!(f, ", {:.0} s", duration.0)?;
+            }
+            write!(f, ")")?;
+        }
+        Ok(())
+    }
+}
+
+/// A part of an edge, which is part of the path (see `Path`'s partial edges).
+struct Leg {
+    edge_idx: EdgeIdx,
+    fraction: f64,
+}
+
+/// Turns a path into maneuvers, starting with `Depart` and ending with `Arrive`.
+///
+/// A maneuver is created at every junction, where the street changes (if the graph has streets, see `network::Street`)
+/// or where the path turns, while other edges could be taken.
+/// Roundabouts and motorway-exits are recognized by their streets.
+/// Paths with shortcuts are flattened before.
+pub fn compute_maneuvers(path: &Path, graph: &Graph) -> Vec<Maneuver> {
+    let fwd_edges = graph.fwd_edges();
+    let bwd_edges = graph.bwd_edges();
+    let streets = graph.streets();
+
+    let path = if path.iter().any(|&edge_idx| fwd_edges.is_shortcut(edge_idx)) {
+        path.clone().flatten(graph)
+    } else {
+        path.clone()
+    };
+
+    // collect legs, where partial edges are shortened
+
+    let mut legs = Vec::with_capacity(path.iter().len() + 2);
+    if let Some(partial_edge) = path.first_partial_edge() {
+        legs.push(Leg {
+            edge_idx: partial_edge.edge_idx,
+            fraction: partial_edge.fraction(),
+        });
+    }
+    legs.extend(path.iter().map(|&edge_idx| Leg {
+        edge_idx,
+        fraction: 1.0,
+    }));
+    if let Some(partial_edge) = path.last_partial_edge() {
+        legs.push(Leg {
+            edge_idx: partial_edge.edge_idx,
+            fraction: partial_edge.fraction(),
+        });
+    }
+    let coords = path.coords(graph);
+
+    let label_of = |leg_idx: usize| {
+        legs.get(leg_idx)
+            .and_then(|leg| streets.of(leg.edge_idx))
+            .and_then(|street| street.label())
+    };
+    let is_roundabout = |leg_idx: usize| match streets.of(legs[leg_idx].edge_idx) {
+        Some(street) => street.is_roundabout,
+        None => false,
+    };
+    // edges, which could be taken instead of the leg's edge (excluding the way back)
+    let alternatives = |leg_idx: usize, is_exit: &dyn Fn(EdgeIdx) -> bool| {
+        let node_idx = bwd_edges.dst_idx(legs[leg_idx].edge_idx);
+        let prev_src_idx = bwd_edges.dst_idx(legs[leg_idx - 1].edge_idx);
+        fwd_edges
+            .starting_from(node_idx)
+            .filter(|edge| !edge.is_shortcut())
+            .filter(|edge| edge.idx() != legs[leg_idx].edge_idx)
+            .filter(|edge| edge.dst_idx() != prev_src_idx)
+            .filter(|edge| is_exit(edge.idx()))
+            .count()
+    };
+
+    // find maneuvers as (leg-idx of the maneuver's first leg, kind, leg-idx of the followed street)
+
+    let mut proto_maneuvers = vec![(0, ManeuverKind::Depart, 0)];
+    let mut leg_idx = 1;
+    while leg_idx < legs.len() {
+        let prev_edge_idx = legs[leg_idx - 1].edge_idx;
+        let edge_idx = legs[leg_idx].edge_idx;
+
+        // roundabouts are summarized by their exit
+
+        if is_roundabout(leg_idx) && !is_roundabout(leg_idx - 1) {
+            let mut exit = 0;
+            let mut exit_leg_idx = leg_idx + 1;
+            while exit_leg_idx < legs.len() && is_roundabout(exit_leg_idx) {
+                exit += alternatives(exit_leg_idx, &|edge_idx| match streets.of(edge_idx) {
+                    Some(street) => !street.is_roundabout,
+                    None => true,
+                })
+                .min(1);
+                exit_leg_idx += 1;
+            }
+            proto_maneuvers.push((
+                leg_idx,
+                ManeuverKind::Roundabout { exit: exit + 1 },
+                exit_leg_idx,
+            ));
+            leg_idx = exit_leg_idx + 1;
+            continue;
+        }
+
+        let prev_street = streets.of(prev_edge_idx);
+        let street = streets.of(edge_idx);
+        let kind = ManeuverKind::from_angle(turn_angle(prev_edge_idx, edge_idx, graph));
+        let (is_exit, is_same_road) = match (prev_street, street) {
+            (Some(prev_street), Some(street)) => (
+                prev_street.is_motorway && !prev_street.is_link && street.is_link,
+                // e.g. a road changing its name in a village
+                prev_street.reference.is_some() && prev_street.reference == street.reference,
+            ),
+            _ => (false, false),
+        };
+        let is_street_changing = label_of(leg_idx - 1) != label_of(leg_idx)
+            && !(is_same_road && kind == ManeuverKind::Continue);
+        if is_exit {
+            proto_maneuvers.push((leg_idx, ManeuverKind::TakeExit, leg_idx));
+        } else if is_street_changing
+            || (kind != ManeuverKind::Continue && alternatives(leg_idx, &|_| true) > 0)
+        {
+            proto_maneuvers.push((leg_idx, kind, leg_idx));
+        }
+        leg_idx += 1;
+    }
+
+    // accumulate metrics until the next maneuver
+
+    let metrics = graph.metrics();
+    let units = &graph.cfg().edges.metrics.units;
+    let distance_idx = units
+        .iter()
+        .position(|unit| *unit == UnitInfo::Kilometers || *unit == UnitInfo::Meters)
+        .map(MetricIdx);
+    let duration_idx = units
+        .iter()
+        .position(|unit| {
+            *unit == UnitInfo::Seconds || *unit == UnitInfo::Minutes || *unit == UnitInfo::Hours
+        })
+        .map(MetricIdx);
+    let create_maneuver = |kind, street_leg_idx, coord, legs: &[Leg]| {
+        let mut costs: DimVec<f64> = smallvec![0.0; metrics.dim()];
+        for leg in legs {
+            let leg_costs: DimVec<f64> = metrics
+                .get(leg.edge_idx)
+                .iter()
+                .map(|metric| leg.fraction * metric)
+                .collect();
+            helpers::add_assign(&mut costs, &leg_costs);
+        }
+        for (metric_idx, cost) in costs.iter_mut().enumerate() {
+            if let Some(mean) = metrics.mean(MetricIdx(metric_idx)) {
+                *cost *= mean;
+            }
+        }
+
+        Maneuver {
+            kind,
+            street: label_of(street_leg_idx),
+            coord,
+            distance: distance_idx.map(|metric_idx| match units[*metric_idx] {
+                UnitInfo::Meters => Kilometers::from(Meters(costs[*metric_idx])),
+                _ => Kilometers(costs[*metric_idx]),
+            }),
+            duration: duration_idx.map(|metric_idx| match units[*metric_idx] {
+                UnitInfo::Minutes => Seconds::from(Minutes(costs[*metric_idx])),
+                UnitInfo::Hours => Seconds::from(Hours(costs[*metric_idx])),
+                _ => Seconds(costs[*metric_idx]),
+            }),
+            costs,
+        }
+    };
+
+    let mut maneuvers = Vec::with_capacity(proto_maneuvers.len() + 1);
+    for (i, &(first_leg_idx, kind, street_leg_idx)) in proto_maneuvers.iter().enumerate() {
+        let end_leg_idx = proto_maneuvers
+            .get(i + 1)
+            .map_or(legs.len(), |&(next_leg_idx, _, _)| next_leg_idx);
+        maneuvers.push(create_maneuver(
+            kind,
+            street_leg_idx,
+            coords[first_leg_idx],
+            &legs[first_leg_idx..end_leg_idx],
+        ));
+    }
+    maneuvers.push(create_maneuver(
+        ManeuverKind::Arrive,
+        legs.len().saturating_sub(1),
+        coords[coords.len() - 1],
+        &[],
+    ));
+    maneuvers
+}
+
+/// The angle in degrees (in `(-180, 180]`) between two consecutive edges, where positive angles are right turns.
+fn turn_angle(prev_edge_idx: EdgeIdx, edge_idx: EdgeIdx, graph: &Graph) -> f64 {
+    let nodes = graph.nodes();
+    let fwd_edges = graph.fwd_edges();
+    let bwd_edges = graph.bwd_edges();
+    let coord_of = |node_idx: NodeIdx| nodes.coord(node_idx);
+
+    let prev_bearing = bearing(
+        &coord_of(bwd_edges.dst_idx(prev_edge_idx)),
+        &coord_of(fwd_edges.dst_idx(prev_edge_idx)),
+    );
+    let next_bearing = bearing(
+        &coord_of(bwd_edges.dst_idx(edge_idx)),
+        &coord_of(fwd_edges.dst_idx(edge_idx)),
+    );
+
+    let mut angle = next_bearing - prev_bearing;
+    while angle <= -180.0 {
+        angle += 360.0;
+    }
+    while angle > 180.0 {
+        angle -= 360.0;
+    }
+    angle
+}
+
+/// The bearing in degrees (clockwise from north), approximated locally by an equirectangular projection.
+fn bearing(from: &Coordinate, to: &Coordinate) -> f64 {
+    let mean_lat = (0.5 * (from.lat + to.lat)).to_radians();
+    let dx = (to.lon - from.lon) * mean_lat.cos();
+    let dy = to.lat - from.lat;
+    dx.atan2(dy).to_degrees()
+}
@@ -0,0 +1,460 @@
+use crate::{
+    configs::{self, routing::RoutingAlgo},
+    defaults,
+    network::{EdgeIdx, EdgeIndex, Graph, NodeIdx, PhantomNode},
+    routing::{
+        dijkstra::{Dijkstra, PhantomQuery},
+        paths::{PartialEdge, Path},
+    },
+};
+use kissunits::geo::{self, Coordinate};
+use log::{debug, info};
+use smallvec::smallvec;
+use std::{collections::HashMap, mem};
+
+/// A measured position of a gps-trace
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct GpsPoint {
+    pub coord: Coordinate,
+    /// Seconds since the unix-epoch, if known
+    pub timestamp: Option<f64>,
+}
+
+/// The result of matching a single gps-point
+#[derive(Clone, Debug)]
+pub struct MatchedPoint {
+    /// The matched position on an edge, or `None` if no edge is within the search-radius.
+    pub phantom: Option<PhantomNode>,
+    /// Idx of the path (in `Matching::paths`) containing the matched position
+    pub path_idx: Option<usize>,
+    /// Probability of the matched position given the whole trace, or `0.0` if unmatched
+    pub confidence: f64,
+}
+
+/// The result of matching a gps-trace
+#[derive(Clone, Debug)]
+pub struct Matching {
+    /// One path per part of the trace, which could be matched continuously.
+    /// Every path starts at the first and ends at the last matched position of its part (see `Path`'s partial edges).
+    pub paths: Vec<Path>,
+    /// One entry per gps-point of the trace
+    pub points: Vec<MatchedPoint>,
+}
+
+/// Matches gps-traces onto the graph's edges using a hidden Markov model.
+///
+/// The states of a gps-point are its candidates, meaning its projections onto nearby edges (see `EdgeIndex::candidates(...)`).
+/// A candidate's emission-probability is gaussian in its distance to the gps-point.
+/// The transition-probability between candidates of consecutive gps-points decreases exponentially with the difference between
+/// their route-distance (computed via Dijkstra) and the gps-points' great-circle-distance.
+/// Routes exceeding the detour or (if timestamps are given) the max-speed are considered impossible.
+///
+/// The matched candidates are the most likely sequence of states (Viterbi).
+/// If no sequence is possible, the trace is split, and the confidences are the posterior-probabilities (forward-backward) of the matched candidates.
+pub struct MapMatcher {
+    cfg: configs::matching::Config,
+    routing_cfg: configs::routing::Config,
+    edge_index: EdgeIndex,
+    dijkstra: Dijkstra,
+}
+
+/// A gps-point with candidates, and the transitions from the previous step's candidates (log-space)
+struct Step {
+    point_idx: usize,
+    candidates: Vec<PhantomNode>,
+    emissions: Vec<f64>,
+    transitions: Vec<Vec<f64>>,
+    forwards: Vec<f64>,
+}
+
+impl MapMatcher {
+    pub fn new(graph: &Graph, cfg: configs::matching::Config) -> MapMatcher {
+        let dim = graph.cfg().edges.metrics.units.len();
+        let mut alphas = smallvec![0.0; dim];
+        alphas[*cfg.distance_idx] = 1.0;
+        let routing_cfg = configs::routing::Config {
+            route_pairs_file: None,
+            routing_algo: RoutingAlgo::Dijkstra,
+            alphas,
+            tolerated_scales: smallvec![defaults::routing::TOLERATED_SCALE_INF; dim],
+        };
+
+        MapMatcher {
+            edge_index: EdgeIndex::new(graph, cfg.snapping_filter),
+            cfg,
+            routing_cfg,
+            dijkstra: Dijkstra::new(),
+        }
+    }
+
+    pub fn cfg(&self) -> &configs::matching::Config {
+        &self.cfg
+    }
+
+    pub fn match_trace(&mut self, trace: &[GpsPoint], graph: &Graph) -> Matching {
+        info!("START Match trace of {} gps-points", trace.len());
+
+        // build the hidden Markov model and split it, where no transition is possible
+
+        let mut parts: Vec<Vec<Step>> = Vec::new();
+        let mut steps: Vec<Step> = Vec::new();
+        for (point_idx, point) in trace.iter().enumerate() {
+            let candidates = self.edge_index.candidates(
+                &point.coord,
+                self.cfg.search_radius,
+                self.cfg.max_candidates,
+            );
+            if candidates.is_empty() {
+                debug!("No candidates for gps-point {}", point_idx);
+                continue;
+            }
+            let emissions: Vec<f64> = candidates
+                .iter()
+                .map(|candidate| -0.5 * (candidate.distance.0 / self.cfg.gps_sigma.0).powi(2))
+                .collect();
+
+            let mut step = match steps.last() {
+                Some(prev_step) => {
+                    let transitions = self.transitions(
+                        &trace[prev_step.point_idx],
+                        &prev_step.candidates,
+                        point,
+                        &candidates,
+                        graph,
+                    );
+                    let forwards = emissions
+                        .iter()
+                        .enumerate()
+                        .map(|(j, emission)| {
+                            emission
+                                + log_sum_exp(
+                                    prev_step
+                                        .forwards
+                                        .iter()
+                                        .zip(transitions.iter())
+                                        .map(|(forward, row)| forward + row[j]),
+                                )
+                        })
+                        .collect();
+                    Step {
+                        point_idx,
+                        candidates,
+                        emissions,
+                        transitions,
+                        forwards,
+                    }
+                }
+                None => Step {
+                    point_idx,
+                    candidates,
+                    forwards: emissions.clone(),
+                    emissions,
+                    transitions: Vec::new(),
+                },
+            };
+
+            if step
+                .forwards
+                .iter()
+                .all(|forward| forward == &f64::NEG_INFINITY)
+            {
+                debug!("Split trace at gps-point {}", point_idx);
+                parts.push(mem::take(&mut steps));
+                step.forwards = step.emissions.clone();
+                step.transitions.clear();
+            }
+            steps.push(step);
+        }
+        if !steps.is_empty() {
+            parts.push(steps);
+        }
+
+        // decode every part and connect its matched candidates
+
+        let mut matching = Matching {
+            paths: Vec::with_capacity(parts.len()),
+            points: vec![
+                MatchedPoint {
+                    phantom: None,
+                    path_idx: None,
+                    confidence: 0.0,
+                };
+                trace.len()
+            ],
+        };
+        for steps in parts {
+            let path_idx = matching.paths.len();
+            let states = viterbi(&steps);
+            let confidences = posteriors(&steps, &states);
+
+            let mut pieces: Vec<PartialEdge> = Vec::new();
+            for (t, step) in steps.iter().enumerate() {
+                let candidate = &step.candidates[states[t]];
+                if t == 0 {
+                    let position = candidate.positions[0];
+                    pieces.push(PartialEdge {
+                        edge_idx: position.edge_idx,
+                        start: position.fraction,
+                        end: position.fraction,
+                    });
+                } else {
+                    let path = self
+                        .dijkstra
+                        .compute_best_phantom_path(PhantomQuery {
+                            src: &steps[t - 1].candidates[states[t - 1]],
+                            dst: candidate,
+                            graph,
+                            routing_cfg: &self.routing_cfg,
+                        })
+                        .expect("Transitions are only possible, if a path exists.")
+                        .flatten(graph);
+                    let path_pieces = path
+                        .first_partial_edge()
+                        .copied()
+                        .into_iter()
+                        .chain(path.iter().map(|&edge_idx| PartialEdge {
+                            edge_idx,
+                            start: 0.0,
+                            end: 1.0,
+                        }))
+                        .chain(path.last_partial_edge().copied());
+                    for piece in path_pieces {
+                        push_piece(&mut pieces, piece);
+                    }
+                }
+
+                matching.points[step.point_idx] = MatchedPoint {
+                    phantom: Some(candidate.clone()),
+                    path_idx: Some(path_idx),
+                    confidence: confidences[t],
+                };
+            }
+
+            matching.paths.push(path_from(&pieces, graph));
+        }
+
+        info!(
+            "FINISHED Matched {} of {} gps-points onto {} paths",
+            matching
+                .points
+                .iter()
+                .filter(|point| point.phantom.is_some())
+                .count(),
+            trace.len(),
+            matching.paths.len()
+        );
+        matching
+    }
+
+    /// Returns the transition-probabilities (log-space) from every previous candidate to every candidate.
+    ///
+    /// Instead of a query per pair, the route-distances are computed with one bounded search per previous candidate.
+    fn transitions(
+        &mut self,
+        prev_point: &GpsPoint,
+        prev_candidates: &[PhantomNode],
+        point: &GpsPoint,
+        candidates: &[PhantomNode],
+        graph: &Graph,
+    ) -> Vec<Vec<f64>> {
+        let fwd_edges = graph.fwd_edges();
+        let bwd_edges = graph.bwd_edges();
+        let graph_metrics = graph.metrics();
+        let distance_idx = self.cfg.distance_idx;
+        let edge_distance = |edge_idx: EdgeIdx| graph_metrics[distance_idx].get(edge_idx);
+
+        // Both candidates may be up to the search-radius away from their gps-points.
+        let tolerance = 2.0 * self.cfg.search_radius.0;
+        let great_circle_distance = geo::haversine_distance_km(&prev_point.coord, &point.coord).0;
+        let mut max_distance = great_circle_distance + tolerance + self.cfg.max_detour.0;
+        if let (Some(max_speed), Some(prev_timestamp), Some(timestamp)) =
+            (self.cfg.max_speed, prev_point.timestamp, point.timestamp)
+        {
+            let hours = (timestamp - prev_timestamp).max(0.0) / 3_600.0;
+            max_distance = max_distance.min(max_speed.0 * hours + tolerance);
+        }
+
+        let mut transitions = Vec::with_capacity(prev_candidates.len());
+        for prev_candidate in prev_candidates {
+            let prev_position = prev_candidate.positions[0];
+            let remaining_distance =
+                (1.0 - prev_position.fraction) * edge_distance(prev_position.edge_idx);
+            let reachable_nodes: HashMap<NodeIdx, f64> = if remaining_distance <= max_distance {
+                self.dijkstra
+                    .compute_reachable_nodes(
+                        fwd_edges.dst_idx(prev_position.edge_idx),
+                        max_distance - remaining_distance,
+                        graph,
+                        &self.routing_cfg,
+                    )
+                    .into_iter()
+                    .collect()
+            } else {
+                HashMap::new()
+            };
+
+            transitions.push(
+                candidates
+                    .iter()
+                    .map(|candidate| {
+                        let position = candidate.positions[0];
+                        let route_distance = if position.edge_idx == prev_position.edge_idx
+                            && prev_position.fraction <= position.fraction
+                        {
+                            Some(
+                                (position.fraction - prev_position.fraction)
+                                    * edge_distance(position.edge_idx),
+                            )
+                        } else {
+                            reachable_nodes
+                                .get(&bwd_edges.dst_idx(position.edge_idx))
+                                .map(|distance| {
+                                    remaining_distance
+                                        + distance
+                                        + position.fraction * edge_distance(position.edge_idx)
+                                })
+                        };
+
+                        match route_distance {
+                            Some(route_distance) if route_distance <= max_distance => {
+                                -(route_distance - great_circle_distance).abs()
+                                    / self.cfg.transition_beta.0
+                            }
+                            _ => f64::NEG_INFINITY,
+                        }
+                    })
+                    .collect(),
+            );
+        }
+        transitions
+    }
+}
+
+/// Returns the most likely candidate per step.
+fn viterbi(steps: &[Step]) -> Vec<usize> {
+    let mut scores: Vec<f64> = steps[0].emissions.clone();
+    let mut predecessors: Vec<Vec<usize>> = Vec::with_capacity(steps.len());
+    for step in &steps[1..] {
+        let mut new_scores = Vec::with_capacity(step.candidates.len());
+        let mut step_predecessors = Vec::with_capacity(step.candidates.len());
+        for (j, emission) in step.emissions.iter().enumerate() {
+            let (best_i, best_score) = scores
+                .iter()
+                .enumerate()
+                .map(|(i, score)| (i, score + step.transitions[i][j]))
+                .fold((0, f64::NEG_INFINITY), |best, current| {
+                    if current.1 > best.1 {
+                        current
+                    } else {
+                        best
+                    }
+                });
+            new_scores.push(emission + best_score);
+            step_predecessors.push(best_i);
+        }
+        scores = new_scores;
+        predecessors.push(step_predecessors);
+    }
+
+    let mut state = argmax(&scores);
+    let mut states = vec![state];
+    for step_predecessors in predecessors.iter().rev() {
+        state = step_predecessors[state];
+        states.push(state);
+    }
+    states.reverse();
+    states
+}
+
+/// Returns the posterior-probability of the given candidate per step.
+fn posteriors(steps: &[Step], states: &[usize]) -> Vec<f64> {
+    let last_step = &steps[steps.len() - 1];
+    let mut backwards = vec![0.0; last_step.candidates.len()];
+    let log_likelihood = log_sum_exp(last_step.forwards.iter().copied());
+
+    let mut confidences = vec![0.0; steps.len()];
+    for t in (0..steps.len()).rev() {
+        let step = &steps[t];
+        confidences[t] = (step.forwards[states[t]] + backwards[states[t]] - log_likelihood).exp();
+
+        if t > 0 {
+            backwards = (0..steps[t - 1].candidates.len())
+                .map(|i| {
+                    log_sum_exp(
+                        (0..step.candidates.len())
+                            .map(|j| step.transitions[i][j] + step.emissions[j] + backwards[j]),
+                    )
+                })
+                .collect();
+        }
+    }
+    confidences
+}
+
+fn log_sum_exp<I: Iterator<Item = f64>>(values: I) -> f64 {
+    let values: Vec<f64> = values.collect();
+    let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    if max == f64::NEG_INFINITY {
+        return max;
+    }
+    max + values
+        .iter()
+        .map(|value| (value - max).exp())
+        .sum::<f64>()
+        .ln()
+}
+
+fn argmax(values: &[f64]) -> usize {
+    let mut best = 0;
+    for (i, value) in values.iter().enumerate() {
+        if value > &values[best] {
+            best = i;
+        }
+    }
+    best
+}
+
+/// Appends the piece, merging it into the last piece if it continues on the same edge.
+fn push_piece(pieces: &mut Vec<PartialEdge>, piece: PartialEdge) {
+    if let Some(last_piece) = pieces.last_mut() {
+        if last_piece.edge_idx == piece.edge_idx && last_piece.end == piece.start {
+            last_piece.end = piece.end;
+            return;
+        }
+    }
+    pieces.push(piece);
+}
+
+/// Builds a path from consecutive pieces, where only the first and the last piece may be partial.
+fn path_from(pieces: &[PartialEdge], graph: &Graph) -> Path {
+    let nodes = graph.nodes();
+    let first_piece = pieces[0];
+    let last_piece = pieces[pieces.len() - 1];
+    let src_idx = graph.bwd_edges().dst_idx(first_piece.edge_idx);
+    let dst_idx = graph.fwd_edges().dst_idx(last_piece.edge_idx);
+
+    let mut path = if pieces.len() == 1 {
+        Path::new(
+            src_idx,
+            nodes.id(src_idx),
+            dst_idx,
+            nodes.id(dst_idx),
+            vec![],
+        )
+        .with_partial_edges(Some(first_piece), None)
+    } else {
+        Path::new(
+            src_idx,
+            nodes.id(src_idx),
+            dst_idx,
+            nodes.id(dst_idx),
+            pieces[1..pieces.len() - 1]
+                .iter()
+                .map(|piece| piece.edge_idx)
+                .collect(),
+        )
+        .with_partial_edges(Some(first_piece), Some(last_piece))
+    };
+    path.calc_costs(graph);
+    path
+}
@@ -1,5 +1,9 @@
 pub mod dijkstra;
 pub mod paths;
 
-#[cfg(feature = "gpl")]
 pub mod explorating;
+pub mod guidance;
+pub mod matching;
+
+mod engine;
+pub use engine::{Engine, PooledWorkspace, Workspace};
@@ -3,13 +3,36 @@ use crate::{
     helpers::{self, err},
     network::{EdgeIdx, Graph, NodeIdx},
 };
+use kissunits::geo::Coordinate;
 use smallvec::smallvec;
 use std::{
     cmp::{Eq, PartialEq},
     fmt::{self, Display},
 };
 
+/// A part of an edge, given as fractions of the edge from its src (`0.0`) to its dst (`1.0`).
+///
+/// Paths between phantom-nodes start and end on such partial edges.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct PartialEdge {
+    pub edge_idx: EdgeIdx,
+    pub start: f64,
+    pub end: f64,
+}
+
+impl PartialEdge {
+    /// Share of the edge's metrics, which is part of the path
+    pub fn fraction(&self) -> f64 {
+        self.end - self.start
+    }
+}
+
 /// A path from a src to a dst storing all edges in between.
+///
+/// Paths between phantom-nodes (see `dijkstra::PhantomQuery`) have a partial first and a partial last edge.
+/// These partial edges are not part of the path's edges (e.g. when iterating), but of its costs.
+/// Together, the partial first edge, the path's edges and the partial last edge form a sequence of edges from the src to the dst.
+/// If both phantom-nodes lie on the same edge, the path consists of only a partial first edge.
 #[derive(Clone, Debug)]
 pub struct Path {
     src_idx: NodeIdx,
@@ -17,6 +40,8 @@ pub struct Path {
     dst_idx: NodeIdx,
     dst_id: i64,
     edges: Vec<EdgeIdx>,
+    first_partial_edge: Option<PartialEdge>,
+    last_partial_edge: Option<PartialEdge>,
     costs: Option<DimVec<f64>>,
 }
 
@@ -50,10 +75,24 @@ impl Path {
             dst_idx,
             dst_id,
             edges,
+            first_partial_edge: None,
+            last_partial_edge: None,
             costs: None,
         }
     }
 
+    /// Sets the partial edges of a path between phantom-nodes, which resets the path's costs.
+    pub fn with_partial_edges(
+        mut self,
+        first_partial_edge: Option<PartialEdge>,
+        last_partial_edge: Option<PartialEdge>,
+    ) -> Path {
+        self.first_partial_edge = first_partial_edge;
+        self.last_partial_edge = last_partial_edge;
+        self.costs = None;
+        self
+    }
+
     pub fn src_idx(&self) -> NodeIdx {
         self.src_idx
     }
@@ -62,6 +101,20 @@ impl Path {
         self.dst_idx
     }
 
+    pub fn first_partial_edge(&self) -> Option<&PartialEdge> {
+        self.first_partial_edge.as_ref()
+    }
+
+    pub fn last_partial_edge(&self) -> Option<&PartialEdge> {
+        self.last_partial_edge.as_ref()
+    }
+
+    fn partial_edges(&self) -> impl Iterator<Item = &PartialEdge> {
+        self.first_partial_edge
+            .iter()
+            .chain(self.last_partial_edge.iter())
+    }
+
     /// ATTENTION! This method panics if the costs hasn't been calculated (e.g. `calc_cost(...)` or `flatten(...)`).
     pub fn costs(&self) -> &DimVec<f64> {
         self.costs
@@ -70,17 +123,23 @@ impl Path {
     }
 
     /// Calculates the path's cost, but only if not calculated already.
+    /// Partial edges contribute their share of the edge's metrics.
     pub fn calc_costs(&mut self, graph: &Graph) -> &DimVec<f64> {
         if self.costs.is_none() {
             let graph_metrics = graph.metrics();
-            self.costs = Some(
-                self.edges
-                    .iter()
-                    .map(|edge_idx| &graph_metrics[edge_idx])
-                    .fold(smallvec![0.0; graph_metrics.dim()], |acc, m| {
-                        helpers::add(&acc, m)
-                    }),
+            let mut costs = self.edges.iter().fold(
+                smallvec![0.0; graph_metrics.dim()],
+                |mut acc, &edge_idx| {
+                    for (cost, values) in acc.iter_mut().zip(graph_metrics.iter()) {
+                        *cost += values.get(edge_idx);
+                    }
+                    acc
+                },
             );
+            for partial_edge in self.partial_edges() {
+                add_partial_costs(&mut costs, partial_edge, graph);
+            }
+            self.costs = Some(costs);
         }
         self.costs
             .as_ref()
@@ -96,8 +155,21 @@ impl Path {
             dst_idx: self.dst_idx,
             dst_id: self.dst_id,
             edges: Vec::with_capacity(self.edges.capacity()),
+            first_partial_edge: self.first_partial_edge,
+            last_partial_edge: self.last_partial_edge,
             costs: Some(smallvec![0.0; graph.metrics().dim()]),
         };
+        // partial edges are never shortcuts
+        for partial_edge in self.partial_edges() {
+            add_partial_costs(
+                flattened_path
+                    .costs
+                    .as_mut()
+                    .expect("Flattened path should have calculated costs."),
+                partial_edge,
+                graph,
+            );
+        }
 
         // interpret old edges as stack, beginning with src
         let mut old_edges = self.edges;
@@ -125,7 +197,7 @@ impl Path {
                     .costs
                     .as_mut()
                     .expect("Flattened path should have calculated costs."),
-                &graph.metrics()[edge_idx],
+                &graph.metrics().get(edge_idx),
             );
         }
 
@@ -139,6 +211,38 @@ impl Path {
             Err(msg) => panic!("{}", msg),
         }
     }
+
+    /// The path's geometry from src to dst, where partial edges start and end at their interpolated coordinates.
+    /// Shortcuts are treated as straight lines, so the path should be flattened before.
+    pub fn coords(&self, graph: &Graph) -> Vec<Coordinate> {
+        let nodes = graph.nodes();
+        let fwd_edges = graph.fwd_edges();
+        let bwd_edges = graph.bwd_edges();
+        let interpolate = |partial_edge: &PartialEdge, fraction: f64| {
+            let src = nodes.coord(bwd_edges.dst_idx(partial_edge.edge_idx));
+            let dst = nodes.coord(fwd_edges.dst_idx(partial_edge.edge_idx));
+            Coordinate {
+                lat: src.lat + fraction * (dst.lat - src.lat),
+                lon: src.lon + fraction * (dst.lon - src.lon),
+            }
+        };
+
+        let mut coords = Vec::with_capacity(self.edges.len() + 3);
+        match &self.first_partial_edge {
+            Some(partial_edge) => {
+                coords.push(interpolate(partial_edge, partial_edge.start));
+                coords.push(interpolate(partial_edge, partial_edge.end));
+            }
+            None => coords.push(nodes.coord(self.src_idx)),
+        }
+        for &edge_idx in &self.edges {
+            coords.push(nodes.coord(fwd_edges.dst_idx(edge_idx)));
+        }
+        if let Some(partial_edge) = &self.last_partial_edge {
+            coords.push(interpolate(partial_edge, partial_edge.end));
+        }
+        coords
+    }
 }
 
 impl Eq for Path {}
@@ -152,6 +256,16 @@ impl PartialEq for Path {
             // -> see libcore in rust-lang/rust
             // && self.edges.len() == other.edges.len()
             && self.edges == other.edges
+            && self.first_partial_edge == other.first_partial_edge
+            && self.last_partial_edge == other.last_partial_edge
+    }
+}
+
+fn add_partial_costs(costs: &mut DimVec<f64>, partial_edge: &PartialEdge, graph: &Graph) {
+    let metrics = graph.metrics().get(partial_edge.edge_idx);
+    let fraction = partial_edge.fraction();
+    for (cost, metric) in costs.iter_mut().zip(metrics.iter()) {
+        *cost += fraction * metric;
     }
 }
 
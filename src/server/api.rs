@@ -0,0 +1,551 @@
+//! Endpoints of the server
+//!
+//! - `GET /health` returns `{ "status": "ok" }`.
+//! - `GET /metadata` returns the graph's counts, bounding-box and metrics, and the default routing-settings.
+//! - `POST /route` with `{ "src": node, "dst": node, "alphas": {...} }` returns `{ "path": path }`.
+//! - `POST /matrix` with `{ "srcs": [node], "dsts": [node], "alphas": {...} }` returns `{ "costs": [[cost]] }`.
+//! - `POST /explorator` with `{ "src": node, "dst": node, "alphas": {...}, "tolerated-scales": {...} }` returns `{ "paths": [path] }`.
+//! - `POST /isochrones` with `{ "src": node, "limits": [cost], "alphas": {...} }` returns `{ "src": node, "isochrones": [{ "limit": cost, "nodes": [node] }] }`.
+//! - `POST /nearest` with `{ "lat": lat, "lon": lon }` returns `{ "node": node, "distance": km }`.
+//!
+//! Nodes in requests are given as `{ "id": node-id }`, `{ "idx": node-idx }` or `{ "lat": lat, "lon": lon }` (snapped to the nearest node passing the server-config's snapping-filter).
+//! Alphas and tolerated-scales are objects from metric-ids to values.
+//! Metrics, which are not mentioned, get an alpha of `0.0` and an infinite tolerated-scale.
+//! If not provided, the routing-config's values are used.
+//!
+//! Paths are flattened and returned as `{ "cost": alpha-weighted cost, "costs": {metric-id: cost}, "nodes": [node] }`.
+//! Costs are returned as stored in the graph (e.g. normalized).
+//! Missing paths (and infinite costs) are returned as `null`.
+//! Errors are returned as `{ "error": msg }` with an according status-code.
+
+use crate::{
+    configs::{self, routing::RoutingAlgo},
+    defaults::{self, capacity::DimVec},
+    helpers,
+    network::{Graph, NodeIdx, RoutePair, SnappingFilter, SpatialIndex},
+    routing::{paths::Path, Engine},
+};
+use kissunits::geo::Coordinate;
+use log::info;
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+
+pub struct Request {
+    pub method: String,
+    pub path: String,
+    pub body: Vec<u8>,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Status {
+    Ok,
+    BadRequest,
+    NotFound,
+    MethodNotAllowed,
+    PayloadTooLarge,
+    RequestHeaderFieldsTooLarge,
+}
+
+impl Status {
+    pub fn code(&self) -> u16 {
+        match self {
+            Status::Ok => 200,
+            Status::BadRequest => 400,
+            Status::NotFound => 404,
+            Status::MethodNotAllowed => 405,
+            Status::PayloadTooLarge => 413,
+            Status::RequestHeaderFieldsTooLarge => 431,
+        }
+    }
+
+    pub fn reason(&self) -> &'static str {
+        match self {
+            Status::Ok => "OK",
+            Status::BadRequest => "Bad Request",
+            Status::NotFound => "Not Found",
+            Status::MethodNotAllowed => "Method Not Allowed",
+            Status::PayloadTooLarge => "Payload Too Large",
+            Status::RequestHeaderFieldsTooLarge => "Request Header Fields Too Large",
+        }
+    }
+}
+
+pub struct Response {
+    pub status: Status,
+    pub body: Value,
+}
+
+impl Response {
+    fn ok(body: Value) -> Response {
+        Response {
+            status: Status::Ok,
+            body,
+        }
+    }
+
+    pub fn error(status: Status, msg: &str) -> Response {
+        Response {
+            status,
+            body: json!({ "error": msg }),
+        }
+    }
+
+    fn bad_request(msg: &str) -> Response {
+        Response::error(Status::BadRequest, msg)
+    }
+}
+
+type Result<T> = std::result::Result<T, Response>;
+
+pub struct Context {
+    engine: Engine,
+    routing_cfg: configs::routing::Config,
+    spatial_index: SpatialIndex,
+    shortcut_count: usize,
+    max_matrix_size: usize,
+}
+
+impl Context {
+    pub fn new(
+        engine: Engine,
+        routing_cfg: configs::routing::Config,
+        snapping_filter: SnappingFilter,
+        max_matrix_size: usize,
+    ) -> Context {
+        info!(
+            "START Build spatial index ({:?}) for snapping.",
+            snapping_filter
+        );
+        let spatial_index = SpatialIndex::new(engine.graph(), snapping_filter);
+        info!("FINISHED Indexed {} nodes.", spatial_index.count());
+
+        let fwd_edges = engine.graph().fwd_edges();
+        let shortcut_count = fwd_edges
+            .iter()
+            .filter(|&edge_idx| fwd_edges.is_shortcut(edge_idx))
+            .count();
+
+        Context {
+            engine,
+            routing_cfg,
+            spatial_index,
+            shortcut_count,
+            max_matrix_size,
+        }
+    }
+
+    fn graph(&self) -> &Graph {
+        self.engine.graph()
+    }
+
+    /// The routing-config of a request, where the alphas and tolerated-scales are overwritten (if provided).
+    ///
+    /// If the config's routing-algo is an explorator, its underlying Dijkstra is used,
+    /// which is needed for routing-requests.
+    /// Exploration-requests can use this routing-config as well.
+    fn routing_cfg(
+        &self,
+        alphas: &Option<HashMap<String, f64>>,
+        tolerated_scales: &Option<HashMap<String, f64>>,
+    ) -> Result<configs::routing::Config> {
+        let mut routing_cfg = self.routing_cfg.clone();
+        routing_cfg.route_pairs_file = None;
+        if let RoutingAlgo::Explorator { algo } = routing_cfg.routing_algo {
+            routing_cfg.routing_algo = RoutingAlgo::from(algo);
+        }
+
+        let metrics_cfg = &self.graph().cfg().edges.metrics;
+        if let Some(alphas) = alphas {
+            routing_cfg.alphas.iter_mut().for_each(|alpha| *alpha = 0.0);
+            for (metric_id, &alpha) in alphas {
+                let metric_idx = metrics_cfg
+                    .try_idx_of(metric_id)
+                    .map_err(|msg| Response::bad_request(&msg.to_string()))?;
+                if !alpha.is_finite() || alpha < 0.0 {
+                    return Err(Response::bad_request(&format!(
+                        "The alpha of metric {} should be finite and non-negative, but is {}.",
+                        metric_id, alpha
+                    )));
+                }
+                routing_cfg.alphas[*metric_idx] = alpha;
+            }
+        }
+        if let Some(tolerated_scales) = tolerated_scales {
+            routing_cfg
+                .tolerated_scales
+                .iter_mut()
+                .for_each(|scale| *scale = defaults::routing::TOLERATED_SCALE_INF);
+            for (metric_id, &scale) in tolerated_scales {
+                let metric_idx = metrics_cfg
+                    .try_idx_of(metric_id)
+                    .map_err(|msg| Response::bad_request(&msg.to_string()))?;
+                if scale.is_nan() || scale < 0.0 {
+                    return Err(Response::bad_request(&format!(
+                        "The tolerated scale of metric {} should be non-negative, but is {}.",
+                        metric_id, scale
+                    )));
+                }
+                routing_cfg.tolerated_scales[*metric_idx] = scale;
+            }
+        }
+
+        if routing_cfg.alphas.iter().all(|&alpha| alpha == 0.0) {
+            return Err(Response::bad_request(
+                "At least one alpha should be positive.",
+            ));
+        }
+
+        Ok(routing_cfg)
+    }
+
+    fn node_idx(&self, node: &NodeSpec) -> Result<NodeIdx> {
+        let nodes = self.graph().nodes();
+        match node {
+            NodeSpec::Id { id } => nodes.idx_from(*id).map_err(|_| {
+                Response::bad_request(&format!("The node-id {} is not part of the graph.", id))
+            }),
+            NodeSpec::Idx { idx } => {
+                if *idx < nodes.count() {
+                    Ok(NodeIdx(*idx))
+                } else {
+                    Err(Response::bad_request(&format!(
+                        "The node-idx {} is not part of the graph.",
+                        idx
+                    )))
+                }
+            }
+            NodeSpec::Coord { lat, lon } => self
+                .nearest_node(&Coordinate {
+                    lat: *lat,
+                    lon: *lon,
+                })
+                .map(|(node_idx, _)| node_idx),
+        }
+    }
+
+    /// Returns the nearest snappable node and its distance in kilometers.
+    fn nearest_node(&self, coord: &Coordinate) -> Result<(NodeIdx, f64)> {
+        self.spatial_index
+            .nearest_node(coord)
+            .map(|(node_idx, km)| (node_idx, km.0))
+            .ok_or_else(|| Response::bad_request("The graph has no snappable nodes."))
+    }
+
+    fn node_to_json(&self, node_idx: NodeIdx) -> Value {
+        let nodes = self.graph().nodes();
+        let coord = nodes.coord(node_idx);
+        json!({
+            "idx": *node_idx,
+            "id": nodes.id(node_idx),
+            "lat": coord.lat,
+            "lon": coord.lon,
+        })
+    }
+
+    fn costs_to_json(&self, costs: &[f64]) -> Value {
+        let metric_ids = &self.graph().cfg().edges.metrics.ids;
+        Value::Object(
+            metric_ids
+                .iter()
+                .zip(costs)
+                .map(|(metric_id, &cost)| (metric_id.0.clone(), json!(cost)))
+                .collect::<Map<_, _>>(),
+        )
+    }
+
+    fn path_to_json(&self, path: Path, alphas: &DimVec<f64>) -> Value {
+        let graph = self.graph();
+        let path = path.flatten(graph);
+        let fwd_edges = graph.fwd_edges();
+
+        let mut nodes = vec![self.node_to_json(path.src_idx())];
+        nodes.extend(
+            path.iter()
+                .map(|&edge_idx| self.node_to_json(fwd_edges.dst_idx(edge_idx))),
+        );
+
+        json!({
+            "cost": helpers::dot_product(alphas, path.costs()),
+            "costs": self.costs_to_json(path.costs()),
+            "nodes": nodes,
+        })
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum NodeSpec {
+    Id { id: i64 },
+    Idx { idx: usize },
+    Coord { lat: f64, lon: f64 },
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RouteRequest {
+    src: NodeSpec,
+    dst: NodeSpec,
+    alphas: Option<HashMap<String, f64>>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct MatrixRequest {
+    srcs: Vec<NodeSpec>,
+    dsts: Vec<NodeSpec>,
+    alphas: Option<HashMap<String, f64>>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ExploratorRequest {
+    src: NodeSpec,
+    dst: NodeSpec,
+    alphas: Option<HashMap<String, f64>>,
+    #[serde(rename = "tolerated-scales")]
+    tolerated_scales: Option<HashMap<String, f64>>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct IsochronesRequest {
+    src: NodeSpec,
+    limits: Vec<f64>,
+    alphas: Option<HashMap<String, f64>>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct NearestRequest {
+    lat: f64,
+    lon: f64,
+}
+
+pub fn handle(request: &Request, context: &Context) -> Response {
+    let result = match (request.method.as_str(), request.path.as_str()) {
+        ("GET", "/health") => Ok(json!({ "status": "ok" })),
+        ("GET", "/metadata") => Ok(metadata(context)),
+        ("POST", "/route") => parse(&request.body).and_then(|r| route(r, context)),
+        ("POST", "/matrix") => parse(&request.body).and_then(|r| matrix(r, context)),
+        ("POST", "/explorator") => parse(&request.body).and_then(|r| explorator(r, context)),
+        ("POST", "/isochrones") => parse(&request.body).and_then(|r| isochrones(r, context)),
+        ("POST", "/nearest") => parse(&request.body).and_then(|r| nearest(r, context)),
+        (_, "/health") | (_, "/metadata") => Err(Response::error(
+            Status::MethodNotAllowed,
+            &format!("{} should be requested via GET.", request.path),
+        )),
+        (_, "/route")
+        | (_, "/matrix")
+        | (_, "/explorator")
+        | (_, "/isochrones")
+        | (_, "/nearest") => Err(Response::error(
+            Status::MethodNotAllowed,
+            &format!("{} should be requested via POST.", request.path),
+        )),
+        _ => Err(Response::error(
+            Status::NotFound,
+            &format!("The endpoint {} doesn't exist.", request.path),
+        )),
+    };
+
+    match result {
+        Ok(body) => Response::ok(body),
+        Err(response) => response,
+    }
+}
+
+fn parse<'a, T: Deserialize<'a>>(body: &'a [u8]) -> Result<T> {
+    serde_json::from_slice(body).map_err(|e| {
+        Response::bad_request(&format!(
+            "Serde couldn't parse the request's body due to error: {}",
+            e
+        ))
+    })
+}
+
+fn metadata(context: &Context) -> Value {
+    let graph = context.graph();
+    let nodes = graph.nodes();
+    let metrics_cfg = &graph.cfg().edges.metrics;
+
+    let bbox = nodes.iter().map(|node_idx| nodes.coord(node_idx)).fold(
+        None,
+        |bbox: Option<(Coordinate, Coordinate)>, coord| {
+            Some(match bbox {
+                Some((min, max)) => (
+                    Coordinate {
+                        lat: min.lat.min(coord.lat),
+                        lon: min.lon.min(coord.lon),
+                    },
+                    Coordinate {
+                        lat: max.lat.max(coord.lat),
+                        lon: max.lon.max(coord.lon),
+                    },
+                ),
+                None => (coord, coord),
+            })
+        },
+    );
+
+    json!({
+        "node-count": nodes.count(),
+        "edge-count": graph.fwd_edges().count(),
+        "shortcut-count": context.shortcut_count,
+        "bbox": bbox.map(|(min, max)| json!({
+            "min-lat": min.lat,
+            "min-lon": min.lon,
+            "max-lat": max.lat,
+            "max-lon": max.lon,
+        })),
+        "metrics": metrics_cfg
+            .ids
+            .iter()
+            .zip(metrics_cfg.units.iter())
+            .map(|(id, unit)| json!({ "id": id.0, "unit": format!("{:?}", unit) }))
+            .collect::<Vec<_>>(),
+        "are-metrics-normalized": metrics_cfg.are_normalized,
+        "routing": {
+            "algorithm": context.routing_cfg.routing_algo.name(),
+            "alphas": context.costs_to_json(&context.routing_cfg.alphas),
+            "tolerated-scales": context.costs_to_json(&context.routing_cfg.tolerated_scales),
+        },
+    })
+}
+
+fn route(request: RouteRequest, context: &Context) -> Result<Value> {
+    let routing_cfg = context.routing_cfg(&request.alphas, &None)?;
+    let route_pair = RoutePair {
+        src: context.node_idx(&request.src)?,
+        dst: context.node_idx(&request.dst)?,
+    };
+
+    let option_path = context.engine.route(route_pair, &routing_cfg);
+    Ok(json!({
+        "path": option_path.map(|path| context.path_to_json(path, &routing_cfg.alphas)),
+    }))
+}
+
+fn matrix(request: MatrixRequest, context: &Context) -> Result<Value> {
+    let size = request.srcs.len().saturating_mul(request.dsts.len());
+    if size > context.max_matrix_size {
+        return Err(Response::bad_request(&format!(
+            "The matrix should have at most {} route-pairs, but has {} srcs times {} dsts.",
+            context.max_matrix_size,
+            request.srcs.len(),
+            request.dsts.len()
+        )));
+    }
+    let routing_cfg = context.routing_cfg(&request.alphas, &None)?;
+    let src_idxs = request
+        .srcs
+        .iter()
+        .map(|src| context.node_idx(src))
+        .collect::<Result<Vec<_>>>()?;
+    let dst_idxs = request
+        .dsts
+        .iter()
+        .map(|dst| context.node_idx(dst))
+        .collect::<Result<Vec<_>>>()?;
+
+    let route_pairs = src_idxs
+        .iter()
+        .flat_map(|&src| dst_idxs.iter().map(move |&dst| RoutePair { src, dst }))
+        .collect();
+    let paths = context
+        .engine
+        .route_batch(route_pairs, &routing_cfg)
+        .map_err(|msg| Response::bad_request(&msg.to_string()))?;
+
+    let graph = context.graph();
+    let mut costs: Vec<Vec<Option<f64>>> = Vec::with_capacity(src_idxs.len());
+    for (i, option_path) in paths.into_iter().enumerate() {
+        if i % dst_idxs.len() == 0 {
+            costs.push(Vec::with_capacity(dst_idxs.len()));
+        }
+        let cost = option_path
+            .map(|mut path| helpers::dot_product(&routing_cfg.alphas, path.calc_costs(graph)));
+        costs
+            .last_mut()
+            .expect("A row should have been pushed.")
+            .push(cost);
+    }
+    // dsts could be empty
+    costs.resize_with(src_idxs.len(), Vec::new);
+
+    Ok(json!({ "costs": costs }))
+}
+
+fn explorator(request: ExploratorRequest, context: &Context) -> Result<Value> {
+    let routing_cfg = context.routing_cfg(&request.alphas, &request.tolerated_scales)?;
+    let route_pair = RoutePair {
+        src: context.node_idx(&request.src)?,
+        dst: context.node_idx(&request.dst)?,
+    };
+
+    let found_paths = context.engine.explore(route_pair, &routing_cfg);
+    Ok(json!({
+        "paths": found_paths
+            .into_iter()
+            .map(|path| context.path_to_json(path, &routing_cfg.alphas))
+            .collect::<Vec<_>>(),
+    }))
+}
+
+fn isochrones(request: IsochronesRequest, context: &Context) -> Result<Value> {
+    let routing_cfg = context.routing_cfg(&request.alphas, &None)?;
+    let src_idx = context.node_idx(&request.src)?;
+
+    if let Some(limit) = request
+        .limits
+        .iter()
+        .find(|limit| !(limit.is_finite() && **limit >= 0.0))
+    {
+        return Err(Response::bad_request(&format!(
+            "Every limit should be finite and non-negative, but a limit is {}.",
+            limit
+        )));
+    }
+    let max_limit = request.limits.iter().cloned().fold(0.0, f64::max);
+
+    let reachable_nodes = context.engine.workspace().dijkstra.compute_reachable_nodes(
+        src_idx,
+        max_limit,
+        context.graph(),
+        &routing_cfg,
+    );
+
+    let isochrones: Vec<_> = request
+        .limits
+        .iter()
+        .map(|&limit| {
+            // reachable nodes are sorted by costs
+            let nodes: Vec<_> = reachable_nodes
+                .iter()
+                .take_while(|(_, cost)| *cost <= limit)
+                .map(|&(node_idx, cost)| {
+                    let mut node = context.node_to_json(node_idx);
+                    node["cost"] = json!(cost);
+                    node
+                })
+                .collect();
+            json!({ "limit": limit, "nodes": nodes })
+        })
+        .collect();
+
+    Ok(json!({
+        "src": context.node_to_json(src_idx),
+        "isochrones": isochrones,
+    }))
+}
+
+fn nearest(request: NearestRequest, context: &Context) -> Result<Value> {
+    let (node_idx, km) = context.nearest_node(&Coordinate {
+        lat: request.lat,
+        lon: request.lon,
+    })?;
+    Ok(json!({
+        "node": context.node_to_json(node_idx),
+        "distance": km,
+    }))
+}
@@ -0,0 +1,202 @@
+use crate::{configs, defaults, helpers::err, routing::Engine};
+use log::{debug, info, warn};
+use std::{
+    io::{BufRead, BufReader, Read, Write},
+    net::{SocketAddr, TcpListener, TcpStream},
+    sync::Arc,
+    thread,
+    time::Duration,
+};
+
+mod api;
+
+/// A small HTTP-server answering routing-requests on a graph, which is loaded only once.
+///
+/// Every connection handles exactly one request and is closed afterwards.
+/// Requests and responses are JSON, see `api` for the endpoints.
+pub struct Server {
+    listener: TcpListener,
+    context: Arc<api::Context>,
+    num_threads: usize,
+}
+
+impl Server {
+    /// Binds the server to the config's address.
+    /// The routing-config defines the default-alphas and the algorithm of every request.
+    pub fn bind(
+        server_cfg: &configs::server::Config,
+        engine: Engine,
+        routing_cfg: configs::routing::Config,
+    ) -> err::Result<Server> {
+        let listener = TcpListener::bind(&server_cfg.address).map_err(|e| {
+            err::Msg::from(format!(
+                "Couldn't bind server to {} due to error: {}",
+                server_cfg.address, e
+            ))
+        })?;
+
+        Ok(Server {
+            listener,
+            context: Arc::new(api::Context::new(
+                engine,
+                routing_cfg,
+                server_cfg.snapping_filter,
+                server_cfg.max_matrix_size,
+            )),
+            num_threads: server_cfg.num_threads.max(1),
+        })
+    }
+
+    /// The actual address, e.g. if the config's port is 0.
+    pub fn local_addr(&self) -> err::Result<SocketAddr> {
+        Ok(self.listener.local_addr()?)
+    }
+
+    /// Serves requests until the listener breaks, which blocks the current thread.
+    pub fn run(self) -> err::Feedback {
+        info!(
+            "START Serving on http://{} with {} threads",
+            self.local_addr()?,
+            self.num_threads
+        );
+
+        let mut handles = Vec::with_capacity(self.num_threads);
+        for _ in 0..self.num_threads {
+            let listener = self.listener.try_clone()?;
+            let context = Arc::clone(&self.context);
+            handles.push(thread::spawn(move || {
+                for stream in listener.incoming() {
+                    match stream {
+                        Ok(stream) => {
+                            if let Err(msg) = handle_connection(stream, &context) {
+                                warn!("{}", msg);
+                            }
+                        }
+                        Err(e) => warn!("Accepting connection failed due to error: {}", e),
+                    }
+                }
+            }));
+        }
+
+        for handle in handles {
+            handle
+                .join()
+                .map_err(|e| err::Msg::from(format!("Joining stucks due to {:?}", e)))?;
+        }
+
+        info!("FINISHED");
+        Ok(())
+    }
+}
+
+fn handle_connection(mut stream: TcpStream, context: &api::Context) -> err::Feedback {
+    stream.set_read_timeout(Some(Duration::from_secs(
+        defaults::server::READ_TIMEOUT_SECS,
+    )))?;
+
+    let response = match read_request(&mut stream) {
+        Ok(request) => {
+            debug!("{} {}", request.method, request.path);
+            api::handle(&request, context)
+        }
+        Err(response) => response,
+    };
+
+    let body = response.body.to_string();
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\n\
+         Content-Type: application/json\r\n\
+         Content-Length: {}\r\n\
+         Connection: close\r\n\
+         \r\n\
+         {}",
+        response.status.code(),
+        response.status.reason(),
+        body.len(),
+        body
+    )?;
+    stream.flush()?;
+    Ok(())
+}
+
+fn read_request(stream: &mut TcpStream) -> Result<api::Request, api::Response> {
+    let mut reader = BufReader::new(stream);
+    let bad_request = |msg: &str| api::Response::error(api::Status::BadRequest, msg);
+
+    // Request-line and headers share one limit, so huge lines can't exhaust the memory.
+    let mut header_size = 0;
+    let mut read_line =
+        |reader: &mut BufReader<&mut TcpStream>, line: &mut String| -> Result<(), api::Response> {
+            let remaining = defaults::server::MAX_HEADER_SIZE - header_size;
+            header_size += reader
+                .by_ref()
+                .take(remaining as u64)
+                .read_line(line)
+                .map_err(|e| bad_request(&format!("Couldn't read header due to error: {}", e)))?;
+            if !line.ends_with('\n') {
+                if header_size == defaults::server::MAX_HEADER_SIZE {
+                    return Err(api::Response::error(
+                        api::Status::RequestHeaderFieldsTooLarge,
+                        &format!(
+                            "The request's headers should have at most {} bytes.",
+                            defaults::server::MAX_HEADER_SIZE
+                        ),
+                    ));
+                }
+                return Err(bad_request("The request's headers are incomplete."));
+            }
+            Ok(())
+        };
+
+    // request-line, e.g. `GET /health HTTP/1.1`
+
+    let mut line = String::new();
+    read_line(&mut reader, &mut line)?;
+    let mut parts = line.split_whitespace();
+    let (method, target) = match (parts.next(), parts.next()) {
+        (Some(method), Some(target)) => (method.to_owned(), target.to_owned()),
+        _ => return Err(bad_request("The request-line is malformed.")),
+    };
+    // query-strings are not supported
+    let path = target.split('?').next().unwrap_or("").to_owned();
+
+    // headers
+
+    let mut content_length = 0;
+    loop {
+        line.clear();
+        read_line(&mut reader, &mut line)?;
+        let header = line.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some(colon_idx) = header.find(':') {
+            let (key, value) = header.split_at(colon_idx);
+            if key.trim().eq_ignore_ascii_case("content-length") {
+                content_length = value[1..]
+                    .trim()
+                    .parse()
+                    .map_err(|_| bad_request("The header Content-Length is not a number."))?;
+            }
+        }
+    }
+
+    // body
+
+    if content_length > defaults::server::MAX_BODY_SIZE {
+        return Err(api::Response::error(
+            api::Status::PayloadTooLarge,
+            &format!(
+                "The request's body should have at most {} bytes.",
+                defaults::server::MAX_BODY_SIZE
+            ),
+        ));
+    }
+    let mut body = vec![0; content_length];
+    reader
+        .read_exact(&mut body)
+        .map_err(|e| bad_request(&format!("Couldn't read body due to error: {}", e)))?;
+
+    Ok(api::Request { method, path, body })
+}
@@ -161,7 +161,7 @@ impl TestEdge {
 
         let expected: DimVec<_> = SmallVec::from_slice(&self.metrics);
         assert!(
-            Approx(edge.metrics()) == Approx(&expected),
+            Approx(&edge.metrics()) == Approx(&expected),
             "Wrong metrics {:?} for {}edge {}. Expected: {:?}",
             edge.metrics(),
             prefix,
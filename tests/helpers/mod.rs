@@ -1,14 +1,23 @@
 // Dead code allowed, because it is actually used in test-modules, but compiler doesn't recognize.
 // March 6th, 2020
 
+use kissunits::{distance::Kilometers, geo::Coordinate};
 use osmgraphing::{
     approximating::Approx,
     configs,
     defaults::capacity::DimVec,
     helpers, io,
-    network::{Graph, MetricIdx, RoutePair},
-    routing::dijkstra::{self, Dijkstra},
+    network::{
+        CustomizableCH, EdgeIdx, EdgePosition, Graph, MetricIdx, NodeOrdering, PhantomNode,
+        RoutePair,
+    },
+    routing::{
+        dijkstra::{self, Dijkstra},
+        explorating::ConvexHullExplorator,
+        Engine,
+    },
 };
+use std::sync::Arc;
 
 #[allow(dead_code)]
 pub mod defaults {
@@ -28,6 +37,8 @@ pub mod defaults {
                 pub const FMI_YAML: &str = "resources/isle_of_man_2020-03-14/fmi.yaml";
                 pub const CH_FMI_YAML: &str = "resources/isle_of_man_2020-03-14/ch.fmi.yaml";
                 pub const OSM_PBF_YAML: &str = "resources/isle_of_man_2020-03-14/osm.pbf.yaml";
+                pub const BICYCLE_OSM_PBF_YAML: &str =
+                    "resources/isle_of_man_2020-03-14/bicycle.osm.pbf.yaml";
             }
 
             #[cfg(feature = "custom")]
@@ -51,11 +62,13 @@ pub mod defaults {
                 pub const FMI_YAML: &str = "resources/simple_stuttgart/fmi.yaml";
                 pub const NORMALIZED_FMI_YAML: &str =
                     "resources/simple_stuttgart/normalized_fmi.yaml";
+                pub const COMPACT_FMI_YAML: &str = "resources/simple_stuttgart/compact_fmi.yaml";
             }
 
             pub mod small {
                 pub const FMI_YAML: &str = "resources/small/fmi.yaml";
                 pub const CH_FMI_YAML: &str = "resources/small/ch.fmi.yaml";
+                pub const MANY_METRICS_FMI_YAML: &str = "resources/small/many_metrics.fmi.yaml";
             }
         }
     }
@@ -63,6 +76,8 @@ pub mod defaults {
 
 mod components;
 pub use components::{TestEdge, TestNode, TestPath};
+#[allow(dead_code)]
+pub mod server;
 
 pub fn parse(cfg: configs::parsing::Config) -> Graph {
     let map_file = cfg.map_file.clone();
@@ -246,6 +261,665 @@ pub fn compare_dijkstras(ch_fmi_config_file: &str, metric_id: &str) {
     }
 }
 
+#[allow(dead_code)]
+pub fn test_query_stats(config_file: &str, metric_id: &str) {
+    // parse graph
+
+    let parsing_cfg = configs::parsing::Config::from_yaml(config_file);
+    let graph = parse(parsing_cfg);
+
+    // get route-pairs from writing-section
+    let routes_cfg = configs::writing::routing::Config::from_yaml(config_file);
+
+    let raw_cfg = format!(
+        "{}\n{}\n{}\n{}\n{}",
+        "routing:",
+        format!("  route-pairs-file: '{}'", routes_cfg.file.display()),
+        "  algorithm: 'Dijkstra'",
+        "  metrics:",
+        format!("  - id: '{}'", metric_id),
+    );
+    let routing_cfg = configs::routing::Config::from_str(&raw_cfg, graph.cfg());
+    let route_pairs = io::routing::Parser::parse(&routing_cfg)
+        .expect("Parsing and finalizing route-pairs didn't work.");
+
+    let mut dijkstra = Dijkstra::new();
+
+    for routing_algo in &[
+        configs::routing::RoutingAlgo::Dijkstra,
+        configs::routing::RoutingAlgo::CHDijkstra,
+    ] {
+        let mut routing_cfg = routing_cfg.clone();
+        routing_cfg.routing_algo = *routing_algo;
+
+        for RoutePair { src, dst } in route_pairs
+            .iter()
+            .map(|(route_pair, _)| route_pair.into_node(&graph))
+        {
+            let query = dijkstra::Query {
+                src_idx: src.idx(),
+                dst_idx: dst.idx(),
+                graph: &graph,
+                routing_cfg: &routing_cfg,
+            };
+
+            // opt-in
+
+            dijkstra.collect_stats(false);
+            dijkstra.record_search_space(false);
+            dijkstra.compute_best_path(query);
+            assert!(
+                dijkstra.last_stats().is_none() && dijkstra.last_search_space().is_none(),
+                "Stats and search-space shouldn't be provided if not activated."
+            );
+
+            dijkstra.collect_stats(true);
+            dijkstra.record_search_space(true);
+            let option_path = dijkstra.compute_best_path(query);
+            let stats = dijkstra
+                .last_stats()
+                .expect("Stats should be provided if activated.");
+            let search_space = dijkstra
+                .last_search_space()
+                .expect("Search-space should be provided if activated.");
+
+            // counts
+
+            assert_eq!(
+                stats.settled_node_count(),
+                dijkstra.settled_node_count(),
+                "Settled nodes of stats and Dijkstra should be equal."
+            );
+            for (dir_stats, settled_nodes) in &[
+                (&stats.fwd, search_space.fwd()),
+                (&stats.bwd, search_space.bwd()),
+            ] {
+                assert_eq!(
+                    dir_stats.settled_node_count,
+                    settled_nodes.len(),
+                    "Every settled node should be part of the search-space \
+                     from ({}) to ({}) for {:?}.",
+                    src,
+                    dst,
+                    routing_algo
+                );
+                assert!(
+                    dir_stats.stalled_node_count <= dir_stats.settled_node_count
+                        && dir_stats.settled_node_count <= dir_stats.pushed_node_count,
+                    "Stats {:?} are inconsistent from ({}) to ({}) for {:?}.",
+                    dir_stats,
+                    src,
+                    dst,
+                    routing_algo
+                );
+                assert!(
+                    settled_nodes
+                        .windows(2)
+                        .all(|w| Approx(w[0].1) <= Approx(w[1].1)),
+                    "Nodes should be settled by increasing costs from ({}) to ({}) for {:?}.",
+                    src,
+                    dst,
+                    routing_algo
+                );
+            }
+
+            // meeting-node
+
+            match (option_path, stats.meeting_node_idx) {
+                (Some(path), Some(meeting_node_idx)) => {
+                    let fwd_edges = graph.fwd_edges();
+                    let bwd_edges = graph.bwd_edges();
+                    let is_on_path = path.src_idx() == meeting_node_idx
+                        || path.iter().any(|&edge_idx| {
+                            fwd_edges.dst_idx(edge_idx) == meeting_node_idx
+                                || bwd_edges.dst_idx(edge_idx) == meeting_node_idx
+                        });
+                    assert!(
+                        is_on_path,
+                        "Meeting-node {} should be part of the path from ({}) to ({}) for {:?}.",
+                        meeting_node_idx, src, dst, routing_algo
+                    );
+                }
+                (None, None) => (),
+                (option_path, option_meeting_node_idx) => panic!(
+                    "Path is {} but meeting-node is {} from ({}) to ({}) for {:?}.",
+                    if option_path.is_some() {
+                        "Some"
+                    } else {
+                        "None"
+                    },
+                    if option_meeting_node_idx.is_some() {
+                        "Some"
+                    } else {
+                        "None"
+                    },
+                    src,
+                    dst,
+                    routing_algo
+                ),
+            }
+        }
+    }
+}
+
+#[allow(dead_code)]
+pub fn test_engine(config_file: &str, metric_ids: &[&str]) {
+    // parse graph
+
+    let parsing_cfg = configs::parsing::Config::from_yaml(config_file);
+    let arc_graph = Arc::new(parse(parsing_cfg));
+
+    // get route-pairs from writing-section
+    let routes_cfg = configs::writing::routing::Config::from_yaml(config_file);
+
+    let raw_cfg = format!(
+        "{}\n{}\n{}\n{}\n{}",
+        "routing:",
+        format!("  route-pairs-file: '{}'", routes_cfg.file.display()),
+        "  algorithm: 'Dijkstra'",
+        "  metrics:",
+        metric_ids
+            .iter()
+            .map(|metric_id| format!("  - id: '{}'", metric_id))
+            .collect::<Vec<_>>()
+            .join("\n"),
+    );
+    let routing_cfg = configs::routing::Config::from_str(&raw_cfg, arc_graph.cfg());
+    let route_pairs: Vec<_> = io::routing::Parser::parse(&routing_cfg)
+        .expect("Parsing and finalizing route-pairs didn't work.")
+        .into_iter()
+        .map(|(route_pair, _)| route_pair.into_idx(&arc_graph))
+        .collect();
+
+    // sequential results
+
+    let mut dijkstra = Dijkstra::new();
+    let mut explorator = ConvexHullExplorator::new();
+    let mut expected_costs = Vec::with_capacity(route_pairs.len());
+    let mut expected_path_counts = Vec::with_capacity(route_pairs.len());
+    for RoutePair { src, dst } in &route_pairs {
+        let query = dijkstra::Query {
+            src_idx: *src,
+            dst_idx: *dst,
+            graph: &arc_graph,
+            routing_cfg: &routing_cfg,
+        };
+        expected_costs.push(
+            dijkstra
+                .compute_best_path(query)
+                .map(|path| path.flatten(&arc_graph).costs().clone()),
+        );
+        expected_path_counts.push(explorator.fully_explorate(query, &mut dijkstra).len());
+    }
+
+    // engine with more threads than route-pairs and with less
+
+    for &num_threads in &[1, 3, route_pairs.len() + 1] {
+        let engine = Engine::with_num_threads(Arc::clone(&arc_graph), num_threads);
+
+        let paths = engine
+            .route_batch(route_pairs.clone(), &routing_cfg)
+            .expect("Routing a batch should work.");
+        assert_eq!(
+            paths.len(),
+            route_pairs.len(),
+            "Every route-pair should get a result."
+        );
+        for (i, option_path) in paths.into_iter().enumerate() {
+            let costs = option_path.map(|path| path.flatten(&arc_graph).costs().clone());
+            let single_costs = engine
+                .route(route_pairs[i], &routing_cfg)
+                .map(|path| path.flatten(&arc_graph).costs().clone());
+            for actual_costs in &[costs, single_costs] {
+                assert_eq!(
+                    actual_costs
+                        .as_ref()
+                        .map(|c| c.iter().map(|&x| Approx(x)).collect::<Vec<_>>()),
+                    expected_costs[i]
+                        .as_ref()
+                        .map(|c| c.iter().map(|&x| Approx(x)).collect::<Vec<_>>()),
+                    "The engine's path from {} to {} should cost as much as the Dijkstra's path \
+                     ({} threads).",
+                    route_pairs[i].src,
+                    route_pairs[i].dst,
+                    num_threads
+                );
+            }
+        }
+
+        // explorator-configs are routed with their underlying Dijkstra
+        let mut explorator_cfg = routing_cfg.clone();
+        explorator_cfg.routing_algo = configs::routing::RoutingAlgo::Explorator {
+            algo: configs::routing::ExploratorAlgo::Dijkstra,
+        };
+        let explorator_costs: Vec<_> = engine
+            .route_batch(route_pairs.clone(), &explorator_cfg)
+            .expect("Routing a batch with an explorator-config should work.")
+            .into_iter()
+            .map(|option_path| {
+                option_path.map(|path| {
+                    path.flatten(&arc_graph)
+                        .costs()
+                        .iter()
+                        .map(|&x| Approx(x))
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+        assert_eq!(
+            explorator_costs,
+            expected_costs
+                .iter()
+                .map(|costs| costs
+                    .as_ref()
+                    .map(|c| c.iter().map(|&x| Approx(x)).collect::<Vec<_>>()))
+                .collect::<Vec<_>>(),
+            "Routing with an explorator-config should use its Dijkstra ({} threads).",
+            num_threads
+        );
+
+        let found_paths = engine
+            .explore_batch(route_pairs.clone(), &routing_cfg)
+            .expect("Exploring a batch should work.");
+        let path_counts: Vec<_> = found_paths.iter().map(|paths| paths.len()).collect();
+        assert_eq!(
+            path_counts, expected_path_counts,
+            "The engine should explorate as many paths as the explorator ({} threads).",
+            num_threads
+        );
+    }
+}
+
+#[allow(dead_code)]
+pub fn test_phantom_paths(
+    config_file: &str,
+    metric_id: &str,
+    routing_algo: configs::routing::RoutingAlgo,
+) {
+    // parse graph
+
+    let parsing_cfg = configs::parsing::Config::from_yaml(config_file);
+    let graph = parse(parsing_cfg);
+    let nodes = graph.nodes();
+    let fwd_edges = graph.fwd_edges();
+    let bwd_edges = graph.bwd_edges();
+
+    let raw_cfg = format!(
+        "{}\n{}\n{}\n{}",
+        "routing:",
+        format!("  algorithm: {}", routing_algo.name()),
+        "  metrics:",
+        format!("  - id: '{}'", metric_id),
+    );
+    let routing_cfg = configs::routing::Config::from_str(&raw_cfg, graph.cfg());
+    let edge_cost = |edge_idx: EdgeIdx| graph.metrics().dot_product(&routing_cfg.alphas, edge_idx);
+
+    // expected costs between nodes
+
+    let mut dijkstra = Dijkstra::new();
+    let node_costs: Vec<Vec<f64>> = nodes
+        .iter()
+        .map(|src_idx| {
+            nodes
+                .iter()
+                .map(|dst_idx| {
+                    dijkstra
+                        .compute_best_path(dijkstra::Query {
+                            src_idx,
+                            dst_idx,
+                            graph: &graph,
+                            routing_cfg: &routing_cfg,
+                        })
+                        .map_or(std::f64::INFINITY, |mut path| {
+                            helpers::dot_product(&routing_cfg.alphas, path.calc_costs(&graph))
+                        })
+                })
+                .collect()
+        })
+        .collect();
+
+    // phantom-nodes on every (non-shortcut) edge
+
+    let mut phantom_nodes = Vec::new();
+    for edge_idx in fwd_edges.iter().filter(|&e| !fwd_edges.is_shortcut(e)) {
+        for &fraction in &[0.0, 0.25, 1.0] {
+            phantom_nodes.push(PhantomNode {
+                coord: Coordinate::zero(),
+                distance: Kilometers(0.0),
+                positions: vec![EdgePosition { edge_idx, fraction }],
+            });
+        }
+    }
+
+    for src in &phantom_nodes {
+        for dst in &phantom_nodes {
+            let (src_pos, dst_pos) = (src.positions[0], dst.positions[0]);
+
+            let mut expected_cost = (1.0 - src_pos.fraction) * edge_cost(src_pos.edge_idx)
+                + node_costs[*fwd_edges.dst_idx(src_pos.edge_idx)]
+                    [*bwd_edges.dst_idx(dst_pos.edge_idx)]
+                + dst_pos.fraction * edge_cost(dst_pos.edge_idx);
+            if src_pos.edge_idx == dst_pos.edge_idx && src_pos.fraction <= dst_pos.fraction {
+                expected_cost = expected_cost
+                    .min((dst_pos.fraction - src_pos.fraction) * edge_cost(src_pos.edge_idx));
+            }
+
+            let option_path = dijkstra.compute_best_phantom_path(dijkstra::PhantomQuery {
+                src,
+                dst,
+                graph: &graph,
+                routing_cfg: &routing_cfg,
+            });
+            let path = match option_path {
+                Some(path) => path,
+                None => {
+                    assert!(
+                        expected_cost.is_infinite(),
+                        "Path from {:?} to {:?} should exist.",
+                        src_pos,
+                        dst_pos
+                    );
+                    continue;
+                }
+            };
+
+            // costs, with and without flattening
+            let first_partial_edge = *path
+                .first_partial_edge()
+                .expect("Phantom-paths should start with a partial edge.");
+            assert_eq!(first_partial_edge.edge_idx, src_pos.edge_idx);
+            assert_eq!(Approx(first_partial_edge.start), Approx(src_pos.fraction));
+            let mut unflattened_path = path.clone();
+            let flattened_path = path.flatten(&graph);
+            for actual_path in &mut [unflattened_path.clone(), flattened_path.clone()] {
+                assert_eq!(
+                    Approx(helpers::dot_product(
+                        &routing_cfg.alphas,
+                        actual_path.calc_costs(&graph)
+                    )),
+                    Approx(expected_cost),
+                    "Phantom-path from {:?} to {:?} has wrong costs.",
+                    src_pos,
+                    dst_pos
+                );
+            }
+            assert_eq!(
+                flattened_path
+                    .costs()
+                    .iter()
+                    .map(|&c| Approx(c))
+                    .collect::<Vec<_>>(),
+                unflattened_path
+                    .calc_costs(&graph)
+                    .iter()
+                    .map(|&c| Approx(c))
+                    .collect::<Vec<_>>()
+            );
+
+            // The edges, including the partial ones, should be connected.
+            let mut edges = vec![first_partial_edge.edge_idx];
+            edges.extend(flattened_path.iter());
+            if let Some(last_partial_edge) = flattened_path.last_partial_edge() {
+                assert_eq!(last_partial_edge.edge_idx, dst_pos.edge_idx);
+                assert_eq!(Approx(last_partial_edge.end), Approx(dst_pos.fraction));
+                edges.push(last_partial_edge.edge_idx);
+            } else {
+                assert_eq!(first_partial_edge.edge_idx, dst_pos.edge_idx);
+                assert_eq!(Approx(first_partial_edge.end), Approx(dst_pos.fraction));
+            }
+            assert_eq!(bwd_edges.dst_idx(edges[0]), flattened_path.src_idx());
+            for window in edges.windows(2) {
+                assert_eq!(fwd_edges.dst_idx(window[0]), bwd_edges.dst_idx(window[1]));
+            }
+            assert_eq!(
+                fwd_edges.dst_idx(edges[edges.len() - 1]),
+                flattened_path.dst_idx()
+            );
+        }
+    }
+}
+
+#[allow(dead_code)]
+pub fn test_exploration(config_file: &str, metric_ids: &[&str]) {
+    // parse graph
+
+    let parsing_cfg = configs::parsing::Config::from_yaml(config_file);
+    let graph = parse(parsing_cfg);
+
+    // get route-pairs from writing-section
+    let routes_cfg = configs::writing::routing::Config::from_yaml(config_file);
+
+    // set up routing
+
+    let raw_cfg = format!(
+        "{}\n{}\n{}\n{}\n{}",
+        "routing:",
+        format!("  route-pairs-file: '{}'", routes_cfg.file.display()),
+        // the explorator uses the routing-algorithm internally
+        "  algorithm: 'Dijkstra'",
+        "  metrics:",
+        metric_ids
+            .iter()
+            .map(|metric_id| format!("  - id: '{}'", metric_id))
+            .collect::<Vec<_>>()
+            .join("\n"),
+    );
+    let routing_cfg = configs::routing::Config::from_str(&raw_cfg, graph.cfg());
+    let mut dijkstra_cfg = routing_cfg.clone();
+    let metric_indices: Vec<_> = metric_ids
+        .iter()
+        .map(|metric_id| graph.cfg().edges.metrics.idx_of(metric_id))
+        .collect();
+
+    let mut dijkstra = Dijkstra::new();
+    let mut explorator = ConvexHullExplorator::new();
+
+    // testing
+
+    let route_pairs = io::routing::Parser::parse(&routing_cfg)
+        .expect("Parsing and finalizing route-pairs didn't work.");
+
+    for RoutePair { src, dst } in route_pairs
+        .iter()
+        .map(|(route_pair, _)| route_pair.into_node(&graph))
+    {
+        let found_paths = explorator.fully_explorate(
+            dijkstra::Query {
+                src_idx: src.idx(),
+                dst_idx: dst.idx(),
+                graph: &graph,
+                routing_cfg: &routing_cfg,
+            },
+            &mut dijkstra,
+        );
+
+        for &metric_idx in &metric_indices {
+            // every metric's best path is part of the pareto-front
+
+            dijkstra_cfg.alphas = smallvec::smallvec![0.0; graph.metrics().dim()];
+            dijkstra_cfg.alphas[*metric_idx] = 1.0;
+            let option_best_path = dijkstra.compute_best_path(dijkstra::Query {
+                src_idx: src.idx(),
+                dst_idx: dst.idx(),
+                graph: &graph,
+                routing_cfg: &dijkstra_cfg,
+            });
+
+            assert_eq!(
+                option_best_path.is_some(),
+                !found_paths.is_empty(),
+                "Exploration from ({}) to ({}) should find a path, iff Dijkstra does.",
+                src,
+                dst
+            );
+
+            if let Some(mut best_path) = option_best_path {
+                best_path.calc_costs(&graph);
+                let best_cost = best_path.costs()[*metric_idx];
+                assert!(
+                    found_paths
+                        .iter()
+                        .any(|path| Approx(path.costs()[*metric_idx]) == Approx(best_cost)),
+                    "Exploration from ({}) to ({}) should contain the best path {}",
+                    src,
+                    dst,
+                    best_path
+                );
+            }
+        }
+
+        // found paths should not dominate each other
+
+        for path in &found_paths {
+            for other in &found_paths {
+                assert!(
+                    !(Approx(path.costs()) < Approx(other.costs())),
+                    "Explorated path {} dominates explorated path {}",
+                    path,
+                    other
+                );
+            }
+        }
+    }
+}
+
+#[allow(dead_code)]
+pub fn test_cch(config_file: &str, metric_ids: &[&str], node_ordering: NodeOrdering) {
+    // parse graph
+
+    let mut parsing_cfg = configs::parsing::Config::from_yaml(config_file);
+    parsing_cfg.node_ordering = node_ordering;
+    let graph = parse(parsing_cfg);
+
+    // get route-pairs from writing-section
+    let routes_cfg = configs::writing::routing::Config::from_yaml(config_file);
+
+    // set up routing
+
+    let raw_cfg = format!(
+        "{}\n{}\n{}\n{}\n{}",
+        "routing:",
+        format!("  route-pairs-file: '{}'", routes_cfg.file.display()),
+        "  algorithm: 'Dijkstra'",
+        "  metrics:",
+        metric_ids
+            .iter()
+            .map(|metric_id| format!("  - id: '{}'", metric_id))
+            .collect::<Vec<_>>()
+            .join("\n"),
+    );
+    let routing_cfg = configs::routing::Config::from_str(&raw_cfg, graph.cfg());
+
+    // customize
+
+    let cch = CustomizableCH::new(&graph);
+    let ch_graph = cch
+        .customize(&graph, &routing_cfg)
+        .expect("Customizing the CCH should be successful.");
+    assert_eq!(
+        graph
+            .fwd_edges()
+            .iter()
+            .filter(|&edge_idx| !graph.fwd_edges().is_shortcut(edge_idx))
+            .count(),
+        ch_graph
+            .fwd_edges()
+            .iter()
+            .filter(|&edge_idx| !ch_graph.fwd_edges().is_shortcut(edge_idx))
+            .count(),
+        "The customized graph should contain all original edges."
+    );
+    // Like the balancer, customize the (maybe reordered) customized graph again.
+    let ch_graph = cch
+        .customize(&ch_graph, &routing_cfg)
+        .expect("Customizing the CCH with a customized graph should be successful.");
+    for node_idx in ch_graph.nodes().iter() {
+        let id = ch_graph.nodes().id(node_idx);
+        assert_eq!(Some(ch_graph.nodes().level(node_idx)), cch.level(id));
+    }
+
+    // Every considered metric on its own and all of them together.
+    let alphas_list: Vec<DimVec<f64>> = metric_ids
+        .iter()
+        .map(|metric_id| {
+            let mut alphas = smallvec::smallvec![0.0; graph.metrics().dim()];
+            alphas[*graph.cfg().edges.metrics.idx_of(metric_id)] = 1.0;
+            alphas
+        })
+        .chain(std::iter::once(routing_cfg.alphas.clone()))
+        .collect();
+
+    let mut dijkstra = Dijkstra::new();
+    let mut dijkstra_cfg = routing_cfg.clone();
+    let mut ch_dijkstra_cfg = routing_cfg.clone();
+    ch_dijkstra_cfg.routing_algo = configs::routing::RoutingAlgo::CHDijkstra;
+
+    // testing
+
+    let route_pairs = io::routing::Parser::parse(&routing_cfg)
+        .expect("Parsing and finalizing route-pairs didn't work.");
+
+    for RoutePair { src, dst } in route_pairs
+        .iter()
+        .map(|(route_pair, _)| route_pair.into_node(&graph))
+    {
+        let ch_src_idx = ch_graph
+            .nodes()
+            .idx_from(src.id())
+            .expect("src should exist");
+        let ch_dst_idx = ch_graph
+            .nodes()
+            .idx_from(dst.id())
+            .expect("dst should exist");
+
+        for alphas in &alphas_list {
+            dijkstra_cfg.alphas = alphas.clone();
+            ch_dijkstra_cfg.alphas = alphas.clone();
+
+            let option_path = dijkstra.compute_best_path(dijkstra::Query {
+                src_idx: src.idx(),
+                dst_idx: dst.idx(),
+                graph: &graph,
+                routing_cfg: &dijkstra_cfg,
+            });
+            let option_ch_path = dijkstra.compute_best_path(dijkstra::Query {
+                src_idx: ch_src_idx,
+                dst_idx: ch_dst_idx,
+                graph: &ch_graph,
+                routing_cfg: &ch_dijkstra_cfg,
+            });
+
+            assert_eq!(
+                option_path.is_some(),
+                option_ch_path.is_some(),
+                "Dijkstra on the customized graph from ({}) to ({}) should find a path, \
+                 iff Dijkstra on the original graph does.",
+                src,
+                dst
+            );
+
+            if let (Some(path), Some(ch_path)) = (option_path, option_ch_path) {
+                let path = path.flatten(&graph);
+                let ch_path = ch_path.flatten(&ch_graph);
+                let cost = helpers::dot_product(alphas, path.costs());
+                let ch_cost = helpers::dot_product(alphas, ch_path.costs());
+                assert!(
+                    Approx(cost) == Approx(ch_cost),
+                    "CH-Dijkstra's path's cost ({}) on the customized graph is different \
+                     from Dijkstra's path's cost ({}) for alphas {:?}. \
+                     --------------------- CH-Dijkstra's path {} \
+                     --------------------- Dijkstra's path {}",
+                    ch_cost,
+                    cost,
+                    alphas,
+                    ch_path,
+                    path
+                );
+            }
+        }
+    }
+}
+
 #[allow(dead_code)]
 pub fn assert_graph(
     test_nodes: Vec<TestNode>,
@@ -389,3 +1063,172 @@ pub fn assert_graph_sloppy(expected_node_count: usize, expected_edge_count: usiz
     //     }
     // }
 }
+
+/// Asserts, that both graphs have the same nodes and edges (in the same order), e.g. after building them differently.
+#[allow(dead_code)]
+pub fn assert_eq_graphs(expected: &Graph, graph: &Graph) {
+    assert_graph_sloppy(
+        expected.nodes().count(),
+        expected.fwd_edges().count(),
+        graph,
+    );
+
+    let (expected_nodes, nodes) = (expected.nodes(), graph.nodes());
+    for node_idx in expected_nodes.iter() {
+        assert_eq!(nodes.id(node_idx), expected_nodes.id(node_idx));
+        assert_eq!(nodes.coord(node_idx), expected_nodes.coord(node_idx));
+    }
+
+    let (expected_fwd_edges, fwd_edges) = (expected.fwd_edges(), graph.fwd_edges());
+    let (expected_bwd_edges, bwd_edges) = (expected.bwd_edges(), graph.bwd_edges());
+    for edge_idx in expected_fwd_edges.iter() {
+        assert_eq!(
+            fwd_edges.dst_idx(edge_idx),
+            expected_fwd_edges.dst_idx(edge_idx),
+            "Edge (idx: {}) should have the same dst.",
+            edge_idx
+        );
+        assert_eq!(
+            bwd_edges.dst_idx(edge_idx),
+            expected_bwd_edges.dst_idx(edge_idx),
+            "Edge (idx: {}) should have the same src.",
+            edge_idx
+        );
+        assert_eq!(
+            fwd_edges.try_id(edge_idx),
+            expected_fwd_edges.try_id(edge_idx)
+        );
+        assert_eq!(
+            graph.metrics().get(edge_idx),
+            expected.metrics().get(edge_idx),
+            "Edge (idx: {}) should have the same metrics.",
+            edge_idx
+        );
+        assert_eq!(
+            fwd_edges.sc_edges(edge_idx),
+            expected_fwd_edges.sc_edges(edge_idx),
+            "Edge (idx: {}) should have the same shortcut-edges.",
+            edge_idx
+        );
+    }
+    for node_idx in expected_nodes.iter() {
+        let expected_leaving: Vec<_> = expected_bwd_edges
+            .starting_from(node_idx)
+            .map(|edge| edge.idx())
+            .collect();
+        let leaving: Vec<_> = bwd_edges
+            .starting_from(node_idx)
+            .map(|edge| edge.idx())
+            .collect();
+        assert_eq!(leaving, expected_leaving);
+    }
+}
+
+/// Asserts, that both graphs have the same nodes and edges, where nodes are compared by id, e.g. after reordering them.
+/// Further, routes between every `node_stride`-th node have to have the same costs.
+#[allow(dead_code)]
+pub fn assert_eq_reordered_graphs(
+    expected: &Graph,
+    graph: &Graph,
+    routing_cfg: &configs::routing::Config,
+    node_stride: usize,
+) {
+    assert_graph_sloppy(
+        expected.nodes().count(),
+        expected.fwd_edges().count(),
+        graph,
+    );
+
+    let (expected_nodes, nodes) = (expected.nodes(), graph.nodes());
+    let (expected_fwd_edges, fwd_edges) = (expected.fwd_edges(), graph.fwd_edges());
+    let leaving_edges_of = |graph: &Graph, node_idx| {
+        let (nodes, fwd_edges) = (graph.nodes(), graph.fwd_edges());
+        let mut leaving: Vec<_> = fwd_edges
+            .starting_from(node_idx)
+            .map(|edge| {
+                (
+                    nodes.id(edge.dst_idx()),
+                    fwd_edges.try_id(edge.idx()),
+                    edge.is_shortcut(),
+                    edge.metrics().clone(),
+                )
+            })
+            .collect();
+        leaving.sort_by(|a, b| a.partial_cmp(b).expect("Metrics should be comparable."));
+        leaving
+    };
+    for expected_idx in expected_nodes.iter() {
+        let id = expected_nodes.id(expected_idx);
+        let node_idx = nodes
+            .idx_from(id)
+            .expect("Reordered graph should contain the same node-ids.");
+        assert_eq!(nodes.id(node_idx), id);
+        assert_eq!(nodes.coord(node_idx), expected_nodes.coord(expected_idx));
+        assert_eq!(nodes.level(node_idx), expected_nodes.level(expected_idx));
+        assert_eq!(
+            leaving_edges_of(graph, node_idx),
+            leaving_edges_of(expected, expected_idx),
+            "Node (id: {}) should have the same leaving edges.",
+            id
+        );
+    }
+    for edge_idx in expected_fwd_edges.iter() {
+        if let Some(id) = expected_fwd_edges.try_id(edge_idx) {
+            assert_eq!(
+                fwd_edges.try_id(fwd_edges.idx_from(id)),
+                Some(id),
+                "Edge (id: {}) should be found by its id.",
+                id
+            );
+        }
+    }
+
+    let mut dijkstra = Dijkstra::new();
+    let node_ids: Vec<_> = expected_nodes
+        .iter()
+        .step_by(node_stride.max(1))
+        .map(|node_idx| expected_nodes.id(node_idx))
+        .collect();
+    for &src_id in &node_ids {
+        for &dst_id in &node_ids {
+            let mut costs_of = |graph: &Graph| {
+                let nodes = graph.nodes();
+                dijkstra
+                    .compute_best_path(dijkstra::Query {
+                        src_idx: nodes.idx_from(src_id).expect("Src-id should exist."),
+                        dst_idx: nodes.idx_from(dst_id).expect("Dst-id should exist."),
+                        graph,
+                        routing_cfg,
+                    })
+                    .map(|path| path.flatten(graph).costs().clone())
+            };
+            let expected_costs = costs_of(expected);
+            let costs = costs_of(graph);
+            assert_eq!(
+                costs.is_some(),
+                expected_costs.is_some(),
+                "Route from {} to {} should be found in both graphs or in none.",
+                src_id,
+                dst_id
+            );
+            if let (Some(costs), Some(expected_costs)) = (costs, expected_costs) {
+                // other metrics may differ for alternative routes of same cost
+                for (i, (cost, expected_cost)) in
+                    costs.iter().zip(expected_costs.iter()).enumerate()
+                {
+                    if routing_cfg.alphas[i] == 0.0 {
+                        continue;
+                    }
+                    assert!(
+                        Approx(*cost) == Approx(*expected_cost),
+                        "Route from {} to {} should cost {:?}, but costs {:?}.",
+                        src_id,
+                        dst_id,
+                        expected_costs,
+                        costs
+                    );
+                }
+            }
+        }
+    }
+}
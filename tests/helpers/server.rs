@@ -0,0 +1,85 @@
+use osmgraphing::{configs, network::SnappingFilter, routing::Engine, server::Server};
+use std::{
+    io::{Read, Write},
+    net::{Shutdown, SocketAddr, TcpStream},
+    sync::Arc,
+    thread,
+};
+
+/// Starts a server on a free local port serving the config's graph and returns its address.
+/// Matrix-requests are limited to 9 route-pairs.
+/// The server runs until the test-process ends.
+pub fn spawn(config_file: &str) -> SocketAddr {
+    let parsing_cfg = configs::parsing::Config::from_yaml(config_file);
+    let graph = super::parse(parsing_cfg);
+    let routing_cfg = configs::routing::Config::from_yaml(config_file, graph.cfg());
+    let server_cfg = configs::server::Config {
+        address: String::from("127.0.0.1:0"),
+        num_threads: 2,
+        snapping_filter: SnappingFilter::LargestComponent,
+        max_matrix_size: 9,
+    };
+
+    let server = Server::bind(&server_cfg, Engine::new(Arc::new(graph)), routing_cfg)
+        .expect("Binding the server to a free port should work.");
+    let addr = server.local_addr().expect("Server should have an address.");
+    thread::spawn(move || server.run());
+    addr
+}
+
+/// Returns the response's status-code and its parsed json-body.
+pub fn request(
+    addr: &SocketAddr,
+    method: &str,
+    path: &str,
+    body: &serde_json::Value,
+) -> (u16, serde_json::Value) {
+    let body = body.to_string();
+    request_raw(
+        addr,
+        &format!(
+            "{} {} HTTP/1.1\r\nHost: {}\r\nContent-Length: {}\r\n\r\n{}",
+            method,
+            path,
+            addr,
+            body.len(),
+            body
+        ),
+    )
+}
+
+/// Like `request(...)`, but sends the raw request, e.g. with malformed headers.
+pub fn request_raw(addr: &SocketAddr, raw_request: &str) -> (u16, serde_json::Value) {
+    let mut stream = TcpStream::connect(addr).expect("Connecting to the server should work.");
+    stream
+        .write_all(raw_request.as_bytes())
+        .expect("Sending the request should work.");
+
+    // Server closes connection after responding,
+    // maybe with a reset if it has rejected the request without reading all of it.
+    // Hence the connection may be closed already.
+    let _ = stream.shutdown(Shutdown::Write);
+    let mut response = Vec::new();
+    if let Err(e) = stream.read_to_end(&mut response) {
+        assert!(
+            !response.is_empty(),
+            "Reading the response should work, but failed due to error: {}",
+            e
+        );
+    }
+    let response = String::from_utf8(response).expect("Response should be utf8.");
+
+    let status = response
+        .split_whitespace()
+        .nth(1)
+        .and_then(|code| code.parse().ok())
+        .expect(&format!("Response {} should have a status-code.", response));
+    let json_body = response
+        .splitn(2, "\r\n\r\n")
+        .nth(1)
+        .expect("Response should have a body.");
+    (
+        status,
+        serde_json::from_str(json_body).expect("Response's body should be json."),
+    )
+}
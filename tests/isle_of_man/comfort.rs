@@ -0,0 +1,113 @@
+use crate::helpers::{defaults, parse};
+use defaults::paths::resources::isle_of_man as resources;
+use osmgraphing::{
+    configs, io,
+    routing::dijkstra::{self, Dijkstra},
+};
+
+#[test]
+fn discomfort_of_bicycle_graph() {
+    let graph = parse(configs::parsing::Config::from_yaml(
+        resources::BICYCLE_OSM_PBF_YAML,
+    ));
+    let fwd_edges = graph.fwd_edges();
+    let metrics = graph.metrics();
+    let discomfort_idx = graph.cfg().edges.metrics.idx_of("discomfort");
+    let kilometers_idx = graph.cfg().edges.metrics.idx_of("kilometers");
+    let discomfort_km_idx = graph.cfg().edges.metrics.idx_of("discomfort-kilometers");
+
+    let mut uncomfortable_count = 0;
+    for edge_idx in fwd_edges.iter() {
+        let discomfort = metrics[discomfort_idx].get(edge_idx);
+        assert!(discomfort >= 1.0);
+        if discomfort > 1.0 {
+            uncomfortable_count += 1;
+        }
+        let kilometers = metrics[kilometers_idx].get(edge_idx);
+        let discomfort_km = metrics[discomfort_km_idx].get(edge_idx);
+        assert!((discomfort_km - kilometers * discomfort).abs() < 1e-9);
+        if graph.streets().tag(edge_idx, "surface") == Some("unpaved") {
+            assert!(discomfort >= 2.0);
+        }
+    }
+    assert!(uncomfortable_count > 0);
+}
+
+#[test]
+fn surface_speed_factors_of_bicycle_graph() {
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::BICYCLE_OSM_PBF_YAML);
+    let graph = parse(parsing_cfg.clone());
+    let unfactored_graph = {
+        let mut parsing_cfg = parsing_cfg;
+        parsing_cfg.vehicles.surface_speed_factors.clear();
+        parse(parsing_cfg)
+    };
+    let kmph_idx = graph.cfg().edges.metrics.idx_of("kmph");
+    assert_eq!(
+        graph.fwd_edges().count(),
+        unfactored_graph.fwd_edges().count()
+    );
+
+    let mut factored_count = 0;
+    for edge_idx in graph.fwd_edges().iter() {
+        let factor = match graph.streets().tag(edge_idx, "surface") {
+            Some("gravel") => 0.7,
+            Some("unpaved") => 0.6,
+            Some("ground") => 0.5,
+            _ => 1.0,
+        };
+        if factor < 1.0 {
+            factored_count += 1;
+        }
+        let kmph = graph.metrics()[kmph_idx].get(edge_idx);
+        let unfactored_kmph = unfactored_graph.metrics()[kmph_idx].get(edge_idx);
+        assert!((kmph - factor * unfactored_kmph).abs() < 1e-9);
+    }
+    assert!(factored_count > 0);
+}
+
+#[test]
+fn comfortable_paths_of_bicycle_graph() {
+    let graph = parse(configs::parsing::Config::from_yaml(
+        resources::BICYCLE_OSM_PBF_YAML,
+    ));
+    let kilometers_idx = graph.cfg().edges.metrics.idx_of("kilometers");
+    let discomfort_km_idx = graph.cfg().edges.metrics.idx_of("discomfort-kilometers");
+    let shortest_cfg = configs::routing::Config::from_str(
+        "routing: { algorithm: 'Dijkstra', metrics: [{ id: 'kilometers' }] }",
+        graph.cfg(),
+    );
+    let comfortable_cfg = configs::routing::Config::from_str(
+        "routing: { algorithm: 'Dijkstra', metrics: [{ id: 'discomfort-kilometers' }] }",
+        graph.cfg(),
+    );
+    let route_pairs_cfg =
+        configs::routing::Config::from_yaml(resources::BICYCLE_OSM_PBF_YAML, graph.cfg());
+    let route_pairs =
+        io::routing::Parser::parse(&route_pairs_cfg).expect("Route-pairs should exist.");
+
+    let mut dijkstra = Dijkstra::new();
+    for (route_pair, _count) in route_pairs.iter().take(10) {
+        let route_pair = route_pair.into_node(&graph);
+        let mut costs = Vec::new();
+        for routing_cfg in &[&shortest_cfg, &comfortable_cfg] {
+            let mut path = match dijkstra.compute_best_path(dijkstra::Query {
+                src_idx: route_pair.src.idx(),
+                dst_idx: route_pair.dst.idx(),
+                graph: &graph,
+                routing_cfg,
+            }) {
+                Some(path) => path,
+                None => break,
+            };
+            costs.push(path.calc_costs(&graph).clone());
+        }
+        if costs.len() < 2 {
+            continue;
+        }
+
+        // preferring comfort costs distance and vice versa
+        assert!(costs[0][*kilometers_idx] <= costs[1][*kilometers_idx] + 1e-9);
+        assert!(costs[1][*discomfort_km_idx] <= costs[0][*discomfort_km_idx] + 1e-9);
+    }
+}
@@ -0,0 +1,54 @@
+use crate::helpers::{defaults, parse};
+use defaults::paths::resources::isle_of_man as resources;
+use osmgraphing::{
+    configs, io,
+    routing::{
+        dijkstra::{self, Dijkstra},
+        guidance::{self, ManeuverKind},
+    },
+};
+
+#[test]
+fn maneuvers_of_pbf_graph() {
+    let graph = parse(configs::parsing::Config::from_yaml(resources::OSM_PBF_YAML));
+    let routing_cfg = configs::routing::Config::from_yaml(resources::OSM_PBF_YAML, graph.cfg());
+    let kilometers_idx = graph.cfg().edges.metrics.idx_of(defaults::DISTANCE_ID);
+    assert!(!graph.streets().is_empty());
+
+    let mut dijkstra = Dijkstra::new();
+    let mut roundabout_count = 0;
+    let route_pairs = io::routing::Parser::parse(&routing_cfg).expect("Route-pairs should exist.");
+    for (route_pair, _count) in route_pairs.iter().take(5) {
+        let route_pair = route_pair.into_node(&graph);
+        let mut path = dijkstra
+            .compute_best_path(dijkstra::Query {
+                src_idx: route_pair.src.idx(),
+                dst_idx: route_pair.dst.idx(),
+                graph: &graph,
+                routing_cfg: &routing_cfg,
+            })
+            .expect("Path should exist.");
+        let maneuvers = guidance::compute_maneuvers(&path, &graph);
+
+        assert_eq!(maneuvers[0].kind, ManeuverKind::Depart);
+        assert_eq!(maneuvers[maneuvers.len() - 1].kind, ManeuverKind::Arrive);
+        for maneuver in &maneuvers[1..(maneuvers.len() - 1)] {
+            assert_ne!(maneuver.kind, ManeuverKind::Depart);
+            assert_ne!(maneuver.kind, ManeuverKind::Arrive);
+            if let ManeuverKind::Roundabout { exit } = maneuver.kind {
+                assert!(exit >= 1);
+                roundabout_count += 1;
+            }
+        }
+        // streets have names or refs (except few unnamed ones)
+        assert!(maneuvers.iter().any(|maneuver| maneuver.street.is_some()));
+
+        // maneuvers split the path's distance
+        let distance: f64 = maneuvers
+            .iter()
+            .map(|maneuver| maneuver.distance.expect("Graph has distances.").0)
+            .sum();
+        assert!((distance - path.calc_costs(&graph)[*kilometers_idx]).abs() < 1e-6);
+    }
+    assert!(roundabout_count > 0);
+}
@@ -1,2 +1,5 @@
+mod comfort;
+mod guidance;
 mod parsing;
 mod routing;
+mod ways;
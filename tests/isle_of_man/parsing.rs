@@ -1,6 +1,9 @@
-use crate::helpers::{assert_graph_sloppy, defaults, parse};
+use crate::helpers::{
+    assert_eq_graphs, assert_eq_reordered_graphs, assert_graph_sloppy, defaults, parse,
+};
 use defaults::paths::resources::isle_of_man as resources;
-use osmgraphing::configs;
+use osmgraphing::{configs, io, network::NodeOrdering};
+use std::fs;
 
 #[test]
 fn pbf_yaml() {
@@ -42,6 +45,97 @@ fn pbf_graph() {
     assert_graph_sloppy(expected_node_count, expected_edge_count, &graph);
 }
 
+#[test]
+fn pbf_graph_with_external_memory() {
+    let graph = parse(configs::parsing::Config::from_yaml(resources::OSM_PBF_YAML));
+
+    let dir = std::env::temp_dir().join(format!(
+        "osmgraphing-test-external-memory-{}",
+        std::process::id()
+    ));
+    fs::create_dir_all(&dir).expect("Temporary directory should be creatable.");
+    let mut parsing_cfg = configs::parsing::Config::from_yaml(resources::OSM_PBF_YAML);
+    // budget of some thousands proto-edges and nodes, so both are spilled into several runs
+    parsing_cfg.external_memory = Some(configs::parsing::external_memory::Config {
+        dir: dir.clone(),
+        memory_budget_b: 1_000_000,
+        max_merged_runs: osmgraphing::defaults::parsing::external_memory::MAX_MERGED_RUNS,
+    });
+    let external_graph = parse(parsing_cfg);
+
+    assert_eq_graphs(&graph, &external_graph);
+    // temporary files are removed
+    let file_count = fs::read_dir(&dir)
+        .expect("Temporary directory should exist.")
+        .count();
+    fs::remove_dir(&dir).expect("Temporary directory should be removable.");
+    assert_eq!(file_count, 0);
+}
+
+#[test]
+fn corrupt_pbf_graph() {
+    // A block of the map-file can't be decoded, which would be an incomplete graph.
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::OSM_PBF_YAML);
+    let mut bytes = fs::read(&parsing_cfg.map_file).expect("Map-file should be readable.");
+    let mid = bytes.len() / 2;
+    bytes[mid..(mid + 100)]
+        .iter_mut()
+        .for_each(|byte| *byte = !*byte);
+    let map_file = std::env::temp_dir().join(format!(
+        "osmgraphing-test-corrupt-{}.osm.pbf",
+        std::process::id()
+    ));
+    fs::write(&map_file, &bytes).expect("Map-file should be writable.");
+
+    let mut parsing_cfg = configs::parsing::Config::from_yaml(resources::OSM_PBF_YAML);
+    parsing_cfg.map_file = map_file.clone();
+    let result = io::network::graph::Parser::parse_and_finalize(parsing_cfg.clone());
+    parsing_cfg.external_memory = Some(configs::parsing::external_memory::Config {
+        dir: std::env::temp_dir(),
+        memory_budget_b: 1_000_000,
+        max_merged_runs: osmgraphing::defaults::parsing::external_memory::MAX_MERGED_RUNS,
+    });
+    let external_result = io::network::graph::Parser::parse_and_finalize(parsing_cfg);
+    fs::remove_file(&map_file).expect("Map-file should be removable.");
+
+    assert!(result.is_err());
+    assert!(external_result.is_err());
+}
+
+#[test]
+fn pbf_graph_with_threads() {
+    let mut parsing_cfg = configs::parsing::Config::from_yaml(resources::OSM_PBF_YAML);
+    parsing_cfg.num_threads = 1;
+    let graph = parse(parsing_cfg);
+
+    let mut parsing_cfg = configs::parsing::Config::from_yaml(resources::OSM_PBF_YAML);
+    parsing_cfg.num_threads = 4;
+    let parallel_graph = parse(parsing_cfg);
+
+    assert_eq_graphs(&graph, &parallel_graph);
+}
+
+#[test]
+fn pbf_graph_with_node_orderings() {
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::OSM_PBF_YAML);
+    let graph = parse(parsing_cfg);
+    let routing_cfg = configs::routing::Config::from_str(
+        &format!(
+            "routing: {{ algorithm: Dijkstra, metrics: [{{ id: '{}' }}] }}",
+            defaults::DISTANCE_ID
+        ),
+        graph.cfg(),
+    );
+
+    for &node_ordering in &[NodeOrdering::Hilbert, NodeOrdering::Dfs, NodeOrdering::Bfs] {
+        let mut parsing_cfg = configs::parsing::Config::from_yaml(resources::OSM_PBF_YAML);
+        parsing_cfg.node_ordering = node_ordering;
+        let reordered_graph = parse(parsing_cfg);
+
+        assert_eq_reordered_graphs(&graph, &reordered_graph, &routing_cfg, 5_000);
+    }
+}
+
 #[test]
 fn fmi_graph() {
     let parsing_cfg = configs::parsing::Config::from_yaml(resources::FMI_YAML);
@@ -0,0 +1,75 @@
+use crate::helpers::{defaults, parse};
+use defaults::paths::resources::isle_of_man as resources;
+use osmgraphing::{
+    approximating::Approx,
+    configs, io,
+    routing::{
+        dijkstra::{self, Dijkstra},
+        explorating::ConvexHullExplorator,
+    },
+};
+
+#[test]
+fn tolerated_paths() {
+    let graph = parse(configs::parsing::Config::from_yaml(resources::OSM_PBF_YAML));
+    let mut routing_cfg = configs::routing::Config::from_yaml(resources::OSM_PBF_YAML, graph.cfg());
+    let tolerated_scale = 1.1;
+    let metric_indices = vec![
+        graph.cfg().edges.metrics.idx_of(defaults::DISTANCE_ID),
+        graph.cfg().edges.metrics.idx_of(defaults::DURATION_ID),
+    ];
+    for &metric_idx in &metric_indices {
+        routing_cfg.tolerated_scales[*metric_idx] = tolerated_scale;
+    }
+    let mut dijkstra_cfg = routing_cfg.clone();
+
+    let mut dijkstra = Dijkstra::new();
+    let mut explorator = ConvexHullExplorator::new();
+    let mut pruned_cell_count = 0;
+
+    let route_pairs = io::routing::Parser::parse(&routing_cfg)
+        .expect("Parsing and finalizing route-pairs didn't work.");
+    for (route_pair, _) in route_pairs.iter().take(100) {
+        let route_pair = route_pair.into_idx(&graph);
+        let found_paths = explorator.fully_explorate(
+            dijkstra::Query {
+                src_idx: route_pair.src,
+                dst_idx: route_pair.dst,
+                graph: &graph,
+                routing_cfg: &routing_cfg,
+            },
+            &mut dijkstra,
+        );
+        pruned_cell_count += explorator.pruned_cell_count();
+
+        // every found path is within the tolerated scale of every metric's best path
+        for &metric_idx in &metric_indices {
+            dijkstra_cfg.alphas = smallvec::smallvec![0.0; graph.metrics().dim()];
+            dijkstra_cfg.alphas[*metric_idx] = 1.0;
+            let best_path = dijkstra.compute_best_path(dijkstra::Query {
+                src_idx: route_pair.src,
+                dst_idx: route_pair.dst,
+                graph: &graph,
+                routing_cfg: &dijkstra_cfg,
+            });
+            let mut best_path = match best_path {
+                Some(best_path) => best_path,
+                None => {
+                    assert!(found_paths.is_empty());
+                    continue;
+                }
+            };
+            let tolerance = best_path.calc_costs(&graph)[*metric_idx] * tolerated_scale;
+            for path in &found_paths {
+                assert!(
+                    Approx(path.costs()[*metric_idx]) <= Approx(tolerance),
+                    "Explorated path {} exceeds the tolerance {} of metric {}.",
+                    path,
+                    tolerance,
+                    graph.cfg().edges.metrics.ids[*metric_idx]
+                );
+            }
+        }
+    }
+    assert!(pruned_cell_count > 0);
+}
@@ -1,2 +1,3 @@
+mod exploring;
 mod fastest;
 mod shortest;
@@ -0,0 +1,100 @@
+use crate::helpers::{defaults, parse};
+use defaults::paths::resources::{isle_of_man as resources, simple_stuttgart};
+use osmgraphing::{
+    configs::{self, SimpleId},
+    io,
+    network::EdgeIdx,
+};
+use std::fs;
+
+#[test]
+fn ways_of_pbf_graph() {
+    let graph = parse(configs::parsing::Config::from_yaml(resources::OSM_PBF_YAML));
+    let fwd_edges = graph.fwd_edges();
+    let streets = graph.streets();
+    assert!(!streets.is_empty());
+    assert!(streets.count() <= fwd_edges.count());
+
+    let mut named_count = 0;
+    for edge_idx in fwd_edges.iter() {
+        let way_id = streets
+            .way_id(edge_idx)
+            .expect("Every pbf-edge should have an osm-way.");
+        assert!(way_id > 0);
+        assert!(streets.tag(edge_idx, "highway").is_some());
+        if streets.tag(edge_idx, "name").is_some() {
+            named_count += 1;
+        }
+        // only configured tags are kept
+        assert!(streets.tag(edge_idx, "maxspeed").is_none());
+    }
+    assert!(named_count > 0);
+
+    // edges of a way are found by the way's id
+    for edge_idx in fwd_edges.iter().take(100) {
+        let way_id = streets
+            .way_id(edge_idx)
+            .expect("Every pbf-edge should have an osm-way.");
+        let way_edges = streets.edges_of_way(way_id);
+        assert!(way_edges.contains(&edge_idx));
+        for way_edge_idx in way_edges {
+            assert_eq!(
+                streets.street_idx(way_edge_idx),
+                streets.street_idx(edge_idx)
+            );
+        }
+    }
+    assert!(streets.way_id(EdgeIdx(fwd_edges.count())).is_none());
+}
+
+#[test]
+fn ways_of_fmi_graph() {
+    let graph = parse(configs::parsing::Config::from_yaml(
+        simple_stuttgart::FMI_YAML,
+    ));
+    assert!(graph.streets().is_empty());
+    assert!(graph.streets().way_id(EdgeIdx(0)).is_none());
+}
+
+#[test]
+fn writing_ways_as_edge_columns() {
+    let graph = parse(configs::parsing::Config::from_yaml(resources::OSM_PBF_YAML));
+    let file = std::env::temp_dir().join(format!("osmgraphing-{}-ways.csv", std::process::id()));
+    let mut writing_cfg = configs::writing::network::edges::Config {
+        file: file.clone(),
+        is_writing_shortcuts: false,
+        is_writing_header: true,
+        is_denormalizing: false,
+        ids: vec![
+            Some(SimpleId::from("src-id")),
+            Some(SimpleId::from("way-id")),
+        ],
+    };
+    io::network::edges::Writer::write(&graph, &writing_cfg).expect("Writing should work.");
+    let content = fs::read_to_string(&file).expect("Written file should exist.");
+    fs::remove_file(&file).expect("Written file should be removable.");
+
+    let mut lines = content.lines();
+    assert_eq!(lines.next(), Some("src-id way-id"));
+    let fwd_edges = graph.fwd_edges();
+    let streets = graph.streets();
+    for (edge_idx, line) in fwd_edges.iter().zip(lines) {
+        let way_id = streets
+            .way_id(edge_idx)
+            .expect("Every pbf-edge should have an osm-way.");
+        let expected = format!(
+            "{} {}",
+            graph.nodes().id(graph.bwd_edges().dst_idx(edge_idx)),
+            way_id
+        );
+        assert_eq!(line, expected);
+    }
+    assert_eq!(content.lines().count(), fwd_edges.count() + 1);
+
+    // tags are free text, which couldn't be parsed back from space-separated columns
+    writing_cfg.ids.push(Some(SimpleId::from("name")));
+    assert!(io::network::edges::Writer::write(&graph, &writing_cfg).is_err());
+    if file.exists() {
+        fs::remove_file(&file).expect("Written file should be removable.");
+    }
+}
@@ -0,0 +1,201 @@
+use crate::helpers::{defaults, parse};
+use defaults::paths::resources::simple_stuttgart as resources;
+use kissunits::geo::Coordinate;
+use osmgraphing::{
+    approximating::Approx,
+    configs,
+    defaults::capacity::DimVec,
+    network::{EdgeRef, Graph, MetricUpdate, NodeIdx},
+    routing::dijkstra::{self, Dijkstra},
+};
+
+const OPPENWEILER: i64 = 26033921;
+const STUTTGART: i64 = 2933335353;
+
+fn node_idx(graph: &Graph, id: i64) -> NodeIdx {
+    graph
+        .nodes()
+        .idx_from(id)
+        .expect(&format!("Node {} exists.", id))
+}
+
+/// Oppenweiler -> Stuttgart
+fn best_kilometers(graph: &Graph, routing_cfg: &configs::routing::Config) -> Option<f64> {
+    let kilometers_idx = graph.cfg().edges.metrics.idx_of(defaults::DISTANCE_ID);
+    let mut path = Dijkstra::new().compute_best_path(dijkstra::Query {
+        src_idx: node_idx(graph, OPPENWEILER),
+        dst_idx: node_idx(graph, STUTTGART),
+        graph,
+        routing_cfg,
+    })?;
+    Some(path.calc_costs(graph)[*kilometers_idx])
+}
+
+/// Metrics of a new road, given in the graph's units
+fn road_metrics(graph: &Graph, kilometers: f64) -> DimVec<f64> {
+    let kmph = 100.0;
+    graph
+        .cfg()
+        .edges
+        .metrics
+        .ids
+        .iter()
+        .map(|id| match id.0.as_ref() {
+            "meters" => 1_000.0 * kilometers,
+            "kilometers" => kilometers,
+            "kmph" => kmph,
+            "hours" => kilometers / kmph,
+            _ => panic!("Unexpected metric {}", id.0),
+        })
+        .collect()
+}
+
+#[test]
+fn adding_bypass_road() {
+    let mut graph = parse(configs::parsing::Config::from_yaml(resources::FMI_YAML));
+    let routing_cfg = configs::routing::Config::from_yaml(resources::FMI_YAML, graph.cfg());
+    let (node_count, edge_count) = (graph.nodes().count(), graph.fwd_edges().count());
+    assert_eq!(
+        best_kilometers(&graph, &routing_cfg).map(Approx),
+        Some(Approx(48.0))
+    );
+
+    // Oppenweiler -> bypass -> Stuttgart
+    let bypass_idx = graph
+        .add_node(42, Coordinate::from_decimicro(489_000_000, 93_000_000))
+        .expect("Node-id 42 should be new.");
+    assert!(graph.is_edited());
+    assert_eq!(graph.nodes().idx_from(42), Ok(bypass_idx));
+    assert!(graph.add_node(OPPENWEILER, Coordinate::zero()).is_err());
+    let metrics = road_metrics(&graph, 5.0);
+    let edge_idx = graph
+        .add_edge(
+            None,
+            node_idx(&graph, OPPENWEILER),
+            bypass_idx,
+            metrics.clone(),
+        )
+        .expect("Edge should be valid.");
+    graph
+        .add_edge(None, bypass_idx, node_idx(&graph, STUTTGART), metrics)
+        .expect("Edge should be valid.");
+    assert_eq!(graph.nodes().count(), node_count + 1);
+    assert_eq!(graph.fwd_edges().count(), edge_count + 2);
+    {
+        let fwd_edges = graph.fwd_edges();
+        let bwd_edges = graph.bwd_edges();
+        let edge = fwd_edges
+            .between(node_idx(&graph, OPPENWEILER), bypass_idx)
+            .expect("Added edge should be accessible.");
+        assert_eq!(edge.idx(), edge_idx);
+        assert_eq!(
+            bwd_edges.dst_idx(edge_idx),
+            node_idx(&graph, OPPENWEILER),
+            "Src of added edge is wrong."
+        );
+        assert_eq!(bwd_edges.starting_from(bypass_idx).count(), 1);
+    }
+    assert_eq!(
+        best_kilometers(&graph, &routing_cfg).map(Approx),
+        Some(Approx(10.0))
+    );
+
+    // removing the bypass restores the old path
+    assert_eq!(
+        graph
+            .remove_node(bypass_idx)
+            .expect("Bypass should be removable."),
+        2
+    );
+    assert!(graph.is_removed_node(bypass_idx));
+    assert!(graph.is_removed_edge(edge_idx));
+    assert!(graph.remove_edge(EdgeRef::Idx(edge_idx)).is_err());
+    assert_eq!(
+        best_kilometers(&graph, &routing_cfg).map(Approx),
+        Some(Approx(48.0))
+    );
+}
+
+#[test]
+fn removing_edges() {
+    let mut graph = parse(configs::parsing::Config::from_yaml(resources::FMI_YAML));
+    let routing_cfg = configs::routing::Config::from_yaml(resources::FMI_YAML, graph.cfg());
+
+    // Backnang -> Waiblingen
+    let src_idx = node_idx(&graph, 26160028);
+    let dst_idx = node_idx(&graph, 252787940);
+    let edge_idx = graph
+        .fwd_edges()
+        .between(src_idx, dst_idx)
+        .expect("Edge Backnang -> Waiblingen exists.")
+        .idx();
+    graph
+        .remove_edge(EdgeRef::Idx(edge_idx))
+        .expect("Edge should be removable.");
+    assert!(graph.fwd_edges().between(src_idx, dst_idx).is_none());
+    assert!(graph
+        .bwd_edges()
+        .starting_from(dst_idx)
+        .all(|edge| edge.idx() != edge_idx));
+    match best_kilometers(&graph, &routing_cfg) {
+        Some(kilometers) => assert!(kilometers > 48.0),
+        None => (),
+    }
+}
+
+#[test]
+fn compacting() {
+    let mut graph = parse(configs::parsing::Config::from_yaml(resources::FMI_YAML));
+    let routing_cfg = configs::routing::Config::from_yaml(resources::FMI_YAML, graph.cfg());
+    let (node_count, edge_count) = (graph.nodes().count(), graph.fwd_edges().count());
+
+    // new bypass, closed edge and removed node
+    let bypass_idx = graph
+        .add_node(42, Coordinate::from_decimicro(489_000_000, 93_000_000))
+        .expect("Node-id 42 should be new.");
+    let metrics = road_metrics(&graph, 5.0);
+    graph
+        .add_edge(
+            None,
+            node_idx(&graph, OPPENWEILER),
+            bypass_idx,
+            metrics.clone(),
+        )
+        .expect("Edge should be valid.");
+    graph
+        .add_edge(None, bypass_idx, node_idx(&graph, STUTTGART), metrics)
+        .expect("Edge should be valid.");
+    let closed_idx = graph
+        .fwd_edges()
+        .starting_from(node_idx(&graph, 26160028))
+        .next()
+        .expect("Backnang has leaving edges.")
+        .idx();
+    graph
+        .update_metrics(&[MetricUpdate::Close {
+            edge: EdgeRef::Idx(closed_idx),
+        }])
+        .expect("Update should be valid.");
+    let removed_edge_count = graph
+        .remove_node(node_idx(&graph, 252787940))
+        .expect("Waiblingen should be removable.");
+    let best = best_kilometers(&graph, &routing_cfg).map(Approx);
+
+    let compacted = graph.compact().expect("Compacting should work.");
+    assert!(!compacted.is_edited());
+    assert_eq!(compacted.nodes().count(), node_count);
+    assert_eq!(
+        compacted.fwd_edges().count(),
+        edge_count + 2 - removed_edge_count
+    );
+    assert!(compacted.nodes().idx_from(252787940).is_err());
+    assert_eq!(best_kilometers(&compacted, &routing_cfg).map(Approx), best);
+
+    // closures are kept
+    let closed_edge_count = compacted
+        .fwd_edges()
+        .iter()
+        .filter(|&edge_idx| compacted.is_closed(edge_idx))
+        .count();
+    assert_eq!(closed_edge_count, 1);
+}
@@ -0,0 +1,75 @@
+use crate::helpers::{defaults, parse};
+use defaults::paths::resources::simple_stuttgart as resources;
+use osmgraphing::{
+    approximating::Approx,
+    configs,
+    routing::{
+        dijkstra::{self, Dijkstra},
+        guidance::{self, ManeuverKind},
+    },
+};
+
+#[test]
+fn maneuvers_without_streets() {
+    let graph = parse(configs::parsing::Config::from_yaml(resources::FMI_YAML));
+    let nodes = graph.nodes();
+    let routing_cfg = configs::routing::Config::from_yaml(resources::FMI_YAML, graph.cfg());
+    assert!(graph.streets().is_empty());
+
+    // Oppenweiler -> Backnang -> Waiblingen -> Stuttgart
+    let path = Dijkstra::new()
+        .compute_best_path(dijkstra::Query {
+            src_idx: nodes.idx_from(26033921).expect("Oppenweiler exists."),
+            dst_idx: nodes.idx_from(2933335353).expect("Stuttgart exists."),
+            graph: &graph,
+            routing_cfg: &routing_cfg,
+        })
+        .expect("Path should exist.");
+    let maneuvers = guidance::compute_maneuvers(&path, &graph);
+
+    // Backnang is passed straight on, but Waiblingen needs a turn
+    assert_eq!(
+        maneuvers
+            .iter()
+            .map(|maneuver| maneuver.kind)
+            .collect::<Vec<_>>(),
+        vec![
+            ManeuverKind::Depart,
+            ManeuverKind::SlightRight,
+            ManeuverKind::Arrive
+        ]
+    );
+    assert!(maneuvers.iter().all(|maneuver| maneuver.street.is_none()));
+    assert_eq!(
+        maneuvers
+            .iter()
+            .map(|maneuver| Approx(maneuver.distance.expect("Graph has distances.").0))
+            .collect::<Vec<_>>(),
+        vec![Approx(31.0), Approx(17.0), Approx(0.0)]
+    );
+    assert!(maneuvers.iter().all(|maneuver| maneuver.duration.is_some()));
+    assert_eq!(maneuvers[1].instruction(), "Turn slightly right");
+}
+
+#[test]
+fn maneuvers_of_empty_path() {
+    let graph = parse(configs::parsing::Config::from_yaml(resources::FMI_YAML));
+    let nodes = graph.nodes();
+    let routing_cfg = configs::routing::Config::from_yaml(resources::FMI_YAML, graph.cfg());
+    let backnang_idx = nodes.idx_from(26160028).expect("Backnang exists.");
+
+    let path = Dijkstra::new()
+        .compute_best_path(dijkstra::Query {
+            src_idx: backnang_idx,
+            dst_idx: backnang_idx,
+            graph: &graph,
+            routing_cfg: &routing_cfg,
+        })
+        .expect("Path should exist.");
+    let maneuvers = guidance::compute_maneuvers(&path, &graph);
+
+    assert_eq!(maneuvers.len(), 2);
+    assert_eq!(maneuvers[0].kind, ManeuverKind::Depart);
+    assert_eq!(maneuvers[1].kind, ManeuverKind::Arrive);
+    assert_eq!(maneuvers[1].coord, nodes.coord(backnang_idx));
+}
@@ -0,0 +1,127 @@
+use crate::helpers::{defaults, parse};
+use defaults::paths::resources::simple_stuttgart as resources;
+use kissunits::speed::KilometersPerHour;
+use osmgraphing::{
+    approximating::Approx,
+    configs, io,
+    network::{EdgeIdx, Graph},
+    routing::matching::MapMatcher,
+};
+use std::path::PathBuf;
+
+const OFF_MAP_POINT_IDX: usize = 4;
+
+fn edge_ids(graph: &Graph, edge_idx: EdgeIdx) -> (i64, i64) {
+    let nodes = graph.nodes();
+    (
+        nodes.id(graph.bwd_edges().dst_idx(edge_idx)),
+        nodes.id(graph.fwd_edges().dst_idx(edge_idx)),
+    )
+}
+
+#[test]
+fn parsing_gpx_and_csv() {
+    let graph = parse(configs::parsing::Config::from_yaml(resources::FMI_YAML));
+    let mut matching_cfg = configs::matching::Config::from_yaml(resources::FMI_YAML, graph.cfg());
+
+    let gpx_trace = io::matching::Parser::parse(&matching_cfg).expect("Parsing gpx should work.");
+    matching_cfg.trace_file = Some(PathBuf::from("resources/simple_stuttgart/trace.csv"));
+    let csv_trace = io::matching::Parser::parse(&matching_cfg).expect("Parsing csv should work.");
+
+    assert_eq!(gpx_trace.len(), 10);
+    assert_eq!(gpx_trace, csv_trace);
+    // 2020-06-01T12:00:00Z and every 10 minutes
+    for (i, point) in gpx_trace.iter().enumerate() {
+        assert_eq!(point.timestamp, Some(1_591_012_800.0 + 600.0 * i as f64));
+    }
+
+    matching_cfg.trace_file = Some(PathBuf::from(resources::FMI_YAML));
+    assert!(io::matching::Parser::parse(&matching_cfg).is_err());
+}
+
+#[test]
+fn match_trace() {
+    let graph = parse(configs::parsing::Config::from_yaml(resources::FMI_YAML));
+    let matching_cfg = configs::matching::Config::from_yaml(resources::FMI_YAML, graph.cfg());
+    let trace = io::matching::Parser::parse(&matching_cfg).expect("Parsing gpx should work.");
+    let mut map_matcher = MapMatcher::new(&graph, matching_cfg);
+
+    let mut matching = map_matcher.match_trace(&trace, &graph);
+
+    // Oppenweiler -> Backnang -> Waiblingen -> Stuttgart
+    assert_eq!(matching.paths.len(), 1);
+    let path = &mut matching.paths[0];
+    let first_partial_edge = *path
+        .first_partial_edge()
+        .expect("Matched paths start with partial edges.");
+    let last_partial_edge = *path
+        .last_partial_edge()
+        .expect("Matched paths end with partial edges.");
+    assert_eq!(
+        edge_ids(&graph, first_partial_edge.edge_idx),
+        (26033921, 26160028)
+    );
+    assert_eq!(
+        path.iter()
+            .map(|&edge_idx| edge_ids(&graph, edge_idx))
+            .collect::<Vec<_>>(),
+        vec![(26160028, 252787940)]
+    );
+    assert_eq!(
+        edge_ids(&graph, last_partial_edge.edge_idx),
+        (252787940, 2933335353)
+    );
+    assert!((first_partial_edge.start - 0.25).abs() < 0.05);
+    assert_eq!(Approx(first_partial_edge.end), Approx(1.0));
+    assert_eq!(Approx(last_partial_edge.start), Approx(0.0));
+    assert!((last_partial_edge.end - 0.75).abs() < 0.05);
+
+    // 8 km, 23 km and 17 km
+    let distance_idx = graph.cfg().edges.metrics.idx_of(defaults::DISTANCE_ID);
+    assert_eq!(
+        Approx(path.calc_costs(&graph)[*distance_idx]),
+        Approx(8.0 * first_partial_edge.fraction() + 23.0 + 17.0 * last_partial_edge.fraction())
+    );
+
+    assert_eq!(matching.points.len(), trace.len());
+    for (point_idx, point) in matching.points.iter().enumerate() {
+        if point_idx == OFF_MAP_POINT_IDX {
+            assert!(point.phantom.is_none());
+            assert_eq!(point.path_idx, None);
+            assert_eq!(point.confidence, 0.0);
+        } else {
+            let phantom = point.phantom.as_ref().expect("Point should be matched.");
+            assert!(phantom.distance.0 < 0.05);
+            assert_eq!(point.path_idx, Some(0));
+            assert!(0.0 < point.confidence && point.confidence <= 1.0 + 1e-9);
+        }
+    }
+}
+
+#[test]
+fn max_speed_splits_trace() {
+    let graph = parse(configs::parsing::Config::from_yaml(resources::FMI_YAML));
+    let mut matching_cfg = configs::matching::Config::from_yaml(resources::FMI_YAML, graph.cfg());
+    let trace = io::matching::Parser::parse(&matching_cfg).expect("Parsing gpx should work.");
+
+    // Consecutive points are at least 2 km apart, which can't be driven in 10 minutes.
+    matching_cfg.max_speed = Some(KilometersPerHour(5.0));
+    let mut map_matcher = MapMatcher::new(&graph, matching_cfg);
+    let matching = map_matcher.match_trace(&trace, &graph);
+
+    assert_eq!(matching.paths.len(), trace.len() - 1);
+    for path in &matching.paths {
+        let partial_edge = path
+            .first_partial_edge()
+            .expect("Matched paths start with partial edges.");
+        assert_eq!(Approx(partial_edge.fraction()), Approx(0.0));
+        assert!(path.last_partial_edge().is_none());
+        assert_eq!(path.iter().count(), 0);
+    }
+    let path_idxs: Vec<_> = matching
+        .points
+        .iter()
+        .filter_map(|point| point.path_idx)
+        .collect();
+    assert_eq!(path_idxs, (0..trace.len() - 1).collect::<Vec<_>>());
+}
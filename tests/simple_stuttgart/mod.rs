@@ -1,2 +1,10 @@
+mod editing;
+mod guidance;
+mod matching;
 mod parsing;
 mod routing;
+mod serving;
+mod snapping;
+mod storing;
+mod updating;
+mod writing;
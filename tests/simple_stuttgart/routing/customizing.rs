@@ -0,0 +1,21 @@
+use crate::helpers::{defaults, test_cch};
+use defaults::paths::resources::simple_stuttgart as resources;
+use osmgraphing::network::NodeOrdering;
+
+#[test]
+fn cch_on_map() {
+    test_cch(
+        resources::FMI_YAML,
+        &[defaults::DISTANCE_ID, defaults::DURATION_ID],
+        NodeOrdering::Id,
+    );
+}
+
+#[test]
+fn cch_on_map_with_hilbert_ordering() {
+    test_cch(
+        resources::FMI_YAML,
+        &[defaults::DISTANCE_ID, defaults::DURATION_ID],
+        NodeOrdering::Hilbert,
+    );
+}
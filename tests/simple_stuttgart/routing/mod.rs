@@ -1,2 +1,5 @@
+mod customizing;
+mod engine;
 mod fastest;
+mod phantoms;
 mod shortest;
@@ -0,0 +1,21 @@
+use crate::helpers::{defaults, test_phantom_paths};
+use defaults::paths::resources::simple_stuttgart as resources;
+use osmgraphing::configs::routing::RoutingAlgo;
+
+#[test]
+fn shortest() {
+    test_phantom_paths(
+        resources::FMI_YAML,
+        defaults::DISTANCE_ID,
+        RoutingAlgo::Dijkstra,
+    );
+}
+
+#[test]
+fn fastest() {
+    test_phantom_paths(
+        resources::FMI_YAML,
+        defaults::DURATION_ID,
+        RoutingAlgo::Dijkstra,
+    );
+}
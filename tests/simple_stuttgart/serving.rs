@@ -0,0 +1,242 @@
+use crate::helpers::{defaults, server};
+use defaults::paths::resources::simple_stuttgart as resources;
+use osmgraphing::approximating::Approx;
+use serde_json::{json, Value};
+
+fn cost(path: &Value) -> f64 {
+    path["cost"].as_f64().expect("Path should have a cost.")
+}
+
+#[test]
+fn health_and_metadata() {
+    let addr = server::spawn(resources::FMI_YAML);
+
+    let (status, body) = server::request(&addr, "GET", "/health", &Value::Null);
+    assert_eq!(status, 200);
+    assert_eq!(body, json!({ "status": "ok" }));
+
+    let (status, body) = server::request(&addr, "GET", "/metadata", &Value::Null);
+    assert_eq!(status, 200);
+    assert_eq!(body["node-count"], json!(6));
+    assert_eq!(body["edge-count"], json!(13));
+    assert_eq!(body["shortcut-count"], json!(0));
+    assert_eq!(body["routing"]["alphas"]["kilometers"], json!(1.0));
+}
+
+#[test]
+fn route() {
+    let addr = server::spawn(resources::FMI_YAML);
+
+    // by idx, by id and by coordinate
+    let (status, by_idx) = server::request(
+        &addr,
+        "POST",
+        "/route",
+        &json!({ "src": { "idx": 0 }, "dst": { "idx": 5 } }),
+    );
+    assert_eq!(status, 200);
+    let nodes = by_idx["path"]["nodes"]
+        .as_array()
+        .expect("Path should have nodes.");
+    assert_eq!(nodes.first().expect("Path has nodes.")["idx"], json!(0));
+    assert_eq!(nodes.last().expect("Path has nodes.")["idx"], json!(5));
+    assert_eq!(Approx(cost(&by_idx["path"])), Approx(48.0));
+
+    let (status, by_id) = server::request(
+        &addr,
+        "POST",
+        "/route",
+        &json!({ "src": { "id": nodes[0]["id"] }, "dst": { "lat": 48.770176, "lon": 9.156577 } }),
+    );
+    assert_eq!(status, 200);
+    assert_eq!(by_id, by_idx);
+
+    // alphas per request
+    let (status, body) = server::request(
+        &addr,
+        "POST",
+        "/route",
+        &json!({ "src": { "idx": 0 }, "dst": { "idx": 5 }, "alphas": { "hours": 1.0 } }),
+    );
+    assert_eq!(status, 200);
+    assert_eq!(
+        Approx(cost(&body["path"])),
+        Approx(
+            body["path"]["costs"]["hours"]
+                .as_f64()
+                .expect("Hours exist.")
+        )
+    );
+}
+
+#[test]
+fn matrix() {
+    let addr = server::spawn(resources::FMI_YAML);
+
+    let (status, body) = server::request(
+        &addr,
+        "POST",
+        "/matrix",
+        &json!({
+            "srcs": [{ "idx": 0 }, { "idx": 1 }],
+            "dsts": [{ "idx": 5 }, { "idx": 2 }, { "idx": 0 }],
+        }),
+    );
+    assert_eq!(status, 200);
+    let costs = body["costs"].as_array().expect("Costs should be a matrix.");
+    assert_eq!(costs.len(), 2);
+    for (src, row) in [0, 1].iter().zip(costs) {
+        let row = row.as_array().expect("Costs should be a matrix.");
+        assert_eq!(row.len(), 3);
+        for (dst, matrix_cost) in [5, 2, 0].iter().zip(row) {
+            let (_, route) = server::request(
+                &addr,
+                "POST",
+                "/route",
+                &json!({ "src": { "idx": src }, "dst": { "idx": dst } }),
+            );
+            assert_eq!(
+                matrix_cost.as_f64().map(Approx),
+                route["path"]["cost"].as_f64().map(Approx),
+                "Matrix-cost from {} to {} should be the route's cost.",
+                src,
+                dst
+            );
+        }
+    }
+
+    // spawned servers accept at most 9 route-pairs
+    let (status, body) = server::request(
+        &addr,
+        "POST",
+        "/matrix",
+        &json!({
+            "srcs": [{ "idx": 0 }, { "idx": 1 }, { "idx": 2 }, { "idx": 3 }],
+            "dsts": [{ "idx": 5 }, { "idx": 2 }, { "idx": 0 }],
+        }),
+    );
+    assert_eq!(status, 400);
+    assert!(body["error"].is_string());
+}
+
+#[test]
+fn explorator_and_isochrones() {
+    let addr = server::spawn(resources::FMI_YAML);
+
+    let (status, body) = server::request(
+        &addr,
+        "POST",
+        "/explorator",
+        &json!({
+            "src": { "idx": 0 },
+            "dst": { "idx": 5 },
+            "alphas": { "kilometers": 1.0, "hours": 1.0 },
+        }),
+    );
+    assert_eq!(status, 200);
+    assert!(!body["paths"]
+        .as_array()
+        .expect("Paths should be an array.")
+        .is_empty());
+
+    let (status, body) = server::request(
+        &addr,
+        "POST",
+        "/isochrones",
+        &json!({ "src": { "idx": 1 }, "limits": [0.0, 25.0, 1000.0] }),
+    );
+    assert_eq!(status, 200);
+    let isochrones = body["isochrones"]
+        .as_array()
+        .expect("Isochrones should be an array.");
+    let counts: Vec<_> = isochrones
+        .iter()
+        .map(|isochrone| {
+            let nodes = isochrone["nodes"]
+                .as_array()
+                .expect("Nodes should be an array.");
+            let limit = isochrone["limit"]
+                .as_f64()
+                .expect("Limit should be a number.");
+            assert!(nodes
+                .iter()
+                .all(|node| node["cost"].as_f64().expect("Nodes have costs.") <= limit));
+            nodes.len()
+        })
+        .collect();
+    assert_eq!(counts[0], 1, "Only the src should be reachable for free.");
+    assert!(counts[0] <= counts[1] && counts[1] <= counts[2] && counts[2] <= 6);
+}
+
+#[test]
+fn nearest_and_errors() {
+    let addr = server::spawn(resources::FMI_YAML);
+
+    let (status, body) = server::request(
+        &addr,
+        "POST",
+        "/nearest",
+        &json!({ "lat": 48.770176, "lon": 9.156577 }),
+    );
+    assert_eq!(status, 200);
+    assert_eq!(body["node"]["idx"], json!(5));
+    assert_eq!(
+        Approx(body["distance"].as_f64().expect("Distance is a number.")),
+        Approx(0.0)
+    );
+
+    let (status, _) = server::request(&addr, "GET", "/unknown", &Value::Null);
+    assert_eq!(status, 404);
+    let (status, _) = server::request(&addr, "GET", "/route", &Value::Null);
+    assert_eq!(status, 405);
+    let (status, body) = server::request(
+        &addr,
+        "POST",
+        "/route",
+        &json!({ "src": { "idx": 0 }, "dst": { "idx": 5 }, "alphas": { "unknown": 1.0 } }),
+    );
+    assert_eq!(status, 400);
+    assert!(body["error"].is_string());
+    let (status, _) = server::request(
+        &addr,
+        "POST",
+        "/route",
+        &json!({ "src": { "idx": 0 }, "dst": { "idx": 100 } }),
+    );
+    assert_eq!(status, 400);
+    let (status, _) = server::request(
+        &addr,
+        "POST",
+        "/explorator",
+        &json!({
+            "src": { "idx": 0 },
+            "dst": { "idx": 5 },
+            "tolerated-scales": { "kilometers": -1.0 }
+        }),
+    );
+    assert_eq!(status, 400);
+    // infinite alphas can only be sent as raw json
+    let body = r#"{ "src": { "idx": 0 }, "dst": { "idx": 5 }, "alphas": { "kilometers": 1e400 } }"#;
+    let (status, _) = server::request_raw(
+        &addr,
+        &format!(
+            "POST /route HTTP/1.1\r\nContent-Length: {}\r\n\r\n{}",
+            body.len(),
+            body
+        ),
+    );
+    assert_eq!(status, 400);
+
+    // headers are limited
+    let (status, body) = server::request_raw(
+        &addr,
+        &format!(
+            "GET /health HTTP/1.1\r\nX-Padding: {}\r\n\r\n",
+            "a".repeat(2 * osmgraphing::defaults::server::MAX_HEADER_SIZE)
+        ),
+    );
+    assert_eq!(status, 431);
+    assert!(body["error"].is_string());
+    let (status, _) = server::request_raw(&addr, "GET /health HTTP/1.1\r\nHost: ");
+    assert_eq!(status, 400);
+}
@@ -0,0 +1,296 @@
+use crate::helpers::{defaults, parse};
+use defaults::paths::resources::simple_stuttgart as resources;
+use kissunits::{
+    distance::Kilometers,
+    geo::{self, Coordinate},
+};
+use osmgraphing::{
+    approximating::Approx,
+    configs, io,
+    network::{EdgeIndex, Graph, NodeIdx, RoutePair, SnappingFilter, SpatialIndex},
+    routing::dijkstra::{self, Dijkstra},
+};
+
+const DEAD_END_ID: i64 = 1621605361;
+
+/// Some coordinates in and around the graph's bounding-box
+fn probes() -> Vec<Coordinate> {
+    let mut probes = Vec::new();
+    for lat in &[48.7, 48.8, 48.9, 48.94, 49.0, 49.1] {
+        for lon in &[9.1, 9.2, 9.3, 9.4, 9.419, 9.5] {
+            probes.push(Coordinate {
+                lat: *lat,
+                lon: *lon,
+            });
+        }
+    }
+    probes
+}
+
+/// All nodes passing the filter with their distances, sorted by distance
+fn brute_force(
+    graph: &Graph,
+    coord: &Coordinate,
+    is_dead_end_excluded: bool,
+) -> Vec<(NodeIdx, f64)> {
+    let nodes = graph.nodes();
+    let mut candidates: Vec<_> = nodes
+        .iter()
+        .filter(|&node_idx| !is_dead_end_excluded || nodes.id(node_idx) != DEAD_END_ID)
+        .map(|node_idx| {
+            (
+                node_idx,
+                geo::haversine_distance_km(coord, &nodes.coord(node_idx)).0,
+            )
+        })
+        .collect();
+    candidates.sort_by(|(_, a), (_, b)| a.partial_cmp(b).expect("Distances are numbers."));
+    candidates
+}
+
+#[test]
+fn nearest_node() {
+    let graph = parse(configs::parsing::Config::from_yaml(resources::FMI_YAML));
+    let nodes = graph.nodes();
+
+    for filter in &[SnappingFilter::All, SnappingFilter::Accessible] {
+        let spatial_index = SpatialIndex::new(&graph, *filter);
+        assert_eq!(spatial_index.count(), nodes.count());
+
+        for node_idx in nodes.iter() {
+            let (nearest_idx, distance) = spatial_index
+                .nearest_node(&nodes.coord(node_idx))
+                .expect("Index should not be empty.");
+            assert_eq!(nearest_idx, node_idx);
+            assert_eq!(Approx(distance.0), Approx(0.0));
+        }
+    }
+
+    // The dead-end is only reachable, but can't be left.
+    let spatial_index = SpatialIndex::new(&graph, SnappingFilter::LargestComponent);
+    assert_eq!(spatial_index.count(), nodes.count() - 1);
+    let dead_end_idx = nodes.idx_from(DEAD_END_ID).expect("Dead-end exists.");
+    let (nearest_idx, _) = spatial_index
+        .nearest_node(&nodes.coord(dead_end_idx))
+        .expect("Index should not be empty.");
+    assert_eq!(nodes.id(nearest_idx), 26160028, "Backnang is closest.");
+}
+
+#[test]
+fn k_nearest_and_within_radius() {
+    let graph = parse(configs::parsing::Config::from_yaml(resources::FMI_YAML));
+
+    for (filter, is_dead_end_excluded) in &[
+        (SnappingFilter::All, false),
+        (SnappingFilter::LargestComponent, true),
+    ] {
+        let spatial_index = SpatialIndex::new(&graph, *filter);
+
+        for coord in probes() {
+            let expected = brute_force(&graph, &coord, *is_dead_end_excluded);
+
+            for k in 0..=expected.len() + 1 {
+                let distances: Vec<_> = spatial_index
+                    .k_nearest(&coord, k)
+                    .into_iter()
+                    .map(|(_, distance)| Approx(distance.0))
+                    .collect();
+                let expected_distances: Vec<_> = expected
+                    .iter()
+                    .take(k)
+                    .map(|(_, distance)| Approx(*distance))
+                    .collect();
+                assert_eq!(distances, expected_distances, "k={} at {}", k, coord);
+            }
+
+            for radius in &[0.0, 1.0, 5.0, 10.0, 20.0, 50.0] {
+                let mut found: Vec<_> = spatial_index
+                    .within_radius(&coord, Kilometers(*radius))
+                    .into_iter()
+                    .map(|(node_idx, _)| node_idx)
+                    .collect();
+                found.sort();
+                let mut expected_found: Vec<_> = expected
+                    .iter()
+                    .filter(|(_, distance)| distance <= radius)
+                    .map(|(node_idx, _)| *node_idx)
+                    .collect();
+                expected_found.sort();
+                assert_eq!(found, expected_found, "radius={} at {}", radius, coord);
+            }
+        }
+    }
+}
+
+#[test]
+fn route_pairs_as_coords() {
+    let graph = parse(configs::parsing::Config::from_yaml(resources::FMI_YAML));
+    let nodes = graph.nodes();
+    let spatial_index = SpatialIndex::new(&graph, SnappingFilter::LargestComponent);
+
+    let raw_cfg = format!(
+        "{}\n{}\n{}\n{}\n{}",
+        "routing:",
+        "  route-pairs-file: 'resources/simple_stuttgart/coords_3.route-pairs'",
+        "  algorithm: 'Dijkstra'",
+        "  metrics:",
+        format!("  - id: '{}'", defaults::DISTANCE_ID),
+    );
+    let routing_cfg = configs::routing::Config::from_str(&raw_cfg, graph.cfg());
+    let route_pairs = io::routing::Parser::parse_coords(&routing_cfg)
+        .expect("Parsing route-pairs of coordinates should work.");
+
+    let expected = vec![
+        ((26033921, 2933335353), 1),
+        ((26160028, 252787940), 2),
+        ((298249467, 26160028), 1),
+    ];
+    assert_eq!(route_pairs.len(), expected.len());
+    for ((route_pair, count), ((src_id, dst_id), expected_count)) in
+        route_pairs.into_iter().zip(expected)
+    {
+        let RoutePair { src, dst } = route_pair
+            .try_into_idx(&spatial_index)
+            .expect("Snapping should work.");
+        assert_eq!((nodes.id(src), nodes.id(dst)), (src_id, dst_id));
+        assert_eq!(count, expected_count);
+    }
+
+    // route-pairs of ids are no route-pairs of coordinates
+    assert!(io::routing::Parser::parse(&routing_cfg).is_err());
+}
+
+#[test]
+fn snap_to_edges() {
+    let graph = parse(configs::parsing::Config::from_yaml(resources::FMI_YAML));
+    let nodes = graph.nodes();
+    let fwd_edges = graph.fwd_edges();
+    let bwd_edges = graph.bwd_edges();
+    let edge_index = EdgeIndex::new(&graph, SnappingFilter::All);
+    assert_eq!(edge_index.count(), fwd_edges.count());
+
+    // between Backnang and Waiblingen, slightly moved to the side
+    let backnang = nodes.coord(nodes.idx_from(26160028).expect("Backnang exists."));
+    let waiblingen = nodes.coord(nodes.idx_from(252787940).expect("Waiblingen exists."));
+    let coord = Coordinate {
+        lat: backnang.lat + 0.25 * (waiblingen.lat - backnang.lat) + 0.001,
+        lon: backnang.lon + 0.25 * (waiblingen.lon - backnang.lon) + 0.001,
+    };
+    let phantom_node = edge_index
+        .snap(&coord, &graph)
+        .expect("Index should not be empty.");
+
+    assert!(phantom_node.distance.0 < 0.2);
+    assert!(
+        phantom_node.distance.0
+            < SpatialIndex::new(&graph, SnappingFilter::All)
+                .nearest_node(&coord)
+                .expect("Index should not be empty.")
+                .1
+                 .0
+    );
+    // both directions of the street
+    assert_eq!(phantom_node.positions.len(), 2);
+    for position in &phantom_node.positions {
+        let src_id = nodes.id(bwd_edges.dst_idx(position.edge_idx));
+        let dst_id = nodes.id(fwd_edges.dst_idx(position.edge_idx));
+        let expected_fraction = match (src_id, dst_id) {
+            (26160028, 252787940) => 0.25,
+            (252787940, 26160028) => 0.75,
+            _ => panic!("Snapped to wrong edge ({} -> {}).", src_id, dst_id),
+        };
+        assert!((position.fraction - expected_fraction).abs() < 0.05);
+    }
+
+    // The dead-end has no reverse edge and is not part of the largest component.
+    let dead_end = nodes.coord(nodes.idx_from(DEAD_END_ID).expect("Dead-end exists."));
+    let phantom_node = edge_index
+        .snap(&dead_end, &graph)
+        .expect("Index should not be empty.");
+    assert_eq!(phantom_node.positions.len(), 1);
+    assert_eq!(Approx(phantom_node.distance.0), Approx(0.0));
+    let phantom_node = EdgeIndex::new(&graph, SnappingFilter::LargestComponent)
+        .snap(&dead_end, &graph)
+        .expect("Index should not be empty.");
+    assert!(phantom_node.distance.0 > 0.0);
+}
+
+#[test]
+fn phantom_path() {
+    let graph = parse(configs::parsing::Config::from_yaml(resources::FMI_YAML));
+    let nodes = graph.nodes();
+    let edge_index = EdgeIndex::new(&graph, SnappingFilter::LargestComponent);
+    let routing_cfg = configs::routing::Config::from_yaml(resources::FMI_YAML, graph.cfg());
+
+    // from the middle of Oppenweiler->Backnang (8 km) to Stuttgart
+    let coord = |id: i64| nodes.coord(nodes.idx_from(id).expect("Node exists."));
+    let (oppenweiler, backnang) = (coord(26033921), coord(26160028));
+    let src = edge_index
+        .snap(
+            &Coordinate {
+                lat: (oppenweiler.lat + backnang.lat) / 2.0,
+                lon: (oppenweiler.lon + backnang.lon) / 2.0,
+            },
+            &graph,
+        )
+        .expect("Index should not be empty.");
+    let dst = edge_index
+        .snap(&coord(2933335353), &graph)
+        .expect("Index should not be empty.");
+
+    let mut dijkstra = Dijkstra::new();
+    let mut path = dijkstra
+        .compute_best_phantom_path(dijkstra::PhantomQuery {
+            src: &src,
+            dst: &dst,
+            graph: &graph,
+            routing_cfg: &routing_cfg,
+        })
+        .expect("Path should exist.");
+
+    // 4 km to Backnang, 23 km to Waiblingen and 17 km to Stuttgart
+    let distance_idx = graph.cfg().edges.metrics.idx_of(defaults::DISTANCE_ID);
+    assert_eq!(Approx(path.calc_costs(&graph)[*distance_idx]), Approx(44.0));
+    let first_partial_edge = path
+        .first_partial_edge()
+        .expect("Phantom-paths start with partial edges.");
+    assert_eq!(Approx(first_partial_edge.fraction()), Approx(0.5));
+    assert_eq!(nodes.id(path.src_idx()), 26033921);
+
+    // The geometry starts and ends at the phantom-nodes.
+    let coords = path.coords(&graph);
+    assert_eq!(coords.len(), path.iter().count() + 3);
+    for (coord, phantom_node) in &[(coords[0], &src), (coords[coords.len() - 1], &dst)] {
+        assert_eq!(Approx(coord.lat), Approx(phantom_node.coord.lat));
+        assert_eq!(Approx(coord.lon), Approx(phantom_node.coord.lon));
+    }
+}
+
+#[test]
+fn edge_candidates() {
+    let graph = parse(configs::parsing::Config::from_yaml(resources::FMI_YAML));
+    let nodes = graph.nodes();
+    let edge_index = EdgeIndex::new(&graph, SnappingFilter::All);
+
+    // near Backnang, where 7 edges start or end
+    let backnang = nodes.coord(nodes.idx_from(26160028).expect("Backnang exists."));
+    let coord = Coordinate {
+        lat: backnang.lat + 0.0005,
+        lon: backnang.lon,
+    };
+
+    let candidates = edge_index.candidates(&coord, Kilometers(1.0), 100);
+    assert_eq!(candidates.len(), 7);
+    for candidate in &candidates {
+        assert_eq!(candidate.positions.len(), 1);
+        assert!(candidate.distance.0 <= 1.0);
+    }
+    for window in candidates.windows(2) {
+        assert!(window[0].distance.0 <= window[1].distance.0);
+    }
+
+    assert_eq!(edge_index.candidates(&coord, Kilometers(1.0), 3).len(), 3);
+    assert!(edge_index
+        .candidates(&coord, Kilometers(0.001), 100)
+        .is_empty());
+}
@@ -0,0 +1,221 @@
+use crate::helpers::{defaults, parse};
+use defaults::paths::resources::simple_stuttgart as resources;
+use osmgraphing::{
+    approximating::Approx,
+    configs::{self, parsing::edges::metrics::Precision, SimpleId},
+    io,
+    network::{EdgeIdx, EdgeRef, MetricIdx, MetricUpdate},
+    routing::dijkstra::{self, Dijkstra},
+};
+
+#[test]
+fn compact_metrics() {
+    let graph = parse(configs::parsing::Config::from_yaml(resources::FMI_YAML));
+    let compact_graph = parse(configs::parsing::Config::from_yaml(
+        resources::COMPACT_FMI_YAML,
+    ));
+    assert_eq!(compact_graph.fwd_edges().count(), graph.fwd_edges().count());
+    assert!(compact_graph.metrics().mem_size_b() < graph.metrics().mem_size_b());
+
+    let metrics_cfg = &compact_graph.cfg().edges.metrics;
+    assert_eq!(
+        metrics_cfg.precision_of(metrics_cfg.idx_of("kilometers")),
+        Precision::F32
+    );
+    assert_eq!(
+        compact_graph.metrics()[metrics_cfg.idx_of("hours")].precision(),
+        Precision::U32 { scale: 0.000_001 }
+    );
+
+    for metric_idx in (0..graph.metrics().dim()).map(MetricIdx) {
+        let precision = metrics_cfg.precision_of(metric_idx);
+        for edge_idx in graph.fwd_edges().iter() {
+            let expected = graph.metrics()[metric_idx].get(edge_idx);
+            let actual = compact_graph.metrics()[metric_idx].get(edge_idx);
+            assert!(
+                Approx(actual).eq_within(&Approx(expected), precision.tolerance(expected)),
+                "Metric {} of edge (idx: {}) is {}, but should be {}.",
+                metrics_cfg.ids[*metric_idx],
+                edge_idx,
+                actual,
+                expected
+            );
+        }
+    }
+}
+
+#[test]
+fn routing_on_compact_metrics() {
+    let graph = parse(configs::parsing::Config::from_yaml(resources::FMI_YAML));
+    let routing_cfg = configs::routing::Config::from_yaml(resources::FMI_YAML, graph.cfg());
+    let compact_graph = parse(configs::parsing::Config::from_yaml(
+        resources::COMPACT_FMI_YAML,
+    ));
+    let compact_routing_cfg =
+        configs::routing::Config::from_yaml(resources::COMPACT_FMI_YAML, compact_graph.cfg());
+    let kilometers_idx = graph.cfg().edges.metrics.idx_of(defaults::DISTANCE_ID);
+    let precision = compact_graph
+        .cfg()
+        .edges
+        .metrics
+        .precision_of(kilometers_idx);
+
+    let mut dijkstra = Dijkstra::new();
+    let nodes = graph.nodes();
+    for src_idx in nodes.iter() {
+        for dst_idx in nodes.iter() {
+            let path = dijkstra.compute_best_path(dijkstra::Query {
+                src_idx,
+                dst_idx,
+                graph: &graph,
+                routing_cfg: &routing_cfg,
+            });
+            let compact_path = dijkstra.compute_best_path(dijkstra::Query {
+                src_idx,
+                dst_idx,
+                graph: &compact_graph,
+                routing_cfg: &compact_routing_cfg,
+            });
+
+            match (path, compact_path) {
+                (Some(mut path), Some(mut compact_path)) => {
+                    let expected = path.calc_costs(&graph)[*kilometers_idx];
+                    let actual = compact_path.calc_costs(&compact_graph)[*kilometers_idx];
+                    // every edge of the path may contribute its rounding-error
+                    let tolerance = precision.tolerance(expected) * nodes.count() as f64;
+                    assert!(
+                        Approx(actual).eq_within(&Approx(expected), tolerance),
+                        "Path from {} to {} has {} km, but should have {} km.",
+                        nodes.id(src_idx),
+                        nodes.id(dst_idx),
+                        actual,
+                        expected
+                    );
+                }
+                (None, None) => (),
+                _ => panic!(
+                    "Path from {} to {} should exist in both graphs or in none.",
+                    nodes.id(src_idx),
+                    nodes.id(dst_idx)
+                ),
+            }
+        }
+    }
+}
+
+#[test]
+fn invalid_precisions() {
+    // unknown metric
+    let mut parsing_cfg = configs::parsing::Config::from_yaml(resources::FMI_YAML);
+    parsing_cfg
+        .edges
+        .metrics
+        .precisions
+        .insert(SimpleId::from("unknown"), Precision::F32);
+    assert!(io::network::graph::Parser::parse_and_finalize(parsing_cfg).is_err());
+
+    // kilometers are too big for this fixed-point-range
+    let mut parsing_cfg = configs::parsing::Config::from_yaml(resources::FMI_YAML);
+    parsing_cfg.edges.metrics.precisions.insert(
+        SimpleId::from("kilometers"),
+        Precision::U32 { scale: 1e-12 },
+    );
+    assert!(io::network::graph::Parser::parse_and_finalize(parsing_cfg).is_err());
+}
+
+#[test]
+fn updating_compact_metrics() {
+    let mut graph = parse(configs::parsing::Config::from_yaml(
+        resources::COMPACT_FMI_YAML,
+    ));
+    let hours_idx = graph.cfg().edges.metrics.idx_of("hours");
+    let edge_idx = EdgeIdx(0);
+
+    // rounded to the fixed-point-scale
+    graph
+        .update_metrics(&[MetricUpdate::Set {
+            edge: EdgeRef::Idx(edge_idx),
+            metric_idx: hours_idx,
+            value: 0.123_456_7,
+        }])
+        .expect("Update should be valid.");
+    assert!(Approx(graph.metrics()[hours_idx].get(edge_idx)).eq_within(
+        &Approx(0.123_457),
+        Precision::U32 { scale: 0.000_001 }.tolerance(0.0)
+    ));
+
+    // too big for the fixed-point-range
+    assert!(graph
+        .update_metrics(&[MetricUpdate::Set {
+            edge: EdgeRef::Idx(edge_idx),
+            metric_idx: hours_idx,
+            value: 1e6,
+        }])
+        .is_err());
+}
+
+#[test]
+fn negative_metrics() {
+    let parsing_cfg_with_offset = || {
+        let mut parsing_cfg = configs::parsing::Config::from_yaml(resources::FMI_YAML);
+        parsing_cfg
+            .generating
+            .as_mut()
+            .expect("Config should generate metrics.")
+            .edges
+            .categories
+            .push(configs::parsing::generating::edges::Category::Custom {
+                unit: configs::parsing::generating::edges::metrics::UnitInfo::F64,
+                id: SimpleId::from("offset"),
+                default: -1.0,
+            });
+        parsing_cfg
+    };
+
+    // every value can be stored as f64
+    let graph = parse(parsing_cfg_with_offset());
+    let offset_idx = graph.cfg().edges.metrics.idx_of("offset");
+    for edge_idx in graph.fwd_edges().iter() {
+        assert_eq!(graph.metrics()[offset_idx].get(edge_idx), -1.0);
+    }
+
+    // but not with lossy precisions
+    let mut parsing_cfg = parsing_cfg_with_offset();
+    parsing_cfg
+        .edges
+        .metrics
+        .precisions
+        .insert(SimpleId::from("offset"), Precision::F32);
+    assert!(io::network::graph::Parser::parse_and_finalize(parsing_cfg).is_err());
+}
+
+#[test]
+fn duplicates_within_precisions() {
+    // duplicate Oppenweiler -> Backnang, differing less than the fixed-point-scale of kmph
+    let map_file = std::env::temp_dir().join(format!(
+        "osmgraphing-{}-duplicates_within_precisions.fmi",
+        std::process::id()
+    ));
+    let fmi = std::fs::read_to_string(resources::FMI_YAML.replace("fmi.yaml", "graph.fmi"))
+        .expect("Map-file should be readable.")
+        .replacen("# edge-count\n13", "# edge-count\n14", 1)
+        .replacen(
+            "# edges:\n",
+            "# edges:\n  26033921    26160028  8000          50.004\n",
+            1,
+        );
+    std::fs::write(&map_file, fmi).expect("Map-file should be writable.");
+
+    // compared with f64-precision, the edges are different
+    let mut parsing_cfg = configs::parsing::Config::from_yaml(resources::FMI_YAML);
+    parsing_cfg.map_file = map_file.clone();
+    let graph = parse(parsing_cfg);
+    assert_eq!(graph.fwd_edges().count(), 14);
+
+    // stored with less precision, the edges can't be distinguished
+    let mut parsing_cfg = configs::parsing::Config::from_yaml(resources::COMPACT_FMI_YAML);
+    parsing_cfg.map_file = map_file.clone();
+    let compact_graph = parse(parsing_cfg);
+    std::fs::remove_file(&map_file).expect("Map-file should be removable.");
+    assert_eq!(compact_graph.fwd_edges().count(), 13);
+}
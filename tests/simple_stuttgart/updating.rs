@@ -0,0 +1,145 @@
+use crate::helpers::{defaults, parse};
+use defaults::paths::resources::simple_stuttgart as resources;
+use osmgraphing::{
+    approximating::Approx,
+    configs, io,
+    network::{EdgeIdx, EdgeRef, Graph, MetricUpdate, NodeIdx},
+    routing::dijkstra::{self, Dijkstra},
+};
+use std::{fs, path::PathBuf};
+
+fn update_file(file_name: &str, content: &str) -> PathBuf {
+    let file =
+        std::env::temp_dir().join(format!("osmgraphing-{}-{}", std::process::id(), file_name));
+    fs::write(&file, content).expect("Update-file should be writable.");
+    file
+}
+
+/// Oppenweiler -> Stuttgart
+fn best_kilometers(graph: &Graph, routing_cfg: &configs::routing::Config) -> Option<f64> {
+    let nodes = graph.nodes();
+    let kilometers_idx = graph.cfg().edges.metrics.idx_of(defaults::DISTANCE_ID);
+    let mut path = Dijkstra::new().compute_best_path(dijkstra::Query {
+        src_idx: nodes.idx_from(26033921).expect("Oppenweiler exists."),
+        dst_idx: nodes.idx_from(2933335353).expect("Stuttgart exists."),
+        graph,
+        routing_cfg,
+    })?;
+    Some(path.calc_costs(graph)[*kilometers_idx])
+}
+
+/// Backnang -> Waiblingen
+fn backnang_waiblingen(graph: &Graph) -> EdgeIdx {
+    let nodes = graph.nodes();
+    let src_idx: NodeIdx = nodes.idx_from(26160028).expect("Backnang exists.");
+    let dst_idx: NodeIdx = nodes.idx_from(252787940).expect("Waiblingen exists.");
+    graph
+        .fwd_edges()
+        .between(src_idx, dst_idx)
+        .expect("Edge Backnang -> Waiblingen exists.")
+        .idx()
+}
+
+#[test]
+fn closing_and_reopening() {
+    let mut graph = parse(configs::parsing::Config::from_yaml(resources::FMI_YAML));
+    let routing_cfg = configs::routing::Config::from_yaml(resources::FMI_YAML, graph.cfg());
+    assert_eq!(
+        best_kilometers(&graph, &routing_cfg).map(Approx),
+        Some(Approx(48.0))
+    );
+
+    let edge = EdgeRef::Idx(backnang_waiblingen(&graph));
+    let stats = graph
+        .update_metrics(&[MetricUpdate::Close { edge }, MetricUpdate::Close { edge }])
+        .expect("Updates should be valid.");
+    assert_eq!(stats.closed_edge_count, 1);
+    match best_kilometers(&graph, &routing_cfg) {
+        Some(kilometers) => assert!(kilometers > 48.0),
+        None => (),
+    }
+
+    // values set while closed are applied when reopening
+    let kilometers_idx = graph.cfg().edges.metrics.idx_of(defaults::DISTANCE_ID);
+    graph
+        .update_metrics(&[
+            MetricUpdate::Set {
+                edge,
+                metric_idx: kilometers_idx,
+                value: 1.0,
+            },
+            MetricUpdate::Reopen { edge },
+        ])
+        .expect("Updates should be valid.");
+    assert!(best_kilometers(&graph, &routing_cfg).expect("Path should exist.") < 48.0);
+}
+
+#[test]
+fn updates_from_files() {
+    let mut graph = parse(configs::parsing::Config::from_yaml(resources::FMI_YAML));
+    let routing_cfg = configs::routing::Config::from_yaml(resources::FMI_YAML, graph.cfg());
+    let edge_idx = backnang_waiblingen(&graph);
+
+    let csv_file = update_file(
+        "updates.csv",
+        &format!(
+            "# live traffic\nedge-idx,metric-id,value\n{},,closed\n{},kilometers,100.0\n",
+            *edge_idx, *edge_idx
+        ),
+    );
+    let updates = io::network::updates::Parser::parse(&csv_file, graph.cfg())
+        .expect("Csv-file should be valid.");
+    fs::remove_file(&csv_file).expect("Csv-file should be removable.");
+    assert_eq!(updates.len(), 2);
+    graph
+        .update_metrics(&updates)
+        .expect("Updates should be valid.");
+    assert!(graph.is_closed(edge_idx));
+
+    let json_file = update_file(
+        "updates.json",
+        &format!("[{{ \"edge-idx\": {}, \"value\": \"open\" }}]", *edge_idx),
+    );
+    let updates = io::network::updates::Parser::parse(&json_file, graph.cfg())
+        .expect("Json-file should be valid.");
+    fs::remove_file(&json_file).expect("Json-file should be removable.");
+    graph
+        .update_metrics(&updates)
+        .expect("Updates should be valid.");
+    assert!(!graph.is_closed(edge_idx));
+    let kilometers_idx = graph.cfg().edges.metrics.idx_of(defaults::DISTANCE_ID);
+    assert_eq!(
+        Approx(graph.metrics()[kilometers_idx].get(edge_idx)),
+        Approx(100.0)
+    );
+    match best_kilometers(&graph, &routing_cfg) {
+        Some(kilometers) => assert!(kilometers > 48.0),
+        None => (),
+    }
+}
+
+#[test]
+fn invalid_update_files() {
+    let graph = parse(configs::parsing::Config::from_yaml(resources::FMI_YAML));
+    for (file_name, content) in &[
+        ("no-edge.csv", "metric-id,value\nkilometers,1.0\n"),
+        (
+            "unknown-metric.csv",
+            "edge-idx,metric-id,value\n0,unknown,1.0\n",
+        ),
+        (
+            "unknown-keyword.csv",
+            "edge-idx,metric-id,value\n0,,blocked\n",
+        ),
+        (
+            "both-edges.json",
+            "[{ \"edge-idx\": 0, \"edge-id\": 0, \"value\": \"closed\" }]",
+        ),
+        ("no-metric.json", "[{ \"edge-idx\": 0, \"value\": 1.0 }]"),
+    ] {
+        let file = update_file(file_name, content);
+        let result = io::network::updates::Parser::parse(&file, graph.cfg());
+        fs::remove_file(&file).expect("Update-file should be removable.");
+        assert!(result.is_err(), "{} should be invalid.", file_name);
+    }
+}
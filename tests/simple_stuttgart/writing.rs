@@ -0,0 +1,338 @@
+use crate::helpers::{defaults, parse};
+use defaults::paths::resources::simple_stuttgart as resources;
+use kissunits::geo::Coordinate;
+use osmgraphing::{
+    approximating::Approx,
+    configs::{self, SimpleId},
+    io,
+    network::{BoundingBox, Graph},
+    routing::dijkstra::{self, Dijkstra},
+};
+use serde_json::Value;
+use std::{fs, path::PathBuf};
+
+const OPPENWEILER_ID: i64 = 26033921;
+const STUTTGART_ID: i64 = 2933335353;
+
+const BACKNANG_ID: i64 = 26160028;
+
+fn geojson_cfg(file_name: &str) -> configs::writing::geojson::Config {
+    let file =
+        std::env::temp_dir().join(format!("osmgraphing-{}-{}", std::process::id(), file_name));
+    configs::writing::geojson::Config {
+        file,
+        is_writing_shortcuts: false,
+        is_denormalizing: false,
+        color_id: None,
+        bbox: None,
+    }
+}
+
+fn read(file: &PathBuf) -> Value {
+    let geojson = fs::read_to_string(file).expect("Written file should be readable.");
+    fs::remove_file(file).expect("Written file should be removable.");
+    serde_json::from_str(&geojson).expect("Written file should be valid json.")
+}
+
+fn features_of(geojson: &Value) -> &Vec<Value> {
+    assert_eq!(geojson["type"], "FeatureCollection");
+    geojson["features"]
+        .as_array()
+        .expect("Features should be an array.")
+}
+
+fn coords_of(graph: &Graph, id: i64) -> Value {
+    let nodes = graph.nodes();
+    let coord = nodes.coord(nodes.idx_from(id).expect("Node exists."));
+    serde_json::json!([coord.lon, coord.lat])
+}
+
+#[test]
+fn geojson_path() {
+    let graph = parse(configs::parsing::Config::from_yaml(resources::FMI_YAML));
+    let nodes = graph.nodes();
+    let routing_cfg = configs::routing::Config::from_yaml(resources::FMI_YAML, graph.cfg());
+
+    // Oppenweiler -> Backnang -> Waiblingen -> Stuttgart
+    let path = Dijkstra::new()
+        .compute_best_path(dijkstra::Query {
+            src_idx: nodes.idx_from(26033921).expect("Oppenweiler exists."),
+            dst_idx: nodes.idx_from(2933335353).expect("Stuttgart exists."),
+            graph: &graph,
+            routing_cfg: &routing_cfg,
+        })
+        .expect("Path should exist.");
+
+    let writing_cfg = geojson_cfg("path.geojson");
+    io::geojson::Writer::write_path(&path, &graph, &writing_cfg)
+        .expect("Writing the path should work.");
+    let feature = read(&writing_cfg.file);
+
+    assert_eq!(feature["type"], "Feature");
+    assert_eq!(feature["geometry"]["type"], "LineString");
+    assert_eq!(
+        feature["geometry"]["coordinates"],
+        Value::Array(
+            [26033921, BACKNANG_ID, 252787940, 2933335353]
+                .iter()
+                .map(|&id| coords_of(&graph, id))
+                .collect()
+        )
+    );
+    let properties = &feature["properties"];
+    assert_eq!(properties["src-id"], 26033921);
+    assert_eq!(properties["dst-id"], 2933335353i64);
+    assert_eq!(
+        Approx(
+            properties[defaults::DISTANCE_ID]
+                .as_f64()
+                .expect("Costs are numbers.")
+        ),
+        Approx(48.0)
+    );
+}
+
+#[test]
+fn geojson_paths() {
+    let graph = parse(configs::parsing::Config::from_yaml(resources::FMI_YAML));
+    let nodes = graph.nodes();
+    let routing_cfg = configs::routing::Config::from_yaml(resources::FMI_YAML, graph.cfg());
+
+    let mut dijkstra = Dijkstra::new();
+    let paths: Vec<_> = [(26033921, BACKNANG_ID), (BACKNANG_ID, BACKNANG_ID)]
+        .iter()
+        .map(|&(src_id, dst_id)| {
+            dijkstra
+                .compute_best_path(dijkstra::Query {
+                    src_idx: nodes.idx_from(src_id).expect("Node exists."),
+                    dst_idx: nodes.idx_from(dst_id).expect("Node exists."),
+                    graph: &graph,
+                    routing_cfg: &routing_cfg,
+                })
+                .expect("Path should exist.")
+        })
+        .collect();
+
+    let writing_cfg = geojson_cfg("paths.geojson");
+    io::geojson::Writer::write_paths(&paths, &graph, &writing_cfg)
+        .expect("Writing the paths should work.");
+    let geojson = read(&writing_cfg.file);
+    let features = features_of(&geojson);
+
+    assert_eq!(features.len(), 2);
+    for (path_idx, feature) in features.iter().enumerate() {
+        assert_eq!(feature["properties"]["path-idx"], path_idx);
+        // LineStrings have at least two positions, even for empty paths.
+        assert_eq!(
+            feature["geometry"]["coordinates"]
+                .as_array()
+                .expect("Coordinates should be an array.")
+                .len(),
+            2
+        );
+    }
+    assert_eq!(
+        Approx(
+            features[1]["properties"][defaults::DISTANCE_ID]
+                .as_f64()
+                .expect("Costs are numbers.")
+        ),
+        Approx(0.0)
+    );
+}
+
+#[test]
+fn geojson_graph() {
+    let graph = parse(configs::parsing::Config::from_yaml(resources::FMI_YAML));
+
+    // coloured by distance: 1.069 km is the shortest, 23 km the longest edge
+    let mut writing_cfg = geojson_cfg("graph.geojson");
+    writing_cfg.color_id = Some(SimpleId::from(defaults::DISTANCE_ID));
+    io::geojson::Writer::write_graph(&graph, &writing_cfg).expect("Writing the graph should work.");
+    let geojson = read(&writing_cfg.file);
+    let features = features_of(&geojson);
+
+    assert_eq!(features.len(), graph.fwd_edges().count());
+    for feature in features {
+        let properties = &feature["properties"];
+        let distance = properties[defaults::DISTANCE_ID]
+            .as_f64()
+            .expect("Metrics are numbers.");
+        let expected_stroke = if distance == 23.0 {
+            Some("#ff0000")
+        } else if distance == 1.069 {
+            Some("#00ff00")
+        } else {
+            None
+        };
+        if let Some(expected_stroke) = expected_stroke {
+            assert_eq!(properties["stroke"], expected_stroke);
+        }
+        assert_eq!(properties["is-shortcut"], false);
+    }
+
+    // only edges of Backnang, coloured by external values
+    let backnang = {
+        let nodes = graph.nodes();
+        nodes.coord(nodes.idx_from(BACKNANG_ID).expect("Backnang exists."))
+    };
+    let mut writing_cfg = geojson_cfg("bbox.geojson");
+    writing_cfg.bbox = Some(BoundingBox {
+        min: Coordinate {
+            lat: backnang.lat - 0.001,
+            lon: backnang.lon - 0.001,
+        },
+        max: Coordinate {
+            lat: backnang.lat + 0.001,
+            lon: backnang.lon + 0.001,
+        },
+    });
+    let workloads: Vec<f64> = graph.fwd_edges().iter().map(|idx| *idx as f64).collect();
+    io::geojson::Writer::write_graph_with_values(&workloads, "workload", &graph, &writing_cfg)
+        .expect("Writing the graph should work.");
+    let geojson = read(&writing_cfg.file);
+    let features = features_of(&geojson);
+
+    assert_eq!(features.len(), 7);
+    for feature in features {
+        let properties = &feature["properties"];
+        assert!(properties["src-id"] == BACKNANG_ID || properties["dst-id"] == BACKNANG_ID);
+        assert!(properties["workload"].is_number());
+        assert!(properties["stroke"].is_string());
+    }
+
+    // one value per edge
+    assert!(io::geojson::Writer::write_graph_with_values(
+        &workloads[1..],
+        "workload",
+        &graph,
+        &writing_cfg
+    )
+    .is_err());
+}
+
+fn paths_cfg(
+    file_name: &str,
+    gpx_kind: configs::writing::paths::GpxKind,
+) -> configs::writing::paths::Config {
+    let file =
+        std::env::temp_dir().join(format!("osmgraphing-{}-{}", std::process::id(), file_name));
+    configs::writing::paths::Config {
+        file,
+        gpx_kind,
+        is_denormalizing: false,
+    }
+}
+
+/// Oppenweiler -> Backnang -> Waiblingen -> Stuttgart
+fn best_path(graph: &Graph) -> osmgraphing::routing::paths::Path {
+    let nodes = graph.nodes();
+    let routing_cfg = configs::routing::Config::from_yaml(resources::FMI_YAML, graph.cfg());
+    Dijkstra::new()
+        .compute_best_path(dijkstra::Query {
+            src_idx: nodes.idx_from(OPPENWEILER_ID).expect("Oppenweiler exists."),
+            dst_idx: nodes.idx_from(STUTTGART_ID).expect("Stuttgart exists."),
+            graph,
+            routing_cfg: &routing_cfg,
+        })
+        .expect("Path should exist.")
+}
+
+fn coord_of(graph: &Graph, id: i64) -> Coordinate {
+    let nodes = graph.nodes();
+    nodes.coord(nodes.idx_from(id).expect("Node exists."))
+}
+
+#[test]
+fn gpx_paths() {
+    let graph = parse(configs::parsing::Config::from_yaml(resources::FMI_YAML));
+    let mut matching_cfg = configs::matching::Config::from_yaml(resources::FMI_YAML, graph.cfg());
+    let path = best_path(&graph);
+    let expected_coords: Vec<Coordinate> = [OPPENWEILER_ID, BACKNANG_ID, 252787940, STUTTGART_ID]
+        .iter()
+        .map(|&id| coord_of(&graph, id))
+        .collect();
+
+    for (gpx_kind, point_tag) in &[
+        (configs::writing::paths::GpxKind::Track, "<trkpt"),
+        (configs::writing::paths::GpxKind::Route, "<rtept"),
+    ] {
+        let writing_cfg = paths_cfg("paths.gpx", *gpx_kind);
+        io::routing::PathsWriter::write(&[path.clone()], &graph, &writing_cfg)
+            .expect("Writing the paths should work.");
+
+        // written points are parsable as gps-trace
+        matching_cfg.trace_file = Some(writing_cfg.file.clone());
+        let trace = io::matching::Parser::parse(&matching_cfg).expect("Parsing gpx should work.");
+        let gpx = fs::read_to_string(&writing_cfg.file).expect("Written file should be readable.");
+        fs::remove_file(&writing_cfg.file).expect("Written file should be removable.");
+
+        assert_eq!(
+            trace.iter().map(|point| point.coord).collect::<Vec<_>>(),
+            expected_coords
+        );
+        assert_eq!(gpx.matches(point_tag).count(), 4);
+        assert!(gpx.contains(&format!(
+            "<name>{} to {}</name>",
+            OPPENWEILER_ID, STUTTGART_ID
+        )));
+        // extensions are in their own namespace
+        assert!(gpx.contains(&format!(
+            "xmlns:osmgraphing=\"{}\"",
+            osmgraphing::defaults::writing::paths::GPX_NAMESPACE
+        )));
+        assert!(!gpx.contains("<metric "));
+        // total costs and one value per leg (Oppenweiler -> Backnang is 8 km)
+        assert_eq!(
+            gpx.matches(&format!(
+                "<osmgraphing:metric id=\"{}\">",
+                defaults::DISTANCE_ID
+            ))
+            .count(),
+            4
+        );
+        assert!(gpx.contains(&format!(
+            "<osmgraphing:metric id=\"{}\">48</osmgraphing:metric>",
+            defaults::DISTANCE_ID
+        )));
+        assert!(gpx.contains(&format!(
+            "<osmgraphing:metric id=\"{}\">8</osmgraphing:metric>",
+            defaults::DISTANCE_ID
+        )));
+    }
+}
+
+#[test]
+fn kml_paths() {
+    let graph = parse(configs::parsing::Config::from_yaml(resources::FMI_YAML));
+    let path = best_path(&graph);
+
+    let writing_cfg = paths_cfg("paths.kml", configs::writing::paths::GpxKind::Track);
+    io::routing::PathsWriter::write(&[path.clone(), path], &graph, &writing_cfg)
+        .expect("Writing the paths should work.");
+    let kml = fs::read_to_string(&writing_cfg.file).expect("Written file should be readable.");
+    fs::remove_file(&writing_cfg.file).expect("Written file should be removable.");
+
+    // one folder per path with the whole path and its 3 legs
+    assert_eq!(kml.matches("<Folder>").count(), 2);
+    assert_eq!(kml.matches("<Placemark>").count(), 8);
+    let oppenweiler = coord_of(&graph, OPPENWEILER_ID);
+    let backnang = coord_of(&graph, BACKNANG_ID);
+    assert!(kml.contains(&format!(
+        "<coordinates>{},{} {},{}</coordinates>",
+        oppenweiler.lon, oppenweiler.lat, backnang.lon, backnang.lat
+    )));
+    assert!(kml.contains(&format!(
+        "<Data name=\"{}\"><value>48</value></Data>",
+        defaults::DISTANCE_ID
+    )));
+}
+
+#[test]
+fn paths_cfg_is_optional() {
+    assert!(
+        configs::writing::paths::Config::try_from_yaml(resources::FMI_YAML)
+            .expect("Parsing the config should work.")
+            .is_none()
+    );
+}
@@ -0,0 +1,160 @@
+use crate::helpers::{defaults, parse};
+use defaults::paths::resources::small as resources;
+use kissunits::geo::Coordinate;
+use osmgraphing::{
+    approximating::Approx,
+    configs::{self, SimpleId},
+    network::BoundingBox,
+};
+
+#[test]
+fn fmi_graph() {
+    let graph = parse(configs::parsing::Config::from_yaml(resources::FMI_YAML));
+    let stats = graph.analyze();
+
+    assert_eq!(stats.node_count, 8);
+    assert_eq!(stats.edge_count, 16);
+    assert_eq!(stats.shortcut_count, 0);
+    assert_eq!(stats.shortcut_ratio(), 0.0);
+    assert_eq!(stats.out_degree_histogram, vec![1, 0, 5, 2]);
+    assert_eq!(stats.in_degree_histogram, vec![1, 0, 5, 2]);
+    // a and g are on their own, b and c reach each other, as well as d, e, f and h
+    assert_eq!(stats.component_count, 4);
+    assert_eq!(stats.largest_component_sizes, vec![4, 2, 1, 1]);
+    assert_eq!(stats.ch_level_histogram, vec![8]);
+    assert_eq!(stats.self_loop_count, 0);
+    assert_eq!(stats.parallel_edge_count, 0);
+    assert_eq!(
+        stats.bounding_box,
+        Some(BoundingBox {
+            min: Coordinate::zero(),
+            max: Coordinate::zero(),
+        })
+    );
+    assert!(stats.issues().is_empty());
+
+    // 13 edges of 1 m, 2 edges of 2 m and 1 edge of 4 m
+    let kilometers = &stats.metrics[*graph.cfg().edges.metrics.idx_of(defaults::DISTANCE_ID)];
+    assert_eq!(kilometers.id, defaults::DISTANCE_ID);
+    assert!(Approx(kilometers.min) == Approx(0.001));
+    assert!(Approx(kilometers.max) == Approx(0.004));
+    assert!(Approx(kilometers.mean) == Approx(0.021 / 16.0));
+    for &(percent, value) in &kilometers.percentiles {
+        let expected = match percent {
+            p if p <= 13 * 100 / 16 => 0.001,
+            p if p <= 15 * 100 / 16 => 0.002,
+            _ => 0.004,
+        };
+        assert!(
+            Approx(value) == Approx(expected),
+            "The {}th percentile should be {}, but is {}.",
+            percent,
+            expected,
+            value
+        );
+    }
+    assert_eq!(kilometers.zero_count, 0);
+    assert_eq!(kilometers.negative_count, 0);
+
+    let json = stats.to_json();
+    assert_eq!(json["node-count"], 8);
+    assert_eq!(json["metrics"].as_array().map(Vec::len), Some(3));
+    assert_eq!(json["issues"].as_array().map(Vec::len), Some(0));
+}
+
+#[test]
+fn ch_fmi_graph() {
+    let graph = parse(configs::parsing::Config::from_yaml(resources::CH_FMI_YAML));
+    let stats = graph.analyze();
+
+    assert_eq!(stats.node_count, graph.nodes().count());
+    assert_eq!(stats.edge_count, graph.fwd_edges().count());
+    assert!(stats.shortcut_count > 0);
+    assert!(
+        Approx(stats.shortcut_ratio())
+            == Approx(stats.shortcut_count as f64 / stats.edge_count as f64)
+    );
+    assert_eq!(
+        stats.ch_level_histogram.iter().sum::<usize>(),
+        stats.node_count
+    );
+    assert!(stats.ch_level_histogram.len() > 1);
+    // shortcuts are parallel by design, but neither counted as parallel nor as degree
+    assert_eq!(stats.parallel_edge_count, 0);
+    assert_eq!(
+        stats.out_degree_histogram.iter().sum::<usize>(),
+        stats.node_count
+    );
+    assert_eq!(
+        stats
+            .out_degree_histogram
+            .iter()
+            .enumerate()
+            .map(|(degree, count)| degree * count)
+            .sum::<usize>(),
+        stats.edge_count - stats.shortcut_count
+    );
+}
+
+#[test]
+fn edited_graph_with_issues() {
+    let mut graph = parse(configs::parsing::Config::from_yaml(resources::FMI_YAML));
+    let nodes = graph.nodes();
+    let (a, b) = (
+        nodes.idx_from(0).expect("Node a should exist."),
+        nodes.idx_from(1).expect("Node b should exist."),
+    );
+    let g = nodes.idx_from(6).expect("Node g should exist.");
+    let metrics = graph
+        .metrics()
+        .get(graph.fwd_edges().between(b, a).unwrap().idx());
+
+    // self-loop and parallel edge
+    graph
+        .add_edge(None, a, a, metrics.clone())
+        .expect("Adding a self-loop should work.");
+    graph
+        .add_edge(None, b, a, metrics)
+        .expect("Adding a parallel edge should work.");
+    // removing g removes its leaving edges as well
+    graph.remove_node(g).expect("Removing g should work.");
+
+    let stats = graph.analyze();
+    assert_eq!(stats.node_count, 7);
+    assert_eq!(stats.edge_count, 16);
+    assert_eq!(stats.self_loop_count, 1);
+    assert_eq!(stats.parallel_edge_count, 1);
+    assert_eq!(stats.component_count, 3);
+    assert_eq!(stats.largest_component_sizes, vec![4, 2, 1]);
+    assert_eq!(stats.issues().len(), 2);
+    assert_eq!(stats.to_json()["issues"].as_array().map(Vec::len), Some(2));
+}
+
+#[test]
+fn negative_metrics() {
+    let mut parsing_cfg = configs::parsing::Config::from_yaml(resources::FMI_YAML);
+    parsing_cfg
+        .generating
+        .as_mut()
+        .expect("Config should generate metrics.")
+        .edges
+        .categories
+        .push(configs::parsing::generating::edges::Category::Custom {
+            unit: configs::parsing::generating::edges::metrics::UnitInfo::F64,
+            id: SimpleId::from("offset"),
+            default: -1.0,
+        });
+    let graph = parse(parsing_cfg);
+    let stats = graph.analyze();
+
+    let offset = &stats.metrics[*graph.cfg().edges.metrics.idx_of("offset")];
+    assert_eq!(offset.negative_count, 16);
+    assert_eq!(offset.zero_count, 0);
+    assert_eq!(offset.min, -1.0);
+    assert_eq!(offset.max, -1.0);
+    assert_eq!(stats.issues().len(), 1);
+    assert_eq!(
+        stats.to_json()["metrics"][*graph.cfg().edges.metrics.idx_of("offset")]["negative-count"],
+        16
+    );
+}
@@ -0,0 +1,122 @@
+use crate::helpers::{defaults, parse};
+use defaults::paths::resources::small as resources;
+use kissunits::geo::Coordinate;
+use osmgraphing::{
+    approximating::Approx,
+    configs, io,
+    network::{DiffKind, EdgeMatching, EdgeRef, MetricIdx, MetricUpdate, NodeIdx},
+};
+use std::fs;
+
+#[test]
+fn equal_graphs() {
+    let old_graph = parse(configs::parsing::Config::from_yaml(resources::FMI_YAML));
+    let new_graph = parse(configs::parsing::Config::from_yaml(resources::FMI_YAML));
+    let diff = old_graph
+        .diff(&new_graph, EdgeMatching::NodeIds)
+        .expect("Diffing equal graphs should work.");
+    assert!(diff.is_empty());
+    assert_eq!(diff.metric_ids[..], old_graph.cfg().edges.metrics.ids[..]);
+
+    // shortcuts are ignored
+    let ch_graph = parse(configs::parsing::Config::from_yaml(resources::CH_FMI_YAML));
+    let diff = old_graph
+        .diff(&ch_graph, EdgeMatching::NodeIds)
+        .expect("Diffing a graph with its ch-graph should work.");
+    assert!(diff.is_empty());
+}
+
+#[test]
+fn edited_graph() {
+    let old_graph = parse(configs::parsing::Config::from_yaml(resources::FMI_YAML));
+    let mut new_graph = parse(configs::parsing::Config::from_yaml(resources::FMI_YAML));
+    let km_idx = *old_graph.cfg().edges.metrics.idx_of(defaults::DISTANCE_ID);
+
+    // b -> c gets longer
+    let edge_idx = new_graph
+        .fwd_edges()
+        .starting_from(NodeIdx(1))
+        .find(|edge| edge.dst_idx() == NodeIdx(2))
+        .expect("Edge b -> c should exist.")
+        .idx();
+    new_graph
+        .update_metrics(&[MetricUpdate::Set {
+            edge: EdgeRef::Idx(edge_idx),
+            metric_idx: MetricIdx(km_idx),
+            value: 0.005,
+        }])
+        .expect("Updating b -> c should work.");
+    // g is removed with its edges g -> e and g -> f
+    new_graph
+        .remove_node(NodeIdx(6))
+        .expect("Removing g should work.");
+    // node 42 is added with an edge to a
+    let metrics = new_graph.metrics().get(edge_idx);
+    let node_idx = new_graph
+        .add_node(42, Coordinate { lat: 1.0, lon: 1.0 })
+        .expect("Adding node 42 should work.");
+    new_graph
+        .add_edge(None, node_idx, NodeIdx(0), metrics)
+        .expect("Adding edge 42 -> a should work.");
+
+    let diff = old_graph
+        .diff(&new_graph, EdgeMatching::NodeIds)
+        .expect("Diffing edited graphs should work.");
+
+    assert_eq!(
+        diff.nodes
+            .iter()
+            .map(|node| (node.kind, node.id))
+            .collect::<Vec<_>>(),
+        vec![(DiffKind::Removed, 6), (DiffKind::Added, 42)]
+    );
+    assert_eq!(
+        diff.edges
+            .iter()
+            .map(|edge| (edge.kind, edge.src_id, edge.dst_id))
+            .collect::<Vec<_>>(),
+        vec![
+            (DiffKind::Changed, 1, 2),
+            (DiffKind::Removed, 6, 4),
+            (DiffKind::Removed, 6, 5),
+            (DiffKind::Added, 42, 0),
+        ]
+    );
+    let deltas = diff.edges[0].deltas().expect("Changed edges have deltas.");
+    assert!(Approx(deltas[km_idx]) == Approx(0.004));
+    assert!(diff.edges[1].new_metrics.is_none());
+    assert!(diff.edges[3].old_metrics.is_none());
+    assert!(diff.edges[3].deltas().is_none());
+
+    // writing csv and geojson
+
+    for (file_name, expected_line_count) in &[("diff.csv", 1 + 2 + 4), ("diff.geojson", 0)] {
+        let file =
+            std::env::temp_dir().join(format!("osmgraphing-{}-{}", std::process::id(), file_name));
+        let writing_cfg = configs::writing::diff::Config {
+            file: file.clone(),
+            edge_matching: EdgeMatching::NodeIds,
+        };
+        io::diff::Writer::write(&diff, &writing_cfg).expect("Writing the diff should work.");
+        let content = fs::read_to_string(&file).expect("Written diff should be readable.");
+        fs::remove_file(&file).expect("Written diff should be removable.");
+
+        if *expected_line_count > 0 {
+            let lines: Vec<_> = content.lines().collect();
+            assert_eq!(lines.len(), *expected_line_count);
+            assert!(lines[0].starts_with("element kind id src-id dst-id"));
+            assert!(lines[1].starts_with("node removed 6 _ _"));
+            assert!(lines[3].starts_with("edge changed _ 1 2"));
+        } else {
+            let json: serde_json::Value =
+                serde_json::from_str(&content).expect("Written diff should be json.");
+            assert_eq!(json["features"].as_array().map(Vec::len), Some(2 + 4));
+        }
+    }
+}
+
+#[test]
+fn matching_by_missing_edge_ids_fails() {
+    let graph = parse(configs::parsing::Config::from_yaml(resources::FMI_YAML));
+    assert!(graph.diff(&graph, EdgeMatching::EdgeIds).is_err());
+}
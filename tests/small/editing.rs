@@ -0,0 +1,76 @@
+use crate::helpers::{defaults, parse};
+use defaults::paths::resources::small as resources;
+use kissunits::geo::Coordinate;
+use osmgraphing::{
+    configs,
+    defaults::capacity::DimVec,
+    network::{EdgeIdx, EdgeRef, NodeIdx},
+};
+
+#[test]
+fn editing_ch_graph_fails() {
+    let mut graph = parse(configs::parsing::Config::from_yaml(resources::CH_FMI_YAML));
+    let edge_count = graph.fwd_edges().count();
+    assert!(graph.add_node(42, Coordinate::zero()).is_err());
+    assert!(graph.remove_edge(EdgeRef::Idx(EdgeIdx(0))).is_err());
+    assert!(graph.remove_node(NodeIdx(0)).is_err());
+    assert!(graph.compact().is_err());
+    assert!(!graph.is_edited());
+    assert_eq!(graph.fwd_edges().count(), edge_count);
+}
+
+#[test]
+fn invalid_edits() {
+    let mut graph = parse(configs::parsing::Config::from_yaml(resources::FMI_YAML));
+    let metrics = graph.metrics().get(EdgeIdx(0));
+    let node_count = graph.nodes().count();
+
+    // unknown nodes
+    assert!(graph
+        .add_edge(None, NodeIdx(0), NodeIdx(node_count), metrics.clone())
+        .is_err());
+    // wrong dimension
+    assert!(graph
+        .add_edge(None, NodeIdx(0), NodeIdx(1), DimVec::from(&metrics[1..]))
+        .is_err());
+    // negative metric
+    let mut negative_metrics = metrics.clone();
+    negative_metrics[0] = -1.0;
+    assert!(graph
+        .add_edge(None, NodeIdx(0), NodeIdx(1), negative_metrics)
+        .is_err());
+    assert!(!graph.is_edited());
+
+    // removed nodes can't be used
+    assert!(graph.remove_node(NodeIdx(0)).is_ok());
+    assert!(graph.remove_node(NodeIdx(0)).is_err());
+    assert!(graph
+        .add_edge(None, NodeIdx(0), NodeIdx(1), metrics)
+        .is_err());
+    assert_eq!(graph.fwd_edges().starting_from(NodeIdx(0)).count(), 0);
+    assert_eq!(graph.bwd_edges().starting_from(NodeIdx(0)).count(), 0);
+}
+
+#[test]
+fn added_edges_are_no_shortcuts() {
+    let mut graph = parse(configs::parsing::Config::from_yaml(resources::FMI_YAML));
+    let metrics = graph.metrics().get(EdgeIdx(0));
+    let edge_count = graph.fwd_edges().count();
+
+    let mut new_idxs = Vec::new();
+    for _ in 0..2 {
+        new_idxs.push(
+            graph
+                .add_edge(None, NodeIdx(0), NodeIdx(1), metrics.clone())
+                .expect("Adding an edge should work."),
+        );
+    }
+
+    // every edge (including the added ones) has its shortcut-offsets
+    let fwd_edges = graph.fwd_edges();
+    assert_eq!(fwd_edges.count(), edge_count + new_idxs.len());
+    for edge_idx in (0..fwd_edges.count()).map(EdgeIdx) {
+        assert!(!fwd_edges.is_shortcut(edge_idx));
+        assert!(fwd_edges.sc_edges(edge_idx).is_none());
+    }
+}
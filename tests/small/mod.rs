@@ -1,2 +1,8 @@
+mod analyzing;
+mod diffing;
+mod editing;
 mod parsing;
 mod routing;
+mod snapping;
+mod subgraphing;
+mod updating;
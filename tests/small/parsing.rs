@@ -1,4 +1,7 @@
-use crate::helpers::{assert_graph, assert_graph_sloppy, defaults, parse, TestEdge, TestNode};
+use crate::helpers::{
+    assert_eq_graphs, assert_eq_reordered_graphs, assert_graph, assert_graph_sloppy, defaults,
+    parse, TestEdge, TestNode,
+};
 use defaults::paths::resources::small as resources;
 use kissunits::{
     distance::Kilometers,
@@ -6,7 +9,13 @@ use kissunits::{
     speed::KilometersPerHour,
     time::{Hours, Seconds},
 };
-use osmgraphing::{configs, network::EdgeIdx};
+use osmgraphing::{
+    approximating::Approx,
+    configs,
+    helpers::spilling,
+    io,
+    network::{EdgeIdx, NodeOrdering},
+};
 
 #[test]
 fn ch_fmi_yaml() {
@@ -140,3 +149,146 @@ fn ch_fmi_graph() {
     let expected_edge_count = 18;
     assert_graph_sloppy(expected_node_count, expected_edge_count, &graph);
 }
+
+#[test]
+fn many_metrics_fmi_graph() {
+    let graph = parse(configs::parsing::Config::from_yaml(resources::FMI_YAML));
+    let many_metrics_graph = parse(configs::parsing::Config::from_yaml(
+        resources::MANY_METRICS_FMI_YAML,
+    ));
+
+    // more metrics than inlined in small vectors
+    let metrics = many_metrics_graph.metrics();
+    assert_eq!(metrics.dim(), 10);
+    assert!(metrics.dim() > osmgraphing::defaults::capacity::SMALL_VEC_INLINE_SIZE);
+    assert_eq!(
+        many_metrics_graph.fwd_edges().count(),
+        graph.fwd_edges().count()
+    );
+
+    let cfg = &many_metrics_graph.cfg().edges.metrics;
+    let kilometers_idx = graph.cfg().edges.metrics.idx_of("kilometers");
+    for edge_idx in graph.fwd_edges().iter() {
+        let edge_metrics = metrics.get(edge_idx);
+        assert_eq!(edge_metrics.len(), metrics.dim());
+        let kilometers = graph.metrics()[kilometers_idx].get(edge_idx);
+        for id in &["kilometers", "kilometers-0", "kilometers-3"] {
+            assert_eq!(
+                Approx(metrics[cfg.idx_of(id)].get(edge_idx)),
+                Approx(kilometers)
+            );
+        }
+        assert_eq!(
+            Approx(edge_metrics[*cfg.idx_of("seconds")]),
+            Approx(3_600.0 * edge_metrics[*cfg.idx_of("hours")])
+        );
+    }
+}
+
+#[test]
+fn fmi_graph_with_external_memory() {
+    let graph = parse(configs::parsing::Config::from_yaml(resources::FMI_YAML));
+
+    // tiny budget, so proto-edges are spilled into several runs,
+    // which are merged in several passes
+    let mut parsing_cfg = configs::parsing::Config::from_yaml(resources::FMI_YAML);
+    parsing_cfg.external_memory = Some(configs::parsing::external_memory::Config {
+        dir: std::env::temp_dir(),
+        memory_budget_b: 1,
+        max_merged_runs: 2,
+    });
+    let external_graph = parse(parsing_cfg);
+
+    assert_eq_graphs(&graph, &external_graph);
+}
+
+#[test]
+fn ch_fmi_graph_with_external_memory() {
+    // shortcuts are not supported
+    let mut parsing_cfg = configs::parsing::Config::from_yaml(resources::CH_FMI_YAML);
+    parsing_cfg.external_memory = Some(configs::parsing::external_memory::Config {
+        dir: std::env::temp_dir(),
+        memory_budget_b: 1,
+        max_merged_runs: osmgraphing::defaults::parsing::external_memory::MAX_MERGED_RUNS,
+    });
+    assert!(io::network::graph::Parser::parse_and_finalize(parsing_cfg).is_err());
+}
+
+#[test]
+fn truncated_spilled_records() {
+    let mut record = [0u8; 4];
+
+    // end of run
+    let mut reader: &[u8] = &[1, 2, 3, 4];
+    assert_eq!(
+        spilling::read_record(&mut reader, &mut record).ok(),
+        Some(true)
+    );
+    assert_eq!(record, [1, 2, 3, 4]);
+    assert_eq!(
+        spilling::read_record(&mut reader, &mut record).ok(),
+        Some(false)
+    );
+
+    // truncated run
+    let mut reader: &[u8] = &[1, 2, 3, 4, 5, 6];
+    assert_eq!(
+        spilling::read_record(&mut reader, &mut record).ok(),
+        Some(true)
+    );
+    assert!(spilling::read_record(&mut reader, &mut record).is_err());
+}
+
+#[test]
+fn ch_fmi_graph_with_threads() {
+    let mut parsing_cfg = configs::parsing::Config::from_yaml(resources::CH_FMI_YAML);
+    parsing_cfg.num_threads = 1;
+    let graph = parse(parsing_cfg);
+
+    // more threads than edges of some nodes
+    let mut parsing_cfg = configs::parsing::Config::from_yaml(resources::CH_FMI_YAML);
+    parsing_cfg.num_threads = 7;
+    let parallel_graph = parse(parsing_cfg);
+
+    assert_eq_graphs(&graph, &parallel_graph);
+}
+
+#[test]
+fn ch_fmi_graph_with_node_orderings() {
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::CH_FMI_YAML);
+    let graph = parse(parsing_cfg);
+    let routing_cfg = configs::routing::Config::from_str(
+        &format!(
+            "routing: {{ algorithm: CHDijkstra, metrics: [{{ id: '{}' }}] }}",
+            defaults::DISTANCE_ID
+        ),
+        graph.cfg(),
+    );
+
+    for &node_ordering in &[
+        NodeOrdering::Id,
+        NodeOrdering::Hilbert,
+        NodeOrdering::Dfs,
+        NodeOrdering::Bfs,
+        NodeOrdering::ChLevel,
+    ] {
+        let mut parsing_cfg = configs::parsing::Config::from_yaml(resources::CH_FMI_YAML);
+        parsing_cfg.node_ordering = node_ordering;
+        parsing_cfg.num_threads = 3;
+        let reordered_graph = parse(parsing_cfg);
+
+        assert_eq_reordered_graphs(&graph, &reordered_graph, &routing_cfg, 1);
+    }
+
+    // highest levels first
+    let mut parsing_cfg = configs::parsing::Config::from_yaml(resources::CH_FMI_YAML);
+    parsing_cfg.node_ordering = NodeOrdering::ChLevel;
+    let reordered_graph = parse(parsing_cfg);
+    let nodes = reordered_graph.nodes();
+    let levels: Vec<_> = nodes.iter().map(|idx| nodes.level(idx)).collect();
+    assert!(
+        levels.windows(2).all(|w| w[0] >= w[1]),
+        "Levels {:?} should be descending.",
+        levels
+    );
+}
@@ -0,0 +1,37 @@
+use crate::helpers::{defaults, test_cch};
+use defaults::paths::resources::small as resources;
+use osmgraphing::network::NodeOrdering;
+
+#[test]
+fn cch_on_map() {
+    test_cch(
+        resources::FMI_YAML,
+        &[defaults::DISTANCE_ID, defaults::DURATION_ID],
+        NodeOrdering::Id,
+    );
+}
+
+#[test]
+fn cch_on_chmap() {
+    test_cch(
+        resources::CH_FMI_YAML,
+        &[defaults::DISTANCE_ID, defaults::DURATION_ID],
+        NodeOrdering::Id,
+    );
+}
+
+#[test]
+fn cch_on_map_with_node_orderings() {
+    for &node_ordering in &[
+        NodeOrdering::Hilbert,
+        NodeOrdering::Dfs,
+        NodeOrdering::Bfs,
+        NodeOrdering::ChLevel,
+    ] {
+        test_cch(
+            resources::FMI_YAML,
+            &[defaults::DISTANCE_ID, defaults::DURATION_ID],
+            node_ordering,
+        );
+    }
+}
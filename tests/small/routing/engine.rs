@@ -0,0 +1,10 @@
+use crate::helpers::{defaults, test_engine};
+use defaults::paths::resources::small as resources;
+
+#[test]
+fn engine_on_map() {
+    test_engine(
+        resources::FMI_YAML,
+        &[defaults::DISTANCE_ID, defaults::DURATION_ID],
+    );
+}
@@ -0,0 +1,103 @@
+use crate::helpers::{defaults, test_exploration};
+use defaults::paths::resources::small as resources;
+use osmgraphing::{approximating::Approx, helpers, helpers::convex_hull::ConvexHull};
+
+#[test]
+fn explorate_distance_and_duration_on_map() {
+    test_exploration(
+        resources::FMI_YAML,
+        &[defaults::DISTANCE_ID, defaults::DURATION_ID],
+    );
+}
+
+#[test]
+fn explorate_distance_and_duration_on_chmap() {
+    test_exploration(
+        resources::CH_FMI_YAML,
+        &[defaults::DISTANCE_ID, defaults::DURATION_ID],
+    );
+}
+
+#[test]
+fn convex_hull_of_duplicate_and_collinear_points() {
+    // cost-vectors of equally expensive paths are duplicate, collinear or coplanar
+    for points in &[
+        vec![
+            vec![0.0, 2.0],
+            vec![2.0, 0.0],
+            vec![1.0, 1.0],
+            vec![1.0, 1.0],
+            vec![0.5, 1.5],
+            vec![-2.0, 4.0],
+        ],
+        vec![
+            vec![2.0, 0.0, 0.0],
+            vec![0.0, 2.0, 0.0],
+            vec![0.0, 0.0, 2.0],
+            vec![1.0, 1.0, 0.0],
+            vec![1.0, 1.0, 0.0],
+            vec![4.0, -2.0, 0.0],
+            vec![0.0, 1.0, 1.0],
+        ],
+        vec![
+            vec![2.0, 0.0, 0.0],
+            vec![0.0, 2.0, 0.0],
+            vec![0.0, 0.0, 2.0],
+            vec![1.0, 1.0, 0.0],
+            vec![0.0, 1.0, 1.0],
+            vec![1.0, 0.0, 1.0],
+            vec![2.0 / 3.0, 2.0 / 3.0, 2.0 / 3.0],
+        ],
+    ] {
+        // the hull doesn't depend on the points' scale
+        let expected_cells = lower_cells_of(points, 1.0);
+        assert!(!expected_cells.is_empty());
+        for &scale in &[1e-6, 1e6] {
+            assert_eq!(lower_cells_of(points, scale), expected_cells);
+        }
+    }
+}
+
+/// Returns the vertices of the lower cells after adding the scaled points and a point beyond them.
+fn lower_cells_of(points: &[Vec<f64>], scale: f64) -> Vec<Vec<usize>> {
+    let dim = points[0].len();
+    let mut convex_hull = ConvexHull::new(dim);
+    for point in points {
+        let point: Vec<f64> = point.iter().map(|x| scale * x).collect();
+        convex_hull
+            .add_vertex(&point)
+            .expect("Point should have the hull's dimension.");
+        for cell in convex_hull.lower_cells() {
+            let normal = cell.normal();
+            assert!(
+                normal.iter().all(|n| n.is_finite()),
+                "Normal {:?} of cell {:?} should be finite.",
+                normal,
+                cell.vertices()
+            );
+            assert!(Approx(helpers::dot_product(normal, normal)) == Approx(1.0));
+        }
+    }
+
+    // a point beyond the first points' facet is still inserted after the degenerate points,
+    // hence the hull has no holes
+    let beyond: Vec<f64> = (0..dim)
+        .map(|i| {
+            scale * (points[..dim].iter().map(|point| point[i]).sum::<f64>() / dim as f64 - 0.5)
+        })
+        .collect();
+    let id = convex_hull
+        .add_vertex(&beyond)
+        .expect("Point should have the hull's dimension.");
+    let mut cells: Vec<Vec<usize>> = convex_hull
+        .lower_cells()
+        .map(|cell| {
+            let mut vertices = cell.vertices().clone();
+            vertices.sort();
+            vertices
+        })
+        .collect();
+    assert!(cells.iter().any(|vertices| vertices.contains(&id)));
+    cells.sort();
+    cells
+}
@@ -1,2 +1,7 @@
+mod customizing;
+mod engine;
+mod exploring;
 mod fastest;
+mod phantoms;
 mod shortest;
+mod stats;
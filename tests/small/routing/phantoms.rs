@@ -0,0 +1,21 @@
+use crate::helpers::{defaults, test_phantom_paths};
+use defaults::paths::resources::small as resources;
+use osmgraphing::configs::routing::RoutingAlgo;
+
+#[test]
+fn dijkstra_on_map() {
+    test_phantom_paths(
+        resources::FMI_YAML,
+        defaults::DISTANCE_ID,
+        RoutingAlgo::Dijkstra,
+    );
+}
+
+#[test]
+fn chdijkstra_on_chmap() {
+    test_phantom_paths(
+        resources::CH_FMI_YAML,
+        defaults::DURATION_ID,
+        RoutingAlgo::CHDijkstra,
+    );
+}
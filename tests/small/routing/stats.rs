@@ -0,0 +1,12 @@
+use crate::helpers::{defaults, test_query_stats};
+use defaults::paths::resources::small as resources;
+
+#[test]
+fn query_stats_on_map() {
+    test_query_stats(resources::FMI_YAML, defaults::DISTANCE_ID);
+}
+
+#[test]
+fn query_stats_on_chmap() {
+    test_query_stats(resources::CH_FMI_YAML, defaults::DURATION_ID);
+}
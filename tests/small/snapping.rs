@@ -0,0 +1,39 @@
+use crate::helpers::{defaults, parse};
+use defaults::paths::resources::small as resources;
+use kissunits::{distance::Kilometers, geo::Coordinate};
+use osmgraphing::{
+    configs,
+    network::{SnappingFilter, SpatialIndex},
+};
+
+/// All nodes of this graph have the same coordinate.
+fn snapped_ids(filter: SnappingFilter) -> Vec<i64> {
+    let graph = parse(configs::parsing::Config::from_yaml(resources::FMI_YAML));
+    let nodes = graph.nodes();
+    let spatial_index = SpatialIndex::new(&graph, filter);
+
+    let mut ids: Vec<_> = spatial_index
+        .within_radius(&Coordinate::zero(), Kilometers(1.0))
+        .into_iter()
+        .map(|(node_idx, _)| nodes.id(node_idx))
+        .collect();
+    ids.sort();
+    ids
+}
+
+#[test]
+fn accessible_nodes() {
+    assert_eq!(
+        snapped_ids(SnappingFilter::Accessible),
+        vec![0, 1, 2, 3, 4, 5, 6, 7]
+    );
+}
+
+#[test]
+fn largest_component() {
+    // d, e, f, h
+    assert_eq!(
+        snapped_ids(SnappingFilter::LargestComponent),
+        vec![3, 4, 5, 7]
+    );
+}
@@ -0,0 +1,233 @@
+use crate::helpers::{assert_eq_graphs, defaults, parse};
+use defaults::paths::resources::small as resources;
+use kissunits::geo::Coordinate;
+use osmgraphing::{
+    configs::{self, SimpleId},
+    io,
+    network::{BoundingBox, Graph, MetricIdx, NodeIdx, SubgraphPredicate},
+};
+use std::fs;
+
+fn idxs_of(graph: &Graph, ids: &[i64]) -> Vec<NodeIdx> {
+    ids.iter()
+        .map(|&id| {
+            graph
+                .nodes()
+                .idx_from(id)
+                .expect("Node-id should be part of the graph.")
+        })
+        .collect()
+}
+
+fn sorted_ids_of(graph: &Graph) -> Vec<i64> {
+    let mut ids: Vec<_> = graph
+        .nodes()
+        .iter()
+        .map(|idx| graph.nodes().id(idx))
+        .collect();
+    ids.sort_unstable();
+    ids
+}
+
+fn shortcut_count_of(graph: &Graph) -> usize {
+    let fwd_edges = graph.fwd_edges();
+    fwd_edges
+        .iter()
+        .filter(|&edge_idx| fwd_edges.is_shortcut(edge_idx))
+        .count()
+}
+
+#[test]
+fn node_predicates() {
+    let graph = parse(configs::parsing::Config::from_yaml(resources::FMI_YAML));
+
+    // d, e, f and h with their 8 edges in between
+    let subgraph = graph
+        .subgraph(&SubgraphPredicate::NodeIdxs(idxs_of(&graph, &[3, 4, 5, 7])))
+        .expect("Subgraph should be extractable.");
+    assert_eq!(sorted_ids_of(&subgraph), vec![3, 4, 5, 7]);
+    assert_eq!(subgraph.fwd_edges().count(), 8);
+
+    // a is only reached by b and c
+    let subgraph = graph
+        .subgraph(&SubgraphPredicate::Neighbourhood {
+            center: idxs_of(&graph, &[0])[0],
+            hops: 1,
+        })
+        .expect("Subgraph should be extractable.");
+    assert_eq!(sorted_ids_of(&subgraph), vec![0, 1, 2]);
+    assert_eq!(subgraph.fwd_edges().count(), 4);
+
+    // all coordinates are zero
+    let subgraph = graph
+        .subgraph(&SubgraphPredicate::BoundingBox(BoundingBox {
+            min: Coordinate {
+                lat: -1.0,
+                lon: -1.0,
+            },
+            max: Coordinate { lat: 1.0, lon: 1.0 },
+        }))
+        .expect("Subgraph should be extractable.");
+    assert_eq_graphs(&graph, &subgraph);
+
+    // unknown nodes
+    assert!(graph
+        .subgraph(&SubgraphPredicate::NodeIdxs(vec![NodeIdx(
+            graph.nodes().count()
+        )]))
+        .is_err());
+}
+
+#[test]
+fn metric_predicate() {
+    let graph = parse(configs::parsing::Config::from_yaml(resources::FMI_YAML));
+    let km_idx = MetricIdx(*graph.cfg().edges.metrics.idx_of(defaults::DISTANCE_ID));
+
+    // d -> e, e -> d and h -> c are longer than 1 m
+    let subgraph = graph
+        .subgraph(&SubgraphPredicate::Metric {
+            idx: km_idx,
+            min: 0.0,
+            max: 0.0015,
+        })
+        .expect("Subgraph should be extractable.");
+    assert_eq!(subgraph.nodes().count(), 8);
+    assert_eq!(subgraph.fwd_edges().count(), 13);
+
+    // combined with node-predicates
+    let subgraph = graph
+        .subgraph(&SubgraphPredicate::All(vec![
+            SubgraphPredicate::NodeIdxs(idxs_of(&graph, &[3, 4, 5, 7])),
+            SubgraphPredicate::Metric {
+                idx: km_idx,
+                min: 0.0,
+                max: 0.0015,
+            },
+        ]))
+        .expect("Subgraph should be extractable.");
+    assert_eq!(sorted_ids_of(&subgraph), vec![3, 4, 5, 7]);
+    assert_eq!(subgraph.fwd_edges().count(), 6);
+
+    // unknown metric
+    assert!(graph
+        .subgraph(&SubgraphPredicate::Metric {
+            idx: MetricIdx(graph.metrics().dim()),
+            min: 0.0,
+            max: 1.0,
+        })
+        .is_err());
+}
+
+#[test]
+fn ch_graph() {
+    let graph = parse(configs::parsing::Config::from_yaml(resources::CH_FMI_YAML));
+    assert_eq!(shortcut_count_of(&graph), 2);
+
+    let subgraph = graph
+        .subgraph(&SubgraphPredicate::All(Vec::new()))
+        .expect("Subgraph should be extractable.");
+    assert_eq_graphs(&graph, &subgraph);
+
+    // shortcuts e -> b and h -> b skip d
+    let subgraph = graph
+        .subgraph(&SubgraphPredicate::NodeIdxs(idxs_of(&graph, &[1, 3, 4, 7])))
+        .expect("Subgraph should be extractable.");
+    assert_eq!(sorted_ids_of(&subgraph), vec![1, 3, 4, 7]);
+    assert_eq!(subgraph.fwd_edges().count(), 5 + 2);
+    assert_eq!(shortcut_count_of(&subgraph), 2);
+
+    // without d, both shortcuts are dropped
+    let subgraph = graph
+        .subgraph(&SubgraphPredicate::NodeIdxs(idxs_of(&graph, &[1, 4, 5, 7])))
+        .expect("Subgraph should be extractable.");
+    assert_eq!(sorted_ids_of(&subgraph), vec![4, 5, 7]);
+    assert_eq!(subgraph.fwd_edges().count(), 4);
+    assert_eq!(shortcut_count_of(&subgraph), 0);
+
+    // d has level 1
+    let subgraph = graph
+        .subgraph(&SubgraphPredicate::MinChLevel(2))
+        .expect("Subgraph should be extractable.");
+    assert_eq!(sorted_ids_of(&subgraph), vec![1, 2, 4, 5, 7]);
+    assert_eq!(subgraph.fwd_edges().count(), 7);
+    assert_eq!(shortcut_count_of(&subgraph), 0);
+}
+
+#[test]
+fn writing_ch_subgraph() {
+    let graph = parse(configs::parsing::Config::from_yaml(resources::CH_FMI_YAML));
+    let subgraph = graph
+        .subgraph(&SubgraphPredicate::NodeIdxs(idxs_of(&graph, &[1, 3, 4, 7])))
+        .expect("Subgraph should be extractable.");
+
+    let map_file = std::env::temp_dir().join(format!(
+        "osmgraphing-{}-subgraph.ch.fmi",
+        std::process::id()
+    ));
+    let to_ids = |ids: &[Option<&str>]| -> Vec<Option<SimpleId>> {
+        ids.iter().map(|id| id.map(SimpleId::from)).collect()
+    };
+    let writing_cfg = configs::writing::network::graph::Config {
+        map_file: map_file.clone(),
+        nodes: configs::writing::network::graph::nodes::Config {
+            ids: to_ids(&[
+                Some("node-id"),
+                None,
+                Some("latitude"),
+                Some("longitude"),
+                None,
+                Some("ch-level"),
+            ]),
+        },
+        edges: configs::writing::network::edges::Config {
+            file: map_file.clone(),
+            is_writing_shortcuts: true,
+            is_writing_header: false,
+            is_denormalizing: false,
+            ids: to_ids(&[
+                Some("src-id"),
+                Some("dst-id"),
+                Some("kilometers"),
+                Some("hours"),
+                Some("sc-idx-0"),
+                Some("sc-idx-1"),
+            ]),
+        },
+    };
+    io::network::graph::Writer::write(&subgraph, &writing_cfg)
+        .expect("Subgraph should be writable.");
+
+    // the written metrics are kilometers and hours instead of meters and seconds
+    let parsing_file = map_file.with_extension("yaml");
+    fs::write(
+        &parsing_file,
+        format!(
+            "parsing:
+  map-file: '{}'
+  vehicles: {{ category: 'Car', are_drivers_picky: false }}
+  nodes:
+  - meta: {{ info: 'NodeId', id: 'node-id' }}
+  - ignored
+  - metric: {{ unit: 'Latitude', id: 'latitude' }}
+  - metric: {{ unit: 'Longitude', id: 'longitude' }}
+  - ignored
+  - meta: {{ info: 'CHLevel', id: 'ch-level' }}
+  edges:
+    data:
+    - meta: {{ info: 'SrcId', id: 'src-id' }}
+    - meta: {{ info: 'DstId', id: 'dst-id' }}
+    - metric: {{ unit: 'Kilometers', id: 'kilometers' }}
+    - metric: {{ unit: 'Hours', id: 'hours' }}
+    - meta: {{ info: 'ShortcutIdx0', id: 'sc-idx-0' }}
+    - meta: {{ info: 'ShortcutIdx1', id: 'sc-idx-1' }}
+",
+            map_file.display()
+        ),
+    )
+    .expect("Parsing-config should be writable.");
+    let parsed_subgraph = parse(configs::parsing::Config::from_yaml(&parsing_file));
+    fs::remove_file(&map_file).expect("Written subgraph should be removable.");
+    fs::remove_file(&parsing_file).expect("Parsing-config should be removable.");
+
+    assert_eq_graphs(&subgraph, &parsed_subgraph);
+}
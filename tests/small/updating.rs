@@ -0,0 +1,112 @@
+use crate::helpers::{defaults, parse};
+use defaults::paths::resources::small as resources;
+use osmgraphing::{
+    approximating::Approx,
+    configs,
+    network::{EdgeIdx, EdgeRef, MetricIdx, MetricUpdate},
+};
+
+#[test]
+fn propagating_to_shortcuts() {
+    let mut graph = parse(configs::parsing::Config::from_yaml(resources::CH_FMI_YAML));
+    let kilometers_idx = graph.cfg().edges.metrics.idx_of("kilometers");
+
+    // shortcut 4 -> 1 replaces 4 -> 3 and 3 -> 1
+    let (sc_idx, sc_edges) = {
+        let nodes = graph.nodes();
+        let fwd_edges = graph.fwd_edges();
+        let src_idx = nodes.idx_from(4).expect("Node 4 exists.");
+        let dst_idx = nodes.idx_from(1).expect("Node 1 exists.");
+        let shortcut = fwd_edges
+            .starting_from(src_idx)
+            .find(|edge| edge.dst_idx() == dst_idx && edge.is_shortcut())
+            .expect("Shortcut 4 -> 1 exists.");
+        (
+            shortcut.idx(),
+            *shortcut.sc_edges().expect("Shortcut has edges."),
+        )
+    };
+    let sc_kilometers =
+        |graph: &osmgraphing::network::Graph| graph.metrics()[kilometers_idx].get(sc_idx);
+    assert_eq!(Approx(sc_kilometers(&graph)), Approx(0.003));
+
+    // setting metrics updates the shortcut
+    let stats = graph
+        .update_metrics(&[MetricUpdate::Set {
+            edge: EdgeRef::Idx(sc_edges[0]),
+            metric_idx: kilometers_idx,
+            value: 0.01,
+        }])
+        .expect("Update should be valid.");
+    assert_eq!(stats.updated_edge_count, 1);
+    assert!(stats.updated_shortcut_count >= 1);
+    assert_eq!(
+        Approx(sc_kilometers(&graph)),
+        Approx(0.01 + graph.metrics()[kilometers_idx].get(sc_edges[1]))
+    );
+
+    // closing makes the shortcut unusable and reopening restores it
+    graph
+        .update_metrics(&[MetricUpdate::Close {
+            edge: EdgeRef::Idx(sc_edges[1]),
+        }])
+        .expect("Update should be valid.");
+    assert!(graph.is_closed(sc_edges[1]));
+    assert!(!graph.is_closed(sc_idx));
+    assert!(sc_kilometers(&graph).is_infinite());
+    graph
+        .update_metrics(&[MetricUpdate::Reopen {
+            edge: EdgeRef::Idx(sc_edges[1]),
+        }])
+        .expect("Update should be valid.");
+    assert!(!graph.is_closed(sc_edges[1]));
+    assert_eq!(
+        Approx(sc_kilometers(&graph)),
+        Approx(0.01 + graph.metrics()[kilometers_idx].get(sc_edges[1]))
+    );
+}
+
+#[test]
+fn invalid_updates_change_nothing() {
+    let mut graph = parse(configs::parsing::Config::from_yaml(resources::CH_FMI_YAML));
+    let shortcut_idx = graph
+        .fwd_edges()
+        .iter()
+        .find(|&edge_idx| graph.fwd_edges().is_shortcut(edge_idx))
+        .expect("Graph has shortcuts.");
+    let edge_idx = EdgeIdx(0);
+    let old_metrics = graph.metrics().get(edge_idx);
+
+    for invalid_update in vec![
+        MetricUpdate::Close {
+            edge: EdgeRef::Idx(shortcut_idx),
+        },
+        MetricUpdate::Close {
+            edge: EdgeRef::Idx(EdgeIdx(graph.fwd_edges().count())),
+        },
+        // graph has no edge-ids
+        MetricUpdate::Close {
+            edge: EdgeRef::Id(0),
+        },
+        MetricUpdate::Set {
+            edge: EdgeRef::Idx(edge_idx),
+            metric_idx: MetricIdx(graph.metrics().dim()),
+            value: 1.0,
+        },
+        MetricUpdate::Set {
+            edge: EdgeRef::Idx(edge_idx),
+            metric_idx: MetricIdx(0),
+            value: -1.0,
+        },
+    ] {
+        let updates = vec![
+            MetricUpdate::Close {
+                edge: EdgeRef::Idx(edge_idx),
+            },
+            invalid_update,
+        ];
+        assert!(graph.update_metrics(&updates).is_err());
+        assert!(!graph.is_closed(edge_idx));
+        assert_eq!(graph.metrics().get(edge_idx), old_metrics);
+    }
+}